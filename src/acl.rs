@@ -0,0 +1,198 @@
+// Evaluates raw Windows DACLs to decide whether ordinary users can write into a directory.
+
+pub const FILE_ADD_FILE: u32 = 0x0000_0002;
+pub const FILE_ADD_SUBDIRECTORY: u32 = 0x0000_0004;
+const WRITE_DAC: u32 = 0x0004_0000;
+const WRITE_OWNER: u32 = 0x0008_0000;
+const GENERIC_ALL: u32 = 0x1000_0000;
+const GENERIC_WRITE: u32 = 0x4000_0000;
+
+const ACCESS_ALLOWED_ACE_TYPE: u8 = 0;
+const ACCESS_DENIED_ACE_TYPE: u8 = 1;
+const INHERIT_ONLY_ACE: u8 = 0x08;
+
+/// Groups every interactive user belongs to: Everyone, Interactive,
+/// Authenticated Users and BUILTIN\Users.
+pub const USER_GROUPS: [&str; 4] = ["S-1-1-0", "S-1-5-4", "S-1-5-11", "S-1-5-32-545"];
+
+/// Formats a binary SID (`S-R-A-S1-S2...`).
+pub fn sid_to_string(sid: &[u8]) -> Option<String> {
+    let (&revision, rest) = sid.split_first()?;
+    let (&count, rest) = rest.split_first()?;
+    let authority = rest.get(..6)?;
+    let sub_authorities = rest.get(6..6 + 4 * count as usize)?;
+    let authority = authority
+        .iter()
+        .fold(0u64, |value, byte| (value << 8) | u64::from(*byte));
+    let mut out = format!("S-{revision}-{authority}");
+    for chunk in sub_authorities.chunks_exact(4) {
+        let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        out.push_str(&format!("-{value}"));
+    }
+    Some(out)
+}
+
+/// Whether `dacl` lets any of `trustees` (SID strings) add entries of the
+/// kind `access` names, or take the directory over through `WRITE_DAC` or
+/// `WRITE_OWNER`. ACEs are evaluated in order, so a deny ACE only hides
+/// rights granted after it. A NULL DACL (`None`) grants everyone
+/// everything; inherit-only and object ACEs do not apply to the directory
+/// itself and are skipped.
+pub fn dacl_allows(dacl: Option<&[u8]>, trustees: &[String], access: u32) -> bool {
+    let Some(dacl) = dacl else {
+        return true;
+    };
+    let wanted = access | WRITE_DAC | WRITE_OWNER;
+    let mut denied = 0u32;
+    for (ace_type, flags, mask, sid) in aces(dacl) {
+        if flags & INHERIT_ONLY_ACE != 0 {
+            continue;
+        }
+        let Some(sid) = sid_to_string(sid) else {
+            continue;
+        };
+        if !trustees
+            .iter()
+            .any(|trustee| trustee.eq_ignore_ascii_case(&sid))
+        {
+            continue;
+        }
+        let mask = expand_generic(mask);
+        match ace_type {
+            ACCESS_DENIED_ACE_TYPE => denied |= mask,
+            ACCESS_ALLOWED_ACE_TYPE if mask & !denied & wanted != 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// The `(type, flags, mask, sid)` of each ACE whose layout is the plain
+/// allowed/denied one.
+fn aces(dacl: &[u8]) -> Vec<(u8, u8, u32, &[u8])> {
+    let mut out = Vec::new();
+    let Some(count) = dacl.get(4..6) else {
+        return out;
+    };
+    let count = u16::from_le_bytes([count[0], count[1]]);
+    let mut offset = 8usize;
+    for _ in 0..count {
+        let Some(header) = dacl.get(offset..offset + 4) else {
+            break;
+        };
+        let size = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let Some(ace) = dacl.get(offset..offset + size).filter(|_| size >= 8) else {
+            break;
+        };
+        if matches!(header[0], ACCESS_ALLOWED_ACE_TYPE | ACCESS_DENIED_ACE_TYPE) {
+            let mask = u32::from_le_bytes([ace[4], ace[5], ace[6], ace[7]]);
+            out.push((header[0], header[1], mask, &ace[8..]));
+        }
+        offset += size;
+    }
+    out
+}
+
+fn expand_generic(mask: u32) -> u32 {
+    if mask & (GENERIC_ALL | GENERIC_WRITE) != 0 {
+        mask | FILE_ADD_FILE | FILE_ADD_SUBDIRECTORY
+    } else {
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dacl_allows, sid_to_string, FILE_ADD_FILE, FILE_ADD_SUBDIRECTORY, USER_GROUPS};
+
+    fn sid(authority: u8, sub_authorities: &[u32]) -> Vec<u8> {
+        let mut out = vec![1, sub_authorities.len() as u8, 0, 0, 0, 0, 0, authority];
+        for value in sub_authorities {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    fn ace(ace_type: u8, flags: u8, mask: u32, sid: &[u8]) -> Vec<u8> {
+        let size = (8 + sid.len()) as u16;
+        let mut out = vec![ace_type, flags];
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&mask.to_le_bytes());
+        out.extend_from_slice(sid);
+        out
+    }
+
+    fn acl(aces: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = aces.concat();
+        let mut out = vec![2, 0];
+        out.extend_from_slice(&((8 + body.len()) as u16).to_le_bytes());
+        out.extend_from_slice(&(aces.len() as u16).to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn user_groups() -> Vec<String> {
+        USER_GROUPS.iter().map(|sid| sid.to_string()).collect()
+    }
+
+    #[test]
+    fn formats_well_known_sids() {
+        assert_eq!(sid_to_string(&sid(1, &[0])).as_deref(), Some("S-1-1-0"));
+        assert_eq!(
+            sid_to_string(&sid(5, &[32, 545])).as_deref(),
+            Some("S-1-5-32-545")
+        );
+        assert_eq!(sid_to_string(&[1, 2, 0, 0, 0, 0, 0, 5, 32, 0]), None);
+    }
+
+    #[test]
+    fn users_may_add_files_through_an_allow_ace() {
+        let users = sid(5, &[32, 545]);
+        let admins = sid(5, &[32, 544]);
+        // The System32 shape: users read and execute, admins write.
+        let system = acl(&[
+            ace(0, 0, 0x001F_01FF, &admins),
+            ace(0, 0, 0x0012_00A9, &users),
+        ]);
+        assert!(!dacl_allows(Some(&system), &user_groups(), FILE_ADD_FILE));
+
+        // C:\ lets users create folders but not files.
+        let root = acl(&[ace(0, 0, FILE_ADD_SUBDIRECTORY, &sid(5, &[11]))]);
+        assert!(dacl_allows(
+            Some(&root),
+            &user_groups(),
+            FILE_ADD_SUBDIRECTORY
+        ));
+        assert!(!dacl_allows(Some(&root), &user_groups(), FILE_ADD_FILE));
+
+        let generic = acl(&[ace(0, 0, 0x4000_0000, &sid(1, &[0]))]);
+        assert!(dacl_allows(Some(&generic), &user_groups(), FILE_ADD_FILE));
+        assert!(dacl_allows(None, &[], FILE_ADD_FILE));
+    }
+
+    #[test]
+    fn earlier_deny_and_inherit_only_aces_are_honored() {
+        let users = sid(5, &[32, 545]);
+        let denied = acl(&[
+            ace(1, 0, FILE_ADD_FILE, &users),
+            ace(0, 0, FILE_ADD_FILE, &users),
+        ]);
+        assert!(!dacl_allows(Some(&denied), &user_groups(), FILE_ADD_FILE));
+
+        let inherit_only = acl(&[ace(0, 0x08 | 0x02, 0x1000_0000, &users)]);
+        assert!(!dacl_allows(
+            Some(&inherit_only),
+            &user_groups(),
+            FILE_ADD_FILE
+        ));
+
+        // A per-user directory is writable by its owner only.
+        let owner = sid(5, &[21, 1, 2, 3, 1001]);
+        let profile = acl(&[ace(0, 0, 0x001F_01FF, &owner)]);
+        assert!(!dacl_allows(Some(&profile), &user_groups(), FILE_ADD_FILE));
+        let mut trustees = user_groups();
+        trustees.push("S-1-5-21-1-2-3-1001".to_string());
+        assert!(dacl_allows(Some(&profile), &trustees, FILE_ADD_FILE));
+    }
+}
@@ -0,0 +1,420 @@
+// Resolves API set names (api-ms-win-*, ext-ms-win-*) to host DLLs from an offline ApiSet schema.
+
+use std::path::Path;
+
+use crate::pe;
+
+/// Parsed contents of the `.apiset` section of `apisetschema.dll`.
+pub struct ApiSetSchema {
+    version: u32,
+    entries: Vec<ApiSetEntry>,
+}
+
+struct ApiSetEntry {
+    /// Lowercased lookup key. v6 keys stop before the final `-N` version
+    /// component; v2/v4 keys are the full name without the `api-` prefix.
+    key: String,
+    values: Vec<ApiSetValue>,
+}
+
+struct ApiSetValue {
+    /// Importing module this value applies to; empty for the default host.
+    importing: String,
+    host: String,
+}
+
+/// Outcome of looking an API set name up in the schema.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApiSetLookup {
+    /// The set is implemented by this host DLL (lowercased).
+    Host(String),
+    /// The schema lists the set but gives it no host on this system.
+    NoHost,
+    /// The schema does not list the set at all.
+    Unknown,
+}
+
+impl ApiSetSchema {
+    /// Reads the schema from an `apisetschema.dll` image.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        Self::from_section_bytes(&pe::open_image(path)?.section(".apiset")?)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_image_bytes(data: &[u8]) -> Result<Self, String> {
+        Self::from_section_bytes(pe::section_bytes(data, ".apiset")?)
+    }
+
+    /// Parses a raw `.apiset` section. Versions 2 (Windows 7), 4 (Windows
+    /// 8.1) and 6 (Windows 10 and later) are supported.
+    pub(crate) fn from_section_bytes(data: &[u8]) -> Result<Self, String> {
+        let version = read_u32(data, 0)?;
+        let entries = match version {
+            2 => parse_v2(data)?,
+            4 => parse_v4(data)?,
+            6 => parse_v6(data)?,
+            other => return Err(format!("unsupported ApiSet schema version {other}")),
+        };
+        Ok(Self { version, entries })
+    }
+
+    /// Resolves `dll` as imported by `importing`. Per-importer exceptions
+    /// (such as kernel32.dll importing a set kernelbase.dll implements)
+    /// take precedence over the default host.
+    pub fn resolve(&self, dll: &str, importing: &str) -> ApiSetLookup {
+        let Some(entry) = self.find(dll) else {
+            return ApiSetLookup::Unknown;
+        };
+        let importing = importing.to_ascii_lowercase();
+        let value = entry
+            .values
+            .iter()
+            .find(|value| !value.importing.is_empty() && value.importing == importing)
+            .or_else(|| entry.values.iter().find(|value| value.importing.is_empty()))
+            .or_else(|| entry.values.first());
+        match value {
+            Some(value) if !value.host.is_empty() => ApiSetLookup::Host(value.host.clone()),
+            _ => ApiSetLookup::NoHost,
+        }
+    }
+
+    fn find(&self, dll: &str) -> Option<&ApiSetEntry> {
+        let lower = dll.to_ascii_lowercase();
+        let name = lower.strip_suffix(".dll").unwrap_or(&lower);
+        let key = if self.version >= 6 {
+            // The loader hashes the name up to its last hyphen, so any
+            // minor revision of a contract maps to the same entry.
+            &name[..name.rfind('-')?]
+        } else {
+            name.strip_prefix("api-").unwrap_or(name)
+        };
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+}
+
+fn parse_v2(data: &[u8]) -> Result<Vec<ApiSetEntry>, String> {
+    let count = read_u32(data, 4)? as usize;
+    let mut entries = Vec::with_capacity(count.min(4096));
+    for index in 0..count {
+        let base = 8 + index * 12;
+        let name = read_utf16(data, read_u32(data, base)?, read_u32(data, base + 4)?)?;
+        let values_offset = read_u32(data, base + 8)? as usize;
+        let value_count = read_u32(data, values_offset)? as usize;
+        let values = parse_values(data, values_offset + 4, value_count, 16, 0)?;
+        entries.push(ApiSetEntry {
+            key: name.to_ascii_lowercase(),
+            values,
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_v4(data: &[u8]) -> Result<Vec<ApiSetEntry>, String> {
+    let count = read_u32(data, 12)? as usize;
+    let mut entries = Vec::with_capacity(count.min(4096));
+    for index in 0..count {
+        let base = 16 + index * 24;
+        let name = read_utf16(data, read_u32(data, base + 4)?, read_u32(data, base + 8)?)?;
+        let values_offset = read_u32(data, base + 20)? as usize;
+        let value_count = read_u32(data, values_offset + 4)? as usize;
+        let values = parse_values(data, values_offset + 8, value_count, 20, 4)?;
+        entries.push(ApiSetEntry {
+            key: name.to_ascii_lowercase(),
+            values,
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_v6(data: &[u8]) -> Result<Vec<ApiSetEntry>, String> {
+    let count = read_u32(data, 12)? as usize;
+    let entries_offset = read_u32(data, 16)? as usize;
+    let mut entries = Vec::with_capacity(count.min(4096));
+    for index in 0..count {
+        let base = entries_offset + index * 24;
+        let name_offset = read_u32(data, base + 4)?;
+        let hashed_length = read_u32(data, base + 12)?;
+        let key = read_utf16(data, name_offset, hashed_length)?;
+        let values_offset = read_u32(data, base + 16)? as usize;
+        let value_count = read_u32(data, base + 20)? as usize;
+        let values = parse_values(data, values_offset, value_count, 20, 4)?;
+        entries.push(ApiSetEntry {
+            key: key.to_ascii_lowercase(),
+            values,
+        });
+    }
+    Ok(entries)
+}
+
+/// Reads `count` value entries of `stride` bytes starting at `offset`. Each
+/// entry holds name offset/length then value offset/length, beginning
+/// `skip` bytes in (v4 and v6 lead with a flags field).
+fn parse_values(
+    data: &[u8],
+    offset: usize,
+    count: usize,
+    stride: usize,
+    skip: usize,
+) -> Result<Vec<ApiSetValue>, String> {
+    let mut values = Vec::with_capacity(count.min(64));
+    for index in 0..count {
+        let base = offset + index * stride + skip;
+        let importing = read_utf16(data, read_u32(data, base)?, read_u32(data, base + 4)?)?;
+        let host = read_utf16(data, read_u32(data, base + 8)?, read_u32(data, base + 12)?)?;
+        values.push(ApiSetValue {
+            importing: importing.to_ascii_lowercase(),
+            host: host.to_ascii_lowercase(),
+        });
+    }
+    Ok(values)
+}
+
+fn read_utf16(data: &[u8], offset: u32, byte_length: u32) -> Result<String, String> {
+    let start = offset as usize;
+    let end = start
+        .checked_add(byte_length as usize)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| "ApiSet string out of bounds".to_string())?;
+    let units: Vec<u16> = data[start..end]
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Ok(String::from_utf16_lossy(&units))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    let bytes = data
+        .get(offset..offset.saturating_add(4))
+        .filter(|bytes| bytes.len() == 4)
+        .ok_or_else(|| "ApiSet schema truncated".to_string())?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Synthetic schema builders for unit tests (used here and by the static
+/// walk tests in main.rs).
+#[cfg(test)]
+pub(crate) mod testschema {
+    /// Minimal schema writer: fixed-size tables up front, strings appended.
+    struct Writer {
+        bytes: Vec<u8>,
+    }
+
+    impl Writer {
+        fn new(header_len: usize) -> Self {
+            Self {
+                bytes: vec![0u8; header_len],
+            }
+        }
+
+        fn u32_at(&mut self, offset: usize, value: u32) {
+            if self.bytes.len() < offset + 4 {
+                self.bytes.resize(offset + 4, 0);
+            }
+            self.bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        /// Appends `text` as UTF-16 and returns (offset, byte length).
+        fn string(&mut self, text: &str) -> (u32, u32) {
+            let offset = self.bytes.len() as u32;
+            for unit in text.encode_utf16() {
+                self.bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+            (offset, text.len() as u32 * 2)
+        }
+    }
+
+    pub(crate) type Entry<'a> = (&'a str, &'a [(&'a str, &'a str)]);
+
+    pub(crate) fn build_v6(entries: &[Entry]) -> Vec<u8> {
+        let entries_offset = 28usize;
+        let values_start = entries_offset + entries.len() * 24;
+        let value_total: usize = entries.iter().map(|(_, values)| values.len()).sum();
+        let mut w = Writer::new(values_start + value_total * 20);
+        w.u32_at(0, 6);
+        w.u32_at(12, entries.len() as u32);
+        w.u32_at(16, entries_offset as u32);
+        let mut value_cursor = values_start;
+        for (index, (name, values)) in entries.iter().enumerate() {
+            let base = entries_offset + index * 24;
+            let (name_offset, name_len) = w.string(name);
+            let hashed = name.rfind('-').unwrap() as u32 * 2;
+            w.u32_at(base + 4, name_offset);
+            w.u32_at(base + 8, name_len);
+            w.u32_at(base + 12, hashed);
+            w.u32_at(base + 16, value_cursor as u32);
+            w.u32_at(base + 20, values.len() as u32);
+            for (importing, host) in values.iter() {
+                let (import_offset, import_len) = w.string(importing);
+                let (host_offset, host_len) = w.string(host);
+                w.u32_at(value_cursor + 4, import_offset);
+                w.u32_at(value_cursor + 8, import_len);
+                w.u32_at(value_cursor + 12, host_offset);
+                w.u32_at(value_cursor + 16, host_len);
+                value_cursor += 20;
+            }
+        }
+        w.bytes
+    }
+
+    pub(crate) fn build_v4(entries: &[Entry]) -> Vec<u8> {
+        let mut w = Writer::new(16 + entries.len() * 24);
+        w.u32_at(0, 4);
+        w.u32_at(12, entries.len() as u32);
+        for (index, (name, values)) in entries.iter().enumerate() {
+            let base = 16 + index * 24;
+            let (name_offset, name_len) = w.string(name);
+            w.u32_at(base + 4, name_offset);
+            w.u32_at(base + 8, name_len);
+            let array = w.bytes.len();
+            w.u32_at(array + 4, values.len() as u32);
+            w.bytes.resize(array + 8 + values.len() * 20, 0);
+            w.u32_at(base + 20, array as u32);
+            for (value_index, (importing, host)) in values.iter().enumerate() {
+                let value = array + 8 + value_index * 20;
+                let (import_offset, import_len) = w.string(importing);
+                let (host_offset, host_len) = w.string(host);
+                w.u32_at(value + 4, import_offset);
+                w.u32_at(value + 8, import_len);
+                w.u32_at(value + 12, host_offset);
+                w.u32_at(value + 16, host_len);
+            }
+        }
+        w.bytes
+    }
+
+    pub(crate) fn build_v2(entries: &[Entry]) -> Vec<u8> {
+        let mut w = Writer::new(8 + entries.len() * 12);
+        w.u32_at(0, 2);
+        w.u32_at(4, entries.len() as u32);
+        for (index, (name, values)) in entries.iter().enumerate() {
+            let base = 8 + index * 12;
+            let (name_offset, name_len) = w.string(name);
+            w.u32_at(base, name_offset);
+            w.u32_at(base + 4, name_len);
+            let array = w.bytes.len();
+            w.u32_at(array, values.len() as u32);
+            w.bytes.resize(array + 4 + values.len() * 16, 0);
+            w.u32_at(base + 8, array as u32);
+            for (value_index, (importing, host)) in values.iter().enumerate() {
+                let value = array + 4 + value_index * 16;
+                let (import_offset, import_len) = w.string(importing);
+                let (host_offset, host_len) = w.string(host);
+                w.u32_at(value, import_offset);
+                w.u32_at(value + 4, import_len);
+                w.u32_at(value + 8, host_offset);
+                w.u32_at(value + 12, host_len);
+            }
+        }
+        w.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testschema::{build_v2, build_v4, build_v6};
+    use super::{ApiSetLookup, ApiSetSchema};
+
+    #[test]
+    fn v6_resolves_any_minor_revision_to_default_host() {
+        let schema = ApiSetSchema::from_section_bytes(&build_v6(&[(
+            "api-ms-win-core-file-l1-2-4",
+            &[("", "kernel32.dll")],
+        )]))
+        .unwrap();
+        assert_eq!(
+            schema.resolve("API-MS-WIN-CORE-FILE-L1-2-0.dll", "app.exe"),
+            ApiSetLookup::Host("kernel32.dll".to_string())
+        );
+        assert_eq!(
+            schema.resolve("api-ms-win-core-file-l1-1-0.dll", "app.exe"),
+            ApiSetLookup::Unknown
+        );
+    }
+
+    #[test]
+    fn v6_prefers_importing_module_exception() {
+        let schema = ApiSetSchema::from_section_bytes(&build_v6(&[(
+            "api-ms-win-core-synch-l1-2-1",
+            &[("", "kernel32.dll"), ("kernel32.dll", "kernelbase.dll")],
+        )]))
+        .unwrap();
+        assert_eq!(
+            schema.resolve("api-ms-win-core-synch-l1-2-0.dll", "KERNEL32.DLL"),
+            ApiSetLookup::Host("kernelbase.dll".to_string())
+        );
+        assert_eq!(
+            schema.resolve("api-ms-win-core-synch-l1-2-0.dll", "app.exe"),
+            ApiSetLookup::Host("kernel32.dll".to_string())
+        );
+    }
+
+    #[test]
+    fn v6_reports_set_without_host() {
+        let schema = ApiSetSchema::from_section_bytes(&build_v6(&[
+            ("ext-ms-win-ntuser-window-l1-1-4", &[("", "")]),
+            ("ext-ms-win-gdi-draw-l1-1-3", &[]),
+        ]))
+        .unwrap();
+        assert_eq!(
+            schema.resolve("ext-ms-win-ntuser-window-l1-1-0.dll", "app.exe"),
+            ApiSetLookup::NoHost
+        );
+        assert_eq!(
+            schema.resolve("ext-ms-win-gdi-draw-l1-1-0.dll", "app.exe"),
+            ApiSetLookup::NoHost
+        );
+    }
+
+    #[test]
+    fn v4_matches_full_name_without_api_prefix() {
+        let schema = ApiSetSchema::from_section_bytes(&build_v4(&[(
+            "MS-Win-Core-File-L1-2-0",
+            &[("", "kernelbase.dll")],
+        )]))
+        .unwrap();
+        assert_eq!(
+            schema.resolve("api-ms-win-core-file-l1-2-0.dll", "app.exe"),
+            ApiSetLookup::Host("kernelbase.dll".to_string())
+        );
+        assert_eq!(
+            schema.resolve("api-ms-win-core-file-l1-2-1.dll", "app.exe"),
+            ApiSetLookup::Unknown
+        );
+    }
+
+    #[test]
+    fn v2_resolves_default_and_exception_hosts() {
+        let schema = ApiSetSchema::from_section_bytes(&build_v2(&[(
+            "MS-Win-Core-Console-L1-1-0",
+            &[("", "kernel32.dll"), ("kernel32.dll", "kernelbase.dll")],
+        )]))
+        .unwrap();
+        assert_eq!(
+            schema.resolve("api-ms-win-core-console-l1-1-0.dll", "app.exe"),
+            ApiSetLookup::Host("kernel32.dll".to_string())
+        );
+        assert_eq!(
+            schema.resolve("api-ms-win-core-console-l1-1-0.dll", "kernel32.dll"),
+            ApiSetLookup::Host("kernelbase.dll".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_version_and_truncated_tables() {
+        let mut bytes = build_v6(&[("api-ms-win-core-file-l1-2-4", &[("", "kernel32.dll")])]);
+        assert!(ApiSetSchema::from_section_bytes(&bytes[..20]).is_err());
+        bytes[0] = 5;
+        assert!(ApiSetSchema::from_section_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn loads_schema_from_apiset_section_of_image() {
+        let section = build_v6(&[("api-ms-win-core-file-l1-2-4", &[("", "kernel32.dll")])]);
+        let image = crate::pe::testpe::build_test_pe_with_section(b".apiset", &section);
+        let schema = ApiSetSchema::from_image_bytes(&image).unwrap();
+        assert_eq!(
+            schema.resolve("api-ms-win-core-file-l1-2-0.dll", "app.exe"),
+            ApiSetLookup::Host("kernel32.dll".to_string())
+        );
+    }
+}
@@ -0,0 +1,310 @@
+// Search-order hijacking rules for `audit-search`: where an unprivileged user could plant a DLL the loader would pick.
+
+use std::path::{Path, PathBuf};
+
+use crate::search::SearchContext;
+use crate::sysroot;
+#[cfg(windows)]
+use crate::{acl, win};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    High,
+    Medium,
+    Low,
+}
+
+impl Severity {
+    pub fn as_token(self) -> &'static str {
+        match self {
+            Severity::High => "HIGH",
+            Severity::Medium => "MEDIUM",
+            Severity::Low => "LOW",
+        }
+    }
+}
+
+/// Who may write into a directory.
+pub enum Writability {
+    /// A `--writable` list: the listed directories and everything below
+    /// them are writable, nothing else is.
+    Listed(Vec<PathBuf>),
+    /// The directory ACLs on this host, checked for these trustee SIDs.
+    #[cfg(windows)]
+    HostAcl(Vec<String>),
+    /// Offline without a list; writability cannot be judged.
+    Unknown,
+}
+
+impl Writability {
+    /// ACL checks for the groups every user is in plus the current user.
+    #[cfg(windows)]
+    pub fn host() -> Self {
+        let mut trustees: Vec<String> =
+            acl::USER_GROUPS.iter().map(|sid| sid.to_string()).collect();
+        trustees.extend(win::current_user_sid().and_then(|sid| acl::sid_to_string(&sid)));
+        Writability::HostAcl(trustees)
+    }
+
+    #[cfg(not(windows))]
+    pub fn host() -> Self {
+        Writability::Unknown
+    }
+
+    /// Whether an ordinary user can add a DLL to `dir`, or `None` when
+    /// that cannot be judged.
+    pub fn is_writable(&self, dir: &Path) -> Option<bool> {
+        match self {
+            Writability::Listed(dirs) => {
+                Some(dirs.iter().any(|listed| is_same_or_under(dir, listed)))
+            }
+            #[cfg(windows)]
+            Writability::HostAcl(trustees) => host_dir_writable(dir, trustees),
+            Writability::Unknown => None,
+        }
+    }
+}
+
+/// A directory that does not exist yet is as good as writable when its
+/// nearest existing ancestor lets users create subdirectories.
+#[cfg(windows)]
+fn host_dir_writable(dir: &Path, trustees: &[String]) -> Option<bool> {
+    let (existing, access) = if dir.is_dir() {
+        (dir, acl::FILE_ADD_FILE)
+    } else if dir.exists() {
+        return Some(false);
+    } else {
+        (
+            dir.ancestors().skip(1).find(|ancestor| ancestor.is_dir())?,
+            acl::FILE_ADD_SUBDIRECTORY,
+        )
+    };
+    let dacl = win::file_dacl(existing).ok()?;
+    Some(acl::dacl_allows(dacl.as_deref(), trustees, access))
+}
+
+/// Parses a `--writable` list: one directory per line; blank lines and `#`
+/// comments are ignored.
+pub fn parse_writable_dirs(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// A search root that lets a planted DLL win.
+pub struct RootRisk {
+    /// `WRITABLE_ROOT` or `CWD_BEFORE_SYSTEM`.
+    pub kind: &'static str,
+    pub severity: Severity,
+    /// 1-based position in the search order.
+    pub order: usize,
+    pub path: PathBuf,
+}
+
+/// The directories a relative DLL name is looked up in, in order: the
+/// `.local` redirection directory, then the search roots.
+pub fn audit_roots(context: &SearchContext) -> Vec<PathBuf> {
+    context
+        .dotlocal_dir
+        .iter()
+        .cloned()
+        .chain(context.ordered_roots())
+        .collect()
+}
+
+/// Risky search roots, in search order. A writable root ahead of the
+/// system directory can shadow system DLLs and is `HIGH`; one after it
+/// still catches every name the system does not ship and is `MEDIUM`. The
+/// current directory searched ahead of the system directory
+/// (SafeDllSearchMode off) is `MEDIUM` whatever its ACL, because it is
+/// wherever the user happened to open a file from.
+pub fn root_risks(context: &SearchContext, writability: &Writability) -> Vec<RootRisk> {
+    let roots = audit_roots(context);
+    let system = roots
+        .iter()
+        .position(|root| same_dir(root, &context.system_dir))
+        .unwrap_or(roots.len());
+    let mut risks = Vec::new();
+    for (idx, root) in roots.iter().enumerate() {
+        if idx < system && !context.safedll && same_dir(root, &context.cwd) {
+            risks.push(RootRisk {
+                kind: "CWD_BEFORE_SYSTEM",
+                severity: Severity::Medium,
+                order: idx + 1,
+                path: root.clone(),
+            });
+        }
+        if writability.is_writable(root) == Some(true) {
+            risks.push(RootRisk {
+                kind: "WRITABLE_ROOT",
+                severity: if idx < system {
+                    Severity::High
+                } else {
+                    Severity::Medium
+                },
+                order: idx + 1,
+                path: root.clone(),
+            });
+        }
+    }
+    risks
+}
+
+/// Severity of `dll` resolving to `chosen` while the system directory has
+/// its own copy, or `None` when the system copy is the one loaded. It is
+/// `HIGH` when users can write where the winning copy lives, `LOW` for an
+/// application that simply ships its own build.
+pub fn system_dll_risk(
+    dll: &str,
+    chosen: &Path,
+    context: &SearchContext,
+    writability: &Writability,
+) -> Option<Severity> {
+    let dir = chosen.parent()?;
+    if same_dir(dir, &context.system_dir)
+        || !sysroot::host_path(&context.system_dir.join(dll)).is_file()
+    {
+        return None;
+    }
+    Some(match writability.is_writable(dir) {
+        Some(true) => Severity::High,
+        _ => Severity::Low,
+    })
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    normalize(a) == normalize(b)
+}
+
+fn is_same_or_under(dir: &Path, parent: &Path) -> bool {
+    let dir = normalize(dir);
+    let parent = normalize(parent);
+    dir == parent || dir.starts_with(&format!("{parent}/"))
+}
+
+fn normalize(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .trim_end_matches('/')
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_writable_dirs, root_risks, system_dll_risk, Severity, Writability};
+    use crate::pe::MachineType;
+    use crate::search::{LoadOptions, SearchContext};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn context(safedll: bool) -> SearchContext {
+        SearchContext {
+            app_dir: PathBuf::from("C:/App"),
+            cwd: PathBuf::from("C:/Users/Public/Downloads"),
+            path_dirs: vec![
+                PathBuf::from("C:/Tools/bin"),
+                PathBuf::from("C:/Windows/System32/Wbem"),
+            ],
+            path_env: r"C:\Tools\bin;C:\Windows\System32\Wbem".to_string(),
+            safedll,
+            system_dir: PathBuf::from("C:/Windows/System32"),
+            windows_dir: PathBuf::from("C:/Windows"),
+            system16_dir: None,
+            machine: MachineType::X64,
+            load: LoadOptions::default(),
+            known_dlls: HashSet::new(),
+            dotlocal_dir: None,
+        }
+    }
+
+    #[test]
+    fn parses_writable_list() {
+        let dirs = parse_writable_dirs("# users\nC:\\Tools\\bin  \n\nC:\\Users # all profiles\n");
+        assert_eq!(
+            dirs,
+            [PathBuf::from("C:\\Tools\\bin"), PathBuf::from("C:\\Users")]
+        );
+    }
+
+    #[test]
+    fn writable_roots_rank_by_position_relative_to_the_system_directory() {
+        let writable = Writability::Listed(parse_writable_dirs("c:\\app\nC:\\TOOLS\n"));
+        let risks = root_risks(&context(true), &writable);
+        let found: Vec<(&str, Severity, usize, String)> = risks
+            .iter()
+            .map(|risk| {
+                (
+                    risk.kind,
+                    risk.severity,
+                    risk.order,
+                    risk.path.display().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("WRITABLE_ROOT", Severity::High, 1, "C:/App".to_string()),
+                (
+                    "WRITABLE_ROOT",
+                    Severity::Medium,
+                    5,
+                    "C:/Tools/bin".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn current_directory_ahead_of_system_is_flagged_without_safedll() {
+        let writable = Writability::Listed(parse_writable_dirs("C:\\Users\\Public"));
+        let risks = root_risks(&context(false), &writable);
+        let kinds: Vec<(&str, usize)> = risks.iter().map(|risk| (risk.kind, risk.order)).collect();
+        assert_eq!(kinds, [("CWD_BEFORE_SYSTEM", 2), ("WRITABLE_ROOT", 2)]);
+        assert_eq!(risks[1].severity, Severity::High);
+
+        // SafeDllSearchMode moves it behind the system directory.
+        let risks = root_risks(&context(true), &writable);
+        let kinds: Vec<(&str, usize)> = risks.iter().map(|risk| (risk.kind, risk.order)).collect();
+        assert_eq!(kinds, [("WRITABLE_ROOT", 4)]);
+        assert_eq!(risks[0].severity, Severity::Medium);
+
+        assert!(root_risks(&context(true), &Writability::Unknown).is_empty());
+    }
+
+    #[test]
+    fn app_local_copy_of_a_system_dll_is_rated_by_its_directory() {
+        let temp =
+            std::env::temp_dir().join(format!("loadwhat-audit-system-dll-{}", std::process::id()));
+        let mut context = context(true);
+        context.app_dir = temp.join("App");
+        context.system_dir = temp.join("System32");
+        fs::create_dir_all(&context.app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&context.system_dir).expect("failed to create system dir");
+        fs::write(context.system_dir.join("version.dll"), b"MZ").expect("failed to write");
+
+        let app_copy = context.app_dir.join("version.dll");
+        let system_copy = context.system_dir.join("version.dll");
+        let writable = Writability::Listed(vec![context.app_dir.clone()]);
+        let risks = (
+            system_dll_risk("version.dll", &app_copy, &context, &writable),
+            system_dll_risk("version.dll", &app_copy, &context, &Writability::Unknown),
+            system_dll_risk("version.dll", &system_copy, &context, &writable),
+            system_dll_risk(
+                "vendor.dll",
+                &context.app_dir.join("vendor.dll"),
+                &context,
+                &writable,
+            ),
+        );
+        let _ = fs::remove_dir_all(temp);
+
+        assert_eq!(
+            risks,
+            (Some(Severity::High), Some(Severity::Low), None, None)
+        );
+    }
+}
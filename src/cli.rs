@@ -1,5 +1,10 @@
 // Parses the public CLI shape and preserves the documented command contract.
 
+use crate::emit::OutputFormat;
+use crate::environment::EnvEdit;
+use crate::graph::{GraphFormat, GraphOptions};
+use crate::search::LoadOptions;
+use crate::tree::TreeOptions;
 use std::env;
 use std::ffi::OsString;
 use std::path::PathBuf;
@@ -8,14 +13,55 @@ use std::path::PathBuf;
 pub enum Command {
     Run(RunOptions),
     Imports(ImportsOptions),
+    Resolve(ResolveOptions),
+    AuditSearch(AuditSearchOptions),
+    PathLint(PathLintOptions),
     Com(ComOptions),
     Help,
 }
 
+impl Command {
+    /// The `--format` the command was given.
+    pub fn output_format(&self) -> OutputFormat {
+        match self {
+            Command::Run(opts) => opts.format,
+            Command::Imports(opts) => opts.format,
+            Command::Resolve(opts) => opts.format,
+            Command::AuditSearch(opts) => opts.format,
+            Command::PathLint(opts) => opts.format,
+            Command::Com(opts) => opts.format,
+            Command::Help => OutputFormat::Text,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ComOptions {
     pub sub: ComSubcommand,
     pub trace: bool,
+    pub sysroot: Option<PathBuf>,
+    pub hives: ComHiveFiles,
+    /// `.reg` exports that replace the registry, applied in order.
+    pub registry_files: Vec<PathBuf>,
+    /// How token lines are written.
+    pub format: OutputFormat,
+}
+
+/// Registry hive files `com` reads instead of the live registry.
+#[derive(Debug, Default)]
+pub struct ComHiveFiles {
+    /// `HKLM\Software` (`Windows\System32\config\SOFTWARE`).
+    pub software: Option<PathBuf>,
+    /// HKCU (`NTUSER.DAT`).
+    pub ntuser: Option<PathBuf>,
+    /// `HKCU\Software\Classes` (`UsrClass.dat`).
+    pub usrclass: Option<PathBuf>,
+}
+
+impl ComHiveFiles {
+    pub fn any(&self) -> bool {
+        self.software.is_some() || self.ntuser.is_some() || self.usrclass.is_some()
+    }
 }
 
 #[derive(Debug)]
@@ -33,7 +79,9 @@ pub enum ComViewArg {
     Both,
 }
 
+/// Parsed on every host, but only read where `run` is built.
 #[derive(Debug)]
+#[cfg_attr(not(windows), allow(dead_code))]
 pub struct RunOptions {
     pub exe_path: PathBuf,
     pub exe_args: Vec<OsString>,
@@ -42,12 +90,68 @@ pub struct RunOptions {
     pub loader_snaps: bool,
     pub trace: bool,
     pub verbose: bool,
+    /// Changes to the environment the target starts with.
+    pub env: Vec<EnvEdit>,
+    /// How token lines are written.
+    pub format: OutputFormat,
+    /// File the JUnit XML report is written to.
+    pub report_junit: Option<PathBuf>,
 }
 
 #[derive(Debug)]
 pub struct ImportsOptions {
     pub module_path: PathBuf,
     pub cwd: Option<PathBuf>,
+    pub sysroot: Option<PathBuf>,
+    /// Host process the module is loaded into; the module is its own
+    /// process image when absent.
+    pub from: Option<PathBuf>,
+    pub load: LoadOptions,
+    /// Host file replacing the machine's `KnownDLLs` list.
+    pub known_dlls: Option<PathBuf>,
+    /// Report every copy of each resolved DLL along the search order.
+    pub shadows: bool,
+    /// Changes to the environment; only PATH affects the search.
+    pub env: Vec<EnvEdit>,
+    /// Print the import closure as a tree instead of the flat walk.
+    pub tree: Option<TreeOptions>,
+    /// Write the import closure as a graph instead of any other output.
+    pub graph: Option<GraphOptions>,
+    /// How token lines are written.
+    pub format: OutputFormat,
+    /// File the JUnit XML report is written to.
+    pub report_junit: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct ResolveOptions {
+    pub dll: String,
+    pub from: PathBuf,
+    pub cwd: Option<PathBuf>,
+    pub sysroot: Option<PathBuf>,
+    pub load: LoadOptions,
+    pub known_dlls: Option<PathBuf>,
+    /// How token lines are written.
+    pub format: OutputFormat,
+}
+
+#[derive(Debug)]
+pub struct AuditSearchOptions {
+    pub module_path: PathBuf,
+    pub cwd: Option<PathBuf>,
+    pub sysroot: Option<PathBuf>,
+    /// Host file listing the directories ordinary users can write to,
+    /// replacing the ACL check.
+    pub writable: Option<PathBuf>,
+    /// How token lines are written.
+    pub format: OutputFormat,
+}
+
+#[derive(Debug)]
+pub struct PathLintOptions {
+    pub sysroot: Option<PathBuf>,
+    /// How token lines are written.
+    pub format: OutputFormat,
 }
 
 pub fn parse() -> Result<Command, String> {
@@ -73,6 +177,9 @@ where
     match sub.as_str() {
         "run" => parse_run(&values[1..]),
         "imports" => parse_imports(&values[1..]),
+        "resolve" => parse_resolve(&values[1..]),
+        "audit-search" => parse_audit_search(&values[1..]),
+        "path-lint" => parse_path_lint(&values[1..]),
         "com" => parse_com(&values[1..]),
         "-h" | "--help" | "help" => Ok(Command::Help),
         other => Err(format!("unknown command: {other}\n\n{}", usage())),
@@ -92,6 +199,9 @@ fn parse_run(values: &[OsString]) -> Result<Command, String> {
     let mut loader_snaps = true;
     let mut trace = false;
     let mut verbose = false;
+    let mut env = Vec::new();
+    let mut format = OutputFormat::Text;
+    let mut report_junit = None;
 
     let mut i = 0usize;
     while i < values.len() {
@@ -99,6 +209,12 @@ fn parse_run(values: &[OsString]) -> Result<Command, String> {
         if !looks_like_run_option(&token) {
             break;
         }
+        if parse_env_option(&token, values, &mut i, &mut env, run_usage)?
+            || parse_format_option(&token, values, &mut i, &mut format, run_usage)?
+        {
+            i += 1;
+            continue;
+        }
 
         match token.as_str() {
             "--cwd" => {
@@ -108,6 +224,16 @@ fn parse_run(values: &[OsString]) -> Result<Command, String> {
                 }
                 cwd = Some(PathBuf::from(values[i].clone()));
             }
+            "--report-junit" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!(
+                        "--report-junit requires a value\n\n{}",
+                        run_usage()
+                    ));
+                }
+                report_junit = Some(PathBuf::from(values[i].clone()));
+            }
             "--timeout" | "--timeout-ms" => {
                 i += 1;
                 if i >= values.len() {
@@ -164,6 +290,9 @@ fn parse_run(values: &[OsString]) -> Result<Command, String> {
         loader_snaps,
         trace,
         verbose,
+        env,
+        format,
+        report_junit,
     }))
 }
 
@@ -174,10 +303,29 @@ fn parse_imports(values: &[OsString]) -> Result<Command, String> {
 
     let module_path = PathBuf::from(values[0].clone());
     let mut cwd = None;
+    let mut sysroot = None;
+    let mut from = None;
+    let mut load = LoadOptions::default();
+    let mut known_dlls = None;
+    let mut shadows = false;
+    let mut env = Vec::new();
+    let mut tree = false;
+    let mut tree_options = TreeOptions::default();
+    let mut graph = None;
+    let mut cluster_system = false;
+    let mut format = OutputFormat::Text;
+    let mut report_junit = None;
 
     let mut i = 1usize;
     while i < values.len() {
         let token = values[i].to_string_lossy().to_string();
+        if parse_load_option(&token, values, &mut i, &mut from, &mut load, usage)?
+            || parse_env_option(&token, values, &mut i, &mut env, usage)?
+            || parse_format_option(&token, values, &mut i, &mut format, usage)?
+        {
+            i += 1;
+            continue;
+        }
         match token.as_str() {
             "--cwd" => {
                 i += 1;
@@ -186,6 +334,58 @@ fn parse_imports(values: &[OsString]) -> Result<Command, String> {
                 }
                 cwd = Some(PathBuf::from(values[i].clone()));
             }
+            "--report-junit" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--report-junit requires a value\n\n{}", usage()));
+                }
+                report_junit = Some(PathBuf::from(values[i].clone()));
+            }
+            "--sysroot" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--sysroot requires a value\n\n{}", usage()));
+                }
+                sysroot = Some(PathBuf::from(values[i].clone()));
+            }
+            "--known-dlls" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--known-dlls requires a value\n\n{}", usage()));
+                }
+                known_dlls = Some(PathBuf::from(values[i].clone()));
+            }
+            "--shadows" => shadows = true,
+            "--tree" => tree = true,
+            "--depth" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--depth requires a value\n\n{}", usage()));
+                }
+                let raw = values[i].to_string_lossy();
+                let depth = raw
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|depth| *depth > 0)
+                    .ok_or_else(|| format!("invalid --depth value: {raw}\n\n{}", usage()))?;
+                tree_options.max_depth = Some(depth);
+            }
+            "--graph" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--graph requires a value\n\n{}", usage()));
+                }
+                let raw = values[i].to_string_lossy();
+                graph = Some(GraphFormat::parse(&raw).ok_or_else(|| {
+                    format!(
+                        "invalid --graph value: {raw} (expected dot or mermaid)\n\n{}",
+                        usage()
+                    )
+                })?);
+            }
+            "--cluster-system" => cluster_system = true,
+            "--only-problems" => tree_options.only_problems = true,
+            "--hide-system" => tree_options.hide_system = true,
             "--quiet" | "--verbose" | "--strict" => {}
             unknown => {
                 return Err(format!("unknown imports option: {unknown}\n\n{}", usage()));
@@ -195,7 +395,333 @@ fn parse_imports(values: &[OsString]) -> Result<Command, String> {
         i += 1;
     }
 
-    Ok(Command::Imports(ImportsOptions { module_path, cwd }))
+    load.validate()
+        .map_err(|err| format!("{err}\n\n{}", usage()))?;
+    if !tree && tree_options != TreeOptions::default() {
+        return Err(format!(
+            "--depth, --only-problems and --hide-system require --tree\n\n{}",
+            usage()
+        ));
+    }
+    if graph.is_none() && cluster_system {
+        return Err(format!("--cluster-system requires --graph\n\n{}", usage()));
+    }
+    if format != OutputFormat::Text && (tree || graph.is_some()) {
+        return Err(format!(
+            "--tree and --graph write text and cannot be combined with --format\n\n{}",
+            usage()
+        ));
+    }
+    if graph.is_some() && (tree || shadows) {
+        return Err(format!(
+            "--graph cannot be combined with --tree or --shadows\n\n{}",
+            usage()
+        ));
+    }
+    Ok(Command::Imports(ImportsOptions {
+        module_path,
+        cwd,
+        sysroot,
+        from,
+        load,
+        known_dlls,
+        shadows,
+        env,
+        tree: tree.then_some(tree_options),
+        graph: graph.map(|format| GraphOptions {
+            format,
+            cluster_system,
+        }),
+        format,
+        report_junit,
+    }))
+}
+
+fn parse_resolve(values: &[OsString]) -> Result<Command, String> {
+    let mut dll = None;
+    let mut cwd = None;
+    let mut sysroot = None;
+    let mut from = None;
+    let mut load = LoadOptions::default();
+    let mut known_dlls = None;
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0usize;
+    while i < values.len() {
+        let token = values[i].to_string_lossy().to_string();
+        if parse_load_option(&token, values, &mut i, &mut from, &mut load, resolve_usage)?
+            || parse_format_option(&token, values, &mut i, &mut format, resolve_usage)?
+        {
+            i += 1;
+            continue;
+        }
+        match token.as_str() {
+            "--cwd" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--cwd requires a value\n\n{}", resolve_usage()));
+                }
+                cwd = Some(PathBuf::from(values[i].clone()));
+            }
+            "--sysroot" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!("--sysroot requires a value\n\n{}", resolve_usage()));
+                }
+                sysroot = Some(PathBuf::from(values[i].clone()));
+            }
+            "--known-dlls" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!(
+                        "--known-dlls requires a value\n\n{}",
+                        resolve_usage()
+                    ));
+                }
+                known_dlls = Some(PathBuf::from(values[i].clone()));
+            }
+            unknown if unknown.starts_with('-') && unknown.len() > 1 => {
+                return Err(format!(
+                    "unknown resolve option: {unknown}\n\n{}",
+                    resolve_usage()
+                ));
+            }
+            _ if dll.is_none() => dll = Some(token),
+            extra => {
+                return Err(format!(
+                    "unexpected resolve argument: {extra}\n\n{}",
+                    resolve_usage()
+                ));
+            }
+        }
+        i += 1;
+    }
+
+    let dll = dll.ok_or_else(|| format!("missing <dll>\n\n{}", resolve_usage()))?;
+    let from =
+        from.ok_or_else(|| format!("resolve requires --from <module>\n\n{}", resolve_usage()))?;
+    load.validate()
+        .map_err(|err| format!("{err}\n\n{}", resolve_usage()))?;
+    reject_sarif(format, "resolve", resolve_usage)?;
+    Ok(Command::Resolve(ResolveOptions {
+        dll,
+        from,
+        cwd,
+        sysroot,
+        load,
+        known_dlls,
+        format,
+    }))
+}
+
+fn parse_audit_search(values: &[OsString]) -> Result<Command, String> {
+    if values.is_empty() {
+        return Err(format!("missing <exe>\n\n{}", audit_search_usage()));
+    }
+
+    let module_path = PathBuf::from(values[0].clone());
+    let mut cwd = None;
+    let mut sysroot = None;
+    let mut writable = None;
+    let mut format = OutputFormat::Text;
+
+    let mut i = 1usize;
+    while i < values.len() {
+        let token = values[i].to_string_lossy().to_string();
+        if parse_format_option(&token, values, &mut i, &mut format, audit_search_usage)? {
+            i += 1;
+            continue;
+        }
+        match token.as_str() {
+            "--cwd" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!(
+                        "--cwd requires a value\n\n{}",
+                        audit_search_usage()
+                    ));
+                }
+                cwd = Some(PathBuf::from(values[i].clone()));
+            }
+            "--sysroot" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!(
+                        "--sysroot requires a value\n\n{}",
+                        audit_search_usage()
+                    ));
+                }
+                sysroot = Some(PathBuf::from(values[i].clone()));
+            }
+            "--writable" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!(
+                        "--writable requires a value\n\n{}",
+                        audit_search_usage()
+                    ));
+                }
+                writable = Some(PathBuf::from(values[i].clone()));
+            }
+            unknown => {
+                return Err(format!(
+                    "unknown audit-search option: {unknown}\n\n{}",
+                    audit_search_usage()
+                ));
+            }
+        }
+
+        i += 1;
+    }
+
+    reject_sarif(format, "audit-search", audit_search_usage)?;
+    Ok(Command::AuditSearch(AuditSearchOptions {
+        module_path,
+        cwd,
+        sysroot,
+        writable,
+        format,
+    }))
+}
+
+fn parse_path_lint(values: &[OsString]) -> Result<Command, String> {
+    let mut sysroot = None;
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0usize;
+    while i < values.len() {
+        let token = values[i].to_string_lossy().to_string();
+        if parse_format_option(&token, values, &mut i, &mut format, path_lint_usage)? {
+            i += 1;
+            continue;
+        }
+        match token.as_str() {
+            "--sysroot" => {
+                i += 1;
+                if i >= values.len() {
+                    return Err(format!(
+                        "--sysroot requires a value\n\n{}",
+                        path_lint_usage()
+                    ));
+                }
+                sysroot = Some(PathBuf::from(values[i].clone()));
+            }
+            unknown => {
+                return Err(format!(
+                    "unknown path-lint option: {unknown}\n\n{}",
+                    path_lint_usage()
+                ));
+            }
+        }
+
+        i += 1;
+    }
+
+    reject_sarif(format, "path-lint", path_lint_usage)?;
+    Ok(Command::PathLint(PathLintOptions { sysroot, format }))
+}
+
+/// Handles the load-simulation options `imports` and `resolve` share.
+/// Returns false when `token` is not one of them.
+fn parse_load_option(
+    token: &str,
+    values: &[OsString],
+    i: &mut usize,
+    from: &mut Option<PathBuf>,
+    load: &mut LoadOptions,
+    usage: fn() -> String,
+) -> Result<bool, String> {
+    if !matches!(
+        token,
+        "--from" | "--flags" | "--default-dirs" | "--dll-dir" | "--user-dir"
+    ) {
+        return Ok(false);
+    }
+    *i += 1;
+    let Some(value) = values.get(*i) else {
+        return Err(format!("{token} requires a value\n\n{}", usage()));
+    };
+    match token {
+        "--from" => *from = Some(PathBuf::from(value)),
+        "--flags" | "--default-dirs" => {
+            let flags = LoadOptions::parse_flags(&value.to_string_lossy())
+                .map_err(|err| format!("{err}\n\n{}", usage()))?;
+            if token == "--flags" {
+                load.flags |= flags;
+            } else {
+                load.default_dirs |= flags;
+            }
+        }
+        "--dll-dir" => load.dll_directory = Some(PathBuf::from(value)),
+        _ => load.user_dirs.push(PathBuf::from(value)),
+    }
+    Ok(true)
+}
+
+/// Handles the environment options `run` and `imports` share. Returns false
+/// when `token` is not one of them.
+fn parse_env_option(
+    token: &str,
+    values: &[OsString],
+    i: &mut usize,
+    env: &mut Vec<EnvEdit>,
+    usage: fn() -> String,
+) -> Result<bool, String> {
+    if !matches!(
+        token,
+        "--env" | "--unset" | "--path-prepend" | "--path-append"
+    ) {
+        return Ok(false);
+    }
+    *i += 1;
+    let Some(value) = values.get(*i) else {
+        return Err(format!("{token} requires a value\n\n{}", usage()));
+    };
+    env.push(match token {
+        "--env" => EnvEdit::parse_set(value).map_err(|err| format!("{err}\n\n{}", usage()))?,
+        "--unset" => EnvEdit::Unset(value.clone()),
+        "--path-prepend" => EnvEdit::PathPrepend(value.clone()),
+        _ => EnvEdit::PathAppend(value.clone()),
+    });
+    Ok(true)
+}
+
+/// Handles `--format`, which every command but `help` takes. Returns false
+/// when `token` is not it.
+fn parse_format_option(
+    token: &str,
+    values: &[OsString],
+    i: &mut usize,
+    format: &mut OutputFormat,
+    usage: fn() -> String,
+) -> Result<bool, String> {
+    if token != "--format" {
+        return Ok(false);
+    }
+    *i += 1;
+    let Some(value) = values.get(*i) else {
+        return Err(format!("--format requires a value\n\n{}", usage()));
+    };
+    let raw = value.to_string_lossy();
+    *format = OutputFormat::parse(&raw).ok_or_else(|| {
+        format!(
+            "invalid --format value: {raw} (expected text, ndjson or sarif)\n\n{}",
+            usage()
+        )
+    })?;
+    Ok(true)
+}
+
+/// SARIF reports the findings of `run`, `imports` and `com`; the other
+/// commands have none it maps.
+fn reject_sarif(format: OutputFormat, command: &str, usage: fn() -> String) -> Result<(), String> {
+    if format == OutputFormat::Sarif {
+        return Err(format!(
+            "--format sarif is not supported by {command}\n\n{}",
+            usage()
+        ));
+    }
+    Ok(())
 }
 
 fn parse_com(values: &[OsString]) -> Result<Command, String> {
@@ -208,11 +734,19 @@ fn parse_com(values: &[OsString]) -> Result<Command, String> {
 
     let mut trace = false;
     let mut view: Option<ComViewArg> = None;
+    let mut sysroot = None;
+    let mut hives = ComHiveFiles::default();
+    let mut registry_files = Vec::new();
+    let mut format = OutputFormat::Text;
     let mut positionals: Vec<String> = Vec::new();
 
     let mut i = 0usize;
     while i < rest.len() {
         let token = rest[i].to_string_lossy().to_string();
+        if parse_format_option(&token, rest, &mut i, &mut format, com_usage)? {
+            i += 1;
+            continue;
+        }
         if token.starts_with('-') && token.len() > 1 {
             match token.as_str() {
                 "--trace" | "--verbose" | "-v" => {
@@ -239,6 +773,35 @@ fn parse_com(values: &[OsString]) -> Result<Command, String> {
                         }
                     });
                 }
+                "--sysroot" => {
+                    i += 1;
+                    if i >= rest.len() {
+                        return Err(format!("--sysroot requires a value\n\n{}", com_usage()));
+                    }
+                    sysroot = Some(PathBuf::from(rest[i].clone()));
+                }
+                "--registry-file" => {
+                    i += 1;
+                    if i >= rest.len() {
+                        return Err(format!(
+                            "--registry-file requires a value\n\n{}",
+                            com_usage()
+                        ));
+                    }
+                    registry_files.push(PathBuf::from(rest[i].clone()));
+                }
+                "--software-hive" | "--ntuser-hive" | "--usrclass-hive" => {
+                    i += 1;
+                    if i >= rest.len() {
+                        return Err(format!("{token} requires a value\n\n{}", com_usage()));
+                    }
+                    let path = Some(PathBuf::from(rest[i].clone()));
+                    match token.as_str() {
+                        "--software-hive" => hives.software = path,
+                        "--ntuser-hive" => hives.ntuser = path,
+                        _ => hives.usrclass = path,
+                    }
+                }
                 unknown => {
                     return Err(format!("unknown com option: {unknown}\n\n{}", com_usage()));
                 }
@@ -312,7 +875,21 @@ fn parse_com(values: &[OsString]) -> Result<Command, String> {
         }
     };
 
-    Ok(Command::Com(ComOptions { sub, trace }))
+    if !registry_files.is_empty() && hives.any() {
+        return Err(format!(
+            "--registry-file cannot be combined with hive file options\n\n{}",
+            com_usage()
+        ));
+    }
+
+    Ok(Command::Com(ComOptions {
+        sub,
+        trace,
+        sysroot,
+        hives,
+        registry_files,
+        format,
+    }))
 }
 
 fn single_positional(
@@ -368,7 +945,16 @@ pub fn usage() -> String {
     out.push_str("loadwhat - diagnose Windows DLL loading failures\n\n");
     out.push_str("Usage:\n");
     out.push_str("  loadwhat run [OPTIONS] <TARGET> [TARGET_ARGS...]\n");
-    out.push_str("  loadwhat imports <exe_or_dll> [--cwd <dir>]\n");
+    out.push_str(
+        "  loadwhat imports <exe_or_dll> [--cwd <dir>] [--sysroot <dir>] [ENV_OPTIONS] [LOAD_OPTIONS]\n",
+    );
+    out.push_str(
+        "  loadwhat resolve <dll> --from <module> [--cwd <dir>] [--sysroot <dir>] [LOAD_OPTIONS]\n",
+    );
+    out.push_str(
+        "  loadwhat audit-search <exe> [--cwd <dir>] [--sysroot <dir>] [--writable <file>]\n",
+    );
+    out.push_str("  loadwhat path-lint [--sysroot <dir>]\n");
     out.push_str("  loadwhat com clsid [OPTIONS] <{CLSID}>\n");
     out.push_str("  loadwhat com progid [OPTIONS] <PROGID>\n");
     out.push_str("  loadwhat com server [OPTIONS] <PATH>\n");
@@ -382,6 +968,18 @@ pub fn usage() -> String {
     out.push_str("  -v, --verbose     Print detailed diagnostic output\n");
     out.push_str("  --quiet           Disable verbose runtime detail\n");
     out.push_str("  --no-loader-snaps Disable loader-snaps Phase C search\n");
+    out.push_str("\nImports and com options:\n");
+    out.push_str("  --sysroot <dir>   Analyze the Windows volume mounted at <dir>\n");
+    out.push_str("  --shadows         imports: list every copy of each resolved DLL\n");
+    out.push_str("  --tree            imports: print the dependency tree\n");
+    out.push_str("  --depth <n>       imports --tree: expand <n> levels of imports\n");
+    out.push_str("  --only-problems   imports --tree: keep branches with a missing or bad DLL\n");
+    out.push_str("  --hide-system     imports --tree: drop clean Windows-directory branches\n");
+    out.push_str("  --graph <format>  imports: write the dependency graph as dot or mermaid\n");
+    out.push_str("  --cluster-system  imports --graph: collapse Windows-directory modules\n");
+    push_env_options_usage(&mut out);
+    push_load_options_usage(&mut out);
+    push_output_options_usage(&mut out);
     out.push_str("\nBehavior:\n");
     out.push_str("  - Loader-snaps Phase C search is enabled by default\n");
     out.push_str("  - Use --no-loader-snaps to disable it\n");
@@ -394,9 +992,86 @@ fn run_usage() -> String {
     let mut out = String::new();
     out.push_str("Usage:\n");
     out.push_str("  loadwhat run [OPTIONS] <TARGET> [TARGET_ARGS...]\n");
+    push_env_options_usage(&mut out);
+    push_output_options_usage(&mut out);
     out
 }
 
+/// Options that change the environment, shared by `run` and `imports`.
+fn push_env_options_usage(out: &mut String) {
+    out.push_str("\nEnvironment options (run, imports; applied in order):\n");
+    out.push_str("  --env <NAME=VALUE>     Set a variable\n");
+    out.push_str("  --unset <NAME>         Remove a variable\n");
+    out.push_str("  --path-prepend <dir>   Put <dir> at the front of PATH (repeatable)\n");
+    out.push_str("  --path-append <dir>    Put <dir> at the end of PATH (repeatable)\n");
+}
+
+/// `--format`, which every command takes.
+fn push_output_options_usage(out: &mut String) {
+    out.push_str("\nOutput options (all commands):\n");
+    out.push_str("  --format <text|ndjson> Write token lines as text (default) or JSON objects\n");
+    out.push_str("  --format sarif         run, imports, com: write the findings as a SARIF log\n");
+    out.push_str(
+        "  --report-junit <file>  run, imports: also write a JUnit XML report to <file>\n",
+    );
+}
+
+fn resolve_usage() -> String {
+    let mut out = String::new();
+    out.push_str("Usage:\n");
+    out.push_str("  loadwhat resolve <dll> --from <module> [OPTIONS]\n");
+    out.push_str("\nResolve options:\n");
+    out.push_str("  --cwd <dir>       Current directory of the loading process\n");
+    out.push_str("  --sysroot <dir>   Analyze the Windows volume mounted at <dir>\n");
+    push_load_options_usage(&mut out);
+    push_output_options_usage(&mut out);
+    out.push_str("\nBehavior:\n");
+    out.push_str("  - prints each SEARCH_PATH candidate and the RESOLVE result\n");
+    out.push_str("  - <module> is the process image; its directory is the application directory\n");
+    out
+}
+
+fn audit_search_usage() -> String {
+    let mut out = String::new();
+    out.push_str("Usage:\n");
+    out.push_str("  loadwhat audit-search <exe> [OPTIONS]\n");
+    out.push_str("\nAudit-search options:\n");
+    out.push_str("  --cwd <dir>       Current directory the process starts in\n");
+    out.push_str("  --sysroot <dir>   Analyze the Windows volume mounted at <dir>\n");
+    out.push_str("  --writable <file> Directories users can write to, one per line\n");
+    push_output_options_usage(&mut out);
+    out.push_str("\nBehavior:\n");
+    out.push_str("  - prints one SEARCH_RISK line per finding, with a severity\n");
+    out.push_str("  - without --writable, directory ACLs are read on Windows hosts\n");
+    out
+}
+
+fn path_lint_usage() -> String {
+    let mut out = String::new();
+    out.push_str("Usage:\n");
+    out.push_str("  loadwhat path-lint [--sysroot <dir>]\n");
+    out.push_str("\nPath-lint options:\n");
+    out.push_str("  --sysroot <dir>   Lint the Path of the Windows volume mounted at <dir>\n");
+    push_output_options_usage(&mut out);
+    out.push_str("\nBehavior:\n");
+    out.push_str("  - prints one PATH_ISSUE line per problem with the PATH value\n");
+    out
+}
+
+/// Options that model how a module is loaded, shared by `imports` and
+/// `resolve`.
+fn push_load_options_usage(out: &mut String) {
+    out.push_str("\nLoad options (imports, resolve):\n");
+    out.push_str("  --from <module>        Host process image that loads the module\n");
+    out.push_str(
+        "  --flags <FLAGS>        LoadLibraryEx flags, e.g. SEARCH_SYSTEM32|SEARCH_USER_DIRS\n",
+    );
+    out.push_str("  --default-dirs <FLAGS> SetDefaultDllDirectories flags\n");
+    out.push_str("  --dll-dir <dir>        SetDllDirectory directory\n");
+    out.push_str("  --user-dir <dir>       AddDllDirectory directory (repeatable)\n");
+    out.push_str("  --known-dlls <file>    KnownDLLs list to use instead of the machine's\n");
+}
+
 fn com_usage() -> String {
     let mut out = String::new();
     out.push_str("Usage:\n");
@@ -410,9 +1085,24 @@ fn com_usage() -> String {
     out.push_str("  --trace           Print supporting COM trace tokens\n");
     out.push_str("  --summary         Print summary output (default)\n");
     out.push_str("  -v, --verbose     Same as --trace for com commands\n");
+    out.push_str("  --sysroot <dir>   Use the Windows volume and registry mounted at <dir>\n");
+    out.push_str("  --software-hive <file>  Read HKLM\\Software from a SOFTWARE hive file\n");
+    out.push_str("  --ntuser-hive <file>    Read HKCU from an NTUSER.DAT hive file\n");
+    out.push_str(
+        "  --usrclass-hive <file>  Read HKCU\\Software\\Classes from a UsrClass.dat file\n",
+    );
+    out.push_str("  --registry-file <file>  Read the registry from a .reg export (repeatable)\n");
+    push_output_options_usage(&mut out);
     out.push_str("\nBehavior:\n");
     out.push_str("  - com audit derives the registry view from the target image\n");
     out.push_str("  - a braced GUID audit query is a CLSID; anything else is a ProgID\n");
+    out.push_str(
+        "  - any hive file option replaces the live registry; hives not given read as empty\n",
+    );
+    out.push_str(
+        "  - --registry-file replaces the live registry and cannot be combined with hive files\n",
+    );
+    out.push_str("  - HKEY_CLASSES_ROOT keys in a .reg file count as HKLM registrations\n");
     out
 }
 
@@ -425,7 +1115,17 @@ mod tests {
     use std::ffi::OsString;
     use std::path::PathBuf;
 
-    use super::{parse_from, Command, ImportsOptions, RunOptions};
+    use super::{
+        parse_from, AuditSearchOptions, Command, ImportsOptions, ResolveOptions, RunOptions,
+    };
+    use crate::emit::OutputFormat;
+    use crate::environment::EnvEdit;
+    use crate::graph::{GraphFormat, GraphOptions};
+    use crate::search::{
+        LOAD_LIBRARY_SEARCH_APPLICATION_DIR, LOAD_LIBRARY_SEARCH_SYSTEM32,
+        LOAD_LIBRARY_SEARCH_USER_DIRS, LOAD_WITH_ALTERED_SEARCH_PATH,
+    };
+    use crate::tree::TreeOptions;
 
     fn parse_run(args: &[&str]) -> RunOptions {
         let mut values = vec!["loadwhat", "run"];
@@ -500,6 +1200,43 @@ mod tests {
         assert!(err.contains("loadwhat run [OPTIONS] <TARGET> [TARGET_ARGS...]"));
     }
 
+    #[test]
+    fn env_options_are_collected_in_order_before_the_target() {
+        let opts = parse_run(&[
+            "--path-prepend",
+            r"C:\deps",
+            "--env",
+            "APP_MODE=debug",
+            "--unset",
+            "TEMP",
+            "--path-append",
+            r"C:\late",
+            "app.exe",
+            "--env",
+        ]);
+        assert_eq!(
+            opts.env,
+            vec![
+                EnvEdit::PathPrepend(OsString::from(r"C:\deps")),
+                EnvEdit::Set(OsString::from("APP_MODE"), OsString::from("debug")),
+                EnvEdit::Unset(OsString::from("TEMP")),
+                EnvEdit::PathAppend(OsString::from(r"C:\late")),
+            ]
+        );
+        assert_eq!(opts.exe_args, vec![OsString::from("--env")]);
+
+        let opts = parse_imports(&[r"C:\app\app.exe", "--path-prepend", r"C:\deps"]);
+        assert_eq!(
+            opts.env,
+            vec![EnvEdit::PathPrepend(OsString::from(r"C:\deps"))]
+        );
+
+        let err = parse_run_err(&["--env", "NO_EQUALS", "app.exe"]);
+        assert!(err.contains("--env expects NAME=VALUE"));
+        let err = parse_run_err(&["--path-append"]);
+        assert!(err.contains("--path-append requires a value"));
+    }
+
     #[test]
     fn missing_cwd_value_reports_error() {
         let err = parse_run_err(&["--cwd"]);
@@ -625,6 +1362,346 @@ mod tests {
         assert_eq!(opts.cwd, Some(PathBuf::from(r"C:\work")));
     }
 
+    #[test]
+    fn imports_parses_optional_sysroot() {
+        let opts = parse_imports(&[r"C:\tool\app.exe", "--sysroot", "/mnt/win"]);
+        assert_eq!(opts.sysroot, Some(PathBuf::from("/mnt/win")));
+        let err = parse_from(["loadwhat", "imports", r"C:\tool\app.exe", "--sysroot"]).unwrap_err();
+        assert!(err.contains("--sysroot requires a value"));
+    }
+
+    #[test]
+    fn imports_parses_load_options() {
+        let opts = parse_imports(&[
+            r"C:\tool\plugin.dll",
+            "--from",
+            r"C:\host\host.exe",
+            "--flags",
+            "LOAD_WITH_ALTERED_SEARCH_PATH",
+        ]);
+        assert_eq!(opts.from, Some(PathBuf::from(r"C:\host\host.exe")));
+        assert_eq!(opts.load.flags, LOAD_WITH_ALTERED_SEARCH_PATH);
+    }
+
+    #[test]
+    fn imports_rejects_altered_search_path_with_search_flags() {
+        let err = parse_from([
+            "loadwhat",
+            "imports",
+            r"C:\tool\plugin.dll",
+            "--flags",
+            "LOAD_WITH_ALTERED_SEARCH_PATH|SEARCH_SYSTEM32",
+        ])
+        .unwrap_err();
+        assert!(err.contains("LOAD_WITH_ALTERED_SEARCH_PATH"));
+    }
+
+    fn parse_resolve(args: &[&str]) -> ResolveOptions {
+        let mut values = vec!["loadwhat", "resolve"];
+        values.extend_from_slice(args);
+        match parse_from(values).unwrap() {
+            Command::Resolve(opts) => opts,
+            _ => panic!("expected resolve command"),
+        }
+    }
+
+    fn parse_resolve_err(args: &[&str]) -> String {
+        let mut values = vec!["loadwhat", "resolve"];
+        values.extend_from_slice(args);
+        parse_from(values).unwrap_err()
+    }
+
+    fn parse_audit_search(args: &[&str]) -> Result<AuditSearchOptions, String> {
+        let mut values = vec!["loadwhat", "audit-search"];
+        values.extend_from_slice(args);
+        match parse_from(values)? {
+            Command::AuditSearch(opts) => Ok(opts),
+            _ => panic!("expected audit-search command"),
+        }
+    }
+
+    #[test]
+    fn audit_search_parses_writable_list_and_cwd() {
+        let opts = parse_audit_search(&[
+            r"C:\app\app.exe",
+            "--writable",
+            "writable.txt",
+            "--cwd",
+            r"C:\Users\Public",
+        ])
+        .unwrap();
+        assert_eq!(opts.module_path, PathBuf::from(r"C:\app\app.exe"));
+        assert_eq!(opts.writable, Some(PathBuf::from("writable.txt")));
+        assert_eq!(opts.cwd, Some(PathBuf::from(r"C:\Users\Public")));
+        assert_eq!(opts.sysroot, None);
+
+        let err = parse_audit_search(&[r"C:\app\app.exe", "--writable"]).unwrap_err();
+        assert!(err.contains("--writable requires a value"));
+        let err = parse_audit_search(&[r"C:\app\app.exe", "--shadows"]).unwrap_err();
+        assert!(err.contains("unknown audit-search option: --shadows"));
+        assert!(parse_audit_search(&[])
+            .unwrap_err()
+            .contains("missing <exe>"));
+    }
+
+    #[test]
+    fn path_lint_accepts_only_sysroot() {
+        let parse = |args: &[&str]| {
+            let mut values = vec!["loadwhat", "path-lint"];
+            values.extend_from_slice(args);
+            parse_from(values)
+        };
+        match parse(&["--sysroot", "/mnt/win"]).unwrap() {
+            Command::PathLint(opts) => assert_eq!(opts.sysroot, Some(PathBuf::from("/mnt/win"))),
+            _ => panic!("expected path-lint command"),
+        }
+        match parse(&[]).unwrap() {
+            Command::PathLint(opts) => assert_eq!(opts.sysroot, None),
+            _ => panic!("expected path-lint command"),
+        }
+        assert!(parse(&["--sysroot"])
+            .unwrap_err()
+            .contains("--sysroot requires a value"));
+        assert!(parse(&["app.exe"])
+            .unwrap_err()
+            .contains("unknown path-lint option: app.exe"));
+    }
+
+    #[test]
+    fn resolve_collects_flags_and_user_dirs() {
+        let opts = parse_resolve(&[
+            "--from",
+            r"C:\host\host.exe",
+            "--flags",
+            "SEARCH_APPLICATION_DIR|0x800",
+            "--user-dir",
+            r"C:\one",
+            "--user-dir",
+            r"C:\two",
+            "--dll-dir",
+            r"C:\dlls",
+            "foo.dll",
+        ]);
+        assert_eq!(opts.dll, "foo.dll");
+        assert_eq!(opts.from, PathBuf::from(r"C:\host\host.exe"));
+        assert_eq!(
+            opts.load.flags,
+            LOAD_LIBRARY_SEARCH_APPLICATION_DIR | LOAD_LIBRARY_SEARCH_SYSTEM32
+        );
+        assert_eq!(
+            opts.load.user_dirs,
+            vec![PathBuf::from(r"C:\one"), PathBuf::from(r"C:\two")]
+        );
+        assert_eq!(opts.load.dll_directory, Some(PathBuf::from(r"C:\dlls")));
+    }
+
+    #[test]
+    fn resolve_parses_default_dirs() {
+        let opts = parse_resolve(&[
+            "foo.dll",
+            "--from",
+            r"C:\host\host.exe",
+            "--default-dirs",
+            "SEARCH_USER_DIRS",
+        ]);
+        assert_eq!(opts.load.default_dirs, LOAD_LIBRARY_SEARCH_USER_DIRS);
+    }
+
+    #[test]
+    fn resolve_and_imports_parse_known_dlls_list() {
+        let opts = parse_resolve(&[
+            "foo.dll",
+            "--from",
+            r"C:\host\host.exe",
+            "--known-dlls",
+            "known.txt",
+        ]);
+        assert_eq!(opts.known_dlls, Some(PathBuf::from("known.txt")));
+        let opts = parse_imports(&[r"C:\tool\app.exe", "--known-dlls", "known.txt"]);
+        assert_eq!(opts.known_dlls, Some(PathBuf::from("known.txt")));
+        let err = parse_resolve_err(&["foo.dll", "--from", r"C:\host\host.exe", "--known-dlls"]);
+        assert!(err.contains("--known-dlls requires a value"));
+    }
+
+    #[test]
+    fn imports_parses_shadows_flag() {
+        assert!(!parse_imports(&[r"C:\tool\app.exe"]).shadows);
+        assert!(parse_imports(&[r"C:\tool\app.exe", "--shadows"]).shadows);
+    }
+
+    #[test]
+    fn imports_tree_filters_require_tree() {
+        assert_eq!(parse_imports(&[r"C:\tool\app.exe"]).tree, None);
+        let opts = parse_imports(&[
+            r"C:\tool\app.exe",
+            "--only-problems",
+            "--tree",
+            "--depth",
+            "3",
+            "--hide-system",
+        ]);
+        assert_eq!(
+            opts.tree,
+            Some(TreeOptions {
+                max_depth: Some(3),
+                only_problems: true,
+                hide_system: true,
+            })
+        );
+
+        let err =
+            parse_from(["loadwhat", "imports", r"C:\tool\app.exe", "--hide-system"]).unwrap_err();
+        assert!(err.contains("require --tree"));
+        let err = parse_from([
+            "loadwhat",
+            "imports",
+            r"C:\tool\app.exe",
+            "--tree",
+            "--depth",
+            "0",
+        ])
+        .unwrap_err();
+        assert!(err.contains("invalid --depth value: 0"));
+    }
+
+    #[test]
+    fn imports_graph_takes_a_format_and_excludes_other_views() {
+        assert_eq!(parse_imports(&[r"C:\tool\app.exe"]).graph, None);
+        let opts = parse_imports(&[r"C:\tool\app.exe", "--cluster-system", "--graph", "Mermaid"]);
+        assert_eq!(
+            opts.graph,
+            Some(GraphOptions {
+                format: GraphFormat::Mermaid,
+                cluster_system: true,
+            })
+        );
+
+        let err =
+            parse_from(["loadwhat", "imports", r"C:\tool\app.exe", "--graph", "svg"]).unwrap_err();
+        assert!(err.contains("invalid --graph value: svg"));
+        let err = parse_from([
+            "loadwhat",
+            "imports",
+            r"C:\tool\app.exe",
+            "--cluster-system",
+        ])
+        .unwrap_err();
+        assert!(err.contains("--cluster-system requires --graph"));
+        let err = parse_from([
+            "loadwhat",
+            "imports",
+            r"C:\tool\app.exe",
+            "--graph",
+            "dot",
+            "--tree",
+        ])
+        .unwrap_err();
+        assert!(err.contains("--graph cannot be combined"));
+    }
+
+    #[test]
+    fn format_option_is_accepted_by_every_command() {
+        let ndjson = Some(OutputFormat::Ndjson);
+        let format = |args: &[&str]| {
+            let mut argv = vec!["loadwhat"];
+            argv.extend_from_slice(args);
+            parse_from(argv).ok().map(|command| command.output_format())
+        };
+
+        assert_eq!(format(&["run", "--format", "ndjson", "app.exe"]), ndjson);
+        assert_eq!(
+            format(&["imports", "app.exe", "--format", "NDJSON"]),
+            ndjson
+        );
+        assert_eq!(
+            format(&["resolve", "a.dll", "--from", "app.exe", "--format", "ndjson"]),
+            ndjson
+        );
+        assert_eq!(
+            format(&["audit-search", "app.exe", "--format", "ndjson"]),
+            ndjson
+        );
+        assert_eq!(format(&["path-lint", "--format", "ndjson"]), ndjson);
+        assert_eq!(
+            format(&["com", "progid", "--format", "ndjson", "A.B"]),
+            ndjson
+        );
+        assert_eq!(format(&["imports", "app.exe"]), Some(OutputFormat::Text));
+        assert_eq!(
+            format(&["run", "app.exe", "--format", "ndjson"]),
+            Some(OutputFormat::Text)
+        );
+
+        let err = parse_from(["loadwhat", "path-lint", "--format", "json"]).unwrap_err();
+        assert!(err.contains("invalid --format value: json (expected text, ndjson or sarif)"));
+        let err = parse_from([
+            "loadwhat", "imports", "app.exe", "--tree", "--format", "ndjson",
+        ])
+        .unwrap_err();
+        assert!(err.contains("cannot be combined with --format"));
+    }
+
+    #[test]
+    fn report_junit_takes_a_file_for_run_and_imports() {
+        let run = parse_run(&["--report-junit", "out.xml", "app.exe", "--report-junit"]);
+        assert_eq!(run.report_junit, Some(PathBuf::from("out.xml")));
+        assert_eq!(run.exe_args, vec![OsString::from("--report-junit")]);
+        assert_eq!(parse_run(&["app.exe"]).report_junit, None);
+
+        let imports = parse_imports(&[r"C:\tool\app.exe", "--report-junit", r"C:\out\a.xml"]);
+        assert_eq!(imports.report_junit, Some(PathBuf::from(r"C:\out\a.xml")));
+
+        let err = parse_run_err(&["--report-junit"]);
+        assert!(err.contains("--report-junit requires a value"));
+        let err =
+            parse_from(["loadwhat", "resolve", "a.dll", "--report-junit", "x.xml"]).unwrap_err();
+        assert!(err.contains("unknown resolve option: --report-junit"));
+    }
+
+    #[test]
+    fn sarif_format_is_limited_to_commands_with_findings() {
+        let sarif = |args: &[&str]| {
+            let mut argv = vec!["loadwhat"];
+            argv.extend_from_slice(args);
+            argv.extend_from_slice(&["--format", "sarif"]);
+            parse_from(argv).map(|command| command.output_format())
+        };
+
+        assert_eq!(sarif(&["imports", "app.exe"]), Ok(OutputFormat::Sarif));
+        assert_eq!(
+            sarif(&["com", "server", r"C:\srv.dll"]),
+            Ok(OutputFormat::Sarif)
+        );
+        let err = sarif(&["audit-search", "app.exe"]).unwrap_err();
+        assert!(err.contains("--format sarif is not supported by audit-search"));
+        let err = sarif(&["path-lint"]).unwrap_err();
+        assert!(err.contains("--format sarif is not supported by path-lint"));
+    }
+
+    #[test]
+    fn resolve_requires_from() {
+        let err = parse_resolve_err(&["foo.dll"]);
+        assert!(err.contains("resolve requires --from <module>"));
+    }
+
+    #[test]
+    fn resolve_requires_dll() {
+        let err = parse_resolve_err(&["--from", r"C:\host\host.exe"]);
+        assert!(err.contains("missing <dll>"));
+    }
+
+    #[test]
+    fn resolve_rejects_unknown_flag_name() {
+        let err = parse_resolve_err(&[
+            "foo.dll",
+            "--from",
+            r"C:\host\host.exe",
+            "--flags",
+            "SEARCH_NOWHERE",
+        ]);
+        assert!(err.contains("SEARCH_NOWHERE"));
+    }
+
     fn parse_com(args: &[&str]) -> super::ComOptions {
         let mut values = vec!["loadwhat", "com"];
         values.extend_from_slice(args);
@@ -712,6 +1789,57 @@ mod tests {
         assert!(opts.trace);
     }
 
+    #[test]
+    fn com_parses_optional_sysroot() {
+        let opts = parse_com(&["server", "--sysroot", "/mnt/win", r"C:\x\a.dll"]);
+        assert_eq!(opts.sysroot, Some(PathBuf::from("/mnt/win")));
+    }
+
+    #[test]
+    fn com_parses_hive_file_options() {
+        let opts = parse_com(&[
+            "clsid",
+            "--software-hive",
+            "SOFTWARE",
+            "--usrclass-hive",
+            "UsrClass.dat",
+            "{00000000-0000-0000-0000-000000000001}",
+        ]);
+        assert_eq!(opts.hives.software, Some(PathBuf::from("SOFTWARE")));
+        assert_eq!(opts.hives.ntuser, None);
+        assert_eq!(opts.hives.usrclass, Some(PathBuf::from("UsrClass.dat")));
+        let err = parse_from(["loadwhat", "com", "clsid", "--ntuser-hive"]).unwrap_err();
+        assert!(err.contains("--ntuser-hive requires a value"));
+    }
+
+    #[test]
+    fn com_collects_registry_files_in_order() {
+        let opts = parse_com(&[
+            "progid",
+            "--registry-file",
+            "clsid.reg",
+            "Vendor.Widget",
+            "--registry-file",
+            "progid.reg",
+        ]);
+        assert_eq!(
+            opts.registry_files,
+            vec![PathBuf::from("clsid.reg"), PathBuf::from("progid.reg")]
+        );
+        let err = parse_from([
+            "loadwhat",
+            "com",
+            "progid",
+            "--registry-file",
+            "a.reg",
+            "--software-hive",
+            "SOFTWARE",
+            "Vendor.Widget",
+        ])
+        .unwrap_err();
+        assert!(err.contains("--registry-file cannot be combined with hive file options"));
+    }
+
     #[test]
     fn com_server_defaults_to_both_views() {
         let opts = parse_com(&["server", r"C:\Vendor\foo.dll"]);
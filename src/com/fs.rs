@@ -1,11 +1,19 @@
 // Injectable file-system abstraction for COM server validation. Production
 // wraps std::fs plus the v1 static dependency walk; tests inject a mock.
 
+use crate::pe::ImportedFunction;
+
 /// Classification of a failing dependency found while walking a COM server.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DepStatus {
     Missing,
     BadImage,
+    /// The DLL resolved but does not export a function the importer binds.
+    MissingExport,
+    /// The DLL forwards an imported function to a target that cannot be bound.
+    BrokenForward,
+    /// The dependency is an API set with no host on this system.
+    UnresolvedApiSet,
 }
 
 impl DepStatus {
@@ -13,6 +21,9 @@ impl DepStatus {
         match self {
             DepStatus::Missing => "MISSING",
             DepStatus::BadImage => "BAD_IMAGE",
+            DepStatus::MissingExport => "MISSING_EXPORT",
+            DepStatus::BrokenForward => "BROKEN_FORWARD",
+            DepStatus::UnresolvedApiSet => "UNRESOLVED_API_SET",
         }
     }
 }
@@ -24,6 +35,8 @@ pub struct DepCandidate {
     pub order: usize,
     pub path: String,
     pub result: &'static str,
+    /// Structural defect token for a `BAD_IMAGE` candidate.
+    pub reason: Option<&'static str>,
 }
 
 /// A failing dependency discovered during a COM server dependency walk.
@@ -33,6 +46,10 @@ pub struct DepFailure {
     pub via: String,
     pub depth: u32,
     pub status: DepStatus,
+    /// The unbindable function, for `MissingExport` and `BrokenForward`.
+    pub export: Option<ImportedFunction>,
+    /// The raw forwarder string, for `DepStatus::BrokenForward`.
+    pub forward: Option<String>,
     pub candidates: Vec<DepCandidate>,
 }
 
@@ -163,6 +180,8 @@ mod mock {
                             via: module_name.clone(),
                             depth: depth + 1,
                             status: DepStatus::Missing,
+                            export: None,
+                            forward: None,
                             candidates: Vec::new(),
                         }),
                         Some(bytes) => {
@@ -172,6 +191,8 @@ mod mock {
                                     via: module_name.clone(),
                                     depth: depth + 1,
                                     status: DepStatus::BadImage,
+                                    export: None,
+                                    forward: None,
                                     candidates: Vec::new(),
                                 });
                             } else if visited.insert(candidate.clone()) {
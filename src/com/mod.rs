@@ -6,6 +6,7 @@
 
 pub mod fs;
 pub mod manifest;
+pub mod regfile;
 pub mod registry;
 pub mod resolver;
 
@@ -94,7 +95,6 @@ pub enum ServerStatus {
     DepsMissing,
     BitnessMismatch,
     AccessDenied,
-    Skipped,
 }
 
 impl ServerStatus {
@@ -106,7 +106,6 @@ impl ServerStatus {
             ServerStatus::DepsMissing => "SERVER_DEPS_MISSING",
             ServerStatus::BitnessMismatch => "BITNESS_MISMATCH",
             ServerStatus::AccessDenied => "ACCESS_DENIED",
-            ServerStatus::Skipped => "SKIPPED",
         }
     }
 }
@@ -125,7 +124,7 @@ pub fn normalize_path_for_compare(path: &str) -> String {
 /// under %SystemRoot%\System32 actually receives the %SystemRoot%\SysWOW64
 /// file. Returns None when redirection does not apply.
 pub fn wow64_redirect(path: &str) -> Option<String> {
-    let system_root = std::env::var("SystemRoot").ok()?;
+    let system_root = crate::sysroot::env_var("SystemRoot")?;
     let prefix = format!(r"{}\system32\", system_root.to_ascii_lowercase());
     let lower = path.to_ascii_lowercase();
     let rest = lower.strip_prefix(&prefix)?;
@@ -147,8 +146,8 @@ pub fn expand_env_value(value: &str) -> String {
         match after.find('%') {
             Some(end) => {
                 let name = &after[..end];
-                match std::env::var(name) {
-                    Ok(replacement) if !name.is_empty() => out.push_str(&replacement),
+                match crate::sysroot::env_var(name) {
+                    Some(replacement) if !name.is_empty() => out.push_str(&replacement),
                     _ => {
                         out.push('%');
                         out.push_str(name);
@@ -194,7 +193,7 @@ mod tests {
 
     #[test]
     fn expand_replaces_known_variables() {
-        let _guard = crate::win::TEST_ENV_LOCK.lock().unwrap();
+        let _guard = crate::test_util::TEST_ENV_LOCK.lock().unwrap();
         std::env::set_var("LOADWHAT_COM_TEST_VAR", r"C:\Base");
         assert_eq!(
             expand_env_value(r"%LOADWHAT_COM_TEST_VAR%\x.dll"),
@@ -210,7 +209,7 @@ mod tests {
 
     #[test]
     fn wow64_redirect_maps_system32_to_syswow64() {
-        let _lock = crate::win::TEST_ENV_LOCK.lock().unwrap();
+        let _lock = crate::test_util::TEST_ENV_LOCK.lock().unwrap();
         let _guard = crate::test_util::EnvVarGuard::set("SystemRoot", r"C:\TESTWIN");
         assert_eq!(
             super::wow64_redirect(r"C:\TestWin\System32\shell32.dll").as_deref(),
@@ -220,7 +219,7 @@ mod tests {
 
     #[test]
     fn wow64_redirect_ignores_other_paths() {
-        let _lock = crate::win::TEST_ENV_LOCK.lock().unwrap();
+        let _lock = crate::test_util::TEST_ENV_LOCK.lock().unwrap();
         let _guard = crate::test_util::EnvVarGuard::set("SystemRoot", r"C:\TESTWIN");
         assert_eq!(super::wow64_redirect(r"C:\Vendor\foo.dll"), None);
         assert_eq!(super::wow64_redirect(r"C:\TESTWIN\SysWOW64\x.dll"), None);
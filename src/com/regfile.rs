@@ -0,0 +1,496 @@
+// Registry snapshot backend over `reg export` / regedit `.reg` files, so a
+// customer's export can drive `ComResolver` without access to their machine.
+
+use super::registry::{
+    classes_path, strip_classes, strip_software, value_from_raw, wow64_path, ComRegistry,
+    RegLocation, RegValue, ERROR_FILE_NOT_FOUND,
+};
+use crate::hive::{REG_DWORD, REG_EXPAND_SZ, REG_MULTI_SZ, REG_SZ};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const REG_BINARY: u32 = 3;
+
+const HEADER_V5: &str = "Windows Registry Editor Version 5.00";
+const HEADER_V4: &str = "REGEDIT4";
+
+/// A value's registry type and raw data.
+type RawValue = (u32, Vec<u8>);
+
+/// One key of the snapshot: values keyed by lowercase name, and child key
+/// names as written, in file order.
+#[derive(Default)]
+struct RegFileKey {
+    values: HashMap<String, RawValue>,
+    subkeys: Vec<String>,
+}
+
+/// Registry reader over one or more `.reg` files (REGEDIT4 or Windows
+/// Registry Editor 5.00), applied in order so later files can add, replace
+/// and delete keys and values like a regedit import would.
+///
+/// `HKEY_LOCAL_MACHINE` and `HKEY_CURRENT_USER` keys are stored by physical
+/// path and read back through the same WOW64 redirection rules as the hive
+/// backend. `HKEY_CLASSES_ROOT` keys, which an export does not attribute to
+/// either hive, are treated as machine registrations
+/// (`HKLM\Software\Classes`). Other roots are ignored.
+#[derive(Default)]
+pub struct RegFileRegistry {
+    keys: HashMap<String, RegFileKey>,
+}
+
+impl RegFileRegistry {
+    pub fn open(paths: &[PathBuf]) -> Result<RegFileRegistry, String> {
+        let mut registry = RegFileRegistry::default();
+        for path in paths {
+            let bytes = std::fs::read(path)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+            registry
+                .load(&bytes)
+                .map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+        Ok(registry)
+    }
+
+    /// Applies one `.reg` file on top of the snapshot.
+    fn load(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let text = decode_text(bytes);
+        let mut lines = logical_lines(&text).into_iter();
+        let unicode = loop {
+            match lines.next() {
+                Some((_, line)) if line.trim().is_empty() => continue,
+                Some((_, line)) if line.trim() == HEADER_V5 => break true,
+                Some((_, line)) if line.trim() == HEADER_V4 => break false,
+                _ => return Err("not a .reg file (missing REGEDIT4 or version 5.00 header)".into()),
+            }
+        };
+
+        // None before the first key and under roots the snapshot ignores.
+        let mut current: Option<String> = None;
+        let mut seen_key = false;
+        for (number, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let at = |message: String| format!("line {number}: {message}");
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| at("unterminated key header".into()))?;
+                seen_key = true;
+                current = None;
+                if let Some(deleted) = header.strip_prefix('-') {
+                    if let Some(path) = physical_key(deleted) {
+                        self.delete_key(&path);
+                    }
+                } else if let Some(path) = physical_key(header) {
+                    current = Some(self.create_key(&path));
+                }
+                continue;
+            }
+            if !seen_key {
+                return Err(at("value before the first key".into()));
+            }
+            let (name, data) = parse_value_line(line, unicode).map_err(at)?;
+            let Some(key) = current.as_ref().and_then(|key| self.keys.get_mut(key)) else {
+                continue;
+            };
+            match data {
+                Some(value) => {
+                    key.values.insert(name.to_lowercase(), value);
+                }
+                None => {
+                    key.values.remove(&name.to_lowercase());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates `path` and any missing ancestors; returns its map key.
+    fn create_key(&mut self, path: &str) -> String {
+        let mut parent: Option<String> = None;
+        let mut prefix = String::new();
+        for part in path.split('\\') {
+            if !prefix.is_empty() {
+                prefix.push('\\');
+            }
+            prefix.push_str(part);
+            let lower = prefix.to_ascii_lowercase();
+            if !self.keys.contains_key(&lower) {
+                self.keys.insert(lower.clone(), RegFileKey::default());
+                if let Some(parent) = parent.as_ref().and_then(|p| self.keys.get_mut(p)) {
+                    parent.subkeys.push(part.to_string());
+                }
+            }
+            parent = Some(lower);
+        }
+        prefix.to_ascii_lowercase()
+    }
+
+    fn delete_key(&mut self, path: &str) {
+        let lower = path.to_ascii_lowercase();
+        let nested = format!("{lower}\\");
+        self.keys
+            .retain(|key, _| *key != lower && !key.starts_with(&nested));
+        if let Some((parent, name)) = lower.rsplit_once('\\') {
+            if let Some(parent) = self.keys.get_mut(parent) {
+                parent
+                    .subkeys
+                    .retain(|child| !child.eq_ignore_ascii_case(name));
+            }
+        }
+    }
+
+    fn key(&self, location: RegLocation, subkey: &str) -> Option<&RegFileKey> {
+        let path = match location {
+            RegLocation::Hklm64 => format!(r"HKLM\{subkey}"),
+            RegLocation::Hklm32 => match strip_software(subkey) {
+                Some(rest) => format!(r"HKLM\Software\{}", wow64_path(rest)),
+                None => format!(r"HKLM\{subkey}"),
+            },
+            RegLocation::Hkcu64 => format!(r"HKCU\{subkey}"),
+            RegLocation::Hkcu32 => match strip_software(subkey).and_then(strip_classes) {
+                Some(rest) => format!(r"HKCU\Software\Classes\{}", classes_path(rest, true)),
+                None => format!(r"HKCU\{subkey}"),
+            },
+        };
+        self.keys
+            .get(&normalize_key_path(&path).to_ascii_lowercase())
+    }
+}
+
+impl ComRegistry for RegFileRegistry {
+    fn read_value(&self, location: RegLocation, subkey: &str, name: &str) -> RegValue {
+        match self
+            .key(location, subkey)
+            .and_then(|key| key.values.get(&name.to_lowercase()))
+        {
+            Some((kind, data)) => value_from_raw(*kind, data.clone()),
+            None => RegValue::NotFound,
+        }
+    }
+
+    fn key_exists(&self, location: RegLocation, subkey: &str) -> bool {
+        self.key(location, subkey).is_some()
+    }
+
+    fn enum_subkeys(&self, location: RegLocation, subkey: &str) -> Result<Vec<String>, u32> {
+        let key = self.key(location, subkey).ok_or(ERROR_FILE_NOT_FOUND)?;
+        let mut names = key.subkeys.clone();
+        names.sort_by_key(|name| name.to_lowercase());
+        Ok(names)
+    }
+}
+
+/// Decodes a `.reg` file: UTF-16 with a byte-order mark (version 5.00),
+/// UTF-8, or ANSI read as Latin-1.
+fn decode_text(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| from([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return utf16(rest, u16::from_le_bytes);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return utf16(rest, u16::from_be_bytes);
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+/// Splits the file into lines, joining `hex` continuations (a trailing `\`
+/// followed by an indented line). Each line keeps its 1-based start number.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut out: Vec<(usize, String)> = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim_end_matches('\r');
+        let (number, mut joined) = match pending.take() {
+            Some((number, head)) => (number, head + line.trim_start()),
+            None => (index + 1, line.to_string()),
+        };
+        let trimmed = joined.trim_end();
+        if trimmed.ends_with('\\') && !trimmed.trim_start().starts_with('[') {
+            joined.truncate(trimmed.len() - 1);
+            pending = Some((number, joined));
+        } else {
+            out.push((number, joined));
+        }
+    }
+    out.extend(pending);
+    out
+}
+
+/// Maps a key header to the snapshot's physical path (`HKLM\...` or
+/// `HKCU\...`); None for roots COM lookups never read.
+fn physical_key(header: &str) -> Option<String> {
+    let (root, rest) = header.split_once('\\').unwrap_or((header, ""));
+    let root = match root.to_ascii_uppercase().as_str() {
+        "HKEY_LOCAL_MACHINE" | "HKLM" => "HKLM",
+        "HKEY_CURRENT_USER" | "HKCU" => "HKCU",
+        "HKEY_CLASSES_ROOT" | "HKCR" => r"HKLM\Software\Classes",
+        _ => return None,
+    };
+    Some(normalize_key_path(&format!(r"{root}\{rest}")))
+}
+
+fn normalize_key_path(path: &str) -> String {
+    path.split('\\')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\\")
+}
+
+/// Parses `"name"=data` or `@=data`. The data is None for a `-` deletion.
+fn parse_value_line(line: &str, unicode: bool) -> Result<(String, Option<RawValue>), String> {
+    let (name, rest) = if let Some(rest) = line.strip_prefix('@') {
+        (String::new(), rest)
+    } else if line.starts_with('"') {
+        parse_quoted(line)?
+    } else {
+        return Err(format!("unrecognized line: {line}"));
+    };
+    let data = rest
+        .trim_start()
+        .strip_prefix('=')
+        .ok_or_else(|| format!("expected '=' after value name: {line}"))?
+        .trim();
+    if data == "-" {
+        return Ok((name, None));
+    }
+    Ok((name, Some(parse_data(data, unicode)?)))
+}
+
+/// Reads a quoted string with regedit's `\\` and `\"` escapes, returning
+/// it and the text after the closing quote.
+fn parse_quoted(text: &str) -> Result<(String, &str), String> {
+    let mut out = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((out, &text[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, escaped)) => out.push(escaped),
+                None => break,
+            },
+            c => out.push(c),
+        }
+    }
+    Err(format!("unterminated string: {text}"))
+}
+
+fn parse_data(data: &str, unicode: bool) -> Result<RawValue, String> {
+    if data.starts_with('"') {
+        let (text, rest) = parse_quoted(data)?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected text after string: {rest}"));
+        }
+        return Ok((REG_SZ, utf16z(&text)));
+    }
+    if let Some(digits) = data.strip_prefix("dword:") {
+        let value = u32::from_str_radix(digits.trim(), 16)
+            .map_err(|_| format!("invalid dword: {digits}"))?;
+        return Ok((REG_DWORD, value.to_le_bytes().to_vec()));
+    }
+    let (kind, bytes) = if let Some(bytes) = data.strip_prefix("hex:") {
+        (REG_BINARY, bytes)
+    } else if let Some(rest) = data.strip_prefix("hex(") {
+        let (kind, bytes) = rest
+            .split_once("):")
+            .ok_or_else(|| format!("invalid hex type: {data}"))?;
+        let kind =
+            u32::from_str_radix(kind, 16).map_err(|_| format!("invalid hex type: {kind}"))?;
+        (kind, bytes)
+    } else {
+        return Err(format!("unrecognized value data: {data}"));
+    };
+    let bytes = bytes
+        .split(',')
+        .map(str::trim)
+        .filter(|byte| !byte.is_empty())
+        .map(|byte| u8::from_str_radix(byte, 16).map_err(|_| format!("invalid hex byte: {byte}")))
+        .collect::<Result<Vec<u8>, String>>()?;
+    // REGEDIT4 writes string types in the ANSI code page; the registry
+    // stores them as UTF-16.
+    let bytes = if !unicode && matches!(kind, REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ) {
+        bytes
+            .iter()
+            .flat_map(|&b| u16::from(b).to_le_bytes())
+            .collect()
+    } else {
+        bytes
+    };
+    Ok((kind, bytes))
+}
+
+fn utf16z(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(text: &str) -> RegFileRegistry {
+        let mut registry = RegFileRegistry::default();
+        registry.load(text.as_bytes()).expect("load should succeed");
+        registry
+    }
+
+    fn utf16le_with_bom(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn reads_a_unicode_export_with_typed_values() {
+        let text = "Windows Registry Editor Version 5.00\r\n\r\n\
+            [HKEY_CLASSES_ROOT\\CLSID\\{A}\\InprocServer32]\r\n\
+            @=hex(2):25,00,53,00,79,00,73,00,74,00,65,00,6d,00,52,00,6f,00,6f,00,74,00,\\\r\n  \
+            25,00,5c,00,61,00,2e,00,64,00,6c,00,6c,00,00,00\r\n\
+            \"ThreadingModel\"=\"Both\"\r\n\
+            \"Flags\"=dword:0000001f\r\n\
+            \"Names\"=hex(7):61,00,00,00,62,00,00,00,00,00\r\n";
+        let mut registry = RegFileRegistry::default();
+        registry.load(&utf16le_with_bom(text)).unwrap();
+        let key = r"Software\Classes\CLSID\{A}\InprocServer32";
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm64, key, ""),
+            RegValue::ExpandString(r"%SystemRoot%\a.dll".to_string())
+        );
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm64, key, "threadingmodel"),
+            RegValue::String("Both".to_string())
+        );
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm64, key, "Flags"),
+            RegValue::Dword(0x1f)
+        );
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm64, key, "Names"),
+            RegValue::Binary(vec![0x61, 0, 0, 0, 0x62, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            registry.enum_subkeys(RegLocation::Hklm64, r"Software\Classes\CLSID"),
+            Ok(vec!["{A}".to_string()])
+        );
+        assert!(!registry.key_exists(RegLocation::Hkcu64, key));
+    }
+
+    #[test]
+    fn regedit4_strings_are_ansi() {
+        let registry = registry(
+            "REGEDIT4\n\n[HKEY_CURRENT_USER\\Software\\Classes\\CLSID\\{B}\\LocalServer32]\n\
+             @=hex(2):43,3a,5c,e9,2e,65,78,65,00\n\
+             \"Quoted\"=\"C:\\\\Program Files\\\\\\\"x\\\".exe\"\n",
+        );
+        let key = r"Software\Classes\CLSID\{B}\LocalServer32";
+        assert_eq!(
+            registry.read_value(RegLocation::Hkcu64, key, ""),
+            RegValue::ExpandString("C:\\\u{e9}.exe".to_string())
+        );
+        assert_eq!(
+            registry.read_value(RegLocation::Hkcu64, key, "Quoted"),
+            RegValue::String(r#"C:\Program Files\"x".exe"#.to_string())
+        );
+    }
+
+    #[test]
+    fn views_follow_wow64_redirection() {
+        let registry = registry(
+            "Windows Registry Editor Version 5.00\n\n\
+             [HKEY_LOCAL_MACHINE\\SOFTWARE\\Classes\\CLSID\\{A}\\InprocServer32]\n@=\"C:\\\\x64\\\\a.dll\"\n\n\
+             [HKEY_LOCAL_MACHINE\\SOFTWARE\\Classes\\WOW6432Node\\CLSID\\{A}\\InprocServer32]\n@=\"C:\\\\x86\\\\a.dll\"\n\n\
+             [HKEY_CURRENT_USER\\Software\\Classes\\Wow6432Node\\CLSID\\{C}]\n@=\"user32\"\n",
+        );
+        let key = r"Software\Classes\CLSID\{A}\InprocServer32";
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm64, key, ""),
+            RegValue::String(r"C:\x64\a.dll".to_string())
+        );
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm32, key, ""),
+            RegValue::String(r"C:\x86\a.dll".to_string())
+        );
+        assert!(registry.key_exists(RegLocation::Hkcu32, r"Software\Classes\CLSID\{C}"));
+        assert!(!registry.key_exists(RegLocation::Hkcu64, r"Software\Classes\CLSID\{C}"));
+    }
+
+    #[test]
+    fn later_entries_delete_keys_and_values() {
+        let registry = registry(
+            "Windows Registry Editor Version 5.00\n\n\
+             [HKEY_CLASSES_ROOT\\Vendor.Widget]\n@=\"Widget\"\n\"Old\"=\"1\"\n\n\
+             [HKEY_CLASSES_ROOT\\Vendor.Widget\\CLSID]\n@=\"{A}\"\n\n\
+             [HKEY_CLASSES_ROOT\\Vendor.Gadget\\CLSID]\n@=\"{B}\"\n\n\
+             [HKEY_CLASSES_ROOT\\Vendor.Widget]\n\"Old\"=-\n\n\
+             [-HKEY_CLASSES_ROOT\\Vendor.Gadget]\n\n\
+             [HKEY_USERS\\.DEFAULT\\Software]\n\"Ignored\"=\"x\"\n",
+        );
+        let classes = r"Software\Classes";
+        assert_eq!(
+            registry.read_value(RegLocation::Hklm64, r"Software\Classes\Vendor.Widget", ""),
+            RegValue::String("Widget".to_string())
+        );
+        assert_eq!(
+            registry.read_value(
+                RegLocation::Hklm64,
+                r"Software\Classes\Vendor.Widget",
+                "Old"
+            ),
+            RegValue::NotFound
+        );
+        assert!(!registry.key_exists(RegLocation::Hklm64, r"Software\Classes\Vendor.Gadget\CLSID"));
+        assert_eq!(
+            registry.enum_subkeys(RegLocation::Hklm64, classes),
+            Ok(vec!["Vendor.Widget".to_string()])
+        );
+    }
+
+    #[test]
+    fn values_land_on_keys_spelled_with_stray_separators() {
+        let odd = registry(
+            "Windows Registry Editor Version 5.00\n\n\
+             [hkcr\\\\Vendor.Odd\\]\n@=\"Odd\"\n",
+        );
+        assert_eq!(
+            odd.read_value(RegLocation::Hklm64, r"Software\Classes\Vendor.Odd", ""),
+            RegValue::String("Odd".to_string())
+        );
+
+        // Values under a deleted key are dropped, not attached anywhere.
+        let deleted = registry(
+            "Windows Registry Editor Version 5.00\n\n\
+             [hkcr\\\\Vendor.Odd\\]\n@=\"Odd\"\n\n\
+             [-HKCR\\Vendor.Odd]\n\"Stray\"=\"1\"\n",
+        );
+        assert!(!deleted.key_exists(RegLocation::Hklm64, r"Software\Classes\Vendor.Odd"));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let load = |text: &str| RegFileRegistry::default().load(text.as_bytes());
+        assert!(load("[HKEY_CLASSES_ROOT\\CLSID]\n")
+            .unwrap_err()
+            .contains("header"));
+        assert!(load("REGEDIT4\n\"x\"=\"y\"\n")
+            .unwrap_err()
+            .contains("line 2: value before the first key"));
+        assert!(load("REGEDIT4\n[HKEY_CLASSES_ROOT\\X]\n\"x\"=dword:zz\n")
+            .unwrap_err()
+            .contains("line 3: invalid dword"));
+        assert!(load("REGEDIT4\n[HKEY_CLASSES_ROOT\\X\n")
+            .unwrap_err()
+            .contains("unterminated key header"));
+    }
+}
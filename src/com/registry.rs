@@ -2,6 +2,7 @@
 // real Windows registry with explicit WOW64 view flags; tests inject a mock.
 
 use super::{Hive, RegView};
+use crate::hive::{REG_DWORD, REG_EXPAND_SZ, REG_SZ};
 
 /// Result of reading a single registry value.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -11,10 +12,18 @@ pub enum RegValue {
     Dword(u32),
     Binary(Vec<u8>),
     NotFound,
+    // Only the live registry reports these.
+    #[cfg_attr(not(windows), allow(dead_code))]
     AccessDenied,
+    #[cfg_attr(not(windows), allow(dead_code))]
     Error(u32),
 }
 
+/// Win32 error code for a key that does not exist.
+pub const ERROR_FILE_NOT_FOUND: u32 = 2;
+/// Win32 error code for a key the caller may not open.
+pub const ERROR_ACCESS_DENIED: u32 = 5;
+
 /// A registry hive + view combination.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum RegLocation {
@@ -48,12 +57,83 @@ pub trait ComRegistry {
     fn enum_subkeys(&self, location: RegLocation, subkey: &str) -> Result<Vec<String>, u32>;
 }
 
+/// Decodes raw registry value data the way every backend reports it.
+pub(crate) fn value_from_raw(kind: u32, data: Vec<u8>) -> RegValue {
+    match kind {
+        REG_SZ | REG_EXPAND_SZ => {
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .take_while(|&u| u != 0)
+                .collect();
+            let text = String::from_utf16_lossy(&units);
+            if kind == REG_SZ {
+                RegValue::String(text)
+            } else {
+                RegValue::ExpandString(text)
+            }
+        }
+        REG_DWORD if data.len() >= 4 => {
+            RegValue::Dword(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+        }
+        _ => RegValue::Binary(data),
+    }
+}
+
+/// `Software\Classes` subkeys the WOW64 registry redirector gives 32-bit
+/// callers their own copy of; the rest of `Classes` is shared.
+const REDIRECTED_CLASSES: [&str; 5] = [
+    "CLSID",
+    "DirectShow",
+    "Interface",
+    "Media Type",
+    "MediaFoundation",
+];
+
+/// The part of a path below `Software`, which is the SOFTWARE hive's
+/// root for HKLM.
+pub(crate) fn strip_software(subkey: &str) -> Option<&str> {
+    let (head, rest) = subkey.split_once('\\').unwrap_or((subkey, ""));
+    head.eq_ignore_ascii_case("Software").then_some(rest)
+}
+
+/// The part of a `Software`-relative path below `Classes`.
+pub(crate) fn strip_classes(path: &str) -> Option<&str> {
+    let (head, rest) = path.split_once('\\').unwrap_or((path, ""));
+    head.eq_ignore_ascii_case("Classes").then_some(rest)
+}
+
+/// Where a 32-bit caller's view of `Software\<path>` lives in the hive.
+pub(crate) fn wow64_path(path: &str) -> String {
+    let (head, _) = path.split_once('\\').unwrap_or((path, ""));
+    if head.eq_ignore_ascii_case("Wow6432Node") {
+        return path.to_string();
+    }
+    match strip_classes(path) {
+        Some(rest) => format!(r"Classes\{}", classes_path(rest, true)),
+        None => format!(r"Wow6432Node\{path}"),
+    }
+}
+
+/// Where a view of `Classes\<path>` lives below the classes root.
+pub(crate) fn classes_path(path: &str, wow64: bool) -> String {
+    let class = path.split('\\').next().unwrap_or("");
+    let redirected = REDIRECTED_CLASSES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(class));
+    if wow64 && redirected {
+        format!(r"Wow6432Node\{path}")
+    } else {
+        path.to_string()
+    }
+}
+
 #[cfg(windows)]
 pub use windows_impl::WindowsRegistry;
 
 #[cfg(windows)]
 mod windows_impl {
-    use super::{ComRegistry, RegLocation, RegValue};
+    use super::{value_from_raw, ComRegistry, RegLocation, RegValue};
     use crate::win;
     use std::ffi::OsStr;
 
@@ -131,25 +211,7 @@ mod windows_impl {
                 code => return RegValue::Error(code),
             }
 
-            match value_type {
-                win::REG_SZ | win::REG_EXPAND_SZ => {
-                    let units: Vec<u16> = data
-                        .chunks_exact(2)
-                        .map(|c| u16::from_le_bytes([c[0], c[1]]))
-                        .take_while(|&u| u != 0)
-                        .collect();
-                    let text = String::from_utf16_lossy(&units);
-                    if value_type == win::REG_SZ {
-                        RegValue::String(text)
-                    } else {
-                        RegValue::ExpandString(text)
-                    }
-                }
-                win::REG_DWORD if data.len() >= 4 => {
-                    RegValue::Dword(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
-                }
-                _ => RegValue::Binary(data),
-            }
+            value_from_raw(value_type, data)
         }
 
         fn key_exists(&self, location: RegLocation, subkey: &str) -> bool {
@@ -210,6 +272,244 @@ mod windows_impl {
     }
 }
 
+pub use hive_impl::HiveRegistry;
+
+mod hive_impl {
+    use super::{
+        classes_path, strip_classes, strip_software, value_from_raw, wow64_path, ComRegistry,
+        RegLocation, RegValue, ERROR_FILE_NOT_FOUND,
+    };
+    use crate::hive::{Hive, HiveKey};
+
+    /// Offline registry reader over hive files copied from a machine:
+    /// `SOFTWARE` serves `HKLM\Software`, `NTUSER.DAT` serves HKCU and
+    /// `UsrClass.dat` serves `HKCU\Software\Classes`. Both views follow the
+    /// WOW64 registry redirection rules. A missing hive reads as empty.
+    pub struct HiveRegistry {
+        machine: Option<Hive>,
+        user: Option<Hive>,
+        user_classes: Option<Hive>,
+    }
+
+    impl HiveRegistry {
+        pub fn new(machine: Option<Hive>, user: Option<Hive>, user_classes: Option<Hive>) -> Self {
+            HiveRegistry {
+                machine,
+                user,
+                user_classes,
+            }
+        }
+
+        fn open(&self, location: RegLocation, subkey: &str) -> Option<(&Hive, HiveKey)> {
+            let (hive, path) = match location {
+                RegLocation::Hklm64 | RegLocation::Hklm32 => {
+                    let rest = strip_software(subkey)?;
+                    let path = if location == RegLocation::Hklm32 {
+                        wow64_path(rest)
+                    } else {
+                        rest.to_string()
+                    };
+                    (self.machine.as_ref()?, path)
+                }
+                RegLocation::Hkcu64 | RegLocation::Hkcu32 => {
+                    self.user_path(subkey, location == RegLocation::Hkcu32)?
+                }
+            };
+            let key = hive.open_key(&path)?;
+            Some((hive, key))
+        }
+
+        /// The hive and path holding an HKCU key. `Software\Classes` is
+        /// `UsrClass.dat` on a real system; the rest of HKCU, and `Classes`
+        /// when only `NTUSER.DAT` was supplied, comes from the user hive.
+        /// Only the redirected `Classes` subkeys differ between the views.
+        fn user_path(&self, subkey: &str, wow64: bool) -> Option<(&Hive, String)> {
+            let classes = strip_software(subkey).and_then(strip_classes);
+            if let (Some(rest), Some(hive)) = (classes, self.user_classes.as_ref()) {
+                return Some((hive, classes_path(rest, wow64)));
+            }
+            let path = match classes {
+                Some(rest) => format!(r"Software\Classes\{}", classes_path(rest, wow64)),
+                None => subkey.to_string(),
+            };
+            Some((self.user.as_ref()?, path))
+        }
+    }
+
+    impl ComRegistry for HiveRegistry {
+        fn read_value(&self, location: RegLocation, subkey: &str, name: &str) -> RegValue {
+            let Some((hive, key)) = self.open(location, subkey) else {
+                return RegValue::NotFound;
+            };
+            let Some((kind, data)) = hive.raw_value(key, name) else {
+                return RegValue::NotFound;
+            };
+            value_from_raw(kind, data)
+        }
+
+        fn key_exists(&self, location: RegLocation, subkey: &str) -> bool {
+            self.open(location, subkey).is_some()
+        }
+
+        fn enum_subkeys(&self, location: RegLocation, subkey: &str) -> Result<Vec<String>, u32> {
+            let (hive, key) = self.open(location, subkey).ok_or(ERROR_FILE_NOT_FOUND)?;
+            Ok(hive
+                .subkeys(key)
+                .into_iter()
+                .map(|child| hive.key_name(child))
+                .collect())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::hive::testhive::{build_hive, KeySpec};
+
+        fn registry() -> HiveRegistry {
+            let root = KeySpec::new("ROOT")
+                .at(r"Classes\CLSID\{A}\InprocServer32", |key| {
+                    key.string("", r"C:\x64\a.dll")
+                        .string("ThreadingModel", "Both")
+                })
+                .at(r"Classes\Wow6432Node\CLSID\{A}\InprocServer32", |key| {
+                    key.string("", r"C:\x86\a.dll")
+                })
+                .at(r"Classes\Vendor.Widget\CLSID", |key| key.string("", "{A}"))
+                .at(r"Wow6432Node\Vendor", |key| key.dword("Bits", 32))
+                .at("Vendor", |key| key.dword("Bits", 64));
+            HiveRegistry::new(
+                Some(Hive::from_bytes(build_hive(&root)).unwrap()),
+                None,
+                None,
+            )
+        }
+
+        #[test]
+        fn views_follow_wow64_redirection() {
+            let registry = registry();
+            let key = r"Software\Classes\CLSID\{A}\InprocServer32";
+            assert_eq!(
+                registry.read_value(RegLocation::Hklm64, key, ""),
+                RegValue::String(r"C:\x64\a.dll".to_string())
+            );
+            assert_eq!(
+                registry.read_value(RegLocation::Hklm32, key, ""),
+                RegValue::String(r"C:\x86\a.dll".to_string())
+            );
+            // ProgIDs are shared between the views.
+            assert_eq!(
+                registry.read_value(
+                    RegLocation::Hklm32,
+                    r"SOFTWARE\Classes\Vendor.Widget\CLSID",
+                    ""
+                ),
+                RegValue::String("{A}".to_string())
+            );
+            assert_eq!(
+                registry.read_value(RegLocation::Hklm32, r"Software\Vendor", "Bits"),
+                RegValue::Dword(32)
+            );
+            assert_eq!(
+                registry.read_value(RegLocation::Hklm64, r"Software\Vendor", "Bits"),
+                RegValue::Dword(64)
+            );
+        }
+
+        #[test]
+        fn enumerates_subkeys_and_reports_missing_keys() {
+            let registry = registry();
+            assert_eq!(
+                registry.enum_subkeys(RegLocation::Hklm64, r"Software\Classes\CLSID"),
+                Ok(vec!["{A}".to_string()])
+            );
+            assert_eq!(
+                registry.enum_subkeys(RegLocation::Hklm64, r"Software\Classes\Missing"),
+                Err(ERROR_FILE_NOT_FOUND)
+            );
+            assert!(registry.key_exists(RegLocation::Hklm64, r"Software\Classes\CLSID\{a}"));
+            assert!(!registry.key_exists(RegLocation::Hkcu64, r"Software\Classes\CLSID\{A}"));
+            assert_eq!(
+                registry.read_value(RegLocation::Hklm64, r"System\Setup", ""),
+                RegValue::NotFound
+            );
+        }
+
+        fn user_hive() -> Hive {
+            let root = KeySpec::new("ROOT")
+                .at(r"Software\Vendor", |key| key.dword("Bits", 64))
+                .at(r"Software\Classes\CLSID\{U}\InprocServer32", |key| {
+                    key.string("", r"C:\Users\u\x64.dll")
+                })
+                .at(
+                    r"Software\Classes\Wow6432Node\CLSID\{U}\InprocServer32",
+                    |key| key.string("", r"C:\Users\u\x86.dll"),
+                );
+            Hive::from_bytes(build_hive(&root)).unwrap()
+        }
+
+        fn user_classes_hive() -> Hive {
+            let root = KeySpec::new("ROOT")
+                .at(r"CLSID\{B}\LocalServer32", |key| {
+                    key.string("", r"C:\Users\u\b64.exe")
+                })
+                .at(r"Wow6432Node\CLSID\{B}\LocalServer32", |key| {
+                    key.string("", r"C:\Users\u\b32.exe")
+                })
+                .at(r"Vendor.Widget\CLSID", |key| key.string("", "{B}"));
+            Hive::from_bytes(build_hive(&root)).unwrap()
+        }
+
+        #[test]
+        fn user_classes_hive_serves_hkcu_classes_in_both_views() {
+            let registry = HiveRegistry::new(None, Some(user_hive()), Some(user_classes_hive()));
+            let key = r"Software\Classes\CLSID\{B}\LocalServer32";
+            assert_eq!(
+                registry.read_value(RegLocation::Hkcu64, key, ""),
+                RegValue::String(r"C:\Users\u\b64.exe".to_string())
+            );
+            assert_eq!(
+                registry.read_value(RegLocation::Hkcu32, key, ""),
+                RegValue::String(r"C:\Users\u\b32.exe".to_string())
+            );
+            assert_eq!(
+                registry.read_value(
+                    RegLocation::Hkcu32,
+                    r"Software\Classes\Vendor.Widget\CLSID",
+                    ""
+                ),
+                RegValue::String("{B}".to_string())
+            );
+            // UsrClass.dat replaces the user hive's own Classes key.
+            assert!(!registry.key_exists(RegLocation::Hkcu64, r"Software\Classes\CLSID\{U}"));
+            // The rest of HKCU\Software is shared between the views.
+            assert_eq!(
+                registry.read_value(RegLocation::Hkcu32, r"Software\Vendor", "Bits"),
+                RegValue::Dword(64)
+            );
+            assert!(!registry.key_exists(RegLocation::Hklm64, r"Software\Classes\CLSID"));
+        }
+
+        #[test]
+        fn user_hive_alone_serves_its_own_classes_key() {
+            let registry = HiveRegistry::new(None, Some(user_hive()), None);
+            let key = r"Software\Classes\CLSID\{U}\InprocServer32";
+            assert_eq!(
+                registry.read_value(RegLocation::Hkcu64, key, ""),
+                RegValue::String(r"C:\Users\u\x64.dll".to_string())
+            );
+            assert_eq!(
+                registry.read_value(RegLocation::Hkcu32, key, ""),
+                RegValue::String(r"C:\Users\u\x86.dll".to_string())
+            );
+            assert_eq!(
+                registry.enum_subkeys(RegLocation::Hkcu64, r"Software\Classes\CLSID"),
+                Ok(vec!["{U}".to_string()])
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 pub use mock::MockRegistry;
 
@@ -301,7 +601,7 @@ mod mock {
 
         fn enum_subkeys(&self, location: RegLocation, subkey: &str) -> Result<Vec<String>, u32> {
             if self.is_denied(location, subkey) {
-                return Err(crate::win::ERROR_ACCESS_DENIED);
+                return Err(super::ERROR_ACCESS_DENIED);
             }
             let mut names = self
                 .subkeys
@@ -5,7 +5,7 @@ use std::collections::HashSet;
 
 use super::fs::{ComFileSystem, DepFailure, DepSearchContext};
 use super::manifest::{parse_manifest_com_classes, ManifestComClass};
-use super::registry::{ComRegistry, RegLocation, RegValue};
+use super::registry::{ComRegistry, RegLocation, RegValue, ERROR_ACCESS_DENIED};
 use super::{
     expand_env_value, normalize_path_for_compare, Hive, LookupStatus, RegView, ServerKind,
     ServerStatus,
@@ -92,9 +92,7 @@ impl ComLookupResult {
             return true;
         }
         match &self.server {
-            Some(validation) => {
-                !matches!(validation.status, ServerStatus::Ok | ServerStatus::Skipped)
-            }
+            Some(validation) => validation.status != ServerStatus::Ok,
             None => false,
         }
     }
@@ -519,21 +517,12 @@ impl<'a> ComResolver<'a> {
 
         if kind == ServerKind::Inproc {
             if let Some(expected) = expected_machine {
-                if machine != MachineType::Unknown
-                    && expected != MachineType::Unknown
-                    && machine != expected
-                {
+                if machine != MachineType::Unknown && !expected.can_load(machine) {
                     return Ok(done(ServerStatus::BitnessMismatch, Some(machine)));
                 }
             }
         }
 
-        // The v1 dependency walk models the x64 loader; walking an x86 image
-        // with x64 search semantics would fabricate results.
-        if machine == MachineType::X86 {
-            return Ok(done(ServerStatus::Skipped, Some(machine)));
-        }
-
         let walk = self
             .fs
             .walk_dependencies(path, context)
@@ -594,7 +583,7 @@ impl<'a> ComResolver<'a> {
                 let clsid_root = format!(r"{CLASSES_ROOT}\CLSID");
                 let mut clsids = match self.registry.enum_subkeys(loc, &clsid_root) {
                     Ok(names) => names,
-                    Err(crate::win::ERROR_ACCESS_DENIED) => {
+                    Err(ERROR_ACCESS_DENIED) => {
                         return Err(ComError::Indeterminate(format!(
                             "access denied enumerating {} {} CLSID registrations",
                             hive.as_token(),
@@ -671,14 +660,15 @@ impl<'a> ComResolver<'a> {
         let target_machine = crate::pe::machine_type_from_bytes(&header)
             .map_err(|e| ComError::Indeterminate(format!("target is not a valid PE image: {e}")))?;
 
+        // ARM64-family processes, like x64 ones, read the 64-bit view.
         let view = match target_machine {
-            MachineType::X64 => RegView::V64,
             MachineType::X86 => RegView::V32,
             MachineType::Unknown => {
                 return Err(ComError::UnsupportedArchitecture(format!(
                     "unsupported target machine type for {target_path}"
                 )));
             }
+            _ => RegView::V64,
         };
 
         let dependency_context = DepSearchContext {
@@ -710,7 +700,7 @@ impl<'a> ComResolver<'a> {
         } else {
             match &lookup.server {
                 Some(validation) => match validation.status {
-                    ServerStatus::Ok | ServerStatus::Skipped => "OK",
+                    ServerStatus::Ok => "OK",
                     other => other.as_token(),
                 },
                 None => "OK",
@@ -811,7 +801,7 @@ impl<'a> ComResolver<'a> {
             dependency_context,
         )?;
         let status = match validation.status {
-            ServerStatus::Ok | ServerStatus::Skipped => "OK",
+            ServerStatus::Ok => "OK",
             other => other.as_token(),
         };
 
@@ -851,6 +841,7 @@ mod tests {
     use super::*;
 
     const MACHINE_X86: u16 = 0x014C;
+    const MACHINE_ARM64: u16 = 0xAA64;
 
     fn set_str(reg: &mut MockRegistry, loc: RegLocation, subkey: &str, name: &str, value: &str) {
         reg.set(loc, subkey, name, RegValue::String(value.to_string()));
@@ -1287,7 +1278,7 @@ mod tests {
 
     #[test]
     fn expand_sz_server_path_is_expanded() {
-        let _guard = crate::win::TEST_ENV_LOCK.lock().unwrap();
+        let _guard = crate::test_util::TEST_ENV_LOCK.lock().unwrap();
         std::env::set_var("LOADWHAT_COM_RESOLVER_TEST_BASE", r"C:\Expanded");
         let (mut reg, fs) = resolver_parts();
         reg.set(
@@ -1668,9 +1659,9 @@ mod tests {
     }
 
     #[test]
-    fn x86_server_for_x86_caller_skips_dependency_walk() {
+    fn x86_server_for_x86_caller_walks_dependencies() {
         let (reg, mut fs) = registered_with_server(r"C:\Vendor\server.dll");
-        fs.add_pe_with_machine(r"C:\Vendor\server.dll", MACHINE_X86, &[]);
+        fs.add_pe_with_machine(r"C:\Vendor\server.dll", MACHINE_X86, &["helper.dll"]);
 
         let resolver = ComResolver::new(&reg, &fs);
         let mut result = resolver.resolve_clsid("{SRV}", RegView::V64);
@@ -1678,14 +1669,16 @@ mod tests {
             .validate_lookup_server(&mut result, Some(MachineType::X86))
             .unwrap();
 
-        assert!(!result.is_issue());
+        assert!(result.is_issue());
         let server = result.server.unwrap();
-        assert_eq!(server.status, ServerStatus::Skipped);
+        assert_eq!(server.status, ServerStatus::DepsMissing);
+        assert_eq!(server.machine, Some(MachineType::X86));
+        assert_eq!(server.failures[0].dll, "helper.dll");
     }
 
     #[test]
     fn x86_expected_caller_validates_wow64_redirected_file() {
-        let _lock = crate::win::TEST_ENV_LOCK.lock().unwrap();
+        let _lock = crate::test_util::TEST_ENV_LOCK.lock().unwrap();
         let _guard = crate::test_util::EnvVarGuard::set("SystemRoot", r"C:\TESTWIN");
 
         let mut reg = MockRegistry::new();
@@ -1709,7 +1702,7 @@ mod tests {
 
         assert!(!result.is_issue());
         let server = result.server.unwrap();
-        assert_eq!(server.status, ServerStatus::Skipped);
+        assert_eq!(server.status, ServerStatus::Ok);
         assert_eq!(
             server.redirected_path.as_deref(),
             Some(r"C:\TESTWIN\SysWOW64\srv.dll")
@@ -2024,6 +2017,41 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn audit_arm64_target_uses_64_bit_view_and_arm64_bitness() {
+        let (mut reg, mut fs) = resolver_parts();
+        let target = r"C:\app\arm.exe";
+        fs.add_pe_with_machine(target, MACHINE_ARM64, &[]);
+        set_inproc(&mut reg, RegLocation::Hklm64, "{X}", r"C:\Vendor\x64.dll");
+        fs.add_pe(r"C:\Vendor\x64.dll", &[]);
+
+        let resolver = ComResolver::new(&reg, &fs);
+        let audit = resolver.audit(target, "{X}", QueryKind::Clsid).unwrap();
+
+        assert_eq!(audit.target_machine, MachineType::Arm64);
+        assert_eq!(audit.view, RegView::V64);
+        assert_eq!(audit.status, "BITNESS_MISMATCH");
+    }
+
+    #[test]
+    fn inproc_arm64x_server_loads_into_x64_caller() {
+        let (reg, mut fs) = registered_with_server(r"C:\Vendor\server.dll");
+        fs.add_raw(
+            r"C:\Vendor\server.dll",
+            crate::pe::testpe::build_test_pe_with_machine(MACHINE_ARM64, true),
+        );
+
+        let resolver = ComResolver::new(&reg, &fs);
+        let mut result = resolver.resolve_clsid("{SRV}", RegView::V64);
+        resolver
+            .validate_lookup_server(&mut result, Some(MachineType::X64))
+            .unwrap();
+
+        let server = result.server.unwrap();
+        assert_eq!(server.status, ServerStatus::Ok);
+        assert_eq!(server.machine, Some(MachineType::Arm64x));
+    }
+
     #[test]
     fn audit_unknown_target_machine_is_unsupported() {
         let (reg, mut fs) = resolver_parts();
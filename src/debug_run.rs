@@ -60,6 +60,7 @@ pub fn run_target(
     exe_path: &Path,
     exe_args: &[OsString],
     cwd: Option<&Path>,
+    env: Option<&[(OsString, OsString)]>,
     timeout_ms: u32,
     enable_loader_snaps_peb: bool,
 ) -> Result<RunOutcome, RunError> {
@@ -88,6 +89,7 @@ pub fn run_target(
     let command_line = build_command_line(exe_path, exe_args);
     let mut cmd_w = win::to_wide(OsStr::new(&command_line));
     let cwd_w = cwd.map(|v| win::to_wide(v.as_os_str()));
+    let mut env_w = env.map(win::environment_block);
 
     let mut si: win::StartupInfoW = unsafe { mem::zeroed() };
     si.cb = mem::size_of::<win::StartupInfoW>() as u32;
@@ -100,8 +102,11 @@ pub fn run_target(
             std::ptr::null_mut(),
             std::ptr::null_mut(),
             0,
-            win::DEBUG_ONLY_THIS_PROCESS,
-            std::ptr::null_mut(),
+            win::DEBUG_ONLY_THIS_PROCESS | win::CREATE_UNICODE_ENVIRONMENT,
+            env_w
+                .as_mut()
+                .map(|v| v.as_mut_ptr().cast())
+                .unwrap_or(std::ptr::null_mut()),
             cwd_w
                 .as_ref()
                 .map(|v| v.as_ptr())
@@ -576,7 +581,7 @@ mod tests {
             .join(format!("loadwhat-missing-{}", process::id()))
             .join("definitely_missing.exe");
         let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        let result = run_target(&missing, &[], Some(&cwd), 1000, false);
+        let result = run_target(&missing, &[], Some(&cwd), None, 1000, false);
         match result {
             Err(RunError::Message(msg)) => {
                 assert!(msg.contains("target does not exist"));
@@ -710,7 +715,7 @@ mod tests {
             OsString::from("0"),
         ];
         let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        let result = run_target(&exe, &args, Some(&cwd), 10_000, true);
+        let result = run_target(&exe, &args, Some(&cwd), None, 10_000, true);
         let outcome = match result {
             Ok(value) => value,
             Err(RunError::PebLoaderSnapsEnableFailed(_, code)) => {
@@ -1,5 +1,9 @@
 // Formats and emits the public line-oriented token output contract.
 
+use std::cell::{Cell, RefCell};
+
+use crate::sarif;
+
 pub const TOKEN_COM_AUDIT: &str = "COM_AUDIT";
 pub const TOKEN_COM_DEPENDENCY_STATUS: &str = "COM_DEPENDENCY_STATUS";
 pub const TOKEN_COM_LOOKUP: &str = "COM_LOOKUP";
@@ -7,24 +11,155 @@ pub const TOKEN_COM_MANIFEST: &str = "COM_MANIFEST";
 pub const TOKEN_COM_PROGID: &str = "COM_PROGID";
 pub const TOKEN_COM_REGISTRATION: &str = "COM_REGISTRATION";
 pub const TOKEN_COM_SERVER: &str = "COM_SERVER";
+#[cfg(windows)]
 pub const TOKEN_DEBUG_STRING: &str = "DEBUG_STRING";
 pub const TOKEN_DYNAMIC_MISSING: &str = "DYNAMIC_MISSING";
+#[cfg(windows)]
 pub const TOKEN_FIRST_BREAK: &str = "FIRST_BREAK";
 pub const TOKEN_NOTE: &str = "NOTE";
+pub const TOKEN_PATH_ISSUE: &str = "PATH_ISSUE";
+pub const TOKEN_RESOLVE: &str = "RESOLVE";
+#[cfg(windows)]
 pub const TOKEN_RUN_END: &str = "RUN_END";
 pub const TOKEN_RUN_START: &str = "RUN_START";
+#[cfg(windows)]
 pub const TOKEN_RUNTIME_LOADED: &str = "RUNTIME_LOADED";
 pub const TOKEN_SEARCH_ORDER: &str = "SEARCH_ORDER";
 pub const TOKEN_SEARCH_PATH: &str = "SEARCH_PATH";
+pub const TOKEN_SEARCH_RISK: &str = "SEARCH_RISK";
+pub const TOKEN_STATIC_APISET: &str = "STATIC_APISET";
+pub const TOKEN_STATIC_APISET_UNRESOLVED: &str = "STATIC_APISET_UNRESOLVED";
 pub const TOKEN_STATIC_BAD_IMAGE: &str = "STATIC_BAD_IMAGE";
+pub const TOKEN_STATIC_DELAY_BAD_IMAGE: &str = "STATIC_DELAY_BAD_IMAGE";
+pub const TOKEN_STATIC_DELAY_IMPORT: &str = "STATIC_DELAY_IMPORT";
+pub const TOKEN_STATIC_DELAY_MISSING: &str = "STATIC_DELAY_MISSING";
 pub const TOKEN_STATIC_END: &str = "STATIC_END";
+pub const TOKEN_STATIC_FORWARD: &str = "STATIC_FORWARD";
+pub const TOKEN_STATIC_FORWARD_BROKEN: &str = "STATIC_FORWARD_BROKEN";
 pub const TOKEN_STATIC_FOUND: &str = "STATIC_FOUND";
 pub const TOKEN_STATIC_IMPORT: &str = "STATIC_IMPORT";
 pub const TOKEN_STATIC_MISSING: &str = "STATIC_MISSING";
+pub const TOKEN_STATIC_MISSING_EXPORT: &str = "STATIC_MISSING_EXPORT";
+pub const TOKEN_STATIC_SHADOW: &str = "STATIC_SHADOW";
 pub const TOKEN_STATIC_START: &str = "STATIC_START";
+#[cfg(windows)]
 pub const TOKEN_SUCCESS: &str = "SUCCESS";
 pub const TOKEN_SUMMARY: &str = "SUMMARY";
 
+/// Version of the `--format ndjson` object layout, carried by every line.
+pub const NDJSON_SCHEMA_VERSION: u32 = 1;
+
+/// How `emit` writes each token line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `TOKEN key=value ...`
+    #[default]
+    Text,
+    /// One JSON object per token.
+    Ndjson,
+    /// One SARIF log, written when the scope ends.
+    Sarif,
+}
+
+impl OutputFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            "sarif" => Some(OutputFormat::Sarif),
+            _ => None,
+        }
+    }
+}
+
+/// One token as `emit` received it, kept for outputs that are written
+/// from the whole stream at once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenLine {
+    pub token: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl TokenLine {
+    /// The unquoted text of field `key`.
+    pub fn value(&self, key: &str) -> Option<String> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| unquote(value))
+    }
+
+    /// The token as a `--format text` line.
+    pub fn text(&self) -> String {
+        text_line(&self.token, &self.fields)
+    }
+}
+
+thread_local! {
+    static FORMAT: Cell<OutputFormat> = const { Cell::new(OutputFormat::Text) };
+    static RECORDED: RefCell<Vec<TokenLine>> = const { RefCell::new(Vec::new()) };
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Makes `format` the way `emit` writes on this thread, until dropped. A
+/// SARIF scope collects the tokens and prints the log when it is dropped.
+pub struct OutputFormatScope {
+    format: OutputFormat,
+    previous: OutputFormat,
+    recorded: Vec<TokenLine>,
+}
+
+impl OutputFormatScope {
+    pub fn enter(format: OutputFormat) -> OutputFormatScope {
+        OutputFormatScope {
+            format,
+            previous: FORMAT.with(|current| current.replace(format)),
+            recorded: RECORDED.with(|recorded| recorded.take()),
+        }
+    }
+}
+
+impl Drop for OutputFormatScope {
+    fn drop(&mut self) {
+        FORMAT.with(|current| current.set(self.previous));
+        let lines = RECORDED.with(|recorded| recorded.replace(std::mem::take(&mut self.recorded)));
+        if self.format == OutputFormat::Sarif {
+            println!("{}", sarif::render(&lines));
+        }
+    }
+}
+
+/// Keeps a copy of every token `emit` writes on this thread, whatever the
+/// format, until dropped.
+pub struct TokenRecording {
+    previous: bool,
+    start: usize,
+}
+
+impl TokenRecording {
+    pub fn start() -> TokenRecording {
+        TokenRecording {
+            previous: RECORDING.with(|recording| recording.replace(true)),
+            start: RECORDED.with(|recorded| recorded.borrow().len()),
+        }
+    }
+
+    /// The tokens emitted since `start`.
+    pub fn lines(&self) -> Vec<TokenLine> {
+        RECORDED.with(|recorded| recorded.borrow()[self.start..].to_vec())
+    }
+}
+
+impl Drop for TokenRecording {
+    fn drop(&mut self) {
+        RECORDING.with(|recording| recording.set(self.previous));
+        // A SARIF scope still needs the tokens.
+        if !self.previous && FORMAT.with(Cell::get) != OutputFormat::Sarif {
+            RECORDED.with(|recorded| recorded.borrow_mut().truncate(self.start));
+        }
+    }
+}
+
 #[derive(Clone, Copy, Default)]
 pub struct SummaryCounts {
     pub static_missing: usize,
@@ -32,9 +167,27 @@ pub struct SummaryCounts {
     pub dynamic_missing: usize,
     pub runtime_loaded: usize,
     pub com_issues: usize,
+    pub static_delay_missing: usize,
 }
 
 pub fn emit(token: &str, fields: &[(String, String)]) {
+    let format = FORMAT.with(Cell::get);
+    if format == OutputFormat::Sarif || RECORDING.with(Cell::get) {
+        RECORDED.with(|recorded| {
+            recorded.borrow_mut().push(TokenLine {
+                token: token.to_string(),
+                fields: fields.to_vec(),
+            })
+        });
+    }
+    match format {
+        OutputFormat::Text => println!("{}", text_line(token, fields)),
+        OutputFormat::Ndjson => println!("{}", ndjson_line(token, fields)),
+        OutputFormat::Sarif => {}
+    }
+}
+
+fn text_line(token: &str, fields: &[(String, String)]) -> String {
     let mut line = String::with_capacity(128);
     line.push_str(token);
     for (key, value) in fields {
@@ -43,7 +196,131 @@ pub fn emit(token: &str, fields: &[(String, String)]) {
         line.push('=');
         line.push_str(value);
     }
-    println!("{line}");
+    line
+}
+
+/// The token as a JSON object: `token` and `schema_version` first, then the
+/// fields in order, each typed by `json_value`.
+fn ndjson_line(token: &str, fields: &[(String, String)]) -> String {
+    let mut line = String::with_capacity(160);
+    line.push_str("{\"token\":");
+    line.push_str(&json_string(token));
+    line.push_str(&format!(",\"schema_version\":{NDJSON_SCHEMA_VERSION}"));
+    for (key, value) in fields {
+        line.push(',');
+        line.push_str(&json_string(key));
+        line.push(':');
+        line.push_str(&json_value(key, value));
+    }
+    line.push('}');
+    line
+}
+
+/// JSON type of a bare field value, part of the versioned NDJSON schema.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum JsonType {
+    Number,
+    /// `true`/`false`, or `1`/`0` as `SEARCH_ORDER` writes `safedll`.
+    Boolean,
+}
+
+/// Field names whose bare values are not strings. A name has the same type
+/// on every token; every other field, including the `0x` hex codes and
+/// addresses, is a string so 64-bit values keep their precision.
+const NDJSON_FIELD_TYPES: &[(&str, JsonType)] = &[
+    ("com_issues", JsonType::Number),
+    ("delay", JsonType::Boolean),
+    ("depth", JsonType::Number),
+    ("dynamic_missing", JsonType::Number),
+    ("elapsed_ms", JsonType::Number),
+    ("exit_code", JsonType::Number),
+    ("first", JsonType::Number),
+    ("first_break", JsonType::Boolean),
+    ("index", JsonType::Number),
+    ("length", JsonType::Number),
+    ("limit", JsonType::Number),
+    ("order", JsonType::Number),
+    ("ordinal", JsonType::Number),
+    ("pid", JsonType::Number),
+    ("registrations", JsonType::Number),
+    ("runtime_loaded", JsonType::Number),
+    ("safedll", JsonType::Boolean),
+    ("signed", JsonType::Boolean),
+    ("size", JsonType::Number),
+    ("static_bad_image", JsonType::Number),
+    ("static_delay_missing", JsonType::Number),
+    ("static_missing", JsonType::Number),
+    ("tid", JsonType::Number),
+];
+
+/// Field `key`'s rendered value as JSON. Quoted values are strings; bare
+/// values take the type `NDJSON_FIELD_TYPES` gives `key`, falling back to a
+/// string when the value does not fit it.
+pub fn json_value(key: &str, value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        return json_string(&unquote(value));
+    }
+    let kind = NDJSON_FIELD_TYPES
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, kind)| *kind);
+    match (kind, value) {
+        (Some(JsonType::Boolean), "true" | "1") => "true".to_string(),
+        (Some(JsonType::Boolean), "false" | "0") => "false".to_string(),
+        (Some(JsonType::Number), _) if is_decimal(value) => value.to_string(),
+        _ => json_string(value),
+    }
+}
+
+fn is_decimal(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+/// Reverses `quote`; a bare value is returned as it is.
+pub fn unquote(value: &str) -> String {
+    let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+pub fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            other => out.push(other),
+        }
+    }
+    out.push('"');
+    out
 }
 
 pub fn field<K: Into<String>, V: Into<String>>(key: K, value: V) -> (String, String) {
@@ -67,10 +344,12 @@ pub fn quote(value: &str) -> String {
     out
 }
 
+#[cfg_attr(not(windows), allow(dead_code))]
 pub fn hex_u32(value: u32) -> String {
     format!("0x{value:08X}")
 }
 
+#[cfg_attr(not(windows), allow(dead_code))]
 pub fn hex_usize(value: usize) -> String {
     format!("0x{value:016X}")
 }
@@ -83,12 +362,19 @@ pub fn summary_fields(first_break: bool, counts: SummaryCounts) -> Vec<(String,
         field("dynamic_missing", counts.dynamic_missing.to_string()),
         field("runtime_loaded", counts.runtime_loaded.to_string()),
         field("com_issues", counts.com_issues.to_string()),
+        field(
+            "static_delay_missing",
+            counts.static_delay_missing.to_string(),
+        ),
     ]
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{field, hex_u32, hex_usize, quote, summary_fields, SummaryCounts};
+    use super::{
+        field, hex_u32, hex_usize, json_value, ndjson_line, quote, summary_fields, OutputFormat,
+        SummaryCounts,
+    };
 
     #[test]
     fn quote_wraps_plain_text() {
@@ -170,6 +456,56 @@ mod tests {
         assert_eq!(hex_usize(0xFEDC_BA98_7654_3210usize), "0xFEDCBA9876543210");
     }
 
+    #[test]
+    fn ndjson_types_fields_by_name_and_unquotes_strings() {
+        let line = ndjson_line(
+            "RUN_END",
+            &[
+                field("kind", quote("exception")),
+                field("code", hex_u32(0xC000_0135)),
+                field("elapsed_ms", "42"),
+                field("first_break", "true"),
+                field("path", quote("C:\\App\\\"odd\"\tname.dll")),
+                field("order", "007"),
+                field("view", "both"),
+            ],
+        );
+        assert_eq!(
+            line,
+            r#"{"token":"RUN_END","schema_version":1,"kind":"exception","code":"0xC0000135","elapsed_ms":42,"first_break":true,"path":"C:\\App\\\"odd\"\tname.dll","order":"007","view":"both"}"#
+        );
+    }
+
+    #[test]
+    fn ndjson_keeps_addresses_above_2_pow_53_as_exact_strings() {
+        let line = ndjson_line(
+            "RUNTIME_LOADED",
+            &[field("base", hex_usize(0xFFFF_F800_0000_1001))],
+        );
+        assert_eq!(
+            line,
+            r#"{"token":"RUNTIME_LOADED","schema_version":1,"base":"0xFFFFF80000001001"}"#
+        );
+    }
+
+    #[test]
+    fn ndjson_types_safedll_as_a_boolean() {
+        assert_eq!(
+            ndjson_line("SEARCH_ORDER", &[field("safedll", "1")]),
+            r#"{"token":"SEARCH_ORDER","schema_version":1,"safedll":true}"#
+        );
+        assert_eq!(json_value("safedll", "0"), "false");
+        assert_eq!(json_value("static_missing", "3"), "3");
+        assert_eq!(json_value("module", "3"), r#""3""#);
+    }
+
+    #[test]
+    fn output_format_names_are_case_insensitive() {
+        assert_eq!(OutputFormat::parse("NDJSON"), Some(OutputFormat::Ndjson));
+        assert_eq!(OutputFormat::parse("text"), Some(OutputFormat::Text));
+        assert_eq!(OutputFormat::parse("json"), None);
+    }
+
     #[test]
     fn summary_fields_reports_all_zero_counts() {
         assert_eq!(
@@ -181,6 +517,7 @@ mod tests {
                 field("dynamic_missing", "0"),
                 field("runtime_loaded", "0"),
                 field("com_issues", "0"),
+                field("static_delay_missing", "0"),
             ]
         );
     }
@@ -196,6 +533,7 @@ mod tests {
                     dynamic_missing: 3,
                     runtime_loaded: 4,
                     com_issues: 5,
+                    static_delay_missing: 6,
                 },
             ),
             vec![
@@ -205,6 +543,7 @@ mod tests {
                 field("dynamic_missing", "3"),
                 field("runtime_loaded", "4"),
                 field("com_issues", "5"),
+                field("static_delay_missing", "6"),
             ]
         );
     }
@@ -224,6 +563,7 @@ mod tests {
                 "dynamic_missing",
                 "runtime_loaded",
                 "com_issues",
+                "static_delay_missing",
             ]
         );
     }
@@ -0,0 +1,158 @@
+// Applies `--env`/`--unset`/`--path-*` edits to a Windows environment.
+
+use std::ffi::{OsStr, OsString};
+
+/// One environment option, applied in command-line order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvEdit {
+    /// `--env NAME=VALUE`
+    Set(OsString, OsString),
+    /// `--unset NAME`
+    Unset(OsString),
+    /// `--path-prepend <dir>`
+    PathPrepend(OsString),
+    /// `--path-append <dir>`
+    PathAppend(OsString),
+}
+
+impl EnvEdit {
+    /// Parses a `--env` value; the name is everything before the first `=`.
+    pub fn parse_set(value: &OsStr) -> Result<Self, String> {
+        let text = value.to_string_lossy();
+        match text.split_once('=') {
+            Some((name, val)) if !name.is_empty() => {
+                Ok(EnvEdit::Set(OsString::from(name), OsString::from(val)))
+            }
+            _ => Err(format!("--env expects NAME=VALUE, got: {text}")),
+        }
+    }
+
+    fn touches_path(&self) -> bool {
+        match self {
+            EnvEdit::Set(name, _) | EnvEdit::Unset(name) => is_path(name),
+            EnvEdit::PathPrepend(_) | EnvEdit::PathAppend(_) => true,
+        }
+    }
+}
+
+/// Whether any edit changes PATH.
+pub fn touches_path(edits: &[EnvEdit]) -> bool {
+    edits.iter().any(EnvEdit::touches_path)
+}
+
+/// Applies `edits` to `vars`. Names match case-insensitively, as on
+/// Windows; a replaced variable keeps its position and spelling.
+pub fn apply(vars: &mut Vec<(OsString, OsString)>, edits: &[EnvEdit]) {
+    for edit in edits {
+        match edit {
+            EnvEdit::Set(name, value) => set(vars, name, value.clone()),
+            EnvEdit::Unset(name) => vars.retain(|(key, _)| !same_name(key, name)),
+            EnvEdit::PathPrepend(dir) | EnvEdit::PathAppend(dir) => {
+                let current = lookup(vars, OsStr::new("PATH")).unwrap_or_default();
+                let mut value = OsString::new();
+                if matches!(edit, EnvEdit::PathAppend(_)) {
+                    value.push(&current);
+                    if !current.is_empty() {
+                        value.push(";");
+                    }
+                    value.push(dir);
+                } else {
+                    value.push(dir);
+                    if !current.is_empty() {
+                        value.push(";");
+                    }
+                    value.push(&current);
+                }
+                set(vars, OsStr::new("PATH"), value);
+            }
+        }
+    }
+}
+
+/// The value of `name` in `vars`.
+pub fn lookup(vars: &[(OsString, OsString)], name: &OsStr) -> Option<OsString> {
+    vars.iter()
+        .find(|(key, _)| same_name(key, name))
+        .map(|(_, value)| value.clone())
+}
+
+fn set(vars: &mut Vec<(OsString, OsString)>, name: &OsStr, value: OsString) {
+    match vars.iter_mut().find(|(key, _)| same_name(key, name)) {
+        Some((_, existing)) => *existing = value,
+        None => vars.push((name.to_os_string(), value)),
+    }
+}
+
+fn is_path(name: &OsStr) -> bool {
+    same_name(name, OsStr::new("PATH"))
+}
+
+fn same_name(a: &OsStr, b: &OsStr) -> bool {
+    a.to_string_lossy()
+        .eq_ignore_ascii_case(&b.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, lookup, touches_path, EnvEdit};
+    use std::ffi::{OsStr, OsString};
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (OsString::from(name), OsString::from(value)))
+            .collect()
+    }
+
+    #[test]
+    fn edits_apply_in_order_with_case_insensitive_names() {
+        let mut env = vars(&[("Path", r"C:\Windows\System32"), ("TEMP", r"C:\Temp")]);
+        let edits = [
+            EnvEdit::PathPrepend(OsString::from(r"C:\first")),
+            EnvEdit::PathPrepend(OsString::from(r"C:\before-first")),
+            EnvEdit::PathAppend(OsString::from(r"C:\last")),
+            EnvEdit::parse_set(OsStr::new("temp=D:\\scratch=1")).unwrap(),
+            EnvEdit::Unset(OsString::from("missing")),
+        ];
+        apply(&mut env, &edits);
+
+        assert_eq!(
+            env,
+            vars(&[
+                (
+                    "Path",
+                    r"C:\before-first;C:\first;C:\Windows\System32;C:\last"
+                ),
+                ("TEMP", r"D:\scratch=1"),
+            ])
+        );
+        assert!(touches_path(&edits));
+        assert!(!touches_path(&edits[3..]));
+    }
+
+    #[test]
+    fn unset_path_then_append_starts_a_fresh_value() {
+        let mut env = vars(&[("PATH", r"C:\old")]);
+        apply(
+            &mut env,
+            &[
+                EnvEdit::Unset(OsString::from("path")),
+                EnvEdit::PathAppend(OsString::from(r"C:\only")),
+            ],
+        );
+        assert_eq!(
+            lookup(&env, OsStr::new("Path")),
+            Some(OsString::from(r"C:\only"))
+        );
+    }
+
+    #[test]
+    fn set_requires_a_name_and_an_equals_sign() {
+        assert!(EnvEdit::parse_set(OsStr::new("=C:")).is_err());
+        assert!(EnvEdit::parse_set(OsStr::new("NAME")).is_err());
+        assert_eq!(
+            EnvEdit::parse_set(OsStr::new("EMPTY=")),
+            Ok(EnvEdit::Set(OsString::from("EMPTY"), OsString::new()))
+        );
+    }
+}
@@ -0,0 +1,384 @@
+// Renders the static import closure as a Graphviz DOT or Mermaid graph for `imports --graph`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::tree::{TreeEdge, TreeStatus};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "dot" => Some(GraphFormat::Dot),
+            "mermaid" => Some(GraphFormat::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+/// What `imports --graph` writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphOptions {
+    pub format: GraphFormat,
+    /// Collapse every module under the Windows directory into one node.
+    pub cluster_system: bool,
+}
+
+const SYSTEM_KEY: &str = "\0system";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Root,
+    Loaded,
+    Missing,
+    BadImage,
+    System,
+}
+
+struct Node {
+    kind: NodeKind,
+    /// Module name, status and directory, one per label line.
+    label: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum EdgeStyle {
+    Import,
+    Delay,
+    Forward,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    style: EdgeStyle,
+    /// The API set name an import went through.
+    via: Option<String>,
+}
+
+/// The graph rooted at `root_key`: one node per module (missing and bad
+/// images included), one edge per distinct import, in walk order.
+pub fn render(
+    root_name: &str,
+    root_path: &str,
+    root_key: &str,
+    edges: &[TreeEdge],
+    options: &GraphOptions,
+) -> Vec<String> {
+    let graph = build(
+        root_name,
+        root_path,
+        root_key,
+        edges,
+        options.cluster_system,
+    );
+    match options.format {
+        GraphFormat::Dot => render_dot(&graph),
+        GraphFormat::Mermaid => render_mermaid(&graph),
+    }
+}
+
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+fn build(
+    root_name: &str,
+    root_path: &str,
+    root_key: &str,
+    edges: &[TreeEdge],
+    cluster_system: bool,
+) -> Graph {
+    let system_modules: HashSet<&str> = edges
+        .iter()
+        .filter(|edge| edge.system)
+        .filter_map(|edge| edge.child.as_deref())
+        .collect();
+    let collapse = |key: &str| {
+        if cluster_system && system_modules.contains(key) {
+            SYSTEM_KEY.to_string()
+        } else {
+            key.to_string()
+        }
+    };
+
+    let mut nodes = vec![Node {
+        kind: NodeKind::Root,
+        label: label_lines(root_name, "ROOT", Some(root_path)),
+    }];
+    let mut index: HashMap<String, usize> = HashMap::from([(root_key.to_string(), 0)]);
+    let mut graph_edges = Vec::new();
+    let mut seen_edges = HashSet::new();
+    for edge in edges {
+        let from = collapse(&edge.parent);
+        let to = match &edge.child {
+            Some(child) => collapse(child),
+            None => format!("\0{}\0{}", edge.dll, edge.status.label()),
+        };
+        if from == SYSTEM_KEY && to == SYSTEM_KEY {
+            continue;
+        }
+        // A parent is always reached before its imports are walked.
+        let Some(&from) = index.get(&from) else {
+            continue;
+        };
+        let to = *index.entry(to.clone()).or_insert_with(|| {
+            nodes.push(target_node(edge, &to, system_modules.len()));
+            nodes.len() - 1
+        });
+        let style = if edge.forward {
+            EdgeStyle::Forward
+        } else if edge.delay {
+            EdgeStyle::Delay
+        } else {
+            EdgeStyle::Import
+        };
+        let via = matches!(edge.status, TreeStatus::ApiSet(_)).then(|| edge.dll.clone());
+        if seen_edges.insert((from, to, style, via.clone())) {
+            graph_edges.push(Edge {
+                from,
+                to,
+                style,
+                via,
+            });
+        }
+    }
+    Graph {
+        nodes,
+        edges: graph_edges,
+    }
+}
+
+fn target_node(edge: &TreeEdge, key: &str, system_count: usize) -> Node {
+    if key == SYSTEM_KEY {
+        return Node {
+            kind: NodeKind::System,
+            label: vec![
+                "Windows directory".to_string(),
+                format!("{system_count} modules"),
+            ],
+        };
+    }
+    let (name, status) = match &edge.status {
+        TreeStatus::ApiSet(host) => (host.as_str(), TreeStatus::Found.label()),
+        status => (edge.dll.as_str(), status.label()),
+    };
+    let kind = match edge.status {
+        TreeStatus::Missing(_) => NodeKind::Missing,
+        TreeStatus::BadImage(_) => NodeKind::BadImage,
+        _ => NodeKind::Loaded,
+    };
+    Node {
+        kind,
+        label: label_lines(name, &status, edge.path.as_deref()),
+    }
+}
+
+/// Name, status and, for a loaded module, the directory it came from.
+fn label_lines(name: &str, status: &str, path: Option<&str>) -> Vec<String> {
+    let mut lines = vec![name.to_string(), status.to_string()];
+    if let Some(dir) = path.and_then(|path| path.rfind(['\\', '/']).map(|end| &path[..end])) {
+        lines.push(dir.to_string());
+    }
+    lines
+}
+
+fn render_dot(graph: &Graph) -> Vec<String> {
+    let mut lines = vec![
+        "digraph imports {".to_string(),
+        "  rankdir=LR;".to_string(),
+        "  node [shape=box];".to_string(),
+    ];
+    for (id, node) in graph.nodes.iter().enumerate() {
+        let label: Vec<String> = node.label.iter().map(|line| dot_escape(line)).collect();
+        let style = match node.kind {
+            NodeKind::Root => ", style=bold",
+            NodeKind::Loaded => "",
+            NodeKind::Missing => ", color=\"#c00000\", style=filled, fillcolor=\"#fde2e2\"",
+            NodeKind::BadImage => ", color=\"#b35c00\", style=filled, fillcolor=\"#fff0d9\"",
+            NodeKind::System => ", shape=folder, style=filled, fillcolor=\"#eeeeee\"",
+        };
+        lines.push(format!("  n{id} [label=\"{}\"{style}];", label.join("\\n")));
+    }
+    for edge in &graph.edges {
+        let mut attrs = Vec::new();
+        match edge.style {
+            EdgeStyle::Import => {}
+            EdgeStyle::Delay => attrs.push("style=dashed".to_string()),
+            EdgeStyle::Forward => {
+                attrs.push("style=dotted".to_string());
+                attrs.push("label=\"forward\"".to_string());
+            }
+        }
+        if let Some(via) = &edge.via {
+            attrs.push(format!("label=\"{}\"", dot_escape(via)));
+        }
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        };
+        lines.push(format!("  n{} -> n{}{attrs};", edge.from, edge.to));
+    }
+    lines.push("}".to_string());
+    lines
+}
+
+fn render_mermaid(graph: &Graph) -> Vec<String> {
+    let mut lines = vec!["flowchart LR".to_string()];
+    for (id, node) in graph.nodes.iter().enumerate() {
+        let label: Vec<String> = node.label.iter().map(|line| mermaid_escape(line)).collect();
+        let label = label.join("<br/>");
+        lines.push(match node.kind {
+            NodeKind::System => format!("  n{id}[[\"{label}\"]]"),
+            _ => format!("  n{id}[\"{label}\"]"),
+        });
+    }
+    for edge in &graph.edges {
+        let arrow = match edge.style {
+            EdgeStyle::Import => "-->",
+            EdgeStyle::Delay | EdgeStyle::Forward => "-.->",
+        };
+        let label = match (edge.style, &edge.via) {
+            (_, Some(via)) => format!("|\"{}\"|", mermaid_escape(via)),
+            (EdgeStyle::Forward, None) => "|forward|".to_string(),
+            _ => String::new(),
+        };
+        lines.push(format!("  n{} {arrow}{label} n{}", edge.from, edge.to));
+    }
+    for (class, kind, style) in [
+        ("missing", NodeKind::Missing, "fill:#fde2e2,stroke:#c00000"),
+        ("bad", NodeKind::BadImage, "fill:#fff0d9,stroke:#b35c00"),
+    ] {
+        let ids: Vec<String> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.kind == kind)
+            .map(|(id, _)| format!("n{id}"))
+            .collect();
+        if !ids.is_empty() {
+            lines.push(format!("  classDef {class} {style}"));
+            lines.push(format!("  class {} {class}", ids.join(",")));
+        }
+    }
+    lines
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(value: &str) -> String {
+    value.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, GraphFormat, GraphOptions};
+    use crate::tree::{TreeEdge, TreeStatus};
+
+    fn edge(parent: &str, dll: &str, status: TreeStatus, path: Option<&str>) -> TreeEdge {
+        TreeEdge {
+            parent: parent.to_ascii_lowercase(),
+            dll: dll.to_string(),
+            delay: false,
+            forward: false,
+            status,
+            path: path.map(str::to_string),
+            child: path.map(str::to_ascii_lowercase),
+            system: path.is_some_and(|path| path.starts_with(r"C:\Windows\")),
+        }
+    }
+
+    fn closure() -> Vec<TreeEdge> {
+        let app = r"C:\App\app.exe";
+        let core = r"C:\App\core.dll";
+        let kernel32 = r"C:\Windows\System32\kernel32.dll";
+        let kernelbase = r"C:\Windows\System32\kernelbase.dll";
+        vec![
+            edge(app, "core.dll", TreeStatus::Found, Some(core)),
+            edge(app, "kernel32.dll", TreeStatus::KnownDll, Some(kernel32)),
+            TreeEdge {
+                delay: true,
+                ..edge(app, "late.dll", TreeStatus::Missing("NOT_FOUND"), None)
+            },
+            edge(
+                core,
+                "api-ms-win-core-file-l1-1-0.dll",
+                TreeStatus::ApiSet("kernelbase.dll".to_string()),
+                Some(kernelbase),
+            ),
+            TreeEdge {
+                forward: true,
+                ..edge(
+                    kernel32,
+                    "kernelbase.dll",
+                    TreeStatus::Found,
+                    Some(kernelbase),
+                )
+            },
+        ]
+    }
+
+    fn graph(format: GraphFormat, cluster_system: bool) -> Vec<String> {
+        render(
+            "app.exe",
+            r"C:\App\app.exe",
+            r"c:\app\app.exe",
+            &closure(),
+            &GraphOptions {
+                format,
+                cluster_system,
+            },
+        )
+    }
+
+    #[test]
+    fn dot_styles_problem_nodes_and_non_import_edges() {
+        assert_eq!(
+            graph(GraphFormat::Dot, false),
+            [
+                "digraph imports {",
+                "  rankdir=LR;",
+                "  node [shape=box];",
+                r#"  n0 [label="app.exe\nROOT\nC:\\App", style=bold];"#,
+                r#"  n1 [label="core.dll\nFOUND\nC:\\App"];"#,
+                r#"  n2 [label="kernel32.dll\nKNOWN_DLL\nC:\\Windows\\System32"];"#,
+                r##"  n3 [label="late.dll\nMISSING NOT_FOUND", color="#c00000", style=filled, fillcolor="#fde2e2"];"##,
+                r#"  n4 [label="kernelbase.dll\nFOUND\nC:\\Windows\\System32"];"#,
+                "  n0 -> n1;",
+                "  n0 -> n2;",
+                "  n0 -> n3 [style=dashed];",
+                r#"  n1 -> n4 [label="api-ms-win-core-file-l1-1-0.dll"];"#,
+                r#"  n2 -> n4 [style=dotted, label="forward"];"#,
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn mermaid_collapses_system_modules_into_one_node() {
+        assert_eq!(
+            graph(GraphFormat::Mermaid, true),
+            [
+                "flowchart LR",
+                r#"  n0["app.exe<br/>ROOT<br/>C:\App"]"#,
+                r#"  n1["core.dll<br/>FOUND<br/>C:\App"]"#,
+                r#"  n2[["Windows directory<br/>2 modules"]]"#,
+                r#"  n3["late.dll<br/>MISSING NOT_FOUND"]"#,
+                "  n0 --> n1",
+                "  n0 --> n2",
+                "  n0 -.-> n3",
+                r#"  n1 -->|"api-ms-win-core-file-l1-1-0.dll"| n2"#,
+                "  classDef missing fill:#fde2e2,stroke:#c00000",
+                "  class n3 missing",
+            ]
+        );
+    }
+}
@@ -0,0 +1,694 @@
+// Reads offline registry hive files (regf) so machine settings can be taken
+// from a mounted Windows volume instead of the live registry.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Offset of the first hive bin; cell offsets are relative to it.
+const HBIN_START: usize = 0x1000;
+/// Largest value payload stored in a single cell; bigger ones use a `db`
+/// cell whose segments each hold at most this many bytes.
+const BIG_DATA_SEGMENT: usize = 16344;
+/// `nk` flag: the key name is stored as Latin-1 rather than UTF-16.
+const KEY_COMP_NAME: u16 = 0x0020;
+/// `vk` flag: the value name is stored as Latin-1 rather than UTF-16.
+const VALUE_COMP_NAME: u16 = 0x0001;
+
+pub const REG_SZ: u32 = 1;
+pub const REG_EXPAND_SZ: u32 = 2;
+pub const REG_DWORD: u32 = 4;
+pub const REG_MULTI_SZ: u32 = 7;
+pub const REG_QWORD: u32 = 11;
+
+/// A registry hive file loaded into memory. Lookups are case-insensitive,
+/// like the registry itself; a damaged cell makes the affected key or value
+/// read as absent rather than failing the whole hive.
+pub struct Hive {
+    data: Vec<u8>,
+    root: usize,
+    /// Lowercased subkey names of each key opened so far, so repeated
+    /// lookups under large keys (`Classes\CLSID`) stay cheap.
+    children: RefCell<HashMap<usize, HashMap<String, HiveKey>>>,
+}
+
+/// A key inside a `Hive`, identified by its `nk` cell offset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HiveKey(usize);
+
+/// One registry value with its declared type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HiveValue {
+    String(String),
+    ExpandString(String),
+    MultiString(Vec<String>),
+    Dword(u32),
+    Qword(u64),
+    Binary { kind: u32, data: Vec<u8> },
+}
+
+impl HiveValue {
+    pub fn as_dword(&self) -> Option<u32> {
+        match self {
+            HiveValue::Dword(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl Hive {
+    pub fn open(path: &Path) -> Result<Hive, String> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        Hive::from_bytes(data).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Hive, String> {
+        if data.len() < HBIN_START || &data[0..4] != b"regf" {
+            return Err("not a registry hive (missing regf signature)".to_string());
+        }
+        let root = HBIN_START + read_u32(&data, 0x24).ok_or("truncated hive header")? as usize;
+        let hive = Hive {
+            data,
+            root,
+            children: RefCell::new(HashMap::new()),
+        };
+        match hive.cell(root) {
+            Some(cell) if cell.starts_with(b"nk") => Ok(hive),
+            _ => Err("hive root key cell is missing or damaged".to_string()),
+        }
+    }
+
+    pub fn root(&self) -> HiveKey {
+        HiveKey(self.root)
+    }
+
+    /// Opens `path` (backslash separated, relative to the hive root). An
+    /// empty path is the root itself.
+    pub fn open_key(&self, path: &str) -> Option<HiveKey> {
+        path.split('\\')
+            .filter(|part| !part.is_empty())
+            .try_fold(self.root(), |key, part| self.subkey(key, part))
+    }
+
+    pub fn subkey(&self, key: HiveKey, name: &str) -> Option<HiveKey> {
+        let mut children = self.children.borrow_mut();
+        let index = children.entry(key.0).or_insert_with(|| {
+            let mut index = HashMap::new();
+            for child in self.subkeys(key) {
+                index
+                    .entry(self.key_name(child).to_lowercase())
+                    .or_insert(child);
+            }
+            index
+        });
+        index.get(&name.to_lowercase()).copied()
+    }
+
+    pub fn key_name(&self, key: HiveKey) -> String {
+        let Some(cell) = self.cell(key.0) else {
+            return String::new();
+        };
+        let flags = read_u16(cell, 0x02).unwrap_or(0);
+        let len = read_u16(cell, 0x48).unwrap_or(0) as usize;
+        cell.get(0x4C..0x4C + len)
+            .map(|raw| decode_name(raw, flags & KEY_COMP_NAME != 0))
+            .unwrap_or_default()
+    }
+
+    /// Subkeys of `key` in the order the hive stores them.
+    pub fn subkeys(&self, key: HiveKey) -> Vec<HiveKey> {
+        let mut out = Vec::new();
+        if let Some(cell) = self.cell(key.0) {
+            let count = read_u32(cell, 0x14).unwrap_or(0);
+            let list = read_u32(cell, 0x1C).unwrap_or(u32::MAX);
+            if count > 0 && list != u32::MAX {
+                self.collect_subkeys(list as usize, &mut out, 0);
+            }
+        }
+        out
+    }
+
+    fn collect_subkeys(&self, list: usize, out: &mut Vec<HiveKey>, depth: usize) {
+        let Some(cell) = self.cell(HBIN_START + list) else {
+            return;
+        };
+        // lf/lh entries carry a name hint after each offset; li/ri do not.
+        let (stride, nested) = match cell.get(0..2) {
+            Some(b"lf") | Some(b"lh") => (8, false),
+            Some(b"li") => (4, false),
+            Some(b"ri") => (4, true),
+            _ => return,
+        };
+        // The stored count is untrusted; never walk past the cell.
+        let count =
+            (read_u16(cell, 2).unwrap_or(0) as usize).min(cell.len().saturating_sub(4) / stride);
+        for index in 0..count {
+            let Some(offset) = read_u32(cell, 4 + index * stride) else {
+                break;
+            };
+            if nested {
+                // Index roots only ever point at leaf lists.
+                if depth == 0 {
+                    self.collect_subkeys(offset as usize, out, depth + 1);
+                }
+            } else if self
+                .cell(HBIN_START + offset as usize)
+                .is_some_and(|child| child.starts_with(b"nk"))
+            {
+                out.push(HiveKey(HBIN_START + offset as usize));
+            }
+        }
+    }
+
+    /// Names of the values stored under `key`; "" is the default value.
+    pub fn value_names(&self, key: HiveKey) -> Vec<String> {
+        self.value_cells(key)
+            .into_iter()
+            .map(|vk| self.value_name(vk))
+            .collect()
+    }
+
+    /// Reads value `name` of `key`; "" reads the default value.
+    pub fn value(&self, key: HiveKey, name: &str) -> Option<HiveValue> {
+        self.raw_value(key, name)
+            .map(|(kind, data)| decode_value(kind, data))
+    }
+
+    /// The type and undecoded payload of value `name`.
+    pub fn raw_value(&self, key: HiveKey, name: &str) -> Option<(u32, Vec<u8>)> {
+        let vk = self
+            .value_cells(key)
+            .into_iter()
+            .find(|vk| self.value_name(*vk).eq_ignore_ascii_case(name))?;
+        let cell = self.cell(vk)?;
+        Some((read_u32(cell, 0x0C)?, self.value_data(cell)?))
+    }
+
+    fn value_cells(&self, key: HiveKey) -> Vec<usize> {
+        let Some(cell) = self.cell(key.0) else {
+            return Vec::new();
+        };
+        let count = read_u32(cell, 0x24).unwrap_or(0) as usize;
+        let Some(list) = read_u32(cell, 0x28)
+            .filter(|offset| count > 0 && *offset != u32::MAX)
+            .and_then(|offset| self.cell(HBIN_START + offset as usize))
+        else {
+            return Vec::new();
+        };
+        // The stored count is untrusted; never walk past the list cell.
+        (0..count.min(list.len() / 4))
+            .map_while(|index| read_u32(list, index * 4))
+            .map(|offset| HBIN_START + offset as usize)
+            .filter(|vk| self.cell(*vk).is_some_and(|cell| cell.starts_with(b"vk")))
+            .collect()
+    }
+
+    fn value_name(&self, vk: usize) -> String {
+        let Some(cell) = self.cell(vk) else {
+            return String::new();
+        };
+        let len = read_u16(cell, 0x02).unwrap_or(0) as usize;
+        let flags = read_u16(cell, 0x10).unwrap_or(0);
+        cell.get(0x14..0x14 + len)
+            .map(|raw| decode_name(raw, flags & VALUE_COMP_NAME != 0))
+            .unwrap_or_default()
+    }
+
+    fn value_data(&self, vk: &[u8]) -> Option<Vec<u8>> {
+        let raw_size = read_u32(vk, 0x04)?;
+        let size = (raw_size & 0x7FFF_FFFF) as usize;
+        if raw_size & 0x8000_0000 != 0 {
+            // Payloads of up to four bytes live in the offset field itself.
+            return vk.get(0x08..0x08 + size.min(4)).map(<[u8]>::to_vec);
+        }
+        let cell = self.cell(HBIN_START + read_u32(vk, 0x08)? as usize)?;
+        if size > BIG_DATA_SEGMENT && cell.starts_with(b"db") {
+            return self.big_data(cell, size);
+        }
+        cell.get(..size).map(<[u8]>::to_vec)
+    }
+
+    fn big_data(&self, db: &[u8], size: usize) -> Option<Vec<u8>> {
+        let list = self.cell(HBIN_START + read_u32(db, 4)? as usize)?;
+        let segments = (read_u16(db, 2)? as usize).min(list.len() / 4);
+        // The stored size is untrusted; reject it before allocating for it.
+        if size > segments * BIG_DATA_SEGMENT {
+            return None;
+        }
+        let mut out = Vec::with_capacity(size);
+        for index in 0..segments {
+            let segment = self.cell(HBIN_START + read_u32(list, index * 4)? as usize)?;
+            let take = (size - out.len()).min(BIG_DATA_SEGMENT).min(segment.len());
+            out.extend_from_slice(&segment[..take]);
+        }
+        (out.len() == size).then_some(out)
+    }
+
+    /// Payload of the allocated cell at `offset` (absolute), without its
+    /// size header.
+    fn cell(&self, offset: usize) -> Option<&[u8]> {
+        let size = read_u32(&self.data, offset)? as i32;
+        // Allocated cells carry a negative size.
+        if size >= 0 {
+            return None;
+        }
+        let len = size.unsigned_abs() as usize;
+        self.data
+            .get(offset.checked_add(4)?..offset.checked_add(len)?)
+    }
+}
+
+fn decode_value(kind: u32, data: Vec<u8>) -> HiveValue {
+    match kind {
+        REG_SZ => HiveValue::String(decode_utf16z(&data)),
+        REG_EXPAND_SZ => HiveValue::ExpandString(decode_utf16z(&data)),
+        REG_MULTI_SZ => HiveValue::MultiString(
+            decode_utf16(&data)
+                .split('\0')
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        REG_DWORD if data.len() >= 4 => {
+            HiveValue::Dword(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+        }
+        REG_QWORD if data.len() >= 8 => {
+            let mut raw = [0u8; 8];
+            raw.copy_from_slice(&data[..8]);
+            HiveValue::Qword(u64::from_le_bytes(raw))
+        }
+        kind => HiveValue::Binary { kind, data },
+    }
+}
+
+fn decode_name(raw: &[u8], latin1: bool) -> String {
+    if latin1 {
+        raw.iter().map(|&byte| byte as char).collect()
+    } else {
+        decode_utf16(raw)
+    }
+}
+
+fn decode_utf16(raw: &[u8]) -> String {
+    let units: Vec<u16> = raw
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// UTF-16 string data up to the first terminator.
+fn decode_utf16z(raw: &[u8]) -> String {
+    let text = decode_utf16(raw);
+    match text.find('\0') {
+        Some(end) => text[..end].to_string(),
+        None => text,
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Builds small regf images for unit tests.
+#[cfg(test)]
+pub(crate) mod testhive {
+    use super::{BIG_DATA_SEGMENT, HBIN_START, KEY_COMP_NAME, VALUE_COMP_NAME};
+
+    /// A key to lay out: name, values as (name, type, data), and children.
+    #[derive(Default)]
+    pub(crate) struct KeySpec {
+        pub name: String,
+        pub values: Vec<(String, u32, Vec<u8>)>,
+        pub children: Vec<KeySpec>,
+    }
+
+    impl KeySpec {
+        pub(crate) fn new(name: &str) -> Self {
+            KeySpec {
+                name: name.to_string(),
+                ..KeySpec::default()
+            }
+        }
+
+        pub(crate) fn value(mut self, name: &str, kind: u32, data: Vec<u8>) -> Self {
+            self.values.push((name.to_string(), kind, data));
+            self
+        }
+
+        pub(crate) fn string(self, name: &str, text: &str) -> Self {
+            self.value(name, super::REG_SZ, utf16z(text))
+        }
+
+        pub(crate) fn dword(self, name: &str, value: u32) -> Self {
+            self.value(name, super::REG_DWORD, value.to_le_bytes().to_vec())
+        }
+
+        pub(crate) fn child(mut self, child: KeySpec) -> Self {
+            self.children.push(child);
+            self
+        }
+
+        /// Adds `path` (backslash separated) below this key, merging with
+        /// existing children, and applies `edit` to the leaf.
+        pub(crate) fn at(mut self, path: &str, edit: impl FnOnce(KeySpec) -> KeySpec) -> Self {
+            let (head, rest) = match path.split_once('\\') {
+                Some((head, rest)) => (head, Some(rest)),
+                None => (path, None),
+            };
+            let index = match self
+                .children
+                .iter()
+                .position(|child| child.name.eq_ignore_ascii_case(head))
+            {
+                Some(index) => index,
+                None => {
+                    self.children.push(KeySpec::new(head));
+                    self.children.len() - 1
+                }
+            };
+            let child = std::mem::take(&mut self.children[index]);
+            self.children[index] = match rest {
+                Some(rest) => child.at(rest, edit),
+                None => edit(child),
+            };
+            self
+        }
+    }
+
+    pub(crate) fn utf16z(text: &str) -> Vec<u8> {
+        text.encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    /// Lays `root` out as a hive. Subkey lists use `lh` cells, switching to
+    /// an `ri` index over two `lf` leaves when a key has more than four
+    /// children, so both list shapes get exercised.
+    pub(crate) fn build_hive(root: &KeySpec) -> Vec<u8> {
+        let mut cells = Vec::new();
+        let root_offset = write_key(&mut cells, root, u32::MAX);
+        let bins_len = (cells.len() + 0x20).next_multiple_of(0x1000);
+
+        let mut out = vec![0u8; HBIN_START];
+        out[0..4].copy_from_slice(b"regf");
+        out[0x14..0x18].copy_from_slice(&1u32.to_le_bytes());
+        out[0x18..0x1C].copy_from_slice(&5u32.to_le_bytes());
+        out[0x24..0x28].copy_from_slice(&root_offset.to_le_bytes());
+        out[0x28..0x2C].copy_from_slice(&(bins_len as u32).to_le_bytes());
+
+        let mut bin = vec![0u8; bins_len];
+        bin[0..4].copy_from_slice(b"hbin");
+        bin[8..12].copy_from_slice(&(bins_len as u32).to_le_bytes());
+        bin[0x20..0x20 + cells.len()].copy_from_slice(&cells);
+        // Remaining space is one free cell.
+        let free = bins_len - 0x20 - cells.len();
+        if free >= 4 {
+            let at = 0x20 + cells.len();
+            bin[at..at + 4].copy_from_slice(&(free as i32).to_le_bytes());
+        }
+        out.extend_from_slice(&bin);
+        out
+    }
+
+    /// Appends a cell and returns its offset relative to the first hbin.
+    fn push_cell(cells: &mut Vec<u8>, payload: &[u8]) -> u32 {
+        let offset = 0x20 + cells.len() as u32;
+        let len = (payload.len() + 4).next_multiple_of(8);
+        cells.extend_from_slice(&(-(len as i32)).to_le_bytes());
+        cells.extend_from_slice(payload);
+        cells.resize(cells.len() + len - 4 - payload.len(), 0);
+        offset
+    }
+
+    fn write_key(cells: &mut Vec<u8>, key: &KeySpec, parent: u32) -> u32 {
+        let name = key.name.as_bytes();
+        let mut nk = vec![0u8; 0x4C + name.len()];
+        nk[0..2].copy_from_slice(b"nk");
+        nk[2..4].copy_from_slice(&KEY_COMP_NAME.to_le_bytes());
+        nk[0x10..0x14].copy_from_slice(&parent.to_le_bytes());
+        nk[0x14..0x18].copy_from_slice(&(key.children.len() as u32).to_le_bytes());
+        nk[0x1C..0x20].copy_from_slice(&u32::MAX.to_le_bytes());
+        nk[0x20..0x24].copy_from_slice(&u32::MAX.to_le_bytes());
+        nk[0x24..0x28].copy_from_slice(&(key.values.len() as u32).to_le_bytes());
+        nk[0x28..0x2C].copy_from_slice(&u32::MAX.to_le_bytes());
+        nk[0x2C..0x30].copy_from_slice(&u32::MAX.to_le_bytes());
+        nk[0x30..0x34].copy_from_slice(&u32::MAX.to_le_bytes());
+        nk[0x48..0x4A].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        nk[0x4C..].copy_from_slice(name);
+        let offset = push_cell(cells, &nk);
+        let nk_at = offset as usize - 0x20 + 4;
+
+        if !key.values.is_empty() {
+            let vks: Vec<u32> = key
+                .values
+                .iter()
+                .map(|(name, kind, data)| write_value(cells, name, *kind, data))
+                .collect();
+            let list: Vec<u8> = vks.iter().flat_map(|vk| vk.to_le_bytes()).collect();
+            let list_offset = push_cell(cells, &list);
+            cells[nk_at + 0x28..nk_at + 0x2C].copy_from_slice(&list_offset.to_le_bytes());
+        }
+
+        if !key.children.is_empty() {
+            let children: Vec<u32> = key
+                .children
+                .iter()
+                .map(|child| write_key(cells, child, offset))
+                .collect();
+            let list_offset = if children.len() > 4 {
+                let (first, second) = children.split_at(children.len() / 2);
+                let leaves = [leaf(cells, b"lf", first), leaf(cells, b"lf", second)];
+                let mut ri = b"ri".to_vec();
+                ri.extend_from_slice(&2u16.to_le_bytes());
+                ri.extend(leaves.iter().flat_map(|leaf| leaf.to_le_bytes()));
+                push_cell(cells, &ri)
+            } else {
+                leaf(cells, b"lh", &children)
+            };
+            cells[nk_at + 0x1C..nk_at + 0x20].copy_from_slice(&list_offset.to_le_bytes());
+        }
+        offset
+    }
+
+    fn leaf(cells: &mut Vec<u8>, signature: &[u8; 2], keys: &[u32]) -> u32 {
+        let mut list = signature.to_vec();
+        list.extend_from_slice(&(keys.len() as u16).to_le_bytes());
+        for key in keys {
+            list.extend_from_slice(&key.to_le_bytes());
+            list.extend_from_slice(&0u32.to_le_bytes());
+        }
+        push_cell(cells, &list)
+    }
+
+    fn write_value(cells: &mut Vec<u8>, name: &str, kind: u32, data: &[u8]) -> u32 {
+        let mut vk = vec![0u8; 0x14 + name.len()];
+        vk[0..2].copy_from_slice(b"vk");
+        vk[2..4].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        vk[0x0C..0x10].copy_from_slice(&kind.to_le_bytes());
+        vk[0x10..0x12].copy_from_slice(&VALUE_COMP_NAME.to_le_bytes());
+        vk[0x14..].copy_from_slice(name.as_bytes());
+        if data.len() <= 4 {
+            vk[4..8].copy_from_slice(&(data.len() as u32 | 0x8000_0000).to_le_bytes());
+            vk[8..8 + data.len()].copy_from_slice(data);
+        } else {
+            vk[4..8].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            let data_offset = if data.len() > BIG_DATA_SEGMENT {
+                let segments: Vec<u32> = data
+                    .chunks(BIG_DATA_SEGMENT)
+                    .map(|chunk| push_cell(cells, chunk))
+                    .collect();
+                let list: Vec<u8> = segments.iter().flat_map(|s| s.to_le_bytes()).collect();
+                let list_offset = push_cell(cells, &list);
+                let mut db = b"db".to_vec();
+                db.extend_from_slice(&(segments.len() as u16).to_le_bytes());
+                db.extend_from_slice(&list_offset.to_le_bytes());
+                push_cell(cells, &db)
+            } else {
+                push_cell(cells, data)
+            };
+            vk[8..12].copy_from_slice(&data_offset.to_le_bytes());
+        }
+        push_cell(cells, &vk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testhive::{build_hive, utf16z, KeySpec};
+    use super::*;
+
+    const REG_BINARY: u32 = 3;
+
+    fn sample() -> Hive {
+        let root = KeySpec::new("ROOT")
+            .at(r"Select", |key| key.dword("Current", 2))
+            .at(r"ControlSet002\Control\Session Manager", |key| {
+                key.dword("SafeDllSearchMode", 0)
+            })
+            .at(
+                r"ControlSet002\Control\Session Manager\Environment",
+                |key| {
+                    key.value(
+                        "Path",
+                        REG_EXPAND_SZ,
+                        utf16z(r"%SystemRoot%\system32;%SystemRoot%"),
+                    )
+                    .value("Multi", REG_MULTI_SZ, utf16z("a\0b\0"))
+                    .value("Blob", REG_BINARY, vec![1, 2, 3])
+                    .string("", "default")
+                },
+            );
+        Hive::from_bytes(build_hive(&root)).unwrap()
+    }
+
+    #[test]
+    fn opens_nested_keys_case_insensitively() {
+        let hive = sample();
+        let key = hive
+            .open_key(r"controlset002\CONTROL\session manager")
+            .unwrap();
+        assert_eq!(hive.key_name(key), "Session Manager");
+        assert_eq!(
+            hive.value(key, "safedllsearchmode"),
+            Some(HiveValue::Dword(0))
+        );
+        assert_eq!(hive.open_key(r"ControlSet002\Missing"), None);
+        assert_eq!(hive.open_key(""), Some(hive.root()));
+    }
+
+    #[test]
+    fn decodes_value_types() {
+        let hive = sample();
+        let key = hive
+            .open_key(r"ControlSet002\Control\Session Manager\Environment")
+            .unwrap();
+        assert_eq!(
+            hive.value(key, "Path"),
+            Some(HiveValue::ExpandString(
+                r"%SystemRoot%\system32;%SystemRoot%".to_string()
+            ))
+        );
+        assert_eq!(
+            hive.value(key, "Multi"),
+            Some(HiveValue::MultiString(vec!["a".into(), "b".into()]))
+        );
+        assert_eq!(
+            hive.value(key, "Blob"),
+            Some(HiveValue::Binary {
+                kind: REG_BINARY,
+                data: vec![1, 2, 3]
+            })
+        );
+        assert_eq!(
+            hive.value(key, ""),
+            Some(HiveValue::String("default".to_string()))
+        );
+        assert_eq!(hive.value(key, "Absent"), None);
+    }
+
+    #[test]
+    fn walks_index_root_subkey_lists() {
+        let mut root = KeySpec::new("ROOT");
+        for index in 0..7 {
+            root = root.child(KeySpec::new(&format!("Key{index}")));
+        }
+        let hive = Hive::from_bytes(build_hive(&root)).unwrap();
+        let names: Vec<String> = hive
+            .subkeys(hive.root())
+            .into_iter()
+            .map(|key| hive.key_name(key))
+            .collect();
+        assert_eq!(names.len(), 7);
+        assert_eq!(names[6], "Key6");
+        assert!(hive.open_key("key5").is_some());
+    }
+
+    #[test]
+    fn reassembles_big_data_values() {
+        let payload: Vec<u8> = (0..BIG_DATA_SEGMENT * 2 + 10)
+            .map(|index| index as u8)
+            .collect();
+        let root = KeySpec::new("ROOT").value("Large", REG_BINARY, payload.clone());
+        let hive = Hive::from_bytes(build_hive(&root)).unwrap();
+        assert_eq!(
+            hive.value(hive.root(), "Large"),
+            Some(HiveValue::Binary {
+                kind: REG_BINARY,
+                data: payload
+            })
+        );
+    }
+
+    /// Absolute offset of the root key's `nk` payload in a built hive.
+    fn root_nk(bytes: &[u8]) -> usize {
+        HBIN_START + read_u32(bytes, 0x24).unwrap() as usize + 4
+    }
+
+    #[test]
+    fn oversized_list_counts_stop_at_the_list_cell() {
+        let root = KeySpec::new("ROOT")
+            .child(KeySpec::new("Child"))
+            .dword("Value", 1);
+        let mut bytes = build_hive(&root);
+        let nk = root_nk(&bytes);
+        bytes[nk + 0x24..nk + 0x28].copy_from_slice(&u32::MAX.to_le_bytes());
+        let list = HBIN_START + read_u32(&bytes, nk + 0x1C).unwrap() as usize + 4;
+        bytes[list + 2..list + 4].copy_from_slice(&u16::MAX.to_le_bytes());
+
+        let hive = Hive::from_bytes(bytes).unwrap();
+        assert_eq!(hive.value_names(hive.root()), vec!["Value".to_string()]);
+        assert_eq!(hive.subkeys(hive.root()).len(), 1);
+        assert!(hive.open_key("Child").is_some());
+    }
+
+    #[test]
+    fn big_data_sizes_beyond_the_segment_list_are_absent() {
+        let payload = vec![7u8; BIG_DATA_SEGMENT * 2];
+        let root = KeySpec::new("ROOT").value("Large", REG_BINARY, payload);
+        let mut bytes = build_hive(&root);
+        let nk = root_nk(&bytes);
+        let list = HBIN_START + read_u32(&bytes, nk + 0x28).unwrap() as usize + 4;
+        let vk = HBIN_START + read_u32(&bytes, list).unwrap() as usize + 4;
+        bytes[vk + 0x04..vk + 0x08].copy_from_slice(&0x7FFF_FFFFu32.to_le_bytes());
+
+        let hive = Hive::from_bytes(bytes).unwrap();
+        assert_eq!(hive.value_names(hive.root()), vec!["Large".to_string()]);
+        assert_eq!(hive.value(hive.root(), "Large"), None);
+    }
+
+    #[test]
+    fn truncated_hives_read_damaged_cells_as_absent() {
+        let mut root = KeySpec::new("ROOT").dword("Value", 1);
+        for index in 0..7 {
+            root = root.child(KeySpec::new(&format!("Key{index}")));
+        }
+        let mut bytes = build_hive(&root);
+        let nk = root_nk(&bytes);
+        bytes[nk + 0x24..nk + 0x28].copy_from_slice(&u32::MAX.to_le_bytes());
+        // Keep the root key but cut off everything it points to.
+        let root_len = (read_u32(&bytes, nk - 4).unwrap() as i32).unsigned_abs() as usize;
+        bytes.truncate(nk - 4 + root_len);
+
+        let hive = Hive::from_bytes(bytes).unwrap();
+        assert!(hive.value_names(hive.root()).is_empty());
+        assert!(hive.subkeys(hive.root()).is_empty());
+        assert_eq!(hive.value(hive.root(), "Value"), None);
+    }
+
+    #[test]
+    fn rejects_files_without_a_regf_header() {
+        assert!(Hive::from_bytes(vec![0u8; 0x2000]).is_err());
+        let mut bytes = build_hive(&KeySpec::new("ROOT"));
+        bytes[0x24..0x28].copy_from_slice(&0x10u32.to_le_bytes());
+        assert!(Hive::from_bytes(bytes).is_err());
+    }
+}
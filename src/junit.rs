@@ -0,0 +1,333 @@
+// Writes the JUnit XML report of one command for `--report-junit`.
+
+use std::path::PathBuf;
+
+use crate::emit::{
+    unquote, TokenLine, TOKEN_DYNAMIC_MISSING, TOKEN_STATIC_APISET_UNRESOLVED,
+    TOKEN_STATIC_BAD_IMAGE, TOKEN_STATIC_DELAY_MISSING, TOKEN_STATIC_FORWARD_BROKEN,
+    TOKEN_STATIC_IMPORT, TOKEN_STATIC_MISSING, TOKEN_STATIC_MISSING_EXPORT, TOKEN_SUMMARY,
+};
+
+/// Tokens counted in `SUMMARY`'s `static_missing`, `static_bad_image`,
+/// `static_delay_missing` and `dynamic_missing`; these are the diagnosed
+/// problems that fail a test.
+const PROBLEM_TOKENS: &[&str] = &[
+    TOKEN_STATIC_MISSING,
+    TOKEN_STATIC_APISET_UNRESOLVED,
+    TOKEN_STATIC_MISSING_EXPORT,
+    TOKEN_STATIC_FORWARD_BROKEN,
+    TOKEN_STATIC_BAD_IMAGE,
+    TOKEN_STATIC_DELAY_MISSING,
+    TOKEN_DYNAMIC_MISSING,
+];
+
+/// Where and what `--report-junit` reports.
+pub struct JunitReport {
+    pub file: PathBuf,
+    /// `run` or `imports`.
+    pub command: &'static str,
+    /// The analyzed target as given on the command line.
+    pub target: String,
+    /// Add one testcase per dependency edge.
+    pub edges: bool,
+}
+
+/// The report for a command that emitted `lines` and exited with `code`.
+///
+/// The target's testcase follows the exit code: `0` passes, `10` (a
+/// diagnosed problem) fails with the problem token lines, and any other
+/// code is an error. With `edges`, every `STATIC_IMPORT` is a testcase too,
+/// failing with the problem reported for it; a problem on an edge the
+/// stream did not list gets a testcase of its own.
+pub fn render(report: &JunitReport, lines: &[TokenLine], code: i32) -> String {
+    let problems: Vec<&TokenLine> = lines
+        .iter()
+        .filter(|line| PROBLEM_TOKENS.contains(&line.token.as_str()))
+        .collect();
+
+    let mut cases = vec![TestCase {
+        classname: report.command.to_string(),
+        name: report.target.clone(),
+        outcome: match code {
+            0 => Outcome::Passed,
+            10 => Outcome::Failed(problems.clone()),
+            other => Outcome::Error(format!("loadwhat exited with code {other}")),
+        },
+    }];
+    if report.edges {
+        cases.extend(edge_cases(lines, &problems));
+    }
+
+    let failures = cases
+        .iter()
+        .filter(|case| matches!(case.outcome, Outcome::Failed(_)))
+        .count();
+    let errors = cases
+        .iter()
+        .filter(|case| matches!(case.outcome, Outcome::Error(_)))
+        .count();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"loadwhat\" tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\">\n",
+        cases.len()
+    ));
+    out.push_str(&format!(
+        "  <testsuite name=\"loadwhat {}\" tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\">\n",
+        report.command,
+        cases.len()
+    ));
+    if let Some(summary) = lines.iter().rev().find(|line| line.token == TOKEN_SUMMARY) {
+        out.push_str("    <properties>\n");
+        for (key, value) in &summary.fields {
+            out.push_str(&format!(
+                "      <property name=\"{}\" value=\"{}\"/>\n",
+                escape(key),
+                escape(&unquote(value))
+            ));
+        }
+        out.push_str("    </properties>\n");
+    }
+    for case in &cases {
+        case.write(&mut out);
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+struct TestCase<'a> {
+    classname: String,
+    name: String,
+    outcome: Outcome<'a>,
+}
+
+enum Outcome<'a> {
+    Passed,
+    Failed(Vec<&'a TokenLine>),
+    Error(String),
+}
+
+impl TestCase<'_> {
+    fn write(&self, out: &mut String) {
+        let open = format!(
+            "    <testcase classname=\"{}\" name=\"{}\"",
+            escape(&self.classname),
+            escape(&self.name)
+        );
+        match &self.outcome {
+            Outcome::Passed => out.push_str(&format!("{open}/>\n")),
+            Outcome::Failed(problems) => {
+                let message = problems
+                    .first()
+                    .map(|line| line.text())
+                    .unwrap_or_else(|| "loadwhat exited with code 10".to_string());
+                let kind = problems.first().map_or("DIAGNOSED", |line| &line.token);
+                let body = problems
+                    .iter()
+                    .map(|line| line.text())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                out.push_str(&format!(
+                    "{open}>\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape(&message),
+                    escape(kind),
+                    escape(&body)
+                ));
+            }
+            Outcome::Error(message) => out.push_str(&format!(
+                "{open}>\n      <error message=\"{}\"/>\n    </testcase>\n",
+                escape(message)
+            )),
+        }
+    }
+}
+
+/// One testcase per `STATIC_IMPORT` edge, named `<module>` / `<dll>`, plus
+/// one for each problem whose edge was not listed.
+fn edge_cases<'a>(lines: &'a [TokenLine], problems: &[&'a TokenLine]) -> Vec<TestCase<'a>> {
+    let mut edges: Vec<(String, String)> = Vec::new();
+    for line in lines
+        .iter()
+        .filter(|line| line.token == TOKEN_STATIC_IMPORT)
+    {
+        let (Some(module), Some(dll)) = (line.value("module"), line.value("needs")) else {
+            continue;
+        };
+        if !edges.iter().any(|edge| same_edge(edge, (&module, &dll))) {
+            edges.push((module, dll));
+        }
+    }
+    for problem in problems {
+        let (Some(module), Some(dll)) = (importer(problem), problem.value("dll")) else {
+            continue;
+        };
+        if !edges.iter().any(|edge| same_edge(edge, (&module, &dll))) {
+            edges.push((module, dll));
+        }
+    }
+
+    edges
+        .into_iter()
+        .map(|edge| {
+            let failed: Vec<&TokenLine> = problems
+                .iter()
+                .copied()
+                .filter(|problem| match (importer(problem), problem.value("dll")) {
+                    (Some(from), Some(to)) => same_edge(&edge, (&from, &to)),
+                    _ => false,
+                })
+                .collect();
+            let (module, dll) = edge;
+            TestCase {
+                classname: module,
+                name: dll,
+                outcome: if failed.is_empty() {
+                    Outcome::Passed
+                } else {
+                    Outcome::Failed(failed)
+                },
+            }
+        })
+        .collect()
+}
+
+/// The module whose import failed: `via` on a first-break line, where
+/// `module` is the root, and `module` otherwise. `DYNAMIC_MISSING` has none.
+fn importer(line: &TokenLine) -> Option<String> {
+    line.value("via").or_else(|| line.value("module"))
+}
+
+fn same_edge((module, dll): &(String, String), (other_module, other_dll): (&str, &str)) -> bool {
+    module.eq_ignore_ascii_case(other_module) && dll.eq_ignore_ascii_case(other_dll)
+}
+
+/// Escapes text for an XML attribute or element. Control characters XML
+/// 1.0 cannot carry become U+FFFD.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            '\t' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => out.push('\u{FFFD}'),
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, render, JunitReport};
+    use crate::emit::{field, quote, TokenLine};
+    use std::path::PathBuf;
+
+    fn line(token: &str, fields: &[(&str, &str)]) -> TokenLine {
+        TokenLine {
+            token: token.to_string(),
+            fields: fields
+                .iter()
+                .map(|(key, value)| field(*key, quote(value)))
+                .collect(),
+        }
+    }
+
+    fn report(edges: bool) -> JunitReport {
+        JunitReport {
+            file: PathBuf::from("report.xml"),
+            command: "imports",
+            target: r"C:\App\app.exe".to_string(),
+            edges,
+        }
+    }
+
+    fn walk() -> Vec<TokenLine> {
+        vec![
+            line(
+                "STATIC_IMPORT",
+                &[("module", "app.exe"), ("needs", "core.dll")],
+            ),
+            line(
+                "STATIC_IMPORT",
+                &[("module", "core.dll"), ("needs", "gone.dll")],
+            ),
+            line(
+                "STATIC_MISSING",
+                &[
+                    ("module", "core.dll"),
+                    ("dll", "gone.dll"),
+                    ("reason", "NOT_FOUND"),
+                ],
+            ),
+            TokenLine {
+                token: "SUMMARY".to_string(),
+                fields: vec![field("first_break", "false"), field("static_missing", "1")],
+            },
+        ]
+    }
+
+    #[test]
+    fn target_fails_with_problem_lines_and_edges_fail_individually() {
+        let xml = render(&report(true), &walk(), 10);
+        assert_eq!(
+            xml,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<testsuites name=\"loadwhat\" tests=\"3\" failures=\"2\" errors=\"0\">\n",
+                "  <testsuite name=\"loadwhat imports\" tests=\"3\" failures=\"2\" errors=\"0\">\n",
+                "    <properties>\n",
+                "      <property name=\"first_break\" value=\"false\"/>\n",
+                "      <property name=\"static_missing\" value=\"1\"/>\n",
+                "    </properties>\n",
+                "    <testcase classname=\"imports\" name=\"C:\\App\\app.exe\">\n",
+                "      <failure message=\"STATIC_MISSING module=&quot;core.dll&quot; dll=&quot;gone.dll&quot; reason=&quot;NOT_FOUND&quot;\" type=\"STATIC_MISSING\">",
+                "STATIC_MISSING module=&quot;core.dll&quot; dll=&quot;gone.dll&quot; reason=&quot;NOT_FOUND&quot;</failure>\n",
+                "    </testcase>\n",
+                "    <testcase classname=\"app.exe\" name=\"core.dll\"/>\n",
+                "    <testcase classname=\"core.dll\" name=\"gone.dll\">\n",
+                "      <failure message=\"STATIC_MISSING module=&quot;core.dll&quot; dll=&quot;gone.dll&quot; reason=&quot;NOT_FOUND&quot;\" type=\"STATIC_MISSING\">",
+                "STATIC_MISSING module=&quot;core.dll&quot; dll=&quot;gone.dll&quot; reason=&quot;NOT_FOUND&quot;</failure>\n",
+                "    </testcase>\n",
+                "  </testsuite>\n",
+                "</testsuites>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn summary_reports_only_the_target_and_errors_follow_the_exit_code() {
+        let first_break = vec![line(
+            "STATIC_MISSING",
+            &[
+                ("module", "app.exe"),
+                ("dll", "gone.dll"),
+                ("reason", "NOT_FOUND"),
+                ("via", "core.dll"),
+            ],
+        )];
+        let xml = render(&report(false), &first_break, 10);
+        assert!(xml.contains("tests=\"1\" failures=\"1\" errors=\"0\""));
+        assert!(!xml.contains("classname=\"core.dll\""));
+
+        let traced = render(&report(true), &first_break, 10);
+        assert!(traced.contains("<testcase classname=\"core.dll\" name=\"gone.dll\">"));
+
+        let xml = render(&report(false), &[], 21);
+        assert!(xml.contains("<error message=\"loadwhat exited with code 21\"/>"));
+        let xml = render(&report(false), &[], 0);
+        assert!(xml.contains("<testcase classname=\"imports\" name=\"C:\\App\\app.exe\"/>"));
+    }
+
+    #[test]
+    fn escape_covers_markup_and_control_characters() {
+        assert_eq!(
+            escape("a<b>&\"c'\n\u{1}"),
+            "a&lt;b&gt;&amp;&quot;c&apos;&#10;\u{FFFD}"
+        );
+    }
+}
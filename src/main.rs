@@ -1,73 +1,81 @@
-#[cfg(not(windows))]
-fn main() {
-    eprintln!("loadwhat currently supports Windows only.");
-    std::process::exit(22);
-}
-
-#[cfg(windows)]
+// Only the live `audit-search` check reads DACLs; the evaluator itself is
+// tested everywhere.
+#[cfg_attr(not(windows), allow(dead_code))]
+mod acl;
+mod apiset;
+mod audit;
 mod cli;
-#[cfg(windows)]
 mod com;
 #[cfg(windows)]
 mod debug_run;
-#[cfg(windows)]
+mod digest;
 mod emit;
+mod environment;
+mod graph;
+mod hive;
+mod junit;
 #[cfg(windows)]
 mod loader_snaps;
-#[cfg(windows)]
+mod pathlint;
 mod pe;
-#[cfg(windows)]
+mod sarif;
 mod search;
+mod sysroot;
+mod tree;
 #[cfg(windows)]
 mod win;
 
 #[cfg(test)]
 mod test_util;
 
-#[cfg(windows)]
-use std::collections::{HashMap, HashSet, VecDeque};
-#[cfg(windows)]
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::env;
-#[cfg(windows)]
-use std::ffi::OsString;
-#[cfg(windows)]
+use std::ffi::{OsStr, OsString};
 use std::path::{Path, PathBuf};
 
-#[cfg(windows)]
-use cli::{ComOptions, ComSubcommand, ComViewArg, Command, ImportsOptions, RunOptions};
-#[cfg(windows)]
+use apiset::{ApiSetLookup, ApiSetSchema};
+use audit::Writability;
+use cli::{
+    AuditSearchOptions, ComHiveFiles, ComOptions, ComSubcommand, ComViewArg, Command,
+    ImportsOptions, PathLintOptions, ResolveOptions, RunOptions,
+};
 use com::fs::{ComFileSystem, DepCandidate, DepFailure, DepStatus, DepWalkReport};
+use com::regfile::RegFileRegistry;
 #[cfg(windows)]
 use com::registry::WindowsRegistry;
-#[cfg(windows)]
+use com::registry::{ComRegistry, HiveRegistry};
 use com::resolver::{
     ComAuditResult, ComError, ComLookupResult, ComRegistration, ComResolver, QueryKind,
     ServerValidation,
 };
-#[cfg(windows)]
 use com::{LookupStatus, RegView, ServerKind, ServerStatus};
 #[cfg(windows)]
 use debug_run::{LoadedModule, RunEndKind, RunError, RunOutcome, RuntimeEvent};
-#[cfg(windows)]
 use emit::{
-    emit, field, hex_u32, hex_usize, quote, summary_fields, SummaryCounts, TOKEN_COM_AUDIT,
+    emit, field, quote, summary_fields, SummaryCounts, TOKEN_COM_AUDIT,
     TOKEN_COM_DEPENDENCY_STATUS, TOKEN_COM_LOOKUP, TOKEN_COM_MANIFEST, TOKEN_COM_PROGID,
-    TOKEN_COM_REGISTRATION, TOKEN_COM_SERVER, TOKEN_DEBUG_STRING, TOKEN_DYNAMIC_MISSING,
-    TOKEN_FIRST_BREAK, TOKEN_NOTE, TOKEN_RUNTIME_LOADED, TOKEN_RUN_END, TOKEN_RUN_START,
-    TOKEN_SEARCH_ORDER, TOKEN_SEARCH_PATH, TOKEN_STATIC_BAD_IMAGE, TOKEN_STATIC_END,
-    TOKEN_STATIC_FOUND, TOKEN_STATIC_IMPORT, TOKEN_STATIC_MISSING, TOKEN_STATIC_START,
-    TOKEN_SUCCESS, TOKEN_SUMMARY,
+    TOKEN_COM_REGISTRATION, TOKEN_COM_SERVER, TOKEN_NOTE, TOKEN_PATH_ISSUE, TOKEN_RESOLVE,
+    TOKEN_SEARCH_ORDER, TOKEN_SEARCH_PATH, TOKEN_SEARCH_RISK, TOKEN_STATIC_APISET,
+    TOKEN_STATIC_APISET_UNRESOLVED, TOKEN_STATIC_BAD_IMAGE, TOKEN_STATIC_DELAY_BAD_IMAGE,
+    TOKEN_STATIC_DELAY_IMPORT, TOKEN_STATIC_DELAY_MISSING, TOKEN_STATIC_END, TOKEN_STATIC_FORWARD,
+    TOKEN_STATIC_FORWARD_BROKEN, TOKEN_STATIC_FOUND, TOKEN_STATIC_IMPORT, TOKEN_STATIC_MISSING,
+    TOKEN_STATIC_MISSING_EXPORT, TOKEN_STATIC_SHADOW, TOKEN_STATIC_START, TOKEN_SUMMARY,
 };
 #[cfg(windows)]
-use loader_snaps::{LoaderSnapsGuard, PebEnableInfo};
+use emit::{
+    hex_u32, hex_usize, TOKEN_DEBUG_STRING, TOKEN_DYNAMIC_MISSING, TOKEN_FIRST_BREAK,
+    TOKEN_RUNTIME_LOADED, TOKEN_RUN_END, TOKEN_RUN_START, TOKEN_SUCCESS,
+};
+use environment::EnvEdit;
 #[cfg(windows)]
+use loader_snaps::{LoaderSnapsGuard, PebEnableInfo};
 use pe::MachineType;
-#[cfg(windows)]
-use search::{CandidateResult, ResolutionKind, SearchContext};
+use search::{CandidateResult, LoadOptions, ResolutionKind, SearchContext};
+use sysroot::{Sysroot, SysrootScope};
+use tree::{TreeEdge, TreeStatus};
 
-#[cfg(windows)]
 fn main() {
-    if !cfg!(target_pointer_width = "64") {
+    if cfg!(windows) && !cfg!(target_pointer_width = "64") {
         eprintln!("unsupported architecture: loadwhat v1 supports x64 only.");
         std::process::exit(22);
     }
@@ -80,18 +88,72 @@ fn main() {
         }
     };
 
-    let code = match command {
-        Command::Run(opts) => run_command(opts),
-        Command::Imports(opts) => imports_command(opts),
-        Command::Com(opts) => com_command(opts),
-        Command::Help => {
-            println!("{}", cli::usage());
-            0
+    // Each file is parsed once per command, however many walks reach it.
+    let _image_cache = pe::ImageCacheScope::enter();
+    // A SARIF scope prints its log when dropped, which must happen before exit.
+    let code = {
+        let _output_format = emit::OutputFormatScope::enter(command.output_format());
+        match command {
+            Command::Run(opts) => {
+                let report = opts.report_junit.clone().map(|file| junit::JunitReport {
+                    file,
+                    command: "run",
+                    target: display_path(&opts.exe_path),
+                    edges: opts.trace,
+                });
+                with_junit_report(report, || run_command(opts))
+            }
+            Command::Imports(opts) => {
+                let report = opts.report_junit.clone().map(|file| junit::JunitReport {
+                    file,
+                    command: "imports",
+                    target: display_path(&opts.module_path),
+                    edges: true,
+                });
+                with_junit_report(report, || imports_command(opts))
+            }
+            Command::Resolve(opts) => resolve_command(opts),
+            Command::AuditSearch(opts) => audit_search_command(opts),
+            Command::PathLint(opts) => path_lint_command(opts),
+            Command::Com(opts) => com_command(opts),
+            Command::Help => {
+                println!("{}", cli::usage());
+                0
+            }
         }
     };
     std::process::exit(code);
 }
 
+/// Runs `command` and, for `--report-junit`, writes the report of the
+/// tokens it emitted and its exit code. A report that cannot be written
+/// turns a passing run into exit code `21`.
+fn with_junit_report(report: Option<junit::JunitReport>, command: impl FnOnce() -> i32) -> i32 {
+    let Some(report) = report else {
+        return command();
+    };
+    let recording = emit::TokenRecording::start();
+    let code = command();
+    let xml = junit::render(&report, &recording.lines(), code);
+    match std::fs::write(&report.file, xml) {
+        Ok(()) => code,
+        Err(err) => {
+            eprintln!("cannot write JUnit report {}: {err}", report.file.display());
+            if code == 0 {
+                21
+            } else {
+                code
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn run_command(_opts: RunOptions) -> i32 {
+    eprintln!("loadwhat run requires Windows; imports and com accept --sysroot on other hosts.");
+    22
+}
+
 #[cfg(windows)]
 fn run_command(opts: RunOptions) -> i32 {
     let test_mode = test_mode_enabled();
@@ -108,9 +170,22 @@ fn run_command(opts: RunOptions) -> i32 {
     let cwd = opts
         .cwd
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    // The target inherits loadwhat's environment unless an env option edits it.
+    let target_env = (!opts.env.is_empty()).then(|| {
+        let mut vars: Vec<(OsString, OsString)> = env::vars_os().collect();
+        environment::apply(&mut vars, &opts.env);
+        vars
+    });
 
     let (outcome, mut snaps_guard) = if opts.loader_snaps {
-        match debug_run::run_target(&exe_path, &opts.exe_args, Some(&cwd), opts.timeout_ms, true) {
+        match debug_run::run_target(
+            &exe_path,
+            &opts.exe_args,
+            Some(&cwd),
+            target_env.as_deref(),
+            opts.timeout_ms,
+            true,
+        ) {
             Ok(value) => (Ok(value), None),
             Err(RunError::PebLoaderSnapsEnableFailed(peb_info, peb_code)) => {
                 let image_name = exe_path
@@ -152,6 +227,7 @@ fn run_command(opts: RunOptions) -> i32 {
                         &exe_path,
                         &opts.exe_args,
                         Some(&cwd),
+                        target_env.as_deref(),
                         opts.timeout_ms,
                         false,
                     ),
@@ -182,6 +258,7 @@ fn run_command(opts: RunOptions) -> i32 {
                 &exe_path,
                 &opts.exe_args,
                 Some(&cwd),
+                target_env.as_deref(),
                 opts.timeout_ms,
                 false,
             ),
@@ -242,6 +319,14 @@ fn run_command(opts: RunOptions) -> i32 {
         emit_run_events(&exe_path, &cwd, &outcome);
     }
 
+    if trace_mode && environment::touches_path(&opts.env) {
+        let path_env = target_env
+            .as_deref()
+            .and_then(|vars| environment::lookup(vars, OsStr::new("PATH")))
+            .unwrap_or_default();
+        emit_effective_path(&path_env.to_string_lossy());
+    }
+
     let mut runtime_loaded: HashSet<String> = HashSet::new();
     let mut runtime_observed: HashMap<String, PathBuf> = HashMap::new();
     for module in &outcome.loaded_modules {
@@ -297,7 +382,7 @@ fn run_command(opts: RunOptions) -> i32 {
             &cwd,
             &runtime_loaded,
             &runtime_observed,
-            env_path_override(&[]),
+            env_path_override(&opts.env),
             mode,
         );
         match diag {
@@ -311,33 +396,7 @@ fn run_command(opts: RunOptions) -> i32 {
                             .or_else(|| Some(issue.dll.to_ascii_lowercase()));
                     }
                     if summary_mode {
-                        match issue.kind {
-                            ResolutionKind::Missing => {
-                                let mut fields = vec![
-                                    field("module", quote(&issue.module)),
-                                    field("dll", quote(&issue.dll)),
-                                    field("reason", quote("NOT_FOUND")),
-                                ];
-                                if issue.depth > 1 {
-                                    fields.push(field("via", quote(&issue.via)));
-                                    fields.push(field("depth", issue.depth.to_string()));
-                                }
-                                emit(TOKEN_STATIC_MISSING, &fields);
-                                summary_line_emitted = true;
-                            }
-                            ResolutionKind::BadImage => {
-                                emit(
-                                    TOKEN_STATIC_BAD_IMAGE,
-                                    &[
-                                        field("module", quote(&issue.module)),
-                                        field("dll", quote(&issue.dll)),
-                                        field("reason", quote("BAD_IMAGE")),
-                                    ],
-                                );
-                                summary_line_emitted = true;
-                            }
-                            ResolutionKind::Found => {}
-                        }
+                        summary_line_emitted = emit_static_issue(issue);
                     } else if opts.verbose {
                         emit(
                             TOKEN_FIRST_BREAK,
@@ -367,40 +426,15 @@ fn run_command(opts: RunOptions) -> i32 {
                             TOKEN_SEARCH_ORDER,
                             &[field("safedll", if report.safedll { "1" } else { "0" })],
                         );
-                        match issue.kind {
-                            ResolutionKind::Missing => {
-                                let mut fields = vec![
-                                    field("module", quote(&issue.module)),
-                                    field("dll", quote(&issue.dll)),
-                                    field("reason", quote("NOT_FOUND")),
-                                ];
-                                if issue.depth > 1 {
-                                    fields.push(field("via", quote(&issue.via)));
-                                    fields.push(field("depth", issue.depth.to_string()));
-                                }
-                                emit(TOKEN_STATIC_MISSING, &fields);
-                            }
-                            ResolutionKind::BadImage => {
-                                emit(
-                                    TOKEN_STATIC_BAD_IMAGE,
-                                    &[
-                                        field("module", quote(&issue.module)),
-                                        field("dll", quote(&issue.dll)),
-                                        field("reason", quote("BAD_IMAGE")),
-                                    ],
-                                );
-                            }
-                            ResolutionKind::Found => {}
-                        }
+                        emit_path_issues(&report.path_env);
+                        emit_static_issue(issue);
                         for candidate in &issue.candidates {
-                            emit(
-                                TOKEN_SEARCH_PATH,
-                                &[
-                                    field("dll", quote(&issue.dll)),
-                                    field("order", candidate.order.to_string()),
-                                    field("path", quote(&display_path(&candidate.path))),
-                                    field("result", quote(candidate.result)),
-                                ],
+                            emit_search_path(
+                                &issue.dll,
+                                candidate.order,
+                                &display_path(&candidate.path),
+                                candidate.result,
+                                candidate.reason,
                             );
                         }
                     }
@@ -444,12 +478,12 @@ fn run_command(opts: RunOptions) -> i32 {
                 dynamic_missing_count = 1;
                 summary_line_emitted = true;
             } else {
-                let app_dir = exe_path.parent().unwrap_or_else(|| Path::new("."));
-                if let Ok(context) = dynamic_trace_search_context(app_dir, &cwd) {
+                if let Ok(context) = dynamic_trace_search_context(&exe_path, &cwd, &opts.env) {
                     emit(
                         TOKEN_SEARCH_ORDER,
                         &[field("safedll", if context.safedll { "1" } else { "0" })],
                     );
+                    emit_path_issues(&context.path_env);
 
                     let mut fields = vec![
                         field("dll", quote(&dm.dll)),
@@ -462,14 +496,12 @@ fn run_command(opts: RunOptions) -> i32 {
 
                     let resolution = search::resolve_dll(&dm.dll, &context);
                     for candidate in &resolution.candidates {
-                        emit(
-                            TOKEN_SEARCH_PATH,
-                            &[
-                                field("dll", quote(&dm.dll)),
-                                field("order", candidate.order.to_string()),
-                                field("path", quote(&display_path(&candidate.path))),
-                                field("result", quote(candidate.result)),
-                            ],
+                        emit_search_path(
+                            &dm.dll,
+                            candidate.order,
+                            &display_path(&candidate.path),
+                            candidate.result,
+                            candidate.reason,
                         );
                     }
                     dynamic_missing_count = 1;
@@ -516,6 +548,7 @@ fn run_command(opts: RunOptions) -> i32 {
                     dynamic_missing: dynamic_missing_count,
                     runtime_loaded: outcome.loaded_modules.len(),
                     com_issues: 0,
+                    static_delay_missing: 0,
                 },
             ),
         );
@@ -535,8 +568,11 @@ fn run_command(opts: RunOptions) -> i32 {
     code
 }
 
-#[cfg(windows)]
 fn imports_command(opts: ImportsOptions) -> i32 {
+    let _sysroot = match enter_sysroot(opts.sysroot.as_deref(), false) {
+        Ok(scope) => scope,
+        Err(code) => return code,
+    };
     let module_path = match normalize_existing_path(&opts.module_path) {
         Ok(p) => p,
         Err(err) => {
@@ -544,36 +580,119 @@ fn imports_command(opts: ImportsOptions) -> i32 {
             return 20;
         }
     };
-    let cwd = opts
-        .cwd
-        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    // A plugin walk runs in the host's process: the host image supplies the
+    // application directory and the plugin is the module being loaded.
+    let process_image = match opts.from.as_deref().map(normalize_existing_path) {
+        Some(Ok(path)) => path,
+        Some(Err(err)) => {
+            eprintln!("{err}");
+            return 20;
+        }
+        None => module_path.clone(),
+    };
+    let Some(app_dir) = process_image.parent() else {
+        eprintln!(
+            "cannot determine app directory for {}",
+            display_path(&process_image)
+        );
+        return 20;
+    };
+    let cwd = match command_cwd(opts.cwd, &process_image) {
+        Ok(cwd) => cwd,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let known_dlls = match opts.known_dlls.as_deref().map(read_known_dlls).transpose() {
+        Ok(known_dlls) => known_dlls,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let mut context =
+        match static_search_context(&module_path, app_dir, &cwd, env_path_override(&opts.env)) {
+            Ok(context) => context,
+            Err(err) => {
+                eprintln!("{err}");
+                return 21;
+            }
+        };
+    context.load = LoadOptions {
+        load_dir: module_path.parent().map(Path::to_path_buf),
+        ..opts.load
+    };
+    // A graph is the whole output, so it can be piped straight to a renderer.
+    if opts.graph.is_none() && environment::touches_path(&opts.env) {
+        emit_effective_path(&context.path_env);
+    }
+    if let Some(known_dlls) = known_dlls {
+        context.known_dlls = known_dlls;
+    }
+    context.redirect_dotlocal(&process_image);
 
     let runtime_loaded: HashSet<String> = HashSet::new();
     let runtime_observed: HashMap<String, PathBuf> = HashMap::new();
-    let diag = diagnose_static_imports(
+    let diag = diagnose_static_imports_in_context(
         &module_path,
-        &cwd,
+        &context,
         &runtime_loaded,
         &runtime_observed,
-        env_path_override(&[]),
-        StaticEmitMode::Full,
+        if opts.tree.is_some() || opts.graph.is_some() {
+            StaticEmitMode::Tree
+        } else {
+            StaticEmitMode::Full
+        },
     );
     match diag {
         Ok(report) => {
-            emit(
-                TOKEN_SUMMARY,
-                &summary_fields(
-                    false,
-                    SummaryCounts {
-                        static_missing: report.missing_count,
-                        static_bad_image: report.bad_image_count,
-                        dynamic_missing: 0,
-                        runtime_loaded: 0,
-                        com_issues: 0,
-                    },
-                ),
-            );
-            if report.missing_count + report.bad_image_count > 0 {
+            let root_name = module_name_lower(&module_path);
+            let root_path = display_path(&module_path);
+            let root_key = normalize_module_visit_key(&module_path);
+            if let Some(graph_options) = &opts.graph {
+                for line in graph::render(
+                    &root_name,
+                    &root_path,
+                    &root_key,
+                    &report.tree,
+                    graph_options,
+                ) {
+                    println!("{line}");
+                }
+            } else {
+                if let Some(tree_options) = &opts.tree {
+                    for line in tree::render(
+                        &root_name,
+                        &root_path,
+                        &root_key,
+                        &report.tree,
+                        tree_options,
+                    ) {
+                        println!("{line}");
+                    }
+                }
+                if opts.shadows {
+                    emit_shadow_report(&report, &context);
+                }
+                emit(
+                    TOKEN_SUMMARY,
+                    &summary_fields(
+                        false,
+                        SummaryCounts {
+                            static_missing: report.missing_count,
+                            static_bad_image: report.bad_image_count,
+                            dynamic_missing: 0,
+                            runtime_loaded: 0,
+                            com_issues: 0,
+                            static_delay_missing: report.delay_missing.len(),
+                        },
+                    ),
+                );
+            }
+            // A missing delay-load DLL never breaks the load, but the first
+            // call into it does, so it still fails the command.
+            if report.missing_count + report.bad_image_count + report.delay_missing.len() > 0 {
                 10
             } else {
                 0
@@ -588,21 +707,18 @@ fn imports_command(opts: ImportsOptions) -> i32 {
 
 /// Production COM file-system backend: std::fs checks plus the v1 static
 /// dependency walk in collect-only mode.
-#[cfg(windows)]
 struct RealComFileSystem;
 
-#[cfg(windows)]
 impl ComFileSystem for RealComFileSystem {
     fn file_exists(&self, path: &str) -> bool {
-        Path::new(path).is_file()
+        sysroot::host_path(&sysroot::native_path(path)).is_file()
     }
 
     fn read_file_header(&self, path: &str, max_bytes: usize) -> Option<Vec<u8>> {
-        use std::io::Read;
-        let file = std::fs::File::open(path).ok()?;
-        let mut buffer = Vec::new();
-        file.take(max_bytes as u64).read_to_end(&mut buffer).ok()?;
-        Some(buffer)
+        pe::open_image(&sysroot::native_path(path))
+            .ok()?
+            .read_range(0, max_bytes)
+            .ok()
     }
 
     fn walk_dependencies(
@@ -610,18 +726,17 @@ impl ComFileSystem for RealComFileSystem {
         path: &str,
         context: &com::fs::DepSearchContext,
     ) -> Result<DepWalkReport, String> {
-        let module_path = Path::new(path);
-        let app_dir = Path::new(&context.app_dir);
-        let cwd = Path::new(&context.cwd);
+        let module_path = &sysroot::native_path(path);
+        let app_dir = &sysroot::native_path(&context.app_dir);
+        let cwd = &sysroot::native_path(&context.cwd);
         let runtime_loaded: HashSet<String> = HashSet::new();
         let runtime_observed: HashMap<String, PathBuf> = HashMap::new();
+        let context = static_search_context(module_path, app_dir, cwd, env_path_override(&[]))?;
         let report = diagnose_static_imports_in_context(
             module_path,
-            app_dir,
-            cwd,
+            &context,
             &runtime_loaded,
             &runtime_observed,
-            env_path_override(&[]),
             StaticEmitMode::CollectOnly,
         )?;
         Ok(DepWalkReport {
@@ -632,10 +747,15 @@ impl ComFileSystem for RealComFileSystem {
                     dll: failure.dll,
                     via: failure.via,
                     depth: failure.depth,
-                    status: match failure.kind {
-                        ResolutionKind::BadImage => DepStatus::BadImage,
-                        _ => DepStatus::Missing,
+                    status: match (&failure.export, &failure.kind) {
+                        (Some(_), _) if failure.forward.is_some() => DepStatus::BrokenForward,
+                        (Some(_), _) => DepStatus::MissingExport,
+                        (None, ResolutionKind::BadImage) => DepStatus::BadImage,
+                        (None, _) if failure.api_set.is_some() => DepStatus::UnresolvedApiSet,
+                        (None, _) => DepStatus::Missing,
                     },
+                    export: failure.export,
+                    forward: failure.forward.map(|forward| forward.forward),
                     candidates: failure
                         .candidates
                         .iter()
@@ -643,6 +763,7 @@ impl ComFileSystem for RealComFileSystem {
                             order: candidate.order,
                             path: display_path(&candidate.path),
                             result: candidate.result,
+                            reason: candidate.reason,
                         })
                         .collect(),
                 })
@@ -652,15 +773,305 @@ impl ComFileSystem for RealComFileSystem {
     }
 
     fn embedded_manifest(&self, path: &str) -> Option<String> {
-        pe::extract_embedded_manifest(Path::new(path))
+        pe::extract_embedded_manifest(&sysroot::native_path(path))
+    }
+}
+
+/// The working directory a command models: `--cwd` (mapped onto the
+/// sysroot), or this process's directory. The target machine's working
+/// directory is unknown offline, so there the process image's own directory
+/// keeps the cwd slot from adding a root.
+fn command_cwd(cwd: Option<PathBuf>, process_image: &Path) -> Result<PathBuf, String> {
+    match (cwd, sysroot::current()) {
+        (Some(cwd), Some(sysroot)) => sysroot.to_virtual(&cwd),
+        (Some(cwd), None) => Ok(cwd),
+        (None, Some(_)) => Ok(process_image
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(sysroot::SYSTEM_DRIVE))),
+        (None, None) => Ok(std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))),
+    }
+}
+
+fn resolve_command(opts: ResolveOptions) -> i32 {
+    let _sysroot = match enter_sysroot(opts.sysroot.as_deref(), false) {
+        Ok(scope) => scope,
+        Err(code) => return code,
+    };
+    let from = match normalize_existing_path(&opts.from) {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let Some(app_dir) = from.parent() else {
+        eprintln!("cannot determine app directory for {}", display_path(&from));
+        return 20;
+    };
+    let cwd = match command_cwd(opts.cwd, &from) {
+        Ok(cwd) => cwd,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    // A full path is loaded as given; under a sysroot it names a file on
+    // the mounted volume.
+    let dll = if search::is_full_path(Path::new(&opts.dll)) {
+        match sysroot_argument(Path::new(&opts.dll)) {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(code) => return code,
+        }
+    } else {
+        opts.dll.clone()
+    };
+    let known_dlls = match opts.known_dlls.as_deref().map(read_known_dlls).transpose() {
+        Ok(known_dlls) => known_dlls,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let machine = pe::module_machine_type(&from)
+        .map(MachineType::process_machine)
+        .unwrap_or(MachineType::Unknown);
+    let mut context =
+        match SearchContext::from_environment(app_dir, &cwd, env_path_override(&[]), machine) {
+            Ok(context) => context,
+            Err(err) => {
+                eprintln!("{err}");
+                return 21;
+            }
+        };
+    context.load = LoadOptions {
+        load_dir: search::is_full_path(Path::new(&dll))
+            .then(|| Path::new(&dll).parent().map(Path::to_path_buf))
+            .flatten(),
+        ..opts.load
+    };
+    if let Some(known_dlls) = known_dlls {
+        context.known_dlls = known_dlls;
+    }
+    context.redirect_dotlocal(&from);
+
+    emit_search_order(&context);
+    let resolution = search::resolve_dll(&dll, &context);
+    for candidate in &resolution.candidates {
+        emit_search_path(
+            &dll,
+            candidate.order,
+            &display_path(&candidate.path),
+            candidate.result,
+            candidate.reason,
+        );
+    }
+    let dll_name = module_name_lower(Path::new(&dll));
+    let mut fields = vec![field("dll", quote(&dll_name))];
+    let code = match resolution.kind {
+        ResolutionKind::Found => {
+            fields.push(field("status", quote("FOUND")));
+            0
+        }
+        ResolutionKind::Missing => {
+            fields.push(field("status", quote("MISSING")));
+            10
+        }
+        ResolutionKind::BadImage => {
+            fields.push(field("status", quote("BAD_IMAGE")));
+            10
+        }
+    };
+    if let Some(chosen) = &resolution.chosen {
+        fields.push(field("path", quote(&display_path(chosen))));
+    }
+    if matches!(resolution.kind, ResolutionKind::BadImage) {
+        fields.push(field("reason", quote(resolution.bad_image_reason())));
+    }
+    emit(TOKEN_RESOLVE, &fields);
+    code
+}
+
+/// `audit-search`: search roots, unresolved imports and app-local copies of
+/// system DLLs that let an unprivileged user plant a DLL the loader picks.
+fn audit_search_command(opts: AuditSearchOptions) -> i32 {
+    let _sysroot = match enter_sysroot(opts.sysroot.as_deref(), false) {
+        Ok(scope) => scope,
+        Err(code) => return code,
+    };
+    let module_path = match normalize_existing_path(&opts.module_path) {
+        Ok(p) => p,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let Some(app_dir) = module_path.parent() else {
+        eprintln!(
+            "cannot determine app directory for {}",
+            display_path(&module_path)
+        );
+        return 20;
+    };
+    let cwd = match command_cwd(opts.cwd, &module_path) {
+        Ok(cwd) => cwd,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let writability = match opts.writable.as_deref().map(read_writable_dirs).transpose() {
+        Ok(Some(dirs)) => Writability::Listed(dirs),
+        Ok(None) if sysroot::current().is_none() => Writability::host(),
+        Ok(None) => Writability::Unknown,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
+    let mut context =
+        match static_search_context(&module_path, app_dir, &cwd, env_path_override(&[])) {
+            Ok(context) => context,
+            Err(err) => {
+                eprintln!("{err}");
+                return 21;
+            }
+        };
+    context.redirect_dotlocal(&module_path);
+    let report = match diagnose_static_imports_in_context(
+        &module_path,
+        &context,
+        &HashSet::new(),
+        &HashMap::new(),
+        StaticEmitMode::Audit,
+    ) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{err}");
+            return 21;
+        }
+    };
+
+    emit_search_order(&context);
+    if matches!(writability, Writability::Unknown) {
+        emit(
+            TOKEN_NOTE,
+            &[
+                field("topic", quote("audit-search")),
+                field("detail", quote("writability-unknown")),
+            ],
+        );
+    }
+    let mut findings = 0usize;
+    let root_risks = audit::root_risks(&context, &writability);
+    for risk in &root_risks {
+        emit(
+            TOKEN_SEARCH_RISK,
+            &[
+                field("kind", quote(risk.kind)),
+                field("severity", quote(risk.severity.as_token())),
+                field("order", risk.order.to_string()),
+                field("path", quote(&display_path(&risk.path))),
+            ],
+        );
+        findings += 1;
+    }
+
+    // A name found nowhere on the search path is loaded from whichever
+    // directory someone drops it into first.
+    let plant = root_risks
+        .iter()
+        .find(|risk| risk.kind == "WRITABLE_ROOT")
+        .map(|risk| &risk.path);
+    let load_time = report.failures.iter().filter(|failure| {
+        matches!(failure.kind, ResolutionKind::Missing)
+            && failure.export.is_none()
+            && failure.forward.is_none()
+    });
+    let mut phantoms = HashSet::new();
+    for (failure, delay) in load_time
+        .map(|failure| (failure, false))
+        .chain(report.delay_missing.iter().map(|failure| (failure, true)))
+    {
+        if failure.api_set.is_some() || !phantoms.insert(failure.dll.to_ascii_lowercase()) {
+            continue;
+        }
+        let severity = if plant.is_some() {
+            audit::Severity::High
+        } else {
+            audit::Severity::Medium
+        };
+        let mut fields = vec![
+            field("kind", quote("PHANTOM_DLL")),
+            field("severity", quote(severity.as_token())),
+            field("dll", quote(&failure.dll)),
+            field("module", quote(&failure.via)),
+        ];
+        if delay {
+            fields.push(field("delay", "true"));
+        }
+        if let Some(plant) = plant {
+            fields.push(field("plant", quote(&display_path(plant))));
+        }
+        emit(TOKEN_SEARCH_RISK, &fields);
+        findings += 1;
+    }
+
+    for (dll, chosen) in &report.found {
+        let Some(severity) = audit::system_dll_risk(dll, chosen, &context, &writability) else {
+            continue;
+        };
+        emit(
+            TOKEN_SEARCH_RISK,
+            &[
+                field("kind", quote("SYSTEM_DLL_APP_LOCAL")),
+                field("severity", quote(severity.as_token())),
+                field("dll", quote(dll)),
+                field("path", quote(&display_path(chosen))),
+                field(
+                    "system_path",
+                    quote(&display_path(&context.system_dir.join(dll))),
+                ),
+            ],
+        );
+        findings += 1;
+    }
+
+    if findings > 0 {
+        10
+    } else {
+        0
+    }
+}
+
+/// `path-lint`: problems with the machine's PATH value.
+fn path_lint_command(opts: PathLintOptions) -> i32 {
+    let _sysroot = match enter_sysroot(opts.sysroot.as_deref(), false) {
+        Ok(scope) => scope,
+        Err(code) => return code,
+    };
+    if emit_path_issues(&search::machine_path_env()) > 0 {
+        10
+    } else {
+        0
     }
 }
 
-#[cfg(windows)]
 fn com_command(opts: ComOptions) -> i32 {
-    let registry = WindowsRegistry;
+    let offline_registry = opts.hives.any() || !opts.registry_files.is_empty();
+    let _sysroot = match enter_sysroot(opts.sysroot.as_deref(), offline_registry) {
+        Ok(scope) => scope,
+        Err(code) => return code,
+    };
+    let registry = match com_registry(&opts.hives, &opts.registry_files) {
+        Ok(registry) => registry,
+        Err(err) => {
+            eprintln!("{err}");
+            return 20;
+        }
+    };
     let fs = RealComFileSystem;
-    let resolver = ComResolver::new(&registry, &fs);
+    let resolver = ComResolver::new(registry.as_ref(), &fs);
     match opts.sub {
         ComSubcommand::Clsid { query, view } => {
             com_lookup_command(&resolver, QueryKind::Clsid, &query, view, opts.trace)
@@ -668,16 +1079,79 @@ fn com_command(opts: ComOptions) -> i32 {
         ComSubcommand::Progid { query, view } => {
             com_lookup_command(&resolver, QueryKind::Progid, &query, view, opts.trace)
         }
-        ComSubcommand::Server { path, view } => {
-            com_server_command(&resolver, &path, view, opts.trace)
-        }
-        ComSubcommand::Audit { target, query } => {
-            com_audit_command(&resolver, &target, &query, opts.trace)
+        ComSubcommand::Server { path, view } => match sysroot_argument(&path) {
+            Ok(path) => com_server_command(&resolver, &path, view, opts.trace),
+            Err(code) => code,
+        },
+        ComSubcommand::Audit { target, query } => match sysroot_argument(&target) {
+            Ok(target) => com_audit_command(&resolver, &target, &query, opts.trace),
+            Err(code) => code,
+        },
+    }
+}
+
+/// The registry COM lookups read: `.reg` snapshots when any are given, then
+/// hive files, with the mounted volume's SOFTWARE hive standing in for
+/// `--software-hive` under `--sysroot`, and the live registry otherwise.
+fn com_registry(
+    hives: &ComHiveFiles,
+    registry_files: &[PathBuf],
+) -> Result<Box<dyn ComRegistry>, String> {
+    if !registry_files.is_empty() {
+        return Ok(Box::new(RegFileRegistry::open(registry_files)?));
+    }
+    let software = hives
+        .software
+        .clone()
+        .or_else(|| sysroot::current().map(|sysroot| sysroot.software_hive()));
+    if software.is_some() || hives.any() {
+        let open = |path: &Option<PathBuf>| path.as_deref().map(hive::Hive::open).transpose();
+        return Ok(Box::new(HiveRegistry::new(
+            open(&software)?,
+            open(&hives.ntuser)?,
+            open(&hives.usrclass)?,
+        )));
+    }
+    #[cfg(windows)]
+    return Ok(Box::new(WindowsRegistry));
+    #[cfg(not(windows))]
+    unreachable!("enter_sysroot requires an offline source off Windows")
+}
+
+/// Activates `--sysroot` for the rest of the command. Without one only a
+/// Windows host has a machine to inspect, so other hosts exit with 22
+/// unless the command has another offline source (`offline`).
+fn enter_sysroot(root: Option<&Path>, offline: bool) -> Result<Option<SysrootScope>, i32> {
+    match root {
+        Some(root) => match Sysroot::open(root) {
+            Ok(sysroot) => Ok(Some(SysrootScope::enter(sysroot))),
+            Err(err) => {
+                eprintln!("{err}");
+                Err(20)
+            }
+        },
+        None if cfg!(windows) || offline => Ok(None),
+        None => {
+            eprintln!(
+                "loadwhat currently supports Windows only; pass --sysroot <dir> to analyze a mounted Windows volume."
+            );
+            Err(22)
         }
     }
 }
 
-#[cfg(windows)]
+/// A path argument in the form the active sysroot uses; unchanged without
+/// one.
+fn sysroot_argument(path: &Path) -> Result<PathBuf, i32> {
+    match sysroot::current() {
+        Some(sysroot) => sysroot.to_virtual(path).map_err(|err| {
+            eprintln!("{err}");
+            20
+        }),
+        None => Ok(path.to_path_buf()),
+    }
+}
+
 fn com_reg_view(view: ComViewArg) -> RegView {
     match view {
         ComViewArg::V32 => RegView::V32,
@@ -686,7 +1160,6 @@ fn com_reg_view(view: ComViewArg) -> RegView {
     }
 }
 
-#[cfg(windows)]
 fn com_error_parts(error: &ComError) -> (&'static str, i32, &str) {
     match error {
         ComError::Indeterminate(message) => ("INDETERMINATE", 21, message),
@@ -694,9 +1167,9 @@ fn com_error_parts(error: &ComError) -> (&'static str, i32, &str) {
     }
 }
 
-#[cfg(windows)]
 fn absolutize_path(path: &Path) -> PathBuf {
-    if path.is_absolute() {
+    // Sysroot paths are already absolute on the mounted volume.
+    if path.is_absolute() || sysroot::current().is_some() {
         path.to_path_buf()
     } else {
         env::current_dir()
@@ -705,7 +1178,6 @@ fn absolutize_path(path: &Path) -> PathBuf {
     }
 }
 
-#[cfg(windows)]
 fn com_lookup_command(
     resolver: &ComResolver,
     kind: QueryKind,
@@ -738,12 +1210,10 @@ fn com_lookup_command(
     com_lookup_exit_code(&result)
 }
 
-#[cfg(windows)]
 fn emit_com_lookup(kind: QueryKind, query: &str, result: &ComLookupResult) {
     emit_com_lookup_with_server_status(kind, query, result, None);
 }
 
-#[cfg(windows)]
 fn emit_com_lookup_with_server_status(
     kind: QueryKind,
     query: &str,
@@ -773,7 +1243,6 @@ fn emit_com_lookup_with_server_status(
     emit(TOKEN_COM_LOOKUP, &fields);
 }
 
-#[cfg(windows)]
 fn emit_com_lookup_trace(result: &ComLookupResult) {
     if let (Some(clsid), Some(progid)) = (&result.clsid, &result.progid_of_clsid) {
         let mut fields = vec![field("clsid", quote(clsid)), field("progid", quote(progid))];
@@ -807,7 +1276,6 @@ fn emit_com_lookup_trace(result: &ComLookupResult) {
     }
 }
 
-#[cfg(windows)]
 fn emit_com_server_detail(
     path: &str,
     server_kind: Option<ServerKind>,
@@ -827,10 +1295,11 @@ fn emit_com_server_detail(
     if let Some(threading_model) = threading_model {
         fields.push(field("threading_model", quote(threading_model)));
     }
+    push_version_fields(&mut fields, Path::new(path));
+    push_signature_fields(&mut fields, Path::new(path));
     emit(TOKEN_COM_SERVER, &fields);
 }
 
-#[cfg(windows)]
 fn emit_com_dependency_trace(server: &ServerValidation) {
     if let Some(redirected) = &server.redirected_path {
         emit(
@@ -848,6 +1317,12 @@ fn emit_com_dependency_trace(server: &ServerValidation) {
             field("status", quote(failure.status.as_token())),
             field("dll", quote(&failure.dll)),
         ];
+        if let Some(function) = &failure.export {
+            fields.push(export_field(function));
+        }
+        if let Some(forward) = &failure.forward {
+            fields.push(field("forward", quote(forward)));
+        }
         if failure.depth > 1 {
             fields.push(field("via", quote(&failure.via)));
             fields.push(field("depth", failure.depth.to_string()));
@@ -862,32 +1337,18 @@ fn emit_com_dependency_trace(server: &ServerValidation) {
                 &[field("safedll", if server.safedll { "1" } else { "0" })],
             );
             for candidate in &first.candidates {
-                emit(
-                    TOKEN_SEARCH_PATH,
-                    &[
-                        field("dll", quote(&first.dll)),
-                        field("order", candidate.order.to_string()),
-                        field("path", quote(&candidate.path)),
-                        field("result", quote(candidate.result)),
-                    ],
+                emit_search_path(
+                    &first.dll,
+                    candidate.order,
+                    &candidate.path,
+                    candidate.result,
+                    candidate.reason,
                 );
             }
         }
     }
-
-    if server.status == ServerStatus::Skipped {
-        emit(
-            TOKEN_NOTE,
-            &[
-                field("topic", quote("com")),
-                field("detail", quote("out-of-scope")),
-                field("feature", quote("x86-dependency-walk")),
-            ],
-        );
-    }
 }
 
-#[cfg(windows)]
 fn com_lookup_exit_code(result: &ComLookupResult) -> i32 {
     if result.status == LookupStatus::AccessDenied {
         return 21;
@@ -904,7 +1365,6 @@ fn com_lookup_exit_code(result: &ComLookupResult) -> i32 {
     }
 }
 
-#[cfg(windows)]
 fn com_server_command(
     resolver: &ComResolver,
     path: &Path,
@@ -957,11 +1417,7 @@ fn com_server_command(
     let status_token = if denied {
         "ACCESS_DENIED"
     } else {
-        match validation.status {
-            // The x86 walk skip leaves existence/image checks authoritative.
-            ServerStatus::Skipped => "OK",
-            other => other.as_token(),
-        }
+        validation.status.as_token()
     };
 
     let mut fields = vec![
@@ -979,6 +1435,10 @@ fn com_server_command(
     if let Some(threading_model) = uniform_threading_model(&registrations) {
         fields.push(field("threading_model", quote(&threading_model)));
     }
+    if trace {
+        push_version_fields(&mut fields, &absolute);
+        push_signature_fields(&mut fields, &absolute);
+    }
     emit(TOKEN_COM_SERVER, &fields);
 
     if denied || validation.status == ServerStatus::AccessDenied {
@@ -993,7 +1453,6 @@ fn com_server_command(
     }
 }
 
-#[cfg(windows)]
 fn uniform_registration_kind(registrations: &[ComRegistration]) -> Option<&'static str> {
     let first = registrations.first()?.kind;
     registrations
@@ -1002,7 +1461,6 @@ fn uniform_registration_kind(registrations: &[ComRegistration]) -> Option<&'stat
         .then(|| first.as_token())
 }
 
-#[cfg(windows)]
 fn uniform_threading_model(registrations: &[ComRegistration]) -> Option<String> {
     let first = registrations.first()?.threading_model.clone()?;
     registrations
@@ -1011,7 +1469,6 @@ fn uniform_threading_model(registrations: &[ComRegistration]) -> Option<String>
         .then_some(first)
 }
 
-#[cfg(windows)]
 fn emit_com_registration(registration: &ComRegistration) {
     let mut fields = vec![
         field("clsid", quote(&registration.clsid)),
@@ -1026,7 +1483,6 @@ fn emit_com_registration(registration: &ComRegistration) {
     emit(TOKEN_COM_REGISTRATION, &fields);
 }
 
-#[cfg(windows)]
 fn com_audit_command(resolver: &ComResolver, target: &Path, query: &str, trace: bool) -> i32 {
     let absolute = absolutize_path(target);
     let target_str = display_path(&absolute);
@@ -1036,12 +1492,17 @@ fn com_audit_command(resolver: &ComResolver, target: &Path, query: &str, trace:
         QueryKind::Progid
     };
 
-    let cwd = std::env::current_dir().unwrap_or_else(|_| {
+    let target_dir = || {
         absolute
             .parent()
             .map(Path::to_path_buf)
             .unwrap_or_else(|| PathBuf::from("."))
-    });
+    };
+    let cwd = if sysroot::current().is_some() {
+        target_dir()
+    } else {
+        std::env::current_dir().unwrap_or_else(|_| target_dir())
+    };
     let audit = match resolver.audit_with_cwd(&target_str, query, query_kind, &display_path(&cwd)) {
         Ok(audit) => audit,
         Err(error) => {
@@ -1094,7 +1555,6 @@ fn com_audit_command(resolver: &ComResolver, target: &Path, query: &str, trace:
     }
 }
 
-#[cfg(windows)]
 fn emit_com_audit_trace(audit: &ComAuditResult) {
     if let Some(hit) = &audit.manifest {
         let mut fields = vec![
@@ -1137,24 +1597,25 @@ fn emit_run_events(exe_path: &Path, cwd: &Path, outcome: &RunOutcome) {
     for event in &outcome.runtime_events {
         match event {
             RuntimeEvent::RuntimeLoaded(module) => {
-                emit(
-                    TOKEN_RUNTIME_LOADED,
-                    &[
-                        field("pid", outcome.pid.to_string()),
-                        field("dll", quote(&module.dll_name)),
-                        field(
-                            "path",
-                            quote(
-                                &module
-                                    .path
-                                    .as_ref()
-                                    .map(|p| display_path(p))
-                                    .unwrap_or_else(|| "UNKNOWN".to_string()),
-                            ),
+                let mut fields = vec![
+                    field("pid", outcome.pid.to_string()),
+                    field("dll", quote(&module.dll_name)),
+                    field(
+                        "path",
+                        quote(
+                            &module
+                                .path
+                                .as_ref()
+                                .map(|p| display_path(p))
+                                .unwrap_or_else(|| "UNKNOWN".to_string()),
                         ),
-                        field("base", hex_usize(module.base)),
-                    ],
-                );
+                    ),
+                    field("base", hex_usize(module.base)),
+                ];
+                if let Some(path) = &module.path {
+                    push_version_fields(&mut fields, path);
+                }
+                emit(TOKEN_RUNTIME_LOADED, &fields);
             }
             RuntimeEvent::DebugString(debug) => {
                 emit(
@@ -1210,7 +1671,8 @@ fn emit_loader_snaps_peb_note(info: PebEnableInfo) {
     );
 }
 
-#[cfg(windows)]
+/// The issue `run` reports as its first break; other commands only rank it.
+#[cfg_attr(not(windows), allow(dead_code))]
 struct FirstIssue {
     module: String,
     via: String,
@@ -1218,46 +1680,97 @@ struct FirstIssue {
     dll: String,
     diagnosis: &'static str,
     kind: ResolutionKind,
+    /// Set when `dll` resolved but does not export this import.
+    export: Option<pe::ImportedFunction>,
+    /// Set when `export` is forwarded to a target that cannot be bound.
+    forward: Option<ForwardBreak>,
+    /// Set when `dll` is an API set with no host: `NO_HOST` or
+    /// `UNKNOWN_API_SET`.
+    api_set: Option<&'static str>,
     candidates: Vec<CandidateResult>,
 }
 
-#[cfg(windows)]
 struct StaticFailure {
     dll: String,
     via: String,
     depth: u32,
     kind: ResolutionKind,
+    export: Option<pe::ImportedFunction>,
+    forward: Option<ForwardBreak>,
+    api_set: Option<&'static str>,
+    candidates: Vec<CandidateResult>,
+}
+
+/// A forwarder whose target DLL or export cannot be bound. `forward` is the
+/// raw `"OTHERDLL.Function"` string; `reason` is `NOT_FOUND`, `BAD_IMAGE`,
+/// `MISSING_EXPORT`, or `NO_HOST` / `UNKNOWN_API_SET` for an API set target.
+#[derive(Clone)]
+struct ForwardBreak {
+    forward: String,
+    reason: &'static str,
+}
+
+/// A resolved load-time import whose bound functions are checked against
+/// the target's export table once the importing module has been scanned.
+struct ExportCheck {
+    dll: String,
+    path: PathBuf,
+    functions: Vec<pe::ImportedFunction>,
     candidates: Vec<CandidateResult>,
 }
 
-#[cfg(windows)]
 struct StaticReport {
     missing_count: usize,
     bad_image_count: usize,
+    /// `run`'s first break.
+    #[cfg_attr(not(windows), allow(dead_code))]
     first_issue: Option<FirstIssue>,
     failures: Vec<StaticFailure>,
     safedll: bool,
+    /// The PATH the walk searched, for `run`'s `PATH_ISSUE` lines.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    path_env: String,
+    /// Where each resolved DLL name was found, for the `--shadows` report.
+    found: BTreeMap<String, PathBuf>,
+    /// Delay-load imports nothing on the search path satisfies, for
+    /// `SUMMARY`'s `static_delay_missing` and `audit-search`.
+    delay_missing: Vec<StaticFailure>,
+    /// Every import and forwarder edge in walk order; only recorded in
+    /// `Tree` mode.
+    tree: Vec<TreeEdge>,
 }
 
-#[cfg(windows)]
 #[derive(Clone, Copy)]
 enum StaticEmitMode {
     Full,
+    /// `run --trace` without `-v`.
+    #[cfg_attr(not(windows), allow(dead_code))]
     FailuresOnly,
+    /// `run` in summary mode.
+    #[cfg_attr(not(windows), allow(dead_code))]
     SummaryOnly,
     /// No token output and no early break; used by COM server validation to
     /// collect the complete failing-dependency list.
     CollectOnly,
+    /// `CollectOnly` that also walks delay-load edges and collects their
+    /// misses; used by `audit-search`.
+    Audit,
+    /// `Audit` that also records every import and forwarder edge for
+    /// `imports --tree` and `--graph`.
+    Tree,
 }
 
-#[cfg(windows)]
+#[derive(Clone)]
 struct WalkNode {
     module_path: PathBuf,
     module_name: String,
     depth: u32,
+    /// Reached only through a delay-load edge; failures below it surface at
+    /// first call rather than at process start.
+    delay: bool,
 }
 
-#[cfg(windows)]
+#[cfg_attr(not(windows), allow(dead_code))]
 fn diagnose_static_imports(
     module_path: &Path,
     cwd: &Path,
@@ -1272,29 +1785,41 @@ fn diagnose_static_imports(
             module_path.display()
         )
     })?;
+    let mut context = static_search_context(module_path, app_dir, cwd, path_env_override)?;
+    context.redirect_dotlocal(module_path);
     diagnose_static_imports_in_context(
         module_path,
-        app_dir,
-        cwd,
+        &context,
         runtime_loaded,
         runtime_observed,
-        path_env_override,
         emit_mode,
     )
 }
 
-#[cfg(windows)]
-fn diagnose_static_imports_in_context(
+/// The search context of a static walk from `module_path`. The walk models
+/// the process the root image would run in; an x86 root resolves against
+/// SysWOW64, and candidates that process could not map are skipped.
+fn static_search_context(
     module_path: &Path,
     app_dir: &Path,
     cwd: &Path,
+    path_env_override: Option<OsString>,
+) -> Result<SearchContext, String> {
+    let machine = pe::module_machine_type(module_path)
+        .map(MachineType::process_machine)
+        .unwrap_or(MachineType::Unknown);
+    SearchContext::from_environment(app_dir, cwd, path_env_override, machine)
+}
+
+fn diagnose_static_imports_in_context(
+    module_path: &Path,
+    context: &SearchContext,
     runtime_loaded: &HashSet<String>,
     runtime_observed: &HashMap<String, PathBuf>,
-    path_env_override: Option<OsString>,
     emit_mode: StaticEmitMode,
 ) -> Result<StaticReport, String> {
-    let context = SearchContext::from_environment(app_dir, cwd, path_env_override)?;
     let root_module_name = module_name_lower(module_path);
+    let api_sets = load_api_set_schema(context);
 
     if matches!(emit_mode, StaticEmitMode::Full) {
         emit(
@@ -1304,28 +1829,52 @@ fn diagnose_static_imports_in_context(
                 field("scope", quote("direct-and-recursive-imports")),
             ],
         );
-        emit(
-            TOKEN_SEARCH_ORDER,
-            &[field("safedll", if context.safedll { "1" } else { "0" })],
-        );
+        emit_search_order(context);
+        emit_path_issues(&context.path_env);
+        if api_sets.is_none() {
+            emit(
+                TOKEN_NOTE,
+                &[
+                    field("topic", quote("apiset")),
+                    field("detail", quote("schema-unavailable")),
+                ],
+            );
+        }
     }
 
     let mut missing_count = 0usize;
     let mut bad_image_count = 0usize;
     let mut first_issue = None::<FirstIssue>;
     let mut failures = Vec::new();
+    let mut found = BTreeMap::new();
+    let mut delay_missing = Vec::new();
     let mut visited = HashSet::new();
     let mut queue = VecDeque::new();
+    let mut delay_queue = VecDeque::new();
+    let mut exports = ExportCache::default();
+    let mut forward_edges = HashSet::new();
     let mut max_parent_depth_for_failures = None::<u32>;
+    // Delay-load edges only matter to the full imports walk and the audit;
+    // run's first-break diagnosis and COM server validation model what fails
+    // at load time.
+    let walk_delay_imports = matches!(
+        emit_mode,
+        StaticEmitMode::Full | StaticEmitMode::Audit | StaticEmitMode::Tree
+    );
+    let mut tree = Vec::new();
 
     visited.insert(normalize_module_visit_key(module_path));
     queue.push_back(WalkNode {
         module_path: module_path.to_path_buf(),
         module_name: root_module_name.clone(),
         depth: 0,
+        delay: false,
     });
 
-    while let Some(node) = queue.pop_front() {
+    while let Some(node) = queue
+        .pop_front()
+        .or_else(|| next_delay_node(&mut visited, &mut delay_queue))
+    {
         if matches!(
             emit_mode,
             StaticEmitMode::FailuresOnly | StaticEmitMode::SummaryOnly
@@ -1337,15 +1886,30 @@ fn diagnose_static_imports_in_context(
             }
         }
 
-        let imports = pe::direct_imports(&node.module_path)?;
-        for dll in imports {
-            if is_api_set_dll(&dll) {
+        let imports = pe::module_dependencies(&node.module_path)?;
+        let tree_parent = matches!(emit_mode, StaticEmitMode::Tree)
+            .then(|| normalize_module_visit_key(&node.module_path));
+        let mut export_checks = Vec::new();
+        for import in imports {
+            let mut dll = import.name;
+            let imported = dll.clone();
+            if import.delay && !walk_delay_imports {
+                continue;
+            }
+            // Without a schema API sets stay unmodeled, as they always were.
+            let api_set_schema = api_sets.as_ref().filter(|_| is_api_set_dll(&dll));
+            if is_api_set_dll(&dll) && api_set_schema.is_none() {
                 continue;
             }
+            let delay_scope = node.delay || import.delay;
 
             if matches!(emit_mode, StaticEmitMode::Full) {
                 emit(
-                    TOKEN_STATIC_IMPORT,
+                    if import.delay {
+                        TOKEN_STATIC_DELAY_IMPORT
+                    } else {
+                        TOKEN_STATIC_IMPORT
+                    },
                     &[
                         field("module", quote(&node.module_name)),
                         field("needs", quote(&dll)),
@@ -1353,67 +1917,173 @@ fn diagnose_static_imports_in_context(
                 );
             }
 
-            if node.depth == 0 && runtime_loaded.contains(&dll) {
-                if matches!(emit_mode, StaticEmitMode::Full) {
-                    emit(
-                        TOKEN_STATIC_FOUND,
-                        &[
-                            field("module", quote(&node.module_name)),
-                            field("dll", quote(&dll)),
-                            field("reason", quote("RUNTIME_OBSERVED")),
-                        ],
-                    );
+            let api_set_lookup =
+                api_set_schema.map(|schema| schema.resolve(&dll, &node.module_name));
+            let api_set_reason = match &api_set_lookup {
+                Some(ApiSetLookup::Host(host)) => {
+                    if matches!(emit_mode, StaticEmitMode::Full) {
+                        emit(
+                            TOKEN_STATIC_APISET,
+                            &[
+                                field("module", quote(&node.module_name)),
+                                field("apiset", quote(&dll)),
+                                field("host", quote(host)),
+                            ],
+                        );
+                    }
+                    dll = host.clone();
+                    None
                 }
+                Some(ApiSetLookup::NoHost) => Some("NO_HOST"),
+                // The loader falls back to a normal search for names the
+                // schema does not know (app-local UCRT forwarders, say).
+                Some(ApiSetLookup::Unknown) => Some("UNKNOWN_API_SET"),
+                None => None,
+            };
 
+            if node.depth == 0 && runtime_loaded.contains(&dll) {
                 let observed_path = runtime_observed
                     .get(&dll)
                     .cloned()
-                    .or_else(|| search::resolve_dll(&dll, &context).chosen);
+                    .or_else(|| search::resolve_dll(&dll, context).chosen);
+                if matches!(emit_mode, StaticEmitMode::Full) {
+                    let mut fields = vec![
+                        field("module", quote(&node.module_name)),
+                        field("dll", quote(&dll)),
+                        field("reason", quote("RUNTIME_OBSERVED")),
+                    ];
+                    if let Some(path) = &observed_path {
+                        push_version_fields(&mut fields, path);
+                        push_signature_fields(&mut fields, path);
+                    }
+                    emit(TOKEN_STATIC_FOUND, &fields);
+                }
                 if let Some(path) = observed_path {
-                    queue_module_if_unvisited(&mut visited, &mut queue, &path, node.depth + 1);
+                    queue_dependency(
+                        &mut visited,
+                        &mut queue,
+                        &mut delay_queue,
+                        &node,
+                        import.delay,
+                        &path,
+                    );
+                    if !delay_scope && !import.functions.is_empty() {
+                        export_checks.push(ExportCheck {
+                            dll: dll.clone(),
+                            path,
+                            functions: import.functions,
+                            candidates: Vec::new(),
+                        });
+                    }
                 }
                 continue;
             }
 
-            let resolution = search::resolve_dll(&dll, &context);
+            let resolution = if api_set_lookup == Some(ApiSetLookup::NoHost) {
+                search::Resolution {
+                    kind: ResolutionKind::Missing,
+                    chosen: None,
+                    candidates: Vec::new(),
+                }
+            } else {
+                search::resolve_dll(&dll, context)
+            };
             if matches!(emit_mode, StaticEmitMode::Full) {
                 for candidate in &resolution.candidates {
-                    emit(
-                        TOKEN_SEARCH_PATH,
-                        &[
-                            field("dll", quote(&dll)),
-                            field("order", candidate.order.to_string()),
-                            field("path", quote(&display_path(&candidate.path))),
-                            field("result", quote(candidate.result)),
-                        ],
+                    emit_search_path(
+                        &dll,
+                        candidate.order,
+                        &display_path(&candidate.path),
+                        candidate.result,
+                        candidate.reason,
                     );
                 }
             }
 
+            if let Some(parent) = &tree_parent {
+                tree.push(tree_edge(
+                    parent,
+                    imported,
+                    import.delay,
+                    &api_set_lookup,
+                    api_set_reason,
+                    &resolution,
+                    context,
+                ));
+            }
+
             match &resolution.kind {
                 ResolutionKind::Found => {
                     if matches!(emit_mode, StaticEmitMode::Full) {
-                        emit(
-                            TOKEN_STATIC_FOUND,
-                            &[
-                                field("module", quote(&node.module_name)),
-                                field("dll", quote(&dll)),
-                                field(
-                                    "path",
-                                    quote(
-                                        &resolution
-                                            .chosen
-                                            .as_ref()
-                                            .map(|v| display_path(v))
-                                            .unwrap_or_else(|| String::from("UNKNOWN")),
-                                    ),
+                        let mut fields = vec![
+                            field("module", quote(&node.module_name)),
+                            field("dll", quote(&dll)),
+                            field(
+                                "path",
+                                quote(
+                                    &resolution
+                                        .chosen
+                                        .as_ref()
+                                        .map(|v| display_path(v))
+                                        .unwrap_or_else(|| String::from("UNKNOWN")),
                                 ),
-                            ],
-                        );
+                            ),
+                        ];
+                        if let Some(chosen) = &resolution.chosen {
+                            push_version_fields(&mut fields, chosen);
+                            push_signature_fields(&mut fields, chosen);
+                        }
+                        emit(TOKEN_STATIC_FOUND, &fields);
                     }
 
                     if let Some(chosen) = resolution.chosen.as_ref() {
-                        queue_module_if_unvisited(&mut visited, &mut queue, chosen, node.depth + 1);
+                        queue_dependency(
+                            &mut visited,
+                            &mut queue,
+                            &mut delay_queue,
+                            &node,
+                            import.delay,
+                            chosen,
+                        );
+                        found.entry(dll.clone()).or_insert_with(|| chosen.clone());
+                        if !delay_scope && !import.functions.is_empty() {
+                            export_checks.push(ExportCheck {
+                                dll: dll.clone(),
+                                path: chosen.clone(),
+                                functions: import.functions,
+                                candidates: resolution.candidates.clone(),
+                            });
+                        }
+                    }
+                }
+                ResolutionKind::Missing if delay_scope => {
+                    if matches!(emit_mode, StaticEmitMode::Full) {
+                        emit(
+                            TOKEN_STATIC_DELAY_MISSING,
+                            &delay_failure_fields(
+                                &node,
+                                &dll,
+                                api_set_reason.unwrap_or("NOT_FOUND"),
+                            ),
+                        );
+                    }
+                    delay_missing.push(StaticFailure {
+                        dll: dll.clone(),
+                        via: node.module_name.clone(),
+                        depth: node.depth + 1,
+                        kind: ResolutionKind::Missing,
+                        export: None,
+                        forward: None,
+                        api_set: api_set_reason,
+                        candidates: resolution.candidates.clone(),
+                    });
+                }
+                ResolutionKind::BadImage if delay_scope => {
+                    if matches!(emit_mode, StaticEmitMode::Full) {
+                        emit(
+                            TOKEN_STATIC_DELAY_BAD_IMAGE,
+                            &delay_failure_fields(&node, &dll, resolution.bad_image_reason()),
+                        );
                     }
                 }
                 ResolutionKind::Missing => {
@@ -1423,6 +2093,9 @@ fn diagnose_static_imports_in_context(
                         via: node.module_name.clone(),
                         depth: node.depth + 1,
                         kind: ResolutionKind::Missing,
+                        export: None,
+                        forward: None,
+                        api_set: api_set_reason,
                         candidates: resolution.candidates.clone(),
                     });
                     let issue = FirstIssue {
@@ -1430,8 +2103,15 @@ fn diagnose_static_imports_in_context(
                         via: node.module_name.clone(),
                         depth: node.depth + 1,
                         dll: dll.clone(),
-                        diagnosis: "MISSING_STATIC_IMPORT",
+                        diagnosis: if api_set_reason.is_some() {
+                            "UNRESOLVED_API_SET"
+                        } else {
+                            "MISSING_STATIC_IMPORT"
+                        },
                         kind: ResolutionKind::Missing,
+                        export: None,
+                        forward: None,
+                        api_set: api_set_reason,
                         candidates: resolution.candidates.clone(),
                     };
                     consider_first_issue(&mut first_issue, issue);
@@ -1440,13 +2120,20 @@ fn diagnose_static_imports_in_context(
                         let mut fields = vec![
                             field("module", quote(&node.module_name)),
                             field("dll", quote(&dll)),
-                            field("reason", quote("NOT_FOUND")),
+                            field("reason", quote(api_set_reason.unwrap_or("NOT_FOUND"))),
                         ];
                         if node.depth + 1 > 1 {
                             fields.push(field("via", quote(&node.module_name)));
                             fields.push(field("depth", (node.depth + 1).to_string()));
                         }
-                        emit(TOKEN_STATIC_MISSING, &fields);
+                        emit(
+                            if api_set_reason.is_some() {
+                                TOKEN_STATIC_APISET_UNRESOLVED
+                            } else {
+                                TOKEN_STATIC_MISSING
+                            },
+                            &fields,
+                        );
                     }
 
                     if matches!(
@@ -1463,6 +2150,9 @@ fn diagnose_static_imports_in_context(
                         via: node.module_name.clone(),
                         depth: node.depth + 1,
                         kind: ResolutionKind::BadImage,
+                        export: None,
+                        forward: None,
+                        api_set: None,
                         candidates: resolution.candidates.clone(),
                     });
                     let issue = FirstIssue {
@@ -1472,6 +2162,9 @@ fn diagnose_static_imports_in_context(
                         dll: dll.clone(),
                         diagnosis: "BAD_STATIC_IMPORT_IMAGE",
                         kind: ResolutionKind::BadImage,
+                        export: None,
+                        forward: None,
+                        api_set: None,
                         candidates: resolution.candidates.clone(),
                     };
                     consider_first_issue(&mut first_issue, issue);
@@ -1482,7 +2175,7 @@ fn diagnose_static_imports_in_context(
                             &[
                                 field("module", quote(&node.module_name)),
                                 field("dll", quote(&dll)),
-                                field("reason", quote("BAD_IMAGE")),
+                                field("reason", quote(resolution.bad_image_reason())),
                             ],
                         );
                     }
@@ -1496,6 +2189,110 @@ fn diagnose_static_imports_in_context(
                 }
             }
         }
+
+        for check in export_checks {
+            let target = WalkNode {
+                module_path: check.path.clone(),
+                module_name: module_name_lower(&check.path),
+                depth: node.depth + 1,
+                delay: false,
+            };
+            for function in check.functions {
+                let (binding, edges) =
+                    exports.bind(context, api_sets.as_ref(), &target, function.clone());
+                for edge in edges {
+                    let first_sighting =
+                        forward_edges.insert((edge.from.module_name.clone(), edge.dll.clone()));
+                    if first_sighting && matches!(emit_mode, StaticEmitMode::Full) {
+                        emit_forward_edge(&edge);
+                    }
+                    if first_sighting && matches!(emit_mode, StaticEmitMode::Tree) {
+                        tree.push(TreeEdge {
+                            forward: true,
+                            ..tree_edge(
+                                &normalize_module_visit_key(&edge.from.module_path),
+                                edge.dll.clone(),
+                                false,
+                                &None,
+                                None,
+                                &edge.resolution,
+                                context,
+                            )
+                        });
+                    }
+                    if let (ResolutionKind::Found, Some(chosen)) =
+                        (&edge.resolution.kind, &edge.resolution.chosen)
+                    {
+                        queue_dependency(
+                            &mut visited,
+                            &mut queue,
+                            &mut delay_queue,
+                            &edge.from,
+                            false,
+                            chosen,
+                        );
+                    }
+                }
+
+                let (dll, export, forward) = match binding {
+                    // An unreadable export directory leaves the check
+                    // inconclusive; the loader's verdict is not modeled.
+                    ExportBinding::Bound | ExportBinding::Inconclusive => continue,
+                    ExportBinding::Missing => (check.dll.clone(), function, None),
+                    ExportBinding::BrokenForward {
+                        dll,
+                        export,
+                        forward,
+                    } => (dll, export, Some(forward)),
+                };
+
+                missing_count += 1;
+                failures.push(StaticFailure {
+                    dll: dll.clone(),
+                    via: node.module_name.clone(),
+                    depth: node.depth + 1,
+                    kind: ResolutionKind::Found,
+                    export: Some(export.clone()),
+                    forward: forward.clone(),
+                    api_set: None,
+                    candidates: check.candidates.clone(),
+                });
+                let issue = FirstIssue {
+                    module: root_module_name.clone(),
+                    via: node.module_name.clone(),
+                    depth: node.depth + 1,
+                    dll: dll.clone(),
+                    diagnosis: if forward.is_some() {
+                        "BROKEN_STATIC_FORWARD"
+                    } else {
+                        "MISSING_STATIC_EXPORT"
+                    },
+                    kind: ResolutionKind::Found,
+                    export: Some(export.clone()),
+                    forward: forward.clone(),
+                    api_set: None,
+                    candidates: check.candidates.clone(),
+                };
+                consider_first_issue(&mut first_issue, issue);
+
+                if matches!(emit_mode, StaticEmitMode::Full) {
+                    let (token, mut fields) =
+                        export_issue_fields(&node.module_name, &dll, &export, forward.as_ref());
+                    if node.depth + 1 > 1 {
+                        fields.push(field("via", quote(&node.module_name)));
+                        fields.push(field("depth", (node.depth + 1).to_string()));
+                    }
+                    emit(token, &fields);
+                }
+
+                if matches!(
+                    emit_mode,
+                    StaticEmitMode::FailuresOnly | StaticEmitMode::SummaryOnly
+                ) {
+                    max_parent_depth_for_failures.get_or_insert(node.depth);
+                }
+            }
+        }
     }
 
     if matches!(emit_mode, StaticEmitMode::Full) {
@@ -1505,38 +2302,349 @@ fn diagnose_static_imports_in_context(
         );
     }
 
-    Ok(StaticReport {
-        missing_count,
-        bad_image_count,
-        first_issue,
-        failures,
-        safedll: context.safedll,
-    })
+    Ok(StaticReport {
+        missing_count,
+        bad_image_count,
+        first_issue,
+        failures,
+        safedll: context.safedll,
+        path_env: context.path_env.clone(),
+        found,
+        delay_missing,
+        tree,
+    })
+}
+
+/// The `imports --tree` edge for one import of `parent`, as the walk
+/// resolved it.
+fn tree_edge(
+    parent: &str,
+    dll: String,
+    delay: bool,
+    api_set_lookup: &Option<ApiSetLookup>,
+    api_set_reason: Option<&'static str>,
+    resolution: &search::Resolution,
+    context: &SearchContext,
+) -> TreeEdge {
+    let status = match (&resolution.kind, api_set_lookup) {
+        (ResolutionKind::Found, Some(ApiSetLookup::Host(host))) => TreeStatus::ApiSet(host.clone()),
+        (ResolutionKind::Found, _)
+            if resolution
+                .candidates
+                .first()
+                .is_some_and(|candidate| candidate.result == "KNOWN_DLL") =>
+        {
+            TreeStatus::KnownDll
+        }
+        (ResolutionKind::Found, _) => TreeStatus::Found,
+        (ResolutionKind::Missing, _) => TreeStatus::Missing(api_set_reason.unwrap_or("NOT_FOUND")),
+        (ResolutionKind::BadImage, _) => TreeStatus::BadImage(resolution.bad_image_reason()),
+    };
+    let windows_dir = display_path(&context.windows_dir).to_ascii_lowercase();
+    let chosen = resolution.chosen.as_ref().map(|path| display_path(path));
+    TreeEdge {
+        parent: parent.to_string(),
+        dll,
+        delay,
+        forward: false,
+        status,
+        system: chosen.as_ref().is_some_and(|path| {
+            path.to_ascii_lowercase()
+                .starts_with(&format!("{}\\", windows_dir.trim_end_matches('\\')))
+        }),
+        child: resolution
+            .chosen
+            .as_ref()
+            .map(|path| normalize_module_visit_key(path)),
+        path: chosen,
+    }
+}
+
+/// Emits the single public token describing a first-break static issue.
+/// Returns false when the issue carries nothing to report.
+#[cfg(windows)]
+fn emit_static_issue(issue: &FirstIssue) -> bool {
+    let (token, mut fields) = match (&issue.export, &issue.kind) {
+        (Some(function), _) => {
+            export_issue_fields(&issue.module, &issue.dll, function, issue.forward.as_ref())
+        }
+        (None, ResolutionKind::Missing) => (
+            if issue.api_set.is_some() {
+                TOKEN_STATIC_APISET_UNRESOLVED
+            } else {
+                TOKEN_STATIC_MISSING
+            },
+            vec![
+                field("module", quote(&issue.module)),
+                field("dll", quote(&issue.dll)),
+                field("reason", quote(issue.api_set.unwrap_or("NOT_FOUND"))),
+            ],
+        ),
+        (None, ResolutionKind::BadImage) => {
+            emit(
+                TOKEN_STATIC_BAD_IMAGE,
+                &[
+                    field("module", quote(&issue.module)),
+                    field("dll", quote(&issue.dll)),
+                    field("reason", quote(search::bad_image_reason(&issue.candidates))),
+                ],
+            );
+            return true;
+        }
+        (None, ResolutionKind::Found) => return false,
+    };
+    if issue.depth > 1 {
+        fields.push(field("via", quote(&issue.via)));
+        fields.push(field("depth", issue.depth.to_string()));
+    }
+    emit(token, &fields);
+    true
+}
+
+fn consider_first_issue(current: &mut Option<FirstIssue>, candidate: FirstIssue) {
+    let replace = match current {
+        None => true,
+        Some(existing) => {
+            (
+                candidate.depth,
+                candidate.via.as_str(),
+                candidate.dll.as_str(),
+            ) < (existing.depth, existing.via.as_str(), existing.dll.as_str())
+        }
+    };
+    if replace {
+        *current = Some(candidate);
+    }
+}
+
+/// Token and leading fields for an import that resolves to a DLL but cannot
+/// be bound: a missing export, or a forwarder whose target is broken.
+fn export_issue_fields(
+    module: &str,
+    dll: &str,
+    export: &pe::ImportedFunction,
+    forward: Option<&ForwardBreak>,
+) -> (&'static str, Vec<(String, String)>) {
+    let mut fields = vec![
+        field("module", quote(module)),
+        field("dll", quote(dll)),
+        export_field(export),
+    ];
+    match forward {
+        Some(forward) => {
+            fields.push(field("forward", quote(&forward.forward)));
+            fields.push(field("reason", quote(forward.reason)));
+            (TOKEN_STATIC_FORWARD_BROKEN, fields)
+        }
+        None => (TOKEN_STATIC_MISSING_EXPORT, fields),
+    }
+}
+
+fn emit_forward_edge(edge: &ForwardEdge) {
+    emit(
+        TOKEN_STATIC_FORWARD,
+        &[
+            field("module", quote(&edge.from.module_name)),
+            field("needs", quote(&edge.dll)),
+        ],
+    );
+    for candidate in &edge.resolution.candidates {
+        emit_search_path(
+            &edge.dll,
+            candidate.order,
+            &display_path(&candidate.path),
+            candidate.result,
+            candidate.reason,
+        );
+    }
+    if let (ResolutionKind::Found, Some(chosen)) = (&edge.resolution.kind, &edge.resolution.chosen)
+    {
+        let mut fields = vec![
+            field("module", quote(&edge.from.module_name)),
+            field("dll", quote(&edge.dll)),
+            field("path", quote(&display_path(chosen))),
+        ];
+        push_version_fields(&mut fields, chosen);
+        push_signature_fields(&mut fields, chosen);
+        emit(TOKEN_STATIC_FOUND, &fields);
+    }
+}
+
+/// Export tables and forwarder-target resolutions shared across one walk.
+#[derive(Default)]
+struct ExportCache {
+    tables: HashMap<String, Option<pe::ExportTable>>,
+    forward_targets: HashMap<String, search::Resolution>,
+}
+
+/// Outcome of binding one imported function against its resolved DLL.
+enum ExportBinding {
+    Bound,
+    /// The resolved DLL does not export the function.
+    Missing,
+    /// A forwarder on the chain cannot be bound; `dll` holds it and
+    /// `export` is the forwarded export there.
+    BrokenForward {
+        dll: String,
+        export: pe::ImportedFunction,
+        forward: ForwardBreak,
+    },
+    /// An export table on the chain is unreadable, or the chain is too long.
+    Inconclusive,
+}
+
+/// One forwarder hop taken while binding an import: `from` forwards into
+/// `dll`, which the walk resolves like any other dependency.
+struct ForwardEdge {
+    from: WalkNode,
+    dll: String,
+    resolution: search::Resolution,
+}
+
+/// Upper bound on forwarder hops per import; longer chains are cycles in
+/// practice and are reported as inconclusive.
+const MAX_FORWARD_HOPS: usize = 16;
+
+impl ExportCache {
+    fn exports(&mut self, path: &Path) -> Option<&pe::ExportTable> {
+        self.tables
+            .entry(normalize_module_visit_key(path))
+            .or_insert_with(|| pe::module_exports(path).ok())
+            .as_ref()
+    }
+
+    /// Binds `function` against `target`, following export forwarders into
+    /// other DLLs. Returns the outcome plus every forwarder hop taken.
+    fn bind(
+        &mut self,
+        context: &SearchContext,
+        api_sets: Option<&ApiSetSchema>,
+        target: &WalkNode,
+        function: pe::ImportedFunction,
+    ) -> (ExportBinding, Vec<ForwardEdge>) {
+        let mut edges = Vec::new();
+        let mut current = target.clone();
+        let mut function = function;
+        // The forwarder that led to `current`, for reporting a target DLL
+        // that lacks the forwarded export.
+        let mut via_forward: Option<(String, pe::ImportedFunction, String)> = None;
+
+        for _ in 0..=MAX_FORWARD_HOPS {
+            let Some(table) = self.exports(&current.module_path) else {
+                return (ExportBinding::Inconclusive, edges);
+            };
+            if !table.provides(&function) {
+                let binding = match via_forward {
+                    None => ExportBinding::Missing,
+                    Some((dll, export, forward)) => ExportBinding::BrokenForward {
+                        dll,
+                        export,
+                        forward: ForwardBreak {
+                            forward,
+                            reason: "MISSING_EXPORT",
+                        },
+                    },
+                };
+                return (binding, edges);
+            }
+            let Some(forward) = table.forward_for(&function).cloned() else {
+                return (ExportBinding::Bound, edges);
+            };
+            let mut target_dll = forward.dll.clone();
+            let mut api_set_reason = None;
+            if is_api_set_dll(&forward.dll) {
+                // Without a schema API set hosts are not modeled; treat the
+                // forward as bound.
+                let Some(schema) = api_sets else {
+                    return (ExportBinding::Bound, edges);
+                };
+                match schema.resolve(&forward.dll, &current.module_name) {
+                    ApiSetLookup::Host(host) => target_dll = host,
+                    ApiSetLookup::NoHost => api_set_reason = Some("NO_HOST"),
+                    ApiSetLookup::Unknown => api_set_reason = Some("UNKNOWN_API_SET"),
+                }
+            }
+
+            let resolution = if api_set_reason == Some("NO_HOST") {
+                search::Resolution {
+                    kind: ResolutionKind::Missing,
+                    chosen: None,
+                    candidates: Vec::new(),
+                }
+            } else {
+                self.forward_targets
+                    .entry(target_dll.clone())
+                    .or_insert_with(|| search::resolve_dll(&target_dll, context))
+                    .clone()
+            };
+            let reason = match resolution.kind {
+                ResolutionKind::Found => None,
+                ResolutionKind::Missing => Some(api_set_reason.unwrap_or("NOT_FOUND")),
+                ResolutionKind::BadImage => Some("BAD_IMAGE"),
+            };
+            let next = resolution.chosen.clone();
+            edges.push(ForwardEdge {
+                from: current.clone(),
+                dll: target_dll,
+                resolution,
+            });
+
+            match (reason, next) {
+                (None, Some(chosen)) => {
+                    via_forward = Some((current.module_name.clone(), function, forward.raw));
+                    current = WalkNode {
+                        module_name: module_name_lower(&chosen),
+                        module_path: chosen,
+                        depth: current.depth + 1,
+                        delay: false,
+                    };
+                    function = forward.function;
+                }
+                (reason, _) => {
+                    return (
+                        ExportBinding::BrokenForward {
+                            dll: current.module_name,
+                            export: function,
+                            forward: ForwardBreak {
+                                forward: forward.raw,
+                                reason: reason.unwrap_or("NOT_FOUND"),
+                            },
+                        },
+                        edges,
+                    );
+                }
+            }
+        }
+        (ExportBinding::Inconclusive, edges)
+    }
+}
+
+fn export_field(function: &pe::ImportedFunction) -> (String, String) {
+    match function {
+        pe::ImportedFunction::Name(name) => field("function", quote(name)),
+        pe::ImportedFunction::Ordinal(ordinal) => field("ordinal", ordinal.to_string()),
+    }
 }
 
-#[cfg(windows)]
-fn consider_first_issue(current: &mut Option<FirstIssue>, candidate: FirstIssue) {
-    let replace = match current {
-        None => true,
-        Some(existing) => {
-            (
-                candidate.depth,
-                candidate.via.as_str(),
-                candidate.dll.as_str(),
-            ) < (existing.depth, existing.via.as_str(), existing.dll.as_str())
-        }
-    };
-    if replace {
-        *current = Some(candidate);
+fn delay_failure_fields(node: &WalkNode, dll: &str, reason: &str) -> Vec<(String, String)> {
+    let mut fields = vec![
+        field("module", quote(&node.module_name)),
+        field("dll", quote(dll)),
+        field("reason", quote(reason)),
+    ];
+    if node.depth > 0 {
+        fields.push(field("via", quote(&node.module_name)));
+        fields.push(field("depth", (node.depth + 1).to_string()));
     }
+    fields
 }
 
-#[cfg(windows)]
 fn queue_module_if_unvisited(
     visited: &mut HashSet<String>,
     queue: &mut VecDeque<WalkNode>,
     module_path: &Path,
     depth: u32,
+    delay: bool,
 ) -> bool {
     let key = normalize_module_visit_key(module_path);
     if !visited.insert(key) {
@@ -1547,24 +2655,68 @@ fn queue_module_if_unvisited(
         module_path: module_path.to_path_buf(),
         module_name: module_name_lower(module_path),
         depth,
+        delay,
     });
     true
 }
 
-#[cfg(windows)]
+/// Queues a resolved dependency of `parent`. Delay-load edges leaving the
+/// static closure are deferred until that closure is fully walked, so a
+/// module reachable both ways is always attributed to its static edge.
+fn queue_dependency(
+    visited: &mut HashSet<String>,
+    queue: &mut VecDeque<WalkNode>,
+    delay_queue: &mut VecDeque<WalkNode>,
+    parent: &WalkNode,
+    edge_delay: bool,
+    module_path: &Path,
+) {
+    if edge_delay && !parent.delay {
+        delay_queue.push_back(WalkNode {
+            module_path: module_path.to_path_buf(),
+            module_name: module_name_lower(module_path),
+            depth: parent.depth + 1,
+            delay: true,
+        });
+    } else {
+        queue_module_if_unvisited(visited, queue, module_path, parent.depth + 1, parent.delay);
+    }
+}
+
+fn next_delay_node(
+    visited: &mut HashSet<String>,
+    delay_queue: &mut VecDeque<WalkNode>,
+) -> Option<WalkNode> {
+    while let Some(node) = delay_queue.pop_front() {
+        if visited.insert(normalize_module_visit_key(&node.module_path)) {
+            return Some(node);
+        }
+    }
+    None
+}
+
+/// The schema the loader would consult: `apisetschema.dll` in the native
+/// System32 directory, which WOW64 processes share. None when it is missing
+/// or unreadable, in which case API sets are left unmodeled.
+fn load_api_set_schema(context: &SearchContext) -> Option<ApiSetSchema> {
+    let path = context
+        .windows_dir
+        .join("System32")
+        .join("apisetschema.dll");
+    ApiSetSchema::load(&path).ok()
+}
+
 fn is_api_set_dll(dll: &str) -> bool {
     let lower = dll.to_ascii_lowercase();
     lower.starts_with("api-ms-win-") || lower.starts_with("ext-ms-win-")
 }
 
-#[cfg(windows)]
 fn module_name_lower(path: &Path) -> String {
     path.file_name()
         .map(|value| value.to_string_lossy().to_ascii_lowercase())
         .unwrap_or_else(|| path.display().to_string().to_ascii_lowercase())
 }
 
-#[cfg(windows)]
 fn normalize_module_visit_key(path: &Path) -> String {
     let absolute = if path.is_absolute() {
         path.to_path_buf()
@@ -1573,7 +2725,7 @@ fn normalize_module_visit_key(path: &Path) -> String {
             .unwrap_or_else(|_| PathBuf::from("."))
             .join(path)
     };
-    let canonical = std::fs::canonicalize(path).unwrap_or(absolute);
+    let canonical = std::fs::canonicalize(sysroot::host_path(path)).unwrap_or(absolute);
     display_path(&canonical)
         .replace('/', "\\")
         .to_ascii_lowercase()
@@ -1616,8 +2768,14 @@ fn emit_non_diagnostic_run_error(outcome: &RunOutcome) {
     }
 }
 
-#[cfg(windows)]
 fn normalize_existing_path(path: &Path) -> Result<PathBuf, String> {
+    if let Some(sysroot) = sysroot::current() {
+        let path = sysroot.to_virtual(path)?;
+        if !sysroot.host_path(&path).exists() {
+            return Err(format!("path does not exist: {}", display_path(&path)));
+        }
+        return Ok(path);
+    }
     if !path.exists() {
         return Err(format!("path does not exist: {}", path.display()));
     }
@@ -2342,12 +3500,23 @@ fn normalize_dll_basename(value: &str) -> Option<String> {
 fn is_loader_related_code(code: u32) -> bool {
     matches!(
         code,
-        0xC0000135 | 0xC0000139 | 0xC000007B | 0xC0000142 | 0xC000001D | 0x8007007E | 0x800700C1
+        0xC0000135
+            | 0xC0000138
+            | 0xC0000139
+            | 0xC000007B
+            | 0xC0000142
+            | 0xC000001D
+            | 0x8007007E
+            | 0x800700C1
     )
 }
 
 #[cfg(windows)]
-fn dynamic_trace_search_context(app_dir: &Path, cwd: &Path) -> Result<SearchContext, String> {
+fn dynamic_trace_search_context(
+    exe_path: &Path,
+    cwd: &Path,
+    env_edits: &[EnvEdit],
+) -> Result<SearchContext, String> {
     // Keep this hook scoped to dynamic trace emission so static diagnosis stays
     // on the normal production path.
     if env::var("LOADWHAT_TEST_FORCE_DYNAMIC_SEARCH_CONTEXT_FAIL")
@@ -2357,17 +3526,215 @@ fn dynamic_trace_search_context(app_dir: &Path, cwd: &Path) -> Result<SearchCont
         return Err("forced dynamic search context failure".to_string());
     }
 
-    SearchContext::from_environment(app_dir, cwd, env_path_override(&[]))
+    // `run` only launches x64 targets.
+    let app_dir = exe_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut context = SearchContext::from_environment(
+        app_dir,
+        cwd,
+        env_path_override(env_edits),
+        MachineType::X64,
+    )?;
+    context.redirect_dotlocal(exe_path);
+    Ok(context)
 }
 
-#[cfg(windows)]
-fn env_path_override(_overrides: &[String]) -> Option<OsString> {
-    None
+/// Reads a `--known-dlls` list. It is a host file even under `--sysroot`.
+fn read_known_dlls(path: &Path) -> Result<HashSet<String>, String> {
+    std::fs::read_to_string(path)
+        .map(|text| search::parse_known_dlls(&text))
+        .map_err(|e| format!("failed to read known DLLs list {}: {e}", path.display()))
+}
+
+/// Reads a `--writable` list. The file is on the host; its entries name
+/// directories on the analyzed machine.
+fn read_writable_dirs(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read writable list {}: {e}", path.display()))?;
+    audit::parse_writable_dirs(&text)
+        .into_iter()
+        .map(|dir| match sysroot::current() {
+            Some(sysroot) => sysroot.to_virtual(&dir),
+            None => Ok(dir),
+        })
+        .collect()
+}
+
+/// The PATH a walk searches after `edits`, or `None` when they leave PATH
+/// alone and the machine's own value applies.
+fn env_path_override(edits: &[EnvEdit]) -> Option<OsString> {
+    if !environment::touches_path(edits) {
+        return None;
+    }
+    let mut vars = vec![(
+        OsString::from("PATH"),
+        OsString::from(search::machine_path_env()),
+    )];
+    environment::apply(&mut vars, edits);
+    Some(environment::lookup(&vars, OsStr::new("PATH")).unwrap_or_default())
+}
+
+/// `NOTE topic="env" detail="effective-path"` with the PATH an `--env`,
+/// `--unset` or `--path-*` option produced.
+fn emit_effective_path(path_env: &str) {
+    emit(
+        TOKEN_NOTE,
+        &[
+            field("topic", quote("env")),
+            field("detail", quote("effective-path")),
+            field("value", quote(path_env)),
+        ],
+    );
+}
+
+/// `STATIC_SHADOW` lines for each resolved DLL with more than one copy along
+/// the search order: the copy the loader picks (`role="CHOSEN"`) and every
+/// other one (`role="SHADOWED"`), fingerprinted by size, SHA-256 and file
+/// version. `differs=` flags shadowed copies whose file version or export set
+/// differs from the chosen copy's.
+fn emit_shadow_report(report: &StaticReport, context: &SearchContext) {
+    for (dll, chosen) in &report.found {
+        let copies = search::find_copies(dll, context);
+        if copies.len() < 2 {
+            continue;
+        }
+        let chosen_version = pe::module_version_info(chosen).and_then(|info| info.file_version);
+        let chosen_exports = pe::module_exports(chosen).ok();
+        for copy in &copies {
+            let is_chosen = search::same_path(&copy.path, chosen);
+            let mut fields = vec![
+                field("dll", quote(dll)),
+                field("order", copy.order.to_string()),
+                field("path", quote(&display_path(&copy.path))),
+                field("result", quote(copy.result)),
+                field("role", quote(if is_chosen { "CHOSEN" } else { "SHADOWED" })),
+            ];
+            let file = std::fs::File::open(sysroot::host_path(&copy.path));
+            if let Ok((size, sha256)) = file.and_then(digest::sha256_reader) {
+                fields.push(field("size", size.to_string()));
+                fields.push(field("sha256", quote(&sha256)));
+            }
+            let version = pe::module_version_info(&copy.path).and_then(|info| info.file_version);
+            if let Some(version) = &version {
+                fields.push(field("file_version", quote(version)));
+            }
+            if !is_chosen {
+                let mut differs = Vec::new();
+                if version != chosen_version {
+                    differs.push("VERSION");
+                }
+                if pe::module_exports(&copy.path).ok() != chosen_exports {
+                    differs.push("EXPORTS");
+                }
+                if !differs.is_empty() {
+                    fields.push(field("differs", quote(&differs.join("|"))));
+                }
+            }
+            emit(TOKEN_STATIC_SHADOW, &fields);
+        }
+    }
+}
+
+/// Appends `file_version`, `product_version` and `company` from the image's
+/// version resource; images without one get no extra fields.
+fn push_version_fields(fields: &mut Vec<(String, String)>, path: &Path) {
+    let Some(info) = pe::module_version_info(path) else {
+        return;
+    };
+    if let Some(version) = &info.file_version {
+        fields.push(field("file_version", quote(version)));
+    }
+    if let Some(version) = &info.product_version {
+        fields.push(field("product_version", quote(version)));
+    }
+    if let Some(company) = &info.company {
+        fields.push(field("company", quote(company)));
+    }
+}
+
+/// Appends `signed` and, when the embedded signature names them, `signer` and
+/// `signer_issuer`. Catalog-signed images (most of System32) report
+/// `signed=false` because only the image's own certificate table is read.
+fn push_signature_fields(fields: &mut Vec<(String, String)>, path: &Path) {
+    let Some(signature) = pe::module_signature(path) else {
+        return;
+    };
+    fields.push(field("signed", signature.signed.to_string()));
+    if let Some(subject) = &signature.subject {
+        fields.push(field("signer", quote(subject)));
+    }
+    if let Some(issuer) = &signature.issuer {
+        fields.push(field("signer_issuer", quote(issuer)));
+    }
+}
+
+/// The `SEARCH_ORDER` line; `load=` names the effective LoadLibraryEx
+/// search mode when the load is not a plain `LoadLibrary`, and `dotlocal=`
+/// the active `.local` redirection directory.
+fn emit_search_order(context: &SearchContext) {
+    let mut fields = vec![field("safedll", if context.safedll { "1" } else { "0" })];
+    if !context.load.is_default() {
+        fields.push(field("load", quote(&context.load.describe())));
+    }
+    if let Some(dir) = &context.dotlocal_dir {
+        fields.push(field("dotlocal", quote(&display_path(dir))));
+    }
+    emit(TOKEN_SEARCH_ORDER, &fields);
+}
+
+/// `PATH_ISSUE` lines for everything `pathlint` finds wrong with `path_env`.
+/// Returns how many were emitted.
+fn emit_path_issues(path_env: &str) -> usize {
+    let issues = pathlint::lint_path(path_env);
+    for issue in &issues {
+        match issue {
+            pathlint::PathIssue::Entry {
+                kind,
+                index,
+                entry,
+                first,
+            } => {
+                let mut fields = vec![
+                    field("kind", quote(kind)),
+                    field("index", index.to_string()),
+                    field("entry", quote(entry)),
+                ];
+                if let Some(first) = first {
+                    fields.push(field("first", first.to_string()));
+                }
+                emit(TOKEN_PATH_ISSUE, &fields);
+            }
+            pathlint::PathIssue::TooLong { length, limit } => emit(
+                TOKEN_PATH_ISSUE,
+                &[
+                    field("kind", quote("TOO_LONG")),
+                    field("length", length.to_string()),
+                    field("limit", limit.to_string()),
+                ],
+            ),
+        }
+    }
+    issues.len()
+}
+
+/// One `SEARCH_PATH` line; bad-image candidates carry their defect `reason`.
+fn emit_search_path(dll: &str, order: usize, path: &str, result: &str, reason: Option<&str>) {
+    let mut fields = vec![
+        field("dll", quote(dll)),
+        field("order", order.to_string()),
+        field("path", quote(path)),
+        field("result", quote(result)),
+    ];
+    if let Some(reason) = reason {
+        fields.push(field("reason", quote(reason)));
+    }
+    emit(TOKEN_SEARCH_PATH, &fields);
 }
 
-#[cfg(windows)]
 fn display_path(path: &Path) -> String {
     let raw = path.display().to_string();
+    if sysroot::current().is_some() {
+        return raw.replace('/', "\\");
+    }
     if let Some(rest) = raw.strip_prefix(r"\\?\UNC\") {
         format!(r"\\{rest}")
     } else if let Some(rest) = raw.strip_prefix(r"\\?\") {
@@ -2393,6 +3760,9 @@ mod static_diagnosis_tests {
             dll: dll.to_string(),
             diagnosis: "MISSING_STATIC_IMPORT",
             kind: ResolutionKind::Missing,
+            export: None,
+            forward: None,
+            api_set: None,
             candidates: Vec::new(),
         }
     }
@@ -2440,6 +3810,192 @@ mod static_diagnosis_tests {
         assert_eq!(selected.dll, "missing_x.dll");
     }
 
+    #[test]
+    fn export_field_reports_name_or_ordinal() {
+        assert_eq!(
+            export_field(&pe::ImportedFunction::Name("CreateWidget".to_string())),
+            field("function", quote("CreateWidget"))
+        );
+        assert_eq!(
+            export_field(&pe::ImportedFunction::Ordinal(42)),
+            field("ordinal", "42")
+        );
+    }
+
+    fn forward_test_context(dir: &Path) -> SearchContext {
+        SearchContext {
+            app_dir: dir.to_path_buf(),
+            cwd: dir.to_path_buf(),
+            path_dirs: Vec::new(),
+            path_env: String::new(),
+            safedll: true,
+            system_dir: dir.join("system32"),
+            windows_dir: dir.join("windows"),
+            system16_dir: None,
+            machine: MachineType::X64,
+            load: search::LoadOptions::default(),
+            known_dlls: HashSet::new(),
+            dotlocal_dir: None,
+        }
+    }
+
+    fn bind_in(dir: &Path, function: &str) -> (ExportBinding, Vec<ForwardEdge>) {
+        bind_with_api_sets(dir, None, function)
+    }
+
+    fn bind_with_api_sets(
+        dir: &Path,
+        api_sets: Option<&ApiSetSchema>,
+        function: &str,
+    ) -> (ExportBinding, Vec<ForwardEdge>) {
+        let target = WalkNode {
+            module_path: dir.join("a.dll"),
+            module_name: "a.dll".to_string(),
+            depth: 1,
+            delay: false,
+        };
+        ExportCache::default().bind(
+            &forward_test_context(dir),
+            api_sets,
+            &target,
+            pe::ImportedFunction::Name(function.to_string()),
+        )
+    }
+
+    fn write_forwarding_dll(dir: &Path) {
+        let bytes = pe::testpe::build_test_pe_with_forwarded_exports(
+            &[("Plain", None), ("Foo", Some("B.Bar"))],
+            1,
+        );
+        fs::write(dir.join("a.dll"), bytes).expect("failed to write a.dll");
+    }
+
+    #[test]
+    fn bind_follows_forwarder_into_target_dll() {
+        let dir = unique_temp_dir("forward-bound");
+        write_forwarding_dll(&dir);
+        fs::write(
+            dir.join("b.dll"),
+            pe::testpe::build_test_pe_with_exports(&["Bar"], 1),
+        )
+        .expect("failed to write b.dll");
+
+        let (binding, edges) = bind_in(&dir, "Foo");
+        assert!(matches!(binding, ExportBinding::Bound));
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].from.module_name, "a.dll");
+        assert_eq!(edges[0].dll, "b.dll");
+        assert!(matches!(edges[0].resolution.kind, ResolutionKind::Found));
+        assert_eq!(edges[0].from.depth, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn bind_reports_forward_to_missing_dll() {
+        let dir = unique_temp_dir("forward-missing-dll");
+        write_forwarding_dll(&dir);
+
+        let (binding, edges) = bind_in(&dir, "Foo");
+        let ExportBinding::BrokenForward {
+            dll,
+            export,
+            forward,
+        } = binding
+        else {
+            panic!("expected a broken forward");
+        };
+        assert_eq!(dll, "a.dll");
+        assert_eq!(export, pe::ImportedFunction::Name("Foo".to_string()));
+        assert_eq!(forward.forward, "B.Bar");
+        assert_eq!(forward.reason, "NOT_FOUND");
+        assert_eq!(edges.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn bind_reports_forward_to_missing_export() {
+        let dir = unique_temp_dir("forward-missing-export");
+        write_forwarding_dll(&dir);
+        fs::write(
+            dir.join("b.dll"),
+            pe::testpe::build_test_pe_with_exports(&["Other"], 1),
+        )
+        .expect("failed to write b.dll");
+
+        let (binding, _) = bind_in(&dir, "Foo");
+        let ExportBinding::BrokenForward { dll, forward, .. } = binding else {
+            panic!("expected a broken forward");
+        };
+        assert_eq!(dll, "a.dll");
+        assert_eq!(forward.reason, "MISSING_EXPORT");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn write_api_set_forwarding_dll(dir: &Path) {
+        let bytes = pe::testpe::build_test_pe_with_forwarded_exports(
+            &[
+                ("Hosted", Some("api-ms-win-lwtest-hosted-l1-1-0.Bar")),
+                ("Hostless", Some("api-ms-win-lwtest-hostless-l1-1-0.Bar")),
+            ],
+            1,
+        );
+        fs::write(dir.join("a.dll"), bytes).expect("failed to write a.dll");
+    }
+
+    fn lwtest_api_sets() -> ApiSetSchema {
+        ApiSetSchema::from_section_bytes(&apiset::testschema::build_v6(&[
+            ("api-ms-win-lwtest-hosted-l1-1-0", &[("", "b.dll")]),
+            ("api-ms-win-lwtest-hostless-l1-1-0", &[("", "")]),
+        ]))
+        .expect("failed to parse test schema")
+    }
+
+    #[test]
+    fn bind_follows_api_set_forward_into_host() {
+        let dir = unique_temp_dir("forward-api-set-host");
+        write_api_set_forwarding_dll(&dir);
+        fs::write(
+            dir.join("b.dll"),
+            pe::testpe::build_test_pe_with_exports(&["Bar"], 1),
+        )
+        .expect("failed to write b.dll");
+        let api_sets = lwtest_api_sets();
+
+        let (binding, edges) = bind_with_api_sets(&dir, Some(&api_sets), "Hosted");
+        assert!(matches!(binding, ExportBinding::Bound));
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].dll, "b.dll");
+
+        let (binding, _) = bind_with_api_sets(&dir, Some(&api_sets), "Hostless");
+        let ExportBinding::BrokenForward { forward, .. } = binding else {
+            panic!("expected a broken forward");
+        };
+        assert_eq!(forward.reason, "NO_HOST");
+
+        let (binding, edges) = bind_with_api_sets(&dir, None, "Hostless");
+        assert!(matches!(binding, ExportBinding::Bound));
+        assert!(edges.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn bind_distinguishes_plain_and_missing_exports() {
+        let dir = unique_temp_dir("forward-plain");
+        write_forwarding_dll(&dir);
+
+        let (plain, plain_edges) = bind_in(&dir, "Plain");
+        assert!(matches!(plain, ExportBinding::Bound));
+        assert!(plain_edges.is_empty());
+        let (missing, _) = bind_in(&dir, "Absent");
+        assert!(matches!(missing, ExportBinding::Missing));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn visited_set_dedups_normalized_absolute_paths() {
         let temp_dir = unique_temp_dir("visited-set");
@@ -2454,13 +4010,15 @@ mod static_diagnosis_tests {
             &mut visited,
             &mut queue,
             &module_path,
-            1
+            1,
+            false
         ));
         assert!(!queue_module_if_unvisited(
             &mut visited,
             &mut queue,
             &duplicate_path,
-            1
+            1,
+            false
         ));
         assert_eq!(queue.len(), 1);
 
@@ -2484,7 +4042,8 @@ mod static_diagnosis_tests {
             &mut visited,
             &mut queue,
             &module_path,
-            4
+            4,
+            false
         ));
         assert_eq!(visited.len(), 1);
         assert_eq!(queue.len(), 1);
@@ -2509,19 +4068,75 @@ mod static_diagnosis_tests {
             &mut visited,
             &mut queue,
             &module_path,
-            1
+            1,
+            false
         ));
         assert!(!queue_module_if_unvisited(
             &mut visited,
             &mut queue,
             &module_path,
-            2
+            2,
+            false
         ));
         assert_eq!(queue.len(), 1);
 
         let _ = fs::remove_dir_all(temp_dir);
     }
 
+    #[test]
+    fn next_delay_node_skips_modules_already_walked_statically() {
+        let temp_dir = unique_temp_dir("delay-queue");
+        let shared = temp_dir.join("shared.dll");
+        let optional = temp_dir.join("optional.dll");
+        fs::write(&shared, b"fixture").expect("failed to create temp module");
+        fs::write(&optional, b"fixture").expect("failed to create temp module");
+
+        let root = WalkNode {
+            module_path: temp_dir.join("host.exe"),
+            module_name: "host.exe".to_string(),
+            depth: 0,
+            delay: false,
+        };
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        let mut delay_queue = VecDeque::new();
+
+        queue_dependency(
+            &mut visited,
+            &mut queue,
+            &mut delay_queue,
+            &root,
+            true,
+            &shared,
+        );
+        queue_dependency(
+            &mut visited,
+            &mut queue,
+            &mut delay_queue,
+            &root,
+            true,
+            &optional,
+        );
+        queue_dependency(
+            &mut visited,
+            &mut queue,
+            &mut delay_queue,
+            &root,
+            false,
+            &shared,
+        );
+        assert_eq!(queue.len(), 1);
+        assert!(!queue[0].delay);
+        assert_eq!(delay_queue.len(), 2);
+
+        let next = next_delay_node(&mut visited, &mut delay_queue).expect("expected delay node");
+        assert_eq!(next.module_name, "optional.dll");
+        assert!(next.delay);
+        assert!(next_delay_node(&mut visited, &mut delay_queue).is_none());
+
+        let _ = fs::remove_dir_all(temp_dir);
+    }
+
     #[test]
     fn is_api_set_dll_accepts_api_ms_prefix() {
         assert!(is_api_set_dll("api-ms-win-core-file-l1-2-0.dll"));
@@ -3154,3 +4769,343 @@ mod dynamic_missing_tests {
         ));
     }
 }
+
+#[cfg(test)]
+mod sysroot_tests {
+    use super::*;
+    use crate::emit::OutputFormat;
+    use crate::pe::testpe::build_test_pe;
+    use std::fs;
+
+    #[test]
+    fn static_imports_resolve_against_the_mounted_volume() {
+        let root =
+            std::env::temp_dir().join(format!("loadwhat-sysroot-imports-{}", std::process::id()));
+        let system32 = root.join("Windows").join("System32");
+        let app_dir = root.join("App");
+        fs::create_dir_all(&system32).expect("failed to create System32");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(system32.join("kernel32.dll"), build_test_pe(&[]).bytes)
+            .expect("failed to write kernel32.dll");
+        fs::write(
+            app_dir.join("app.exe"),
+            build_test_pe(&["KERNEL32.dll", "missing.dll"]).bytes,
+        )
+        .expect("failed to write app.exe");
+
+        let report = {
+            let _scope = SysrootScope::enter(Sysroot::open(&root).expect("sysroot should open"));
+            let module = normalize_existing_path(&app_dir.join("app.exe"))
+                .expect("module should map into the sysroot");
+            assert_eq!(display_path(&module), r"C:\App\app.exe");
+            let cwd = module.parent().expect("module has a parent").to_path_buf();
+            let report = diagnose_static_imports(
+                &module,
+                &cwd,
+                &HashSet::new(),
+                &HashMap::new(),
+                None,
+                StaticEmitMode::CollectOnly,
+            )
+            .expect("walk should succeed");
+            let candidates: Vec<String> = report.failures[0]
+                .candidates
+                .iter()
+                .map(|candidate| display_path(&candidate.path))
+                .collect();
+            assert!(
+                candidates.contains(&r"C:\Windows\System32\missing.dll".to_string()),
+                "{candidates:?}"
+            );
+            let found: Vec<(&str, String)> = report
+                .found
+                .iter()
+                .map(|(dll, path)| (dll.as_str(), display_path(path)))
+                .collect();
+            assert_eq!(
+                found,
+                [(
+                    "kernel32.dll",
+                    r"C:\Windows\System32\kernel32.dll".to_string()
+                )]
+            );
+            report
+        };
+        let _ = fs::remove_dir_all(root);
+
+        assert_eq!(report.missing_count, 1);
+        assert_eq!(report.bad_image_count, 0);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].dll, "missing.dll");
+    }
+
+    #[test]
+    fn tree_mode_records_each_import_edge() {
+        let root =
+            std::env::temp_dir().join(format!("loadwhat-sysroot-tree-{}", std::process::id()));
+        let system32 = root.join("Windows").join("System32");
+        let app_dir = root.join("App");
+        fs::create_dir_all(&system32).expect("failed to create System32");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(system32.join("kernel32.dll"), build_test_pe(&[]).bytes)
+            .expect("failed to write kernel32.dll");
+        fs::write(
+            app_dir.join("core.dll"),
+            build_test_pe(&["KERNEL32.dll"]).bytes,
+        )
+        .expect("failed to write core.dll");
+        fs::write(
+            app_dir.join("app.exe"),
+            build_test_pe(&["core.dll", "missing.dll"]).bytes,
+        )
+        .expect("failed to write app.exe");
+
+        let edges = {
+            let _scope = SysrootScope::enter(Sysroot::open(&root).expect("sysroot should open"));
+            let module = PathBuf::from("C:/App/app.exe");
+            let report = diagnose_static_imports(
+                &module,
+                Path::new("C:/App"),
+                &HashSet::new(),
+                &HashMap::new(),
+                None,
+                StaticEmitMode::Tree,
+            )
+            .expect("walk should succeed");
+            report
+                .tree
+                .into_iter()
+                .map(|edge| (edge.dll, edge.status, edge.path, edge.system))
+                .collect::<Vec<_>>()
+        };
+        let _ = fs::remove_dir_all(root);
+
+        assert_eq!(
+            edges,
+            [
+                (
+                    "core.dll".to_string(),
+                    TreeStatus::Found,
+                    Some(r"C:\App\core.dll".to_string()),
+                    false
+                ),
+                (
+                    "missing.dll".to_string(),
+                    TreeStatus::Missing("NOT_FOUND"),
+                    None,
+                    false
+                ),
+                (
+                    "kernel32.dll".to_string(),
+                    TreeStatus::Found,
+                    Some(r"C:\Windows\System32\kernel32.dll".to_string()),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_searches_user_dirs_only_when_flagged() {
+        let root =
+            std::env::temp_dir().join(format!("loadwhat-sysroot-resolve-{}", std::process::id()));
+        let app_dir = root.join("App");
+        let plugins = root.join("Plugins");
+        fs::create_dir_all(root.join("Windows").join("System32"))
+            .expect("failed to create System32");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&plugins).expect("failed to create plugin dir");
+        fs::write(app_dir.join("host.exe"), build_test_pe(&[]).bytes)
+            .expect("failed to write host.exe");
+        fs::write(plugins.join("plugin.dll"), build_test_pe(&[]).bytes)
+            .expect("failed to write plugin.dll");
+
+        let options = |flags: &str| ResolveOptions {
+            dll: "plugin.dll".to_string(),
+            from: PathBuf::from(r"C:\App\host.exe"),
+            cwd: None,
+            sysroot: Some(root.clone()),
+            format: OutputFormat::Text,
+            load: LoadOptions {
+                flags: LoadOptions::parse_flags(flags).expect("flags should parse"),
+                user_dirs: vec![PathBuf::from(r"C:\Plugins")],
+                ..LoadOptions::default()
+            },
+            known_dlls: None,
+        };
+        let standard = resolve_command(options("0x0"));
+        let user_dirs = resolve_command(options("SEARCH_APPLICATION_DIR|SEARCH_USER_DIRS"));
+        let _ = fs::remove_dir_all(root);
+
+        assert_eq!(standard, 10);
+        assert_eq!(user_dirs, 0);
+    }
+
+    #[test]
+    fn audit_search_exits_10_only_when_something_can_be_planted() {
+        let root =
+            std::env::temp_dir().join(format!("loadwhat-sysroot-audit-{}", std::process::id()));
+        let system32 = root.join("Windows").join("System32");
+        let app_dir = root.join("App");
+        fs::create_dir_all(&system32).expect("failed to create System32");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(system32.join("kernel32.dll"), build_test_pe(&[]).bytes)
+            .expect("failed to write kernel32.dll");
+        fs::write(
+            app_dir.join("clean.exe"),
+            build_test_pe(&["KERNEL32.dll"]).bytes,
+        )
+        .expect("failed to write clean.exe");
+        fs::write(
+            app_dir.join("phantom.exe"),
+            build_test_pe(&["KERNEL32.dll", "phantom.dll"]).bytes,
+        )
+        .expect("failed to write phantom.exe");
+        let writable = root.join("writable.txt");
+        fs::write(&writable, "C:\\App\n").expect("failed to write writable list");
+
+        let audit = |exe: &str, writable: Option<PathBuf>| {
+            audit_search_command(AuditSearchOptions {
+                module_path: PathBuf::from(format!(r"C:\App\{exe}")),
+                cwd: None,
+                sysroot: Some(root.clone()),
+                format: OutputFormat::Text,
+                writable,
+            })
+        };
+        let clean = audit("clean.exe", None);
+        let phantom = audit("phantom.exe", None);
+        let writable_app_dir = audit("clean.exe", Some(writable.clone()));
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(clean, 0);
+        assert_eq!(phantom, 10);
+        assert_eq!(writable_app_dir, 10);
+    }
+
+    #[test]
+    fn imports_searches_the_edited_path() {
+        let root =
+            std::env::temp_dir().join(format!("loadwhat-sysroot-env-{}", std::process::id()));
+        let app_dir = root.join("App");
+        let vendor = root.join("Vendor");
+        fs::create_dir_all(root.join("Windows").join("System32"))
+            .expect("failed to create System32");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&vendor).expect("failed to create vendor dir");
+        fs::write(
+            app_dir.join("app.exe"),
+            build_test_pe(&["vendor.dll"]).bytes,
+        )
+        .expect("failed to write app.exe");
+        fs::write(vendor.join("vendor.dll"), build_test_pe(&[]).bytes)
+            .expect("failed to write vendor.dll");
+
+        let imports = |env: Vec<EnvEdit>| {
+            imports_command(ImportsOptions {
+                module_path: PathBuf::from(r"C:\App\app.exe"),
+                cwd: None,
+                sysroot: Some(root.clone()),
+                format: OutputFormat::Text,
+                from: None,
+                load: LoadOptions::default(),
+                known_dlls: None,
+                shadows: false,
+                env,
+                tree: None,
+                graph: None,
+                report_junit: None,
+            })
+        };
+        let machine_path = imports(Vec::new());
+        let prepended = imports(vec![EnvEdit::PathPrepend(OsString::from(r"C:\Vendor"))]);
+        let replaced = imports(vec![EnvEdit::Set(
+            OsString::from("Path"),
+            OsString::from(r"C:\Vendor"),
+        )]);
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(machine_path, 10);
+        assert_eq!(prepended, 0);
+        assert_eq!(replaced, 0);
+    }
+
+    #[test]
+    fn junit_report_has_a_testcase_per_target_and_import_edge() {
+        let root =
+            std::env::temp_dir().join(format!("loadwhat-sysroot-junit-{}", std::process::id()));
+        let app_dir = root.join("App");
+        fs::create_dir_all(root.join("Windows").join("System32"))
+            .expect("failed to create System32");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(app_dir.join("app.exe"), build_test_pe(&["core.dll"]).bytes)
+            .expect("failed to write app.exe");
+        fs::write(app_dir.join("core.dll"), build_test_pe(&["gone.dll"]).bytes)
+            .expect("failed to write core.dll");
+        let file = root.join("report.xml");
+
+        let report = junit::JunitReport {
+            file: file.clone(),
+            command: "imports",
+            target: r"C:\App\app.exe".to_string(),
+            edges: true,
+        };
+        let code = with_junit_report(Some(report), || {
+            imports_command(ImportsOptions {
+                module_path: PathBuf::from(r"C:\App\app.exe"),
+                cwd: None,
+                sysroot: Some(root.clone()),
+                format: OutputFormat::Text,
+                from: None,
+                load: LoadOptions::default(),
+                known_dlls: None,
+                shadows: false,
+                env: Vec::new(),
+                tree: None,
+                graph: None,
+                report_junit: None,
+            })
+        });
+        let xml = fs::read_to_string(&file).expect("report should be written");
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(code, 10);
+        assert!(xml
+            .contains(r#"<testsuite name="loadwhat imports" tests="3" failures="2" errors="0">"#));
+        assert!(xml.contains(r#"<testcase classname="app.exe" name="core.dll"/>"#));
+        assert!(xml.contains(r#"<testcase classname="core.dll" name="gone.dll">"#));
+        assert!(xml.contains(r#"<property name="static_missing" value="1"/>"#));
+    }
+
+    #[test]
+    fn path_lint_reads_the_sysroot_path() {
+        use crate::hive::testhive::{build_hive, utf16z, KeySpec};
+
+        let lint = |path: &str| {
+            let root = std::env::temp_dir().join(format!(
+                "loadwhat-sysroot-path-lint-{}-{}",
+                std::process::id(),
+                path.len()
+            ));
+            let config = root.join("Windows").join("System32").join("config");
+            fs::create_dir_all(&config).expect("failed to create config dir");
+            let system = KeySpec::new("ROOT")
+                .at("Select", |key| key.dword("Current", 1))
+                .at(
+                    r"ControlSet001\Control\Session Manager\Environment",
+                    |key| key.value("Path", hive::REG_EXPAND_SZ, utf16z(path)),
+                );
+            fs::write(config.join("SYSTEM"), build_hive(&system)).expect("failed to write SYSTEM");
+            let code = path_lint_command(PathLintOptions {
+                sysroot: Some(root.clone()),
+                format: OutputFormat::Text,
+            });
+            let _ = fs::remove_dir_all(&root);
+            code
+        };
+
+        assert_eq!(lint(r"%SystemRoot%\System32;C:\Windows"), 0);
+        assert_eq!(lint(r"%SystemRoot%\System32;C:\Tools\bin"), 10);
+        assert_eq!(lint(r"C:\Windows\System32;%TOOLS%\bin"), 10);
+    }
+}
@@ -0,0 +1,220 @@
+// Lints a Windows PATH value for entries the loader cannot use or that slow every search.
+
+use std::path::{Path, PathBuf};
+
+use crate::search;
+use crate::sysroot;
+
+/// Longest PATH the System Properties editor and `setx` accept.
+pub const PATH_EDITOR_LIMIT: usize = 2047;
+/// Longest value an environment variable can hold.
+pub const PATH_ENV_LIMIT: usize = 32767;
+
+pub enum PathIssue {
+    /// A problem with one `;`-separated entry. `index` is 1-based and
+    /// counts empty entries, so it matches the entry's position in PATH.
+    Entry {
+        kind: &'static str,
+        index: usize,
+        entry: String,
+        /// Index of the earlier entry a `DUPLICATE` repeats.
+        first: Option<usize>,
+    },
+    /// The whole value is longer than `limit` UTF-16 units.
+    TooLong { length: usize, limit: usize },
+}
+
+/// Every issue in `path_env`, the value-wide one first, then entries in
+/// order. An entry can have several issues; `NOT_FOUND`/`NOT_DIRECTORY`
+/// are only checked for entries that are otherwise usable local paths, so
+/// a network share is never touched.
+pub fn lint_path(path_env: &str) -> Vec<PathIssue> {
+    let mut issues = Vec::new();
+    let length = path_env.encode_utf16().count();
+    if length > PATH_ENV_LIMIT {
+        issues.push(PathIssue::TooLong {
+            length,
+            limit: PATH_ENV_LIMIT,
+        });
+    } else if length > PATH_EDITOR_LIMIT {
+        issues.push(PathIssue::TooLong {
+            length,
+            limit: PATH_EDITOR_LIMIT,
+        });
+    }
+
+    let mut seen: Vec<(String, usize)> = Vec::new();
+    for (idx, entry) in path_env.split(';').enumerate() {
+        let index = idx + 1;
+        let trimmed = entry.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let mut push = |kind: &'static str, first: Option<usize>| {
+            issues.push(PathIssue::Entry {
+                kind,
+                index,
+                entry: entry.to_string(),
+                first,
+            })
+        };
+
+        let unexpanded = has_unexpanded_var(trimmed);
+        let quoted = trimmed.contains('"');
+        let unc = is_unc(trimmed);
+        let relative = !unc && !search::is_full_path(Path::new(trimmed));
+        if unexpanded {
+            push("UNEXPANDED_VAR", None);
+        }
+        if quoted {
+            push("QUOTED", None);
+        }
+        if trimmed.len() != entry.len() {
+            push("WHITESPACE", None);
+        }
+        if unc {
+            push("UNC", None);
+        }
+        if relative && !unexpanded {
+            push("RELATIVE", None);
+        }
+
+        let key = normalize(trimmed);
+        match seen.iter().find(|(seen_key, _)| *seen_key == key) {
+            Some((_, first)) => push("DUPLICATE", Some(*first)),
+            None => seen.push((key, index)),
+        }
+
+        if !(unexpanded || quoted || unc || relative) {
+            let host = sysroot::host_path(&entry_path(trimmed));
+            if !host.exists() {
+                push("NOT_FOUND", None);
+            } else if !host.is_dir() {
+                push("NOT_DIRECTORY", None);
+            }
+        }
+    }
+    issues
+}
+
+/// `%NAME%` with a non-empty name that is not itself a path fragment.
+fn has_unexpanded_var(entry: &str) -> bool {
+    let mut rest = entry;
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(0) => rest = &after[1..],
+            Some(end) => {
+                if !after[..end].contains(['\\', '/']) {
+                    return true;
+                }
+                rest = &after[end..];
+            }
+            None => return false,
+        }
+    }
+    false
+}
+
+/// A `\\server\share` path, including the `\\?\UNC\` form; device paths
+/// (`\\?\C:\`, `\\.\`) are local.
+fn is_unc(entry: &str) -> bool {
+    let entry = entry.replace('/', "\\");
+    if let Some(rest) = entry.strip_prefix(r"\\?\") {
+        return rest.len() >= 4 && rest[..4].eq_ignore_ascii_case(r"UNC\");
+    }
+    entry.starts_with(r"\\") && !entry.starts_with(r"\\.\")
+}
+
+/// The entry as a path on the analyzed machine: a sysroot's `C:/...` form,
+/// or the host path as written.
+fn entry_path(entry: &str) -> PathBuf {
+    if sysroot::current().is_some() {
+        PathBuf::from(entry.replace('\\', "/"))
+    } else {
+        PathBuf::from(entry)
+    }
+}
+
+fn normalize(entry: &str) -> String {
+    entry
+        .replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint_path, PathIssue, PATH_EDITOR_LIMIT};
+    use std::fs;
+
+    fn entry_issues(path_env: &str) -> Vec<(&'static str, usize, Option<usize>)> {
+        lint_path(path_env)
+            .into_iter()
+            .filter_map(|issue| match issue {
+                PathIssue::Entry {
+                    kind, index, first, ..
+                } => Some((kind, index, first)),
+                PathIssue::TooLong { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flags_malformed_entries() {
+        let issues = entry_issues(
+            r#"%JAVA_HOME%\bin;"C:\Program Files\Tool";bin; C:\Padded ;\\server\share\bin;;%%"#,
+        );
+        assert_eq!(
+            issues,
+            [
+                ("UNEXPANDED_VAR", 1, None),
+                ("QUOTED", 2, None),
+                ("RELATIVE", 2, None),
+                ("RELATIVE", 3, None),
+                ("WHITESPACE", 4, None),
+                ("NOT_FOUND", 4, None),
+                ("UNC", 5, None),
+                ("RELATIVE", 7, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_missing_and_duplicate_directories() {
+        let temp = std::env::temp_dir().join(format!("loadwhat-pathlint-{}", std::process::id()));
+        let dir = temp.join("bin");
+        fs::create_dir_all(&dir).expect("failed to create dir");
+        let file = temp.join("tool.exe");
+        fs::write(&file, b"MZ").expect("failed to write file");
+        let dir = dir.display().to_string();
+        let path_env = format!(
+            "{dir};{};{}/;{}",
+            file.display(),
+            dir.to_uppercase(),
+            temp.join("gone").display()
+        );
+        let issues = entry_issues(&path_env);
+        let _ = fs::remove_dir_all(&temp);
+
+        // Case-insensitive matching means the uppercased copy may not exist
+        // on a case-sensitive host; only the duplicate is asserted for it.
+        assert!(issues.contains(&("NOT_DIRECTORY", 2, None)));
+        assert!(issues.contains(&("DUPLICATE", 3, Some(1))));
+        assert!(issues.contains(&("NOT_FOUND", 4, None)));
+        assert!(!issues.iter().any(|(_, index, _)| *index == 1));
+    }
+
+    #[test]
+    fn reports_length_limits() {
+        let long = format!("C:\\{}", "a".repeat(PATH_EDITOR_LIMIT));
+        let limits: Vec<(usize, usize)> = lint_path(&long)
+            .into_iter()
+            .filter_map(|issue| match issue {
+                PathIssue::TooLong { length, limit } => Some((length, limit)),
+                PathIssue::Entry { .. } => None,
+            })
+            .collect();
+        assert_eq!(limits, [(PATH_EDITOR_LIMIT + 3, PATH_EDITOR_LIMIT)]);
+    }
+}
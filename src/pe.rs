@@ -1,22 +1,32 @@
 // Parses PE import tables and performs lightweight PE validity checks for search results.
 
-use std::collections::BTreeSet;
-use std::fs;
-use std::path::Path;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 #[derive(Clone, Copy)]
 struct Section {
+    name: [u8; 8],
     virtual_address: u32,
     virtual_size: u32,
     raw_data_ptr: u32,
     raw_data_size: u32,
 }
 
-/// Machine type of a PE image, derived from the COFF header `Machine` field.
+/// Machine type of a PE image, derived from the COFF header `Machine` field
+/// and, for hybrid images, the CHPE metadata in the load config directory.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MachineType {
     X64,
     X86,
+    Arm64,
+    /// x64-ABI-compatible ARM64 code: an AMD64 header plus CHPE metadata.
+    Arm64ec,
+    /// Hybrid ARM64 image carrying both a native and an ARM64EC view.
+    Arm64x,
     Unknown,
 }
 
@@ -25,45 +35,376 @@ impl MachineType {
         match self {
             MachineType::X64 => "x64",
             MachineType::X86 => "x86",
+            MachineType::Arm64 => "arm64",
+            MachineType::Arm64ec => "arm64ec",
+            MachineType::Arm64x => "arm64x",
             MachineType::Unknown => "unknown",
         }
     }
+
+    /// Architecture of the process an executable of this type starts. An
+    /// ARM64X executable runs its native view.
+    pub fn process_machine(self) -> MachineType {
+        match self {
+            MachineType::Arm64x => MachineType::Arm64,
+            other => other,
+        }
+    }
+
+    /// Whether a process of this architecture can map an image of
+    /// `image`'s architecture. ARM64EC processes (which is how x64 code runs
+    /// on ARM64 Windows) accept x64, ARM64EC and ARM64X images; native ARM64
+    /// processes accept ARM64 and ARM64X. An unknown process accepts
+    /// anything, so callers that cannot tell do not invent mismatches.
+    pub fn can_load(self, image: MachineType) -> bool {
+        match self.process_machine() {
+            MachineType::Unknown => true,
+            MachineType::X86 => image == MachineType::X86,
+            MachineType::X64 | MachineType::Arm64ec => matches!(
+                image,
+                MachineType::X64 | MachineType::Arm64ec | MachineType::Arm64x
+            ),
+            MachineType::Arm64 | MachineType::Arm64x => {
+                matches!(image, MachineType::Arm64 | MachineType::Arm64x)
+            }
+        }
+    }
 }
 
-pub fn direct_imports(module_path: &Path) -> Result<Vec<String>, String> {
-    let data = fs::read(module_path)
-        .map_err(|e| format!("failed to read {}: {e}", module_path.display()))?;
-    direct_imports_from_bytes(&data)
+/// One DLL named by a module's import tables. `delay` marks entries that
+/// appear only in the delay-load import table (data directory 13); the
+/// loader resolves those on first call rather than at module load.
+/// `functions` lists what the regular import table binds from the DLL; it is
+/// empty for delay-only imports and when the thunk array cannot be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportedDll {
+    pub name: String,
+    pub delay: bool,
+    pub functions: Vec<ImportedFunction>,
 }
 
-pub fn is_probably_pe_file(module_path: &Path) -> bool {
-    let Ok(data) = fs::read(module_path) else {
-        return false;
-    };
-    parse_pe_layout(&data).is_ok()
+/// One function a module imports from a DLL, by name or by ordinal.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImportedFunction {
+    Name(String),
+    Ordinal(u16),
+}
+
+/// Exported names and ordinals of a DLL.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportTable {
+    names: BTreeSet<String>,
+    ordinals: BTreeSet<u32>,
+    forwards: BTreeMap<ImportedFunction, ExportForward>,
+}
+
+/// Target of a forwarded export, parsed from an `"OTHERDLL.Function"` or
+/// `"OTHERDLL.#ordinal"` forwarder string. Binding the export makes the
+/// loader load `dll` and bind `function` there instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportForward {
+    pub raw: String,
+    pub dll: String,
+    pub function: ImportedFunction,
+}
+
+impl ExportTable {
+    /// Whether the loader can bind `function` against this table. Name
+    /// lookups are case-sensitive, matching `GetProcAddress`.
+    pub fn provides(&self, function: &ImportedFunction) -> bool {
+        match function {
+            ImportedFunction::Name(name) => self.names.contains(name),
+            ImportedFunction::Ordinal(ordinal) => self.ordinals.contains(&(*ordinal as u32)),
+        }
+    }
+
+    /// The forwarder behind `function`, when the export is forwarded.
+    pub fn forward_for(&self, function: &ImportedFunction) -> Option<&ExportForward> {
+        self.forwards.get(function)
+    }
+}
+
+/// Regular and delay-load imports of a module. Regular imports come first
+/// in lexicographic order, followed by delay-only imports in the same order;
+/// a DLL named by both tables is reported once as a regular import.
+pub fn module_dependencies(module_path: &Path) -> Result<Vec<ImportedDll>, String> {
+    open_image(module_path)?.dependencies().map(<[_]>::to_vec)
+}
+
+pub fn module_exports(module_path: &Path) -> Result<ExportTable, String> {
+    open_image(module_path)?.exports().cloned()
+}
+
+/// Machine type of a PE file on disk, or None when it is unreadable or not a
+/// valid image.
+pub fn module_machine_type(module_path: &Path) -> Option<MachineType> {
+    open_image(module_path).ok()?.machine()
 }
 
 pub fn machine_type_from_bytes(data: &[u8]) -> Result<MachineType, String> {
     let pe = parse_pe_layout(data)?;
-    Ok(match pe.machine {
+    Ok(machine_from_layout(&pe, || has_chpe_metadata(data, &pe)))
+}
+
+/// Maps the COFF machine to a `MachineType`; `chpe` is only consulted for
+/// the AMD64 and ARM64 headers that hybrid images share.
+fn machine_from_layout(pe: &PeLayout, chpe: impl Fn() -> bool) -> MachineType {
+    match pe.machine {
+        0x8664 if chpe() => MachineType::Arm64ec,
         0x8664 => MachineType::X64,
         0x014C => MachineType::X86,
+        0xAA64 if chpe() => MachineType::Arm64x,
+        0xAA64 => MachineType::Arm64,
+        0xA641 => MachineType::Arm64ec,
         _ => MachineType::Unknown,
-    })
+    }
+}
+
+/// Why a file cannot be mapped as an image. The tokens are the stable
+/// `reason=` values on `STATIC_BAD_IMAGE` and bad-image `SEARCH_PATH` lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageDefect {
+    /// The file exists but could not be read.
+    Unreadable,
+    ZeroLength,
+    /// Headers or section data run past the end of the file, as after an
+    /// interrupted copy.
+    Truncated,
+    /// No MZ/PE signature: not an image at all.
+    NotPe,
+    /// Unsupported optional header, missing data directories, or
+    /// SizeOfHeaders/SizeOfImage that contradict the section table.
+    CorruptHeaders,
+    /// Sections overlap, are out of order, or extend past SizeOfImage.
+    CorruptSections,
+    /// PE32 optional header on a 64-bit machine, or PE32+ on x86.
+    HeaderMachineMismatch,
+    /// A well-formed image built for a machine the process cannot load.
+    WrongMachine,
+}
+
+impl ImageDefect {
+    pub fn as_token(self) -> &'static str {
+        match self {
+            ImageDefect::Unreadable => "UNREADABLE",
+            ImageDefect::ZeroLength => "ZERO_LENGTH",
+            ImageDefect::Truncated => "TRUNCATED",
+            ImageDefect::NotPe => "NOT_PE",
+            ImageDefect::CorruptHeaders => "CORRUPT_HEADERS",
+            ImageDefect::CorruptSections => "CORRUPT_SECTIONS",
+            ImageDefect::HeaderMachineMismatch => "HEADER_MACHINE_MISMATCH",
+            ImageDefect::WrongMachine => "WRONG_MACHINE",
+        }
+    }
+}
+
+/// Structural validation of a PE file on disk, stricter than the lenient
+/// layout parse used for import walking: it checks what the loader checks
+/// before mapping. Returns the machine type of an acceptable image.
+pub fn validate_image(module_path: &Path) -> Result<MachineType, ImageDefect> {
+    open_image(module_path)
+        .map_err(|_| ImageDefect::Unreadable)?
+        .validate()
+}
+
+#[cfg(test)]
+pub(crate) fn validate_image_bytes(data: &[u8]) -> Result<MachineType, ImageDefect> {
+    validate_headers(data, data.len())?;
+    machine_type_from_bytes(data).map_err(|_| ImageDefect::CorruptHeaders)
+}
+
+/// The structural checks of `validate_image`, which need only the header
+/// bytes (through the section table) and the file length.
+fn validate_headers(data: &[u8], file_len: usize) -> Result<(), ImageDefect> {
+    if file_len == 0 {
+        return Err(ImageDefect::ZeroLength);
+    }
+    if data.len() < 2 || &data[0..2] != b"MZ" {
+        return Err(ImageDefect::NotPe);
+    }
+    let read16 = |offset: usize| read_u16(data, offset).map_err(|_| ImageDefect::Truncated);
+    let read32 = |offset: usize| read_u32(data, offset).map_err(|_| ImageDefect::Truncated);
+
+    let pe_offset = read32(0x3C)? as usize;
+    let signature = pe_offset
+        .checked_add(4)
+        .and_then(|end| data.get(pe_offset..end))
+        .ok_or(ImageDefect::Truncated)?;
+    if signature != b"PE\0\0" {
+        return Err(ImageDefect::NotPe);
+    }
+
+    let machine = read16(pe_offset + 4)?;
+    let number_of_sections = read16(pe_offset + 6)? as usize;
+    let size_of_optional_header = read16(pe_offset + 20)? as usize;
+    let optional_header_off = pe_offset + 24;
+    let section_table_off = optional_header_off + size_of_optional_header;
+    let section_table_end = section_table_off + number_of_sections * 40;
+    if section_table_end > file_len {
+        return Err(ImageDefect::Truncated);
+    }
+
+    if size_of_optional_header < 64 {
+        return Err(ImageDefect::CorruptHeaders);
+    }
+    let magic = read16(optional_header_off)?;
+    let data_dir_start = match magic {
+        0x010B => optional_header_off + 96,
+        0x020B => optional_header_off + 112,
+        _ => return Err(ImageDefect::CorruptHeaders),
+    };
+    if data_dir_start + 16 > section_table_off {
+        return Err(ImageDefect::CorruptHeaders);
+    }
+    let mismatch = match machine {
+        0x014C => magic != 0x010B,
+        0x8664 | 0xAA64 | 0xA641 => magic != 0x020B,
+        _ => false,
+    };
+    if mismatch {
+        return Err(ImageDefect::HeaderMachineMismatch);
+    }
+
+    let size_of_image = read32(optional_header_off + 56)? as usize;
+    let size_of_headers = read32(optional_header_off + 60)? as usize;
+    if size_of_headers > file_len {
+        return Err(ImageDefect::Truncated);
+    }
+    if size_of_headers < section_table_end || size_of_headers > size_of_image {
+        return Err(ImageDefect::CorruptHeaders);
+    }
+
+    let mut previous_end = 0usize;
+    for i in 0..number_of_sections {
+        let base = section_table_off + i * 40;
+        let virtual_size = read32(base + 8)? as usize;
+        let virtual_address = read32(base + 12)? as usize;
+        let raw_data_size = read32(base + 16)? as usize;
+        let raw_data_ptr = read32(base + 20)? as usize;
+
+        // The loader sizes a section with no VirtualSize by its raw data.
+        let mapped_size = if virtual_size == 0 {
+            raw_data_size
+        } else {
+            virtual_size
+        };
+        let end = virtual_address
+            .checked_add(mapped_size)
+            .ok_or(ImageDefect::CorruptSections)?;
+        if virtual_address < previous_end || end > size_of_image {
+            return Err(ImageDefect::CorruptSections);
+        }
+        previous_end = end;
+
+        if raw_data_size > 0
+            && raw_data_ptr
+                .checked_add(raw_data_size)
+                .is_none_or(|raw_end| raw_end > file_len)
+        {
+            return Err(ImageDefect::Truncated);
+        }
+    }
+    Ok(())
+}
+
+/// Whether the load config directory carries a non-null CHPE metadata
+/// pointer, which marks ARM64EC code (AMD64 header) or an ARM64X image
+/// (ARM64 header). Best-effort: a short or unmapped load config is treated
+/// as "no metadata".
+fn has_chpe_metadata(data: &[u8], pe: &PeLayout) -> bool {
+    load_config_offset(pe)
+        .and_then(|offset| data.get(offset..))
+        .is_some_and(|load_config| chpe_pointer_set(load_config, pe.pe32_plus))
+}
+
+fn load_config_offset(pe: &PeLayout) -> Option<usize> {
+    if pe.load_config_rva == 0 {
+        return None;
+    }
+    rva_to_offset(pe.load_config_rva, &pe.sections)
 }
 
+/// Bytes of the load config structure that `chpe_pointer_set` may read.
+const LOAD_CONFIG_CHPE_SPAN: usize = 0xC8 + 8;
+
+/// `load_config` starts at the IMAGE_LOAD_CONFIG_DIRECTORY structure.
+fn chpe_pointer_set(load_config: &[u8], pe32_plus: bool) -> bool {
+    let Ok(size) = read_u32(load_config, 0) else {
+        return false;
+    };
+    // CHPEMetadataPointer sits at 0xC8 in IMAGE_LOAD_CONFIG_DIRECTORY64 and
+    // at 0x7C in the 32-bit layout; the structure's own Size field says
+    // whether this revision has it.
+    let pointer = if pe32_plus {
+        if size < 0xC8 + 8 {
+            return false;
+        }
+        read_u64(load_config, 0xC8)
+    } else {
+        if size < 0x7C + 4 {
+            return false;
+        }
+        read_u32(load_config, 0x7C).map(u64::from)
+    };
+    matches!(pointer, Ok(value) if value != 0)
+}
+
+#[cfg(test)]
+pub(crate) fn module_dependencies_from_bytes(data: &[u8]) -> Result<Vec<ImportedDll>, String> {
+    dependencies_from(data, &parse_pe_layout(data)?)
+}
+
+fn dependencies_from(
+    data: &(impl FileBytes + ?Sized),
+    pe: &PeLayout,
+) -> Result<Vec<ImportedDll>, String> {
+    let regular = import_table(data, pe)?;
+    // Best-effort: the loader never reads the delay table at module load, so
+    // a damaged one must not turn a loadable image into a walk failure.
+    let delay = delay_imports(data, pe).unwrap_or_default();
+    let delay_only: Vec<String> = delay
+        .into_iter()
+        .filter(|name| !regular.contains_key(name))
+        .collect();
+    let mut out: Vec<ImportedDll> = regular
+        .into_iter()
+        .map(|(name, functions)| ImportedDll {
+            name,
+            delay: false,
+            functions: functions.into_iter().collect(),
+        })
+        .collect();
+    out.extend(delay_only.into_iter().map(|name| ImportedDll {
+        name,
+        delay: true,
+        functions: Vec::new(),
+    }));
+    Ok(out)
+}
+
+#[cfg(test)]
 pub(crate) fn direct_imports_from_bytes(data: &[u8]) -> Result<Vec<String>, String> {
-    let pe = parse_pe_layout(data)?;
+    Ok(import_table(data, &parse_pe_layout(data)?)?
+        .into_keys()
+        .collect())
+}
+
+/// Regular import descriptors keyed by lowercased DLL name, with the
+/// functions bound from each. Descriptors naming the same DLL are merged.
+fn import_table(
+    data: &(impl FileBytes + ?Sized),
+    pe: &PeLayout,
+) -> Result<BTreeMap<String, BTreeSet<ImportedFunction>>, String> {
     if pe.import_rva == 0 {
-        return Ok(Vec::new());
+        return Ok(BTreeMap::new());
     }
 
-    let mut imports = BTreeSet::new();
+    let mut imports: BTreeMap<String, BTreeSet<ImportedFunction>> = BTreeMap::new();
     let mut off = rva_to_offset(pe.import_rva, &pe.sections)
         .ok_or_else(|| "invalid import table RVA".to_string())?;
 
     loop {
-        if off + 20 > data.len() {
+        if data.tail(off).len() < 20 {
             return Err("truncated import descriptor table".to_string());
         }
 
@@ -85,17 +426,252 @@ pub(crate) fn direct_imports_from_bytes(data: &[u8]) -> Result<Vec<String>, Stri
         let name_off = rva_to_offset(name_rva, &pe.sections)
             .ok_or_else(|| "invalid import name RVA".to_string())?;
         let name = read_c_string(data, name_off)?;
-        imports.insert(name.to_ascii_lowercase());
+
+        // Prefer the import name table; without one, an unbound IAT still
+        // holds the same thunks on disk. A bound IAT holds addresses instead.
+        let thunk_rva = if original_first_thunk != 0 {
+            original_first_thunk
+        } else if time_date_stamp == 0 {
+            first_thunk
+        } else {
+            0
+        };
+        // Best-effort: an unreadable thunk array leaves the function list
+        // unknown rather than failing the whole module.
+        let functions = if thunk_rva == 0 {
+            Vec::new()
+        } else {
+            imported_functions(data, pe, thunk_rva).unwrap_or_default()
+        };
+        imports
+            .entry(name.to_ascii_lowercase())
+            .or_default()
+            .extend(functions);
 
         off += 20;
     }
 
+    Ok(imports)
+}
+
+fn imported_functions(
+    data: &(impl FileBytes + ?Sized),
+    pe: &PeLayout,
+    thunk_rva: u32,
+) -> Result<Vec<ImportedFunction>, String> {
+    let mut off = rva_to_offset(thunk_rva, &pe.sections)
+        .ok_or_else(|| "invalid import thunk RVA".to_string())?;
+    let (width, ordinal_flag) = if pe.pe32_plus {
+        (8, 1u64 << 63)
+    } else {
+        (4, 1u64 << 31)
+    };
+
+    let mut functions = Vec::new();
+    loop {
+        let thunk = if pe.pe32_plus {
+            read_u64(data, off)?
+        } else {
+            read_u32(data, off)? as u64
+        };
+        if thunk == 0 {
+            break;
+        }
+
+        if thunk & ordinal_flag != 0 {
+            functions.push(ImportedFunction::Ordinal((thunk & 0xFFFF) as u16));
+        } else {
+            // IMAGE_IMPORT_BY_NAME: a u16 hint followed by the name.
+            let by_name_off = rva_to_offset((thunk & 0x7FFF_FFFF) as u32, &pe.sections)
+                .ok_or_else(|| "invalid import by-name RVA".to_string())?;
+            functions.push(ImportedFunction::Name(read_c_string(
+                data,
+                by_name_off + 2,
+            )?));
+        }
+        off += width;
+    }
+
+    Ok(functions)
+}
+
+#[cfg(test)]
+pub(crate) fn exports_from_bytes(data: &[u8]) -> Result<ExportTable, String> {
+    exports_from(data, &parse_pe_layout(data)?)
+}
+
+fn exports_from(data: &(impl FileBytes + ?Sized), pe: &PeLayout) -> Result<ExportTable, String> {
+    let mut table = ExportTable::default();
+    if pe.export_rva == 0 {
+        return Ok(table);
+    }
+
+    let off = rva_to_offset(pe.export_rva, &pe.sections)
+        .ok_or_else(|| "invalid export table RVA".to_string())?;
+    if data.tail(off).len() < 40 {
+        return Err("truncated export directory".to_string());
+    }
+
+    let ordinal_base = read_u32(data, off + 16)?;
+    let number_of_functions = read_u32(data, off + 20)? as usize;
+    let number_of_names = read_u32(data, off + 24)? as usize;
+    let functions_rva = read_u32(data, off + 28)?;
+    let names_rva = read_u32(data, off + 32)?;
+    let name_ordinals_rva = read_u32(data, off + 36)?;
+    // Export address table entries pointing back inside the export
+    // directory are forwarder strings rather than code.
+    let forward_range = pe.export_rva..pe.export_rva.saturating_add(pe.export_size);
+
+    // The counts are untrusted: nothing is sized from them until the table
+    // they describe is known to fit in the file.
+    let mut function_rvas = Vec::new();
+    if number_of_functions > 0 {
+        let functions_off = rva_to_offset(functions_rva, &pe.sections)
+            .filter(|&start| table_fits(data, start, number_of_functions, 4))
+            .ok_or_else(|| "invalid export address table".to_string())?;
+        function_rvas.reserve_exact(number_of_functions);
+        for index in 0..number_of_functions {
+            let rva = read_u32(data, functions_off + index * 4)?;
+            function_rvas.push(rva);
+            // Unused slots in a sparse ordinal range hold zero.
+            if rva == 0 {
+                continue;
+            }
+            let ordinal = ordinal_base.wrapping_add(index as u32);
+            table.ordinals.insert(ordinal);
+            if forward_range.contains(&rva) {
+                // Only ordinals that fit an import thunk can be bound by one.
+                if let (Ok(ordinal), Some(forward)) =
+                    (u16::try_from(ordinal), read_forward(data, pe, rva))
+                {
+                    table
+                        .forwards
+                        .insert(ImportedFunction::Ordinal(ordinal), forward);
+                }
+            }
+        }
+    }
+
+    if number_of_names > 0 {
+        let names_off = rva_to_offset(names_rva, &pe.sections)
+            .filter(|&start| table_fits(data, start, number_of_names, 4))
+            .ok_or_else(|| "invalid export name table".to_string())?;
+        let name_ordinals_off = rva_to_offset(name_ordinals_rva, &pe.sections)
+            .filter(|&start| table_fits(data, start, number_of_names, 2));
+        for index in 0..number_of_names {
+            let name_rva = read_u32(data, names_off + index * 4)?;
+            let name_off = rva_to_offset(name_rva, &pe.sections)
+                .ok_or_else(|| "invalid export name RVA".to_string())?;
+            let name = read_c_string(data, name_off)?;
+
+            let function_rva = name_ordinals_off
+                .and_then(|start| read_u16(data, start + index * 2).ok())
+                .and_then(|slot| function_rvas.get(slot as usize).copied());
+            if let Some(rva) = function_rva.filter(|rva| forward_range.contains(rva)) {
+                if let Some(forward) = read_forward(data, pe, rva) {
+                    table
+                        .forwards
+                        .insert(ImportedFunction::Name(name.clone()), forward);
+                }
+            }
+            table.names.insert(name);
+        }
+    }
+
+    Ok(table)
+}
+
+/// Whether `count` entries of `width` bytes starting at `start` lie within
+/// `data`.
+fn table_fits(data: &(impl FileBytes + ?Sized), start: usize, count: usize, width: usize) -> bool {
+    count
+        .checked_mul(width)
+        .is_some_and(|len| len <= data.tail(start).len())
+}
+
+/// Parses the forwarder string at `rva`. Malformed strings yield None and
+/// the export is treated as an ordinary one.
+fn read_forward(
+    data: &(impl FileBytes + ?Sized),
+    pe: &PeLayout,
+    rva: u32,
+) -> Option<ExportForward> {
+    let raw = read_c_string(data, rva_to_offset(rva, &pe.sections)?).ok()?;
+    // API set forwarders carry dots in the module part, so split on the last.
+    let (module, target) = raw.rsplit_once('.')?;
+    if module.is_empty() || target.is_empty() {
+        return None;
+    }
+    let function = match target.strip_prefix('#') {
+        Some(ordinal) => ImportedFunction::Ordinal(ordinal.parse().ok()?),
+        None => ImportedFunction::Name(target.to_string()),
+    };
+    let mut dll = module.to_ascii_lowercase();
+    if !dll.ends_with(".dll") {
+        dll.push_str(".dll");
+    }
+    Some(ExportForward { raw, dll, function })
+}
+
+/// Attribute bit set on `IMAGE_DELAYLOAD_DESCRIPTOR` entries whose fields are
+/// RVAs. Descriptors without it (pre-VC7 linkers) carry virtual addresses.
+const DELAYLOAD_RVA_BASED: u32 = 0x1;
+
+#[cfg(test)]
+pub(crate) fn delay_imports_from_bytes(data: &[u8]) -> Result<Vec<String>, String> {
+    delay_imports(data, &parse_pe_layout(data)?)
+}
+
+fn delay_imports(data: &(impl FileBytes + ?Sized), pe: &PeLayout) -> Result<Vec<String>, String> {
+    if pe.delay_import_rva == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut imports = BTreeSet::new();
+    let mut off = rva_to_offset(pe.delay_import_rva, &pe.sections)
+        .ok_or_else(|| "invalid delay import table RVA".to_string())?;
+
+    loop {
+        if data.tail(off).len() < 32 {
+            return Err("truncated delay import descriptor table".to_string());
+        }
+
+        let attributes = read_u32(data, off)?;
+        let name_field = read_u32(data, off + 4)?;
+        if name_field == 0 {
+            break;
+        }
+
+        let name_rva = if attributes & DELAYLOAD_RVA_BASED != 0 {
+            name_field
+        } else {
+            (name_field as u64)
+                .checked_sub(pe.image_base)
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or_else(|| "invalid delay import name address".to_string())?
+        };
+        let name_off = rva_to_offset(name_rva, &pe.sections)
+            .ok_or_else(|| "invalid delay import name RVA".to_string())?;
+        let name = read_c_string(data, name_off)?;
+        imports.insert(name.to_ascii_lowercase());
+
+        off += 32;
+    }
+
     Ok(imports.into_iter().collect())
 }
 
 struct PeLayout {
+    pe32_plus: bool,
+    export_rva: u32,
+    export_size: u32,
     import_rva: u32,
     resource_rva: u32,
+    security_offset: u32,
+    security_size: u32,
+    delay_import_rva: u32,
+    load_config_rva: u32,
+    image_base: u64,
     machine: u16,
     sections: Vec<Section>,
 }
@@ -140,6 +716,13 @@ fn parse_pe_layout(data: &[u8]) -> Result<PeLayout, String> {
         return Err("optional header missing data directories".to_string());
     }
 
+    let image_base = match magic {
+        0x010B => read_u32(data, optional_header_off + 28)? as u64,
+        _ => read_u64(data, optional_header_off + 24)?,
+    };
+
+    let export_rva = read_u32(data, data_dir_start)?;
+    let export_size = read_u32(data, data_dir_start + 4)?;
     let import_rva = read_u32(data, data_dir_start + 8)?;
     // Resource table is data directory index 2; only present when the
     // optional header carries at least three directory entries.
@@ -148,6 +731,30 @@ fn parse_pe_layout(data: &[u8]) -> Result<PeLayout, String> {
     } else {
         0
     };
+    // Certificate table is data directory index 4. Unlike the others its
+    // address is a file offset: the table is not mapped into the image.
+    let (security_offset, security_size) =
+        if data_dir_start + 40 <= optional_header_off + size_of_optional_header {
+            (
+                read_u32(data, data_dir_start + 32)?,
+                read_u32(data, data_dir_start + 36)?,
+            )
+        } else {
+            (0, 0)
+        };
+    // Delay import table is data directory index 13.
+    let delay_import_rva = if data_dir_start + 112 <= optional_header_off + size_of_optional_header
+    {
+        read_u32(data, data_dir_start + 104)?
+    } else {
+        0
+    };
+    // Load config table is data directory index 10.
+    let load_config_rva = if data_dir_start + 88 <= optional_header_off + size_of_optional_header {
+        read_u32(data, data_dir_start + 80)?
+    } else {
+        0
+    };
     let machine = read_u16(data, pe_offset + 4)?;
     let section_table_off = optional_header_off + size_of_optional_header;
     let section_table_len = number_of_sections
@@ -165,7 +772,10 @@ fn parse_pe_layout(data: &[u8]) -> Result<PeLayout, String> {
         let virtual_address = read_u32(data, base + 12)?;
         let raw_data_size = read_u32(data, base + 16)?;
         let raw_data_ptr = read_u32(data, base + 20)?;
+        let mut name = [0u8; 8];
+        name.copy_from_slice(&data[base..base + 8]);
         sections.push(Section {
+            name,
             virtual_address,
             virtual_size,
             raw_data_ptr,
@@ -174,42 +784,92 @@ fn parse_pe_layout(data: &[u8]) -> Result<PeLayout, String> {
     }
 
     Ok(PeLayout {
+        pe32_plus: magic == 0x020B,
+        export_rva,
+        export_size,
         import_rva,
         resource_rva,
+        security_offset,
+        security_size,
+        delay_import_rva,
+        load_config_rva,
+        image_base,
         machine,
         sections,
     })
 }
 
+/// The first section named `name` (at most eight bytes, matched exactly).
+fn find_section<'a>(pe: &'a PeLayout, name: &str) -> Result<&'a Section, String> {
+    pe.sections
+        .iter()
+        .find(|section| {
+            let len = section.name.iter().position(|&b| b == 0).unwrap_or(8);
+            &section.name[..len] == name.as_bytes()
+        })
+        .ok_or_else(|| format!("no {name} section"))
+}
+
+/// Raw contents of the first section named `name`, clipped to the file.
+#[cfg(test)]
+pub(crate) fn section_bytes<'a>(data: &'a [u8], name: &str) -> Result<&'a [u8], String> {
+    let pe = parse_pe_layout(data)?;
+    let section = find_section(&pe, name)?;
+    let start = section.raw_data_ptr as usize;
+    let end = start
+        .saturating_add(section.raw_data_size as usize)
+        .min(data.len());
+    if start >= end {
+        return Err(format!("{name} section has no raw data"));
+    }
+    Ok(&data[start..end])
+}
+
+const RT_VERSION: u32 = 16;
 const RT_MANIFEST: u32 = 24;
 
 /// Extracts the embedded RT_MANIFEST resource from a PE image, if any.
 /// Best-effort: returns None for missing/unparseable resources rather than
 /// failing, because a broken resource tree should not abort COM diagnosis.
 pub fn extract_embedded_manifest(module_path: &Path) -> Option<String> {
-    let data = fs::read(module_path).ok()?;
-    extract_embedded_manifest_from_bytes(&data)
+    open_image(module_path)
+        .ok()?
+        .embedded_manifest()
+        .map(str::to_string)
 }
 
+#[cfg(test)]
 pub(crate) fn extract_embedded_manifest_from_bytes(data: &[u8]) -> Option<String> {
-    let pe = parse_pe_layout(data).ok()?;
+    manifest_from(data, &parse_pe_layout(data).ok()?)
+}
+
+fn manifest_from(data: &(impl FileBytes + ?Sized), pe: &PeLayout) -> Option<String> {
+    resource_payload(data, pe, RT_MANIFEST).map(decode_manifest_text)
+}
+
+/// Returns the payload of the first resource of `type_id`, taking the first
+/// name and first language beneath it.
+fn resource_payload<'a>(
+    data: &'a (impl FileBytes + ?Sized),
+    pe: &PeLayout,
+    type_id: u32,
+) -> Option<&'a [u8]> {
     if pe.resource_rva == 0 {
         return None;
     }
     let rsrc_off = rva_to_offset(pe.resource_rva, &pe.sections)?;
 
-    // Level 1: resource type directory; find the RT_MANIFEST ID entry.
-    let manifest_dir = find_resource_entry(data, rsrc_off, rsrc_off, Some(RT_MANIFEST))?;
+    // Level 1: resource type directory; find the requested ID entry.
+    let type_dir = find_resource_entry(data, rsrc_off, rsrc_off, Some(type_id))?;
     // Level 2: resource name/ID; take the first entry.
-    let lang_dir = find_resource_entry(data, rsrc_off, manifest_dir, None)?;
+    let lang_dir = find_resource_entry(data, rsrc_off, type_dir, None)?;
     // Level 3: language; take the first entry, which must be a data entry.
     let data_entry_off = find_resource_entry(data, rsrc_off, lang_dir, None)?;
 
     let data_rva = read_u32(data, data_entry_off).ok()?;
     let size = read_u32(data, data_entry_off + 4).ok()? as usize;
     let payload_off = rva_to_offset(data_rva, &pe.sections)?;
-    let payload = data.get(payload_off..payload_off.checked_add(size)?)?;
-    Some(decode_manifest_text(payload))
+    data.tail(payload_off).get(..size)
 }
 
 /// Walks one level of the resource directory at `dir_off`. With `Some(id)`,
@@ -217,7 +877,7 @@ pub(crate) fn extract_embedded_manifest_from_bytes(data: &[u8]) -> Option<String
 /// returns the target offset of the first entry. Subdirectory targets are
 /// resolved relative to `rsrc_off`; data-entry targets likewise.
 fn find_resource_entry(
-    data: &[u8],
+    data: &(impl FileBytes + ?Sized),
     rsrc_off: usize,
     dir_off: usize,
     id: Option<u32>,
@@ -263,6 +923,425 @@ fn decode_manifest_text(payload: &[u8]) -> String {
     String::from_utf8_lossy(body).into_owned()
 }
 
+/// Version metadata read from an image's VS_VERSIONINFO resource.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub file_version: Option<String>,
+    pub product_version: Option<String>,
+    pub company: Option<String>,
+}
+
+impl VersionInfo {
+    pub fn is_empty(&self) -> bool {
+        self.file_version.is_none() && self.product_version.is_none() && self.company.is_none()
+    }
+}
+
+const VS_FIXEDFILEINFO_SIGNATURE: u32 = 0xFEEF_04BD;
+
+/// Reads the RT_VERSION resource from a PE image. Best-effort like the
+/// manifest reader: a missing or damaged resource yields None.
+pub fn module_version_info(module_path: &Path) -> Option<VersionInfo> {
+    open_image(module_path).ok()?.version_info().cloned()
+}
+
+#[cfg(test)]
+pub(crate) fn version_info_from_bytes(data: &[u8]) -> Option<VersionInfo> {
+    version_info_from(data, &parse_pe_layout(data).ok()?)
+}
+
+fn version_info_from(data: &(impl FileBytes + ?Sized), pe: &PeLayout) -> Option<VersionInfo> {
+    let payload = resource_payload(data, pe, RT_VERSION)?;
+    let root = read_version_block(payload, 0)?;
+    if root.key != "VS_VERSION_INFO" {
+        return None;
+    }
+
+    let mut info = VersionInfo::default();
+    // The binary VS_FIXEDFILEINFO numbers are what the loader and installers
+    // compare, so they win over the free-form string table values.
+    if root.value.len() >= 24 && read_u32(root.value, 0).ok()? == VS_FIXEDFILEINFO_SIGNATURE {
+        info.file_version = Some(format_version(
+            read_u32(root.value, 8).ok()?,
+            read_u32(root.value, 12).ok()?,
+        ));
+        info.product_version = Some(format_version(
+            read_u32(root.value, 16).ok()?,
+            read_u32(root.value, 20).ok()?,
+        ));
+    }
+
+    for child in version_children(payload, &root) {
+        if child.key != "StringFileInfo" {
+            continue;
+        }
+        // Only the first string table (normally the image's own language) is
+        // consulted, matching the first-language rule used for the resource.
+        let Some(table) = version_children(payload, &child).into_iter().next() else {
+            continue;
+        };
+        for entry in version_children(payload, &table) {
+            let slot = match entry.key.as_str() {
+                "CompanyName" => &mut info.company,
+                "FileVersion" => &mut info.file_version,
+                "ProductVersion" => &mut info.product_version,
+                _ => continue,
+            };
+            if slot.is_none() {
+                *slot = version_text(entry.value);
+            }
+        }
+    }
+
+    if info.is_empty() {
+        None
+    } else {
+        Some(info)
+    }
+}
+
+/// One node of the VS_VERSIONINFO tree: a key, its value bytes, and the byte
+/// range holding its children.
+struct VersionBlock<'a> {
+    key: String,
+    value: &'a [u8],
+    children: std::ops::Range<usize>,
+}
+
+fn read_version_block(data: &[u8], offset: usize) -> Option<VersionBlock<'_>> {
+    let length = read_u16(data, offset).ok()? as usize;
+    let value_length = read_u16(data, offset + 2).ok()? as usize;
+    let value_type = read_u16(data, offset + 4).ok()?;
+    let end = offset.checked_add(length)?;
+    if length < 6 || end > data.len() {
+        return None;
+    }
+
+    let mut cursor = offset + 6;
+    let mut units = Vec::new();
+    loop {
+        let unit = read_u16(data, cursor).ok()?;
+        cursor += 2;
+        if unit == 0 {
+            break;
+        }
+        units.push(unit);
+    }
+    let key = String::from_utf16_lossy(&units);
+
+    // Text values count UTF-16 units; binary values count bytes.
+    let value_bytes = if value_type == 1 {
+        value_length.checked_mul(2)?
+    } else {
+        value_length
+    };
+    let value_start = align4(cursor).min(end);
+    let value_end = value_start.checked_add(value_bytes)?.min(end);
+    let children_start = align4(value_end).min(end);
+    Some(VersionBlock {
+        key,
+        value: &data[value_start..value_end],
+        children: children_start..end,
+    })
+}
+
+fn version_children<'a>(data: &'a [u8], parent: &VersionBlock<'_>) -> Vec<VersionBlock<'a>> {
+    let mut children = Vec::new();
+    let mut cursor = parent.children.start;
+    while cursor + 6 <= parent.children.end {
+        let Some(child) = read_version_block(data, cursor) else {
+            break;
+        };
+        let next = align4(child.children.end);
+        children.push(child);
+        if next <= cursor {
+            break;
+        }
+        cursor = next;
+    }
+    children
+}
+
+fn version_text(value: &[u8]) -> Option<String> {
+    let units: Vec<u16> = value
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    let text = String::from_utf16_lossy(&units).trim().to_string();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+fn format_version(high: u32, low: u32) -> String {
+    format!(
+        "{}.{}.{}.{}",
+        high >> 16,
+        high & 0xFFFF,
+        low >> 16,
+        low & 0xFFFF
+    )
+}
+
+fn align4(offset: usize) -> usize {
+    offset.saturating_add(3) & !3
+}
+
+/// Embedded Authenticode signature of an image. Only the certificate table is
+/// read: catalog signatures and chain trust are out of scope.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub signed: bool,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+}
+
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 0x0002;
+// 1.2.840.113549.1.7.2 (PKCS#7 signedData).
+const OID_SIGNED_DATA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+
+/// Reads the embedded signature from a PE image. None means the image could
+/// not be read or parsed; an unsigned image yields `signed: false`.
+pub fn module_signature(module_path: &Path) -> Option<SignatureInfo> {
+    open_image(module_path).ok()?.signature().cloned()
+}
+
+#[cfg(test)]
+pub(crate) fn signature_from_bytes(data: &[u8]) -> Option<SignatureInfo> {
+    let pe = parse_pe_layout(data).ok()?;
+    let start = pe.security_offset as usize;
+    let table = start
+        .checked_add(pe.security_size as usize)
+        .and_then(|end| data.get(start..end));
+    Some(signature_from_table(&pe, table))
+}
+
+/// Parses the certificate table; `table` is None when the directory points
+/// outside the file.
+fn signature_from_table(pe: &PeLayout, table: Option<&[u8]>) -> SignatureInfo {
+    if pe.security_offset == 0 || pe.security_size < 8 {
+        return SignatureInfo::default();
+    }
+    let Some(table) = table else {
+        return SignatureInfo::default();
+    };
+
+    // WIN_CERTIFICATE entries are 8-byte aligned; the first PKCS#7 one is the
+    // primary signature (nested signatures live inside it).
+    let mut cursor = 0usize;
+    while cursor + 8 <= table.len() {
+        let (Ok(length), Ok(cert_type)) = (read_u32(table, cursor), read_u16(table, cursor + 6))
+        else {
+            break;
+        };
+        let length = length as usize;
+        if length < 8 || cursor + length > table.len() {
+            break;
+        }
+        if cert_type == WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+            let (subject, issuer) =
+                signer_names(&table[cursor + 8..cursor + length]).unwrap_or((None, None));
+            return SignatureInfo {
+                signed: true,
+                subject,
+                issuer,
+            };
+        }
+        cursor += length.next_multiple_of(8);
+    }
+    SignatureInfo::default()
+}
+
+/// Finds the signer certificate named by the first SignerInfo and returns its
+/// subject and issuer. When the certificate is not embedded, only the issuer
+/// from the SignerInfo is known.
+fn signer_names(pkcs7: &[u8]) -> Option<(Option<String>, Option<String>)> {
+    let (content_info, _) = der_read(pkcs7)?;
+    let mut fields = der_children(content_info.body);
+    let oid = fields.next()?;
+    if oid.tag != 0x06 || oid.body != OID_SIGNED_DATA {
+        return None;
+    }
+    let explicit = fields.next().filter(|v| v.tag == 0xA0)?;
+    let (signed_data, _) = der_read(explicit.body)?;
+
+    let mut certificates = None;
+    let mut signer_infos = None;
+    for child in der_children(signed_data.body) {
+        match child.tag {
+            0xA0 => certificates = Some(child.body),
+            0x31 => signer_infos = Some(child.body),
+            _ => {}
+        }
+    }
+
+    let signer_info = der_children(signer_infos?).next()?;
+    let mut signer_fields = der_children(signer_info.body);
+    signer_fields.next()?; // version
+    let sid = signer_fields.next()?;
+    let (sid_issuer, sid_serial) = if sid.tag == 0x30 {
+        let mut parts = der_children(sid.body);
+        (parts.next(), parts.next())
+    } else {
+        // SubjectKeyIdentifier form: no issuer/serial to match on.
+        (None, None)
+    };
+
+    if let (Some(certificates), Some(issuer), Some(serial)) = (certificates, sid_issuer, sid_serial)
+    {
+        for certificate in der_children(certificates) {
+            let Some(tbs) = der_children(certificate.body).next() else {
+                continue;
+            };
+            let mut tbs_fields = der_children(tbs.body).peekable();
+            if tbs_fields.peek().map(|v| v.tag) == Some(0xA0) {
+                tbs_fields.next();
+            }
+            let cert_serial = tbs_fields.next();
+            tbs_fields.next(); // signature algorithm
+            let cert_issuer = tbs_fields.next();
+            tbs_fields.next(); // validity
+            let cert_subject = tbs_fields.next();
+            if cert_serial.map(|v| v.body) == Some(serial.body)
+                && cert_issuer.map(|v| v.raw) == Some(issuer.raw)
+            {
+                return Some((
+                    cert_subject.and_then(|v| format_name(v.body)),
+                    format_name(issuer.body),
+                ));
+            }
+        }
+    }
+    Some((None, sid_issuer.and_then(|v| format_name(v.body))))
+}
+
+/// One DER element: its tag, contents, and the full encoding (for exact
+/// Name comparisons).
+#[derive(Clone, Copy)]
+struct DerValue<'a> {
+    tag: u8,
+    body: &'a [u8],
+    raw: &'a [u8],
+}
+
+fn der_read(data: &[u8]) -> Option<(DerValue<'_>, &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)? as usize;
+    let (length, header) = if first < 0x80 {
+        (first, 2)
+    } else {
+        // Long form only; BER indefinite lengths are not DER.
+        let count = first & 0x7F;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let mut length = 0usize;
+        for i in 0..count {
+            length = (length << 8) | *data.get(2 + i)? as usize;
+        }
+        (length, 2 + count)
+    };
+    let end = header.checked_add(length)?;
+    let raw = data.get(..end)?;
+    Some((
+        DerValue {
+            tag,
+            body: &raw[header..],
+            raw,
+        },
+        &data[end..],
+    ))
+}
+
+fn der_children(data: &[u8]) -> impl Iterator<Item = DerValue<'_>> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let (value, next) = der_read(rest)?;
+        rest = next;
+        Some(value)
+    })
+}
+
+/// Renders an X.501 Name the way Windows displays it: most specific RDN
+/// first, e.g. `CN=Contoso Ltd, O=Contoso Ltd, C=US`.
+fn format_name(name: &[u8]) -> Option<String> {
+    let mut parts = Vec::new();
+    for rdn in der_children(name) {
+        for attribute in der_children(rdn.body) {
+            let mut fields = der_children(attribute.body);
+            let (Some(oid), Some(value)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let Some(text) = der_string(value) else {
+                continue;
+            };
+            parts.push(format!("{}={}", attribute_label(oid.body), text));
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    parts.reverse();
+    Some(parts.join(", "))
+}
+
+fn attribute_label(oid: &[u8]) -> String {
+    match oid {
+        [0x55, 0x04, 0x03] => "CN".to_string(),
+        [0x55, 0x04, 0x06] => "C".to_string(),
+        [0x55, 0x04, 0x07] => "L".to_string(),
+        [0x55, 0x04, 0x08] => "S".to_string(),
+        [0x55, 0x04, 0x0A] => "O".to_string(),
+        [0x55, 0x04, 0x0B] => "OU".to_string(),
+        [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x01] => "E".to_string(),
+        _ => format!("OID.{}", format_oid(oid)),
+    }
+}
+
+fn format_oid(oid: &[u8]) -> String {
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+    for &byte in oid {
+        value = (value << 7) | u64::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter()
+        .map(|arc| arc.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn der_string(value: DerValue<'_>) -> Option<String> {
+    match value.tag {
+        // UTF8String, PrintableString, IA5String.
+        0x0C | 0x13 | 0x16 => Some(String::from_utf8_lossy(value.body).into_owned()),
+        // T61String: treat as Latin-1, which is what issuers put there.
+        0x14 => Some(value.body.iter().map(|&b| b as char).collect()),
+        // BMPString: big-endian UTF-16.
+        0x1E => {
+            let units: Vec<u16> = value
+                .body
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        _ => None,
+    }
+}
+
 fn rva_to_offset(rva: u32, sections: &[Section]) -> Option<usize> {
     for section in sections {
         let start = section.virtual_address;
@@ -276,39 +1355,367 @@ fn rva_to_offset(rva: u32, sections: &[Section]) -> Option<usize> {
     None
 }
 
-fn read_c_string(data: &[u8], offset: usize) -> Result<String, String> {
-    if offset >= data.len() {
-        return Err("string offset out of bounds".to_string());
-    }
-    let mut end = offset;
-    while end < data.len() && data[end] != 0 {
-        end += 1;
+/// Image bytes addressed by file offset: a whole file held in memory, or a
+/// `SectionReader` that loads only the sections a directory walk reaches.
+trait FileBytes {
+    /// The bytes from `offset` to the end of the contiguous run holding it;
+    /// empty when nothing is loaded there.
+    fn tail(&self, offset: usize) -> &[u8];
+}
+
+impl FileBytes for [u8] {
+    fn tail(&self, offset: usize) -> &[u8] {
+        self.get(offset..).unwrap_or_default()
     }
-    if end == data.len() {
-        return Err("unterminated import string".to_string());
+}
+
+fn read_c_string(data: &(impl FileBytes + ?Sized), offset: usize) -> Result<String, String> {
+    let tail = data.tail(offset);
+    if tail.is_empty() {
+        return Err("string offset out of bounds".to_string());
     }
-    String::from_utf8(data[offset..end].to_vec())
+    let end = tail
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or_else(|| "unterminated import string".to_string())?;
+    String::from_utf8(tail[..end].to_vec())
         .map_err(|_| "import name is not valid UTF-8".to_string())
 }
 
-fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+fn read_u16(data: &(impl FileBytes + ?Sized), offset: usize) -> Result<u16, String> {
     let bytes = data
-        .get(offset..offset + 2)
+        .tail(offset)
+        .get(..2)
         .ok_or_else(|| "unexpected EOF".to_string())?;
     Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
 }
 
-fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+fn read_u32(data: &(impl FileBytes + ?Sized), offset: usize) -> Result<u32, String> {
     let bytes = data
-        .get(offset..offset + 4)
+        .tail(offset)
+        .get(..4)
         .ok_or_else(|| "unexpected EOF".to_string())?;
     Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
 }
 
+fn read_u64(data: &(impl FileBytes + ?Sized), offset: usize) -> Result<u64, String> {
+    let bytes = data
+        .tail(offset)
+        .get(..8)
+        .ok_or_else(|| "unexpected EOF".to_string())?;
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(bytes);
+    Ok(u64::from_le_bytes(raw))
+}
+
+/// Bytes read up front when an image is opened. Covers the DOS header, PE
+/// headers and section table of ordinary images; larger header spans are
+/// re-read once their size is known.
+const HEADER_READ_SIZE: usize = 4096;
+
+/// A PE file on disk, parsed lazily. Opening reads only the headers; every
+/// other view comes from bounded reads: the load config for the machine
+/// type, the certificate table for the signature, and the sections holding
+/// the import, delay-import, export and resource directories for the rest.
+/// Only the parsed views are kept, memoized, so an image shared through
+/// `open_image` is parsed once per command without holding its file bytes.
+pub struct PeImage {
+    path: PathBuf,
+    file_len: usize,
+    headers: Vec<u8>,
+    layout: OnceCell<Result<PeLayout, String>>,
+    validation: OnceCell<Result<MachineType, ImageDefect>>,
+    dependencies: OnceCell<Result<Vec<ImportedDll>, String>>,
+    exports: OnceCell<Result<ExportTable, String>>,
+    manifest: OnceCell<Option<String>>,
+    version: OnceCell<Option<VersionInfo>>,
+    signature: OnceCell<Option<SignatureInfo>>,
+    bytes_read: Cell<usize>,
+}
+
+impl PeImage {
+    pub fn open(path: &Path) -> Result<PeImage, String> {
+        let mut file =
+            File::open(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let file_len = file
+            .metadata()
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?
+            .len() as usize;
+        let mut headers = read_at(&mut file, 0, file_len.min(HEADER_READ_SIZE))
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let mut bytes_read = headers.len();
+        if let Some(span) = header_span(&headers) {
+            if span > headers.len() && span <= file_len {
+                headers = read_at(&mut file, 0, span)
+                    .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+                bytes_read += headers.len();
+            }
+        }
+        Ok(PeImage {
+            path: path.to_path_buf(),
+            file_len,
+            headers,
+            layout: OnceCell::new(),
+            validation: OnceCell::new(),
+            dependencies: OnceCell::new(),
+            exports: OnceCell::new(),
+            manifest: OnceCell::new(),
+            version: OnceCell::new(),
+            signature: OnceCell::new(),
+            bytes_read: Cell::new(bytes_read),
+        })
+    }
+
+    /// Up to `len` bytes at `offset`, served from the headers when they
+    /// cover the range and from a bounded read otherwise.
+    pub fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, String> {
+        let end = offset.saturating_add(len).min(self.file_len);
+        if offset >= end {
+            return Ok(Vec::new());
+        }
+        if let Some(bytes) = self.headers.get(offset..end) {
+            return Ok(bytes.to_vec());
+        }
+        let mut file = File::open(&self.path)
+            .map_err(|e| format!("failed to read {}: {e}", self.path.display()))?;
+        let bytes = read_at(&mut file, offset, end - offset)
+            .map_err(|e| format!("failed to read {}: {e}", self.path.display()))?;
+        self.bytes_read.set(self.bytes_read.get() + bytes.len());
+        Ok(bytes)
+    }
+
+    fn layout(&self) -> Result<&PeLayout, String> {
+        self.layout
+            .get_or_init(|| parse_pe_layout(&self.headers))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Structural validation and machine type, as `validate_image`.
+    pub fn validate(&self) -> Result<MachineType, ImageDefect> {
+        *self.validation.get_or_init(|| {
+            validate_headers(&self.headers, self.file_len)?;
+            let pe = self.layout().map_err(|_| ImageDefect::CorruptHeaders)?;
+            Ok(machine_from_layout(pe, || self.has_chpe_metadata(pe)))
+        })
+    }
+
+    /// Machine type, or None when the image is not a valid PE.
+    pub fn machine(&self) -> Option<MachineType> {
+        let pe = self.layout().ok()?;
+        match self.validation.get() {
+            Some(Ok(machine)) => Some(*machine),
+            _ => Some(machine_from_layout(pe, || self.has_chpe_metadata(pe))),
+        }
+    }
+
+    fn has_chpe_metadata(&self, pe: &PeLayout) -> bool {
+        load_config_offset(pe)
+            .and_then(|offset| self.read_range(offset, LOAD_CONFIG_CHPE_SPAN).ok())
+            .is_some_and(|load_config| chpe_pointer_set(&load_config, pe.pe32_plus))
+    }
+
+    /// Raw contents of the first section named `name`, clipped to the file.
+    pub fn section(&self, name: &str) -> Result<Vec<u8>, String> {
+        let section = find_section(self.layout()?, name)?;
+        let data = self.read_range(
+            section.raw_data_ptr as usize,
+            section.raw_data_size as usize,
+        )?;
+        if data.is_empty() {
+            return Err(format!("{name} section has no raw data"));
+        }
+        Ok(data)
+    }
+
+    pub fn dependencies(&self) -> Result<&[ImportedDll], String> {
+        self.dependencies
+            .get_or_init(|| {
+                let pe = self.layout()?;
+                dependencies_from(&SectionReader::new(self, pe), pe)
+            })
+            .as_deref()
+            .map_err(Clone::clone)
+    }
+
+    pub fn exports(&self) -> Result<&ExportTable, String> {
+        self.exports
+            .get_or_init(|| {
+                let pe = self.layout()?;
+                exports_from(&SectionReader::new(self, pe), pe)
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    pub fn embedded_manifest(&self) -> Option<&str> {
+        self.manifest
+            .get_or_init(|| {
+                let pe = self.layout().ok()?;
+                manifest_from(&SectionReader::new(self, pe), pe)
+            })
+            .as_deref()
+    }
+
+    pub fn version_info(&self) -> Option<&VersionInfo> {
+        self.version
+            .get_or_init(|| {
+                let pe = self.layout().ok()?;
+                version_info_from(&SectionReader::new(self, pe), pe)
+            })
+            .as_ref()
+    }
+
+    pub fn signature(&self) -> Option<&SignatureInfo> {
+        self.signature
+            .get_or_init(|| {
+                let pe = self.layout().ok()?;
+                let table = self
+                    .read_range(pe.security_offset as usize, pe.security_size as usize)
+                    .ok()
+                    .filter(|table| table.len() == pe.security_size as usize);
+                Some(signature_from_table(pe, table.as_deref()))
+            })
+            .as_ref()
+    }
+
+    /// Bytes this image has read from disk so far.
+    #[cfg(test)]
+    pub(crate) fn bytes_read(&self) -> usize {
+        self.bytes_read.get()
+    }
+}
+
+/// A `PeImage`'s bytes as seen by one directory walk: each section is read
+/// whole through `read_range` the first time an offset inside it is needed,
+/// and everything is dropped with the reader once the walk is parsed.
+/// Offsets outside every section fall back to the headers.
+struct SectionReader<'a> {
+    image: &'a PeImage,
+    pe: &'a PeLayout,
+    sections: Vec<OnceCell<Vec<u8>>>,
+}
+
+impl<'a> SectionReader<'a> {
+    fn new(image: &'a PeImage, pe: &'a PeLayout) -> Self {
+        SectionReader {
+            image,
+            pe,
+            sections: pe.sections.iter().map(|_| OnceCell::new()).collect(),
+        }
+    }
+}
+
+impl FileBytes for SectionReader<'_> {
+    fn tail(&self, offset: usize) -> &[u8] {
+        for (section, loaded) in self.pe.sections.iter().zip(&self.sections) {
+            let start = section.raw_data_ptr as usize;
+            let len = section.raw_data_size as usize;
+            if offset < start || offset - start >= len {
+                continue;
+            }
+            // Best-effort like the slice view: an unreadable section reads
+            // as absent and the walk reports what it was looking for.
+            let bytes =
+                loaded.get_or_init(|| self.image.read_range(start, len).unwrap_or_default());
+            return bytes.get(offset - start..).unwrap_or_default();
+        }
+        self.image.headers.tail(offset)
+    }
+}
+
+/// Length of the DOS header, PE headers and section table described by
+/// `headers`, or None when they do not describe a PE image.
+fn header_span(headers: &[u8]) -> Option<usize> {
+    if headers.get(0..2)? != b"MZ" {
+        return None;
+    }
+    let pe_offset = read_u32(headers, 0x3C).ok()? as usize;
+    let size_of_optional_header = read_u16(headers, pe_offset.checked_add(20)?).ok()? as usize;
+    let number_of_sections = read_u16(headers, pe_offset + 6).ok()? as usize;
+    (pe_offset + 24 + size_of_optional_header).checked_add(number_of_sections * 40)
+}
+
+fn read_at(file: &mut File, offset: usize, len: usize) -> std::io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset as u64))?;
+    let mut bytes = Vec::with_capacity(len);
+    file.take(len as u64).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+thread_local! {
+    static IMAGE_CACHE: RefCell<Option<HashMap<String, Rc<PeImage>>>> =
+        const { RefCell::new(None) };
+}
+
+/// Keeps images opened through `open_image` cached until dropped. A command
+/// holds one for its whole run so that the static walk, COM validation and
+/// search classification parse each file once; outside a scope every open
+/// reads the file afresh. Nested scopes share the outermost cache.
+pub struct ImageCacheScope {
+    owner: bool,
+}
+
+impl ImageCacheScope {
+    pub fn enter() -> ImageCacheScope {
+        let owner = IMAGE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.is_some() {
+                return false;
+            }
+            *cache = Some(HashMap::new());
+            true
+        });
+        ImageCacheScope { owner }
+    }
+}
+
+impl Drop for ImageCacheScope {
+    fn drop(&mut self) {
+        if self.owner {
+            IMAGE_CACHE.with(|cache| *cache.borrow_mut() = None);
+        }
+    }
+}
+
+/// Opens `path` as a `PeImage`, sharing the cached image when an
+/// `ImageCacheScope` is active. Under an active sysroot `path` names a file
+/// on the mounted volume.
+pub fn open_image(path: &Path) -> Result<Rc<PeImage>, String> {
+    let path = &crate::sysroot::host_path(path);
+    let key = image_cache_key(path);
+    if let Some(image) =
+        IMAGE_CACHE.with(|cache| cache.borrow().as_ref().and_then(|c| c.get(&key).cloned()))
+    {
+        return Ok(image);
+    }
+    let image = Rc::new(PeImage::open(path)?);
+    IMAGE_CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.insert(key, Rc::clone(&image));
+        }
+    });
+    Ok(image)
+}
+
+/// The absolute host path, compared case-insensitively only on Windows.
+/// Elsewhere file names are case-sensitive, and sysroot paths have already
+/// been mapped onto the host's own spelling by `sysroot::host_path`.
+fn image_cache_key(path: &Path) -> String {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    if cfg!(windows) {
+        absolute.to_string_lossy().replace('/', "\\").to_lowercase()
+    } else {
+        absolute.to_string_lossy().into_owned()
+    }
+}
+
 /// Shared synthetic-PE builder for unit tests (used by pe tests and the COM
 /// mock file system).
 #[cfg(test)]
 pub(crate) mod testpe {
+    use super::ImportedFunction;
+
     pub(crate) const PE_OFFSET: usize = 0x80;
     pub(crate) const OPTIONAL_HEADER_SIZE: u16 = 0xF0;
     pub(crate) const SECTION_TABLE_OFFSET: usize = PE_OFFSET + 24 + OPTIONAL_HEADER_SIZE as usize;
@@ -319,6 +1726,8 @@ pub(crate) mod testpe {
     pub(crate) const SIZE_OF_OPTIONAL_HEADER_OFFSET: usize = PE_OFFSET + 20;
     pub(crate) const SECTION_VIRTUAL_ADDRESS: u32 = 0x1000;
     pub(crate) const SECTION_RAW_DATA_PTR: u32 = 0x200;
+    /// Covers every section VA the builders below use (up to 0x7000).
+    pub(crate) const SIZE_OF_IMAGE: u32 = 0x10000;
 
     pub(crate) struct BuiltPe {
         pub(crate) bytes: Vec<u8>,
@@ -346,6 +1755,12 @@ pub(crate) mod testpe {
         );
 
         write_u16(&mut bytes, OPTIONAL_HEADER_OFFSET, 0x020B);
+        write_u32(&mut bytes, OPTIONAL_HEADER_OFFSET + 56, SIZE_OF_IMAGE);
+        write_u32(
+            &mut bytes,
+            OPTIONAL_HEADER_OFFSET + 60,
+            SECTION_RAW_DATA_PTR,
+        );
         write_u32(
             &mut bytes,
             IMPORT_DIRECTORY_RVA_OFFSET,
@@ -378,41 +1793,390 @@ pub(crate) mod testpe {
             descriptor_offsets.push(descriptor_offset);
             name_offsets.push(string_cursor);
 
-            bytes[string_cursor..string_cursor + import.len()].copy_from_slice(import.as_bytes());
-            string_cursor += import.len();
-            bytes[string_cursor] = 0;
-            string_cursor += 1;
+            bytes[string_cursor..string_cursor + import.len()].copy_from_slice(import.as_bytes());
+            string_cursor += import.len();
+            bytes[string_cursor] = 0;
+            string_cursor += 1;
+        }
+
+        BuiltPe {
+            bytes,
+            descriptor_offsets,
+            name_offsets,
+        }
+    }
+
+    pub(crate) fn write_u16(bytes: &mut [u8], offset: usize, value: u16) {
+        bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Appends a `.didat` section holding RVA-based delay-load descriptors
+    /// for `delay_imports` to a PE built from the regular `imports`.
+    pub(crate) fn build_test_pe_with_delay_imports(
+        imports: &[&str],
+        delay_imports: &[&str],
+    ) -> Vec<u8> {
+        let base = build_test_pe(imports);
+        let mut bytes = base.bytes;
+        let didat_raw = bytes.len();
+        let didat_va = 0x3000u32;
+
+        let descriptor_bytes = (delay_imports.len() + 1) * 32;
+        let mut didat = vec![0u8; descriptor_bytes];
+        for (idx, name) in delay_imports.iter().enumerate() {
+            let name_rva = didat_va + didat.len() as u32;
+            write_u32(&mut didat, idx * 32, 1);
+            write_u32(&mut didat, idx * 32 + 4, name_rva);
+            didat.extend_from_slice(name.as_bytes());
+            didat.push(0);
+        }
+
+        let didat_len = didat.len();
+        bytes.extend_from_slice(&didat);
+
+        write_u16(&mut bytes, NUMBER_OF_SECTIONS_OFFSET, 2);
+        let s2 = SECTION_TABLE_OFFSET + 40;
+        bytes[s2..s2 + 6].copy_from_slice(b".didat");
+        write_u32(&mut bytes, s2 + 8, didat_len as u32);
+        write_u32(&mut bytes, s2 + 12, didat_va);
+        write_u32(&mut bytes, s2 + 16, didat_len as u32);
+        write_u32(&mut bytes, s2 + 20, didat_raw as u32);
+
+        write_u32(&mut bytes, DATA_DIR_START + 104, didat_va);
+        write_u32(&mut bytes, DATA_DIR_START + 108, descriptor_bytes as u32);
+        bytes
+    }
+
+    /// Builds a PE whose import descriptors carry an import name table in a
+    /// separate `.idata` section, one PE32+ thunk per listed function.
+    pub(crate) fn build_test_pe_with_import_functions(
+        imports: &[(&str, &[ImportedFunction])],
+    ) -> Vec<u8> {
+        let names: Vec<&str> = imports.iter().map(|(name, _)| *name).collect();
+        let base = build_test_pe(&names);
+        let mut bytes = base.bytes;
+        let idata_va = 0x4000u32;
+
+        let thunk_bytes: usize = imports
+            .iter()
+            .map(|(_, functions)| (functions.len() + 1) * 8)
+            .sum();
+        let mut idata = vec![0u8; thunk_bytes];
+        let mut thunk_off = 0usize;
+        for (idx, (_, functions)) in imports.iter().enumerate() {
+            write_u32(
+                &mut bytes,
+                base.descriptor_offsets[idx],
+                idata_va + thunk_off as u32,
+            );
+            for function in functions.iter() {
+                let thunk = match function {
+                    ImportedFunction::Ordinal(ordinal) => (1u64 << 63) | *ordinal as u64,
+                    ImportedFunction::Name(name) => {
+                        let by_name_rva = idata_va + idata.len() as u32;
+                        idata.extend_from_slice(&[0, 0]);
+                        idata.extend_from_slice(name.as_bytes());
+                        idata.push(0);
+                        by_name_rva as u64
+                    }
+                };
+                idata[thunk_off..thunk_off + 8].copy_from_slice(&thunk.to_le_bytes());
+                thunk_off += 8;
+            }
+            thunk_off += 8;
+        }
+
+        append_section(&mut bytes, b".idata", idata_va, &idata);
+        bytes
+    }
+
+    /// Builds a PE exporting `names` (ordinals `ordinal_base..`) from an
+    /// `.edata` section.
+    pub(crate) fn build_test_pe_with_exports(names: &[&str], ordinal_base: u32) -> Vec<u8> {
+        let exports: Vec<(&str, Option<&str>)> = names.iter().map(|name| (*name, None)).collect();
+        build_test_pe_with_forwarded_exports(&exports, ordinal_base)
+    }
+
+    /// Like `build_test_pe_with_exports`, but entries with a forwarder
+    /// string point their address slot at that string inside `.edata`.
+    pub(crate) fn build_test_pe_with_forwarded_exports(
+        exports: &[(&str, Option<&str>)],
+        ordinal_base: u32,
+    ) -> Vec<u8> {
+        let names: Vec<&str> = exports.iter().map(|(name, _)| *name).collect();
+        let mut bytes = build_test_pe(&[]).bytes;
+        let edata_va = 0x5000u32;
+
+        let functions_off = 40usize;
+        let names_off = functions_off + names.len() * 4;
+        let ordinals_off = names_off + names.len() * 4;
+        let mut edata = vec![0u8; ordinals_off + names.len() * 2];
+        write_u32(&mut edata, 16, ordinal_base);
+        write_u32(&mut edata, 20, names.len() as u32);
+        write_u32(&mut edata, 24, names.len() as u32);
+        write_u32(&mut edata, 28, edata_va + functions_off as u32);
+        write_u32(&mut edata, 32, edata_va + names_off as u32);
+        write_u32(&mut edata, 36, edata_va + ordinals_off as u32);
+        for (idx, name) in names.iter().enumerate() {
+            // Code exports point outside the export directory, forwarders
+            // at their string inside it.
+            let function_rva = match exports[idx].1 {
+                Some(forward) => {
+                    let forward_rva = edata_va + edata.len() as u32;
+                    edata.extend_from_slice(forward.as_bytes());
+                    edata.push(0);
+                    forward_rva
+                }
+                None => SECTION_VIRTUAL_ADDRESS,
+            };
+            write_u32(&mut edata, functions_off + idx * 4, function_rva);
+            let name_rva = edata_va + edata.len() as u32;
+            write_u32(&mut edata, names_off + idx * 4, name_rva);
+            write_u16(&mut edata, ordinals_off + idx * 2, idx as u16);
+            edata.extend_from_slice(name.as_bytes());
+            edata.push(0);
+        }
+
+        append_section(&mut bytes, b".edata", edata_va, &edata);
+        write_u32(&mut bytes, DATA_DIR_START, edata_va);
+        write_u32(&mut bytes, DATA_DIR_START + 4, edata.len() as u32);
+        bytes
+    }
+
+    /// Appends `contents` as the next section header/raw-data pair. The
+    /// header gap in `build_test_pe` leaves room for one extra section.
+    fn append_section(bytes: &mut Vec<u8>, name: &[u8], virtual_address: u32, contents: &[u8]) {
+        let raw_offset = bytes.len();
+        let index = u16::from_le_bytes([
+            bytes[NUMBER_OF_SECTIONS_OFFSET],
+            bytes[NUMBER_OF_SECTIONS_OFFSET + 1],
+        ]);
+        bytes.extend_from_slice(contents);
+
+        write_u16(bytes, NUMBER_OF_SECTIONS_OFFSET, index + 1);
+        let header = SECTION_TABLE_OFFSET + index as usize * 40;
+        bytes[header..header + name.len()].copy_from_slice(name);
+        write_u32(bytes, header + 8, contents.len() as u32);
+        write_u32(bytes, header + 12, virtual_address);
+        write_u32(bytes, header + 16, contents.len() as u32);
+        write_u32(bytes, header + 20, raw_offset as u32);
+    }
+
+    /// Import-free image with the given COFF machine; `hybrid` appends a
+    /// `.rdata2` section holding a 64-bit load config whose CHPE metadata
+    /// pointer is set.
+    pub(crate) fn build_test_pe_with_machine(machine: u16, hybrid: bool) -> Vec<u8> {
+        let mut bytes = build_test_pe(&[]).bytes;
+        write_u16(&mut bytes, PE_OFFSET + 4, machine);
+        if hybrid {
+            let load_config_va = 0x6000u32;
+            let mut load_config = vec![0u8; 0xD0];
+            write_u32(&mut load_config, 0, 0xD0);
+            load_config[0xC8..0xD0].copy_from_slice(&0x1_4000_7000u64.to_le_bytes());
+            append_section(&mut bytes, b".rdata2", load_config_va, &load_config);
+            write_u32(&mut bytes, DATA_DIR_START + 80, load_config_va);
+            write_u32(&mut bytes, DATA_DIR_START + 84, 0xD0);
+        }
+        bytes
+    }
+
+    /// Import-free image with one extra section holding `contents`.
+    pub(crate) fn build_test_pe_with_section(name: &[u8], contents: &[u8]) -> Vec<u8> {
+        let mut bytes = build_test_pe(&[]).bytes;
+        append_section(&mut bytes, name, 0x7000, contents);
+        bytes
+    }
+
+    /// Encodes a VS_VERSIONINFO tree: optional fixed file/product versions
+    /// (as 64-bit MS:LS pairs) plus a single StringFileInfo table.
+    pub(crate) fn build_version_resource(
+        fixed: Option<(u64, u64)>,
+        strings: &[(&str, &str)],
+    ) -> Vec<u8> {
+        let mut fixed_info = Vec::new();
+        if let Some((file, product)) = fixed {
+            fixed_info = vec![0u8; 52];
+            write_u32(&mut fixed_info, 0, 0xFEEF_04BD);
+            write_u32(&mut fixed_info, 4, 0x0001_0000);
+            write_u32(&mut fixed_info, 8, (file >> 32) as u32);
+            write_u32(&mut fixed_info, 12, file as u32);
+            write_u32(&mut fixed_info, 16, (product >> 32) as u32);
+            write_u32(&mut fixed_info, 20, product as u32);
         }
 
-        BuiltPe {
-            bytes,
-            descriptor_offsets,
-            name_offsets,
+        let mut entries = Vec::new();
+        for (key, value) in strings {
+            let mut text = Vec::new();
+            for unit in value.encode_utf16().chain(std::iter::once(0)) {
+                text.extend_from_slice(&unit.to_le_bytes());
+            }
+            let units = text.len() / 2;
+            entries.extend(version_block(key, 1, units, &text, &[]));
         }
+        let table = version_block("040904b0", 1, 0, &[], &entries);
+        let string_file_info = version_block("StringFileInfo", 1, 0, &[], &table);
+        version_block(
+            "VS_VERSION_INFO",
+            0,
+            fixed_info.len(),
+            &fixed_info,
+            &string_file_info,
+        )
     }
 
-    pub(crate) fn write_u16(bytes: &mut [u8], offset: usize, value: u16) {
-        bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    fn version_block(
+        key: &str,
+        value_type: u16,
+        value_length: usize,
+        value: &[u8],
+        children: &[u8],
+    ) -> Vec<u8> {
+        let mut block = vec![0u8; 6];
+        for unit in key.encode_utf16().chain(std::iter::once(0)) {
+            block.extend_from_slice(&unit.to_le_bytes());
+        }
+        block.resize(block.len().next_multiple_of(4), 0);
+        block.extend_from_slice(value);
+        block.resize(block.len().next_multiple_of(4), 0);
+        block.extend_from_slice(children);
+        let length = block.len();
+        write_u16(&mut block, 0, length as u16);
+        write_u16(&mut block, 2, value_length as u16);
+        write_u16(&mut block, 4, value_type);
+        block.resize(block.len().next_multiple_of(4), 0);
+        block
     }
 
-    pub(crate) fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
-        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    /// Appends `pkcs7` as a WIN_CERTIFICATE (PKCS_SIGNED_DATA) and points the
+    /// certificate table directory at it.
+    pub(crate) fn build_test_pe_with_signature(pkcs7: &[u8]) -> Vec<u8> {
+        let mut bytes = build_test_pe(&[]).bytes;
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        let offset = bytes.len();
+        let length = 8 + pkcs7.len();
+        bytes.extend_from_slice(&(length as u32).to_le_bytes());
+        bytes.extend_from_slice(&0x0200u16.to_le_bytes());
+        bytes.extend_from_slice(&0x0002u16.to_le_bytes());
+        bytes.extend_from_slice(pkcs7);
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        write_u32(&mut bytes, DATA_DIR_START + 32, offset as u32);
+        let table_len = bytes.len() - offset;
+        write_u32(&mut bytes, DATA_DIR_START + 36, table_len as u32);
+        bytes
+    }
+
+    pub(crate) fn der(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match body.len() {
+            len @ 0..=0x7F => out.push(len as u8),
+            len @ 0x80..=0xFF => out.extend_from_slice(&[0x81, len as u8]),
+            len => out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+        }
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// Encodes an X.501 Name from (attribute OID body, value) pairs, most
+    /// general RDN first as certificates store them.
+    pub(crate) fn der_name(attributes: &[(&[u8], &str)]) -> Vec<u8> {
+        let mut rdns = Vec::new();
+        for (oid, value) in attributes {
+            let attribute = [der(0x06, oid), der(0x0C, value.as_bytes())].concat();
+            rdns.extend(der(0x31, &der(0x30, &attribute)));
+        }
+        der(0x30, &rdns)
+    }
+
+    pub(crate) const OID_CN: &[u8] = &[0x55, 0x04, 0x03];
+    pub(crate) const OID_O: &[u8] = &[0x55, 0x04, 0x0A];
+    pub(crate) const OID_C: &[u8] = &[0x55, 0x04, 0x06];
+
+    /// Builds a PKCS#7 SignedData blob whose single SignerInfo names the
+    /// signer by issuer and serial; the signer certificate is embedded only
+    /// when `embed_certificate` is set.
+    pub(crate) fn build_signed_data(
+        subject: &[u8],
+        issuer: &[u8],
+        embed_certificate: bool,
+    ) -> Vec<u8> {
+        let serial = der(0x02, &[0x01, 0x23]);
+        let algorithm = der(0x30, &der(0x06, &[0x2B, 0x0E, 0x03, 0x02, 0x1A]));
+        let tbs = der(
+            0x30,
+            &[
+                der(0xA0, &der(0x02, &[0x02])),
+                serial.clone(),
+                algorithm.clone(),
+                issuer.to_vec(),
+                der(0x30, &[]),
+                subject.to_vec(),
+                der(0x30, &[]),
+            ]
+            .concat(),
+        );
+        let certificate = der(0x30, &[tbs, algorithm.clone(), der(0x03, &[0x00])].concat());
+        let signer_info = der(
+            0x30,
+            &[
+                der(0x02, &[0x01]),
+                der(0x30, &[issuer.to_vec(), serial].concat()),
+                algorithm.clone(),
+            ]
+            .concat(),
+        );
+
+        let mut signed_data = [
+            der(0x02, &[0x01]),
+            der(0x31, &algorithm),
+            der(
+                0x30,
+                &der(
+                    0x06,
+                    &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x02, 0x01, 0x04],
+                ),
+            ),
+        ]
+        .concat();
+        if embed_certificate {
+            signed_data.extend(der(0xA0, &certificate));
+        }
+        signed_data.extend(der(0x31, &signer_info));
+
+        der(
+            0x30,
+            &[
+                der(
+                    0x06,
+                    &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02],
+                ),
+                der(0xA0, &der(0x30, &signed_data)),
+            ]
+            .concat(),
+        )
     }
 
     pub(crate) fn build_test_pe_with_manifest(xml_payload: &[u8]) -> Vec<u8> {
+        build_test_pe_with_resource(24, xml_payload)
+    }
+
+    pub(crate) fn build_test_pe_with_resource(type_id: u32, payload: &[u8]) -> Vec<u8> {
         let base = build_test_pe(&[]);
         let mut bytes = base.bytes;
         let rsrc_raw = bytes.len();
         let rsrc_va = 0x2000u32;
 
         // Resource section layout (offsets relative to section start):
-        //   0x00 root dir -> RT_MANIFEST subdir at 0x18
+        //   0x00 root dir -> type subdir at 0x18
         //   0x18 name dir -> language subdir at 0x30
         //   0x30 lang dir -> data entry at 0x48
         //   0x48 data entry -> payload at 0x58
         let mut rsrc = vec![0u8; 0x58];
         write_u16(&mut rsrc, 14, 1);
-        write_u32(&mut rsrc, 16, 24);
+        write_u32(&mut rsrc, 16, type_id);
         write_u32(&mut rsrc, 20, 0x8000_0000 | 0x18);
         write_u16(&mut rsrc, 0x18 + 14, 1);
         write_u32(&mut rsrc, 0x18 + 16, 1);
@@ -421,8 +2185,8 @@ pub(crate) mod testpe {
         write_u32(&mut rsrc, 0x30 + 16, 0x409);
         write_u32(&mut rsrc, 0x30 + 20, 0x48);
         write_u32(&mut rsrc, 0x48, rsrc_va + 0x58);
-        write_u32(&mut rsrc, 0x48 + 4, xml_payload.len() as u32);
-        rsrc.extend_from_slice(xml_payload);
+        write_u32(&mut rsrc, 0x48 + 4, payload.len() as u32);
+        rsrc.extend_from_slice(payload);
 
         let rsrc_len = rsrc.len();
         bytes.extend_from_slice(&rsrc);
@@ -465,6 +2229,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn machine_type_reports_arm64_family() {
+        let cases = [
+            (0xAA64, false, MachineType::Arm64),
+            (0xAA64, true, MachineType::Arm64x),
+            (0x8664, true, MachineType::Arm64ec),
+            (0x8664, false, MachineType::X64),
+        ];
+        for (machine, hybrid, expected) in cases {
+            let bytes = build_test_pe_with_machine(machine, hybrid);
+            assert_eq!(machine_type_from_bytes(&bytes).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn machine_type_ignores_load_config_too_short_for_chpe_pointer() {
+        let mut bytes = build_test_pe_with_machine(0xAA64, true);
+        let offset = bytes.len() - 0xD0;
+        write_u32(&mut bytes, offset, 0x94);
+        assert_eq!(machine_type_from_bytes(&bytes).unwrap(), MachineType::Arm64);
+    }
+
+    #[test]
+    fn compatibility_matrix_models_arm64ec_and_arm64x() {
+        use MachineType::*;
+        assert!(Arm64ec.can_load(X64));
+        assert!(Arm64ec.can_load(Arm64x));
+        assert!(!Arm64ec.can_load(Arm64));
+        assert!(X64.can_load(Arm64ec));
+        assert!(Arm64.can_load(Arm64x));
+        assert!(!Arm64.can_load(X64));
+        assert!(Arm64x.can_load(Arm64));
+        assert!(!X86.can_load(X64));
+        assert!(!X64.can_load(X86));
+        assert!(Unknown.can_load(Arm64));
+        assert!(!X64.can_load(Unknown));
+    }
+
     #[test]
     fn machine_type_reports_unknown_for_other_values() {
         let mut pe = build_test_pe(&[]);
@@ -522,6 +2324,231 @@ mod tests {
         );
     }
 
+    #[test]
+    fn version_info_returns_none_without_resource_section() {
+        let pe = build_test_pe(&[]);
+        assert_eq!(version_info_from_bytes(&pe.bytes), None);
+    }
+
+    #[test]
+    fn version_info_prefers_fixed_versions_and_reads_company() {
+        let resource = build_version_resource(
+            Some((0x000A_0000_4A61_0001, 0x000A_0000_4A61_0000)),
+            &[
+                ("CompanyName", "Contoso Ltd."),
+                ("FileVersion", "10.0 (free-form)"),
+            ],
+        );
+        let bytes = build_test_pe_with_resource(16, &resource);
+        assert_eq!(
+            version_info_from_bytes(&bytes),
+            Some(VersionInfo {
+                file_version: Some("10.0.19041.1".to_string()),
+                product_version: Some("10.0.19041.0".to_string()),
+                company: Some("Contoso Ltd.".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn version_info_falls_back_to_string_table_versions() {
+        let resource = build_version_resource(
+            None,
+            &[("FileVersion", "2.1"), ("ProductVersion", " 2.1 beta ")],
+        );
+        let bytes = build_test_pe_with_resource(16, &resource);
+        let info = version_info_from_bytes(&bytes).expect("version info");
+        assert_eq!(info.file_version.as_deref(), Some("2.1"));
+        assert_eq!(info.product_version.as_deref(), Some("2.1 beta"));
+        assert_eq!(info.company, None);
+    }
+
+    #[test]
+    fn version_info_ignores_damaged_resource() {
+        let mut resource = build_version_resource(Some((1, 1)), &[]);
+        let oversized = (resource.len() + 64) as u16;
+        write_u16(&mut resource, 0, oversized);
+        let bytes = build_test_pe_with_resource(16, &resource);
+        assert_eq!(version_info_from_bytes(&bytes), None);
+
+        let manifest_only = build_test_pe_with_manifest(b"<assembly/>");
+        assert_eq!(version_info_from_bytes(&manifest_only), None);
+    }
+
+    fn contoso_names() -> (Vec<u8>, Vec<u8>) {
+        let subject = der_name(&[
+            (OID_C, "US"),
+            (OID_O, "Contoso Ltd"),
+            (OID_CN, "Contoso Ltd"),
+        ]);
+        let issuer = der_name(&[
+            (OID_C, "US"),
+            (OID_O, "Contoso CA"),
+            (OID_CN, "Contoso Code Signing CA"),
+        ]);
+        (subject, issuer)
+    }
+
+    #[test]
+    fn signature_is_absent_without_certificate_table() {
+        let pe = build_test_pe(&[]);
+        assert_eq!(
+            signature_from_bytes(&pe.bytes),
+            Some(SignatureInfo::default())
+        );
+        assert_eq!(signature_from_bytes(b"not a pe"), None);
+    }
+
+    #[test]
+    fn signature_reports_embedded_signer_certificate() {
+        let (subject, issuer) = contoso_names();
+        let bytes = build_test_pe_with_signature(&build_signed_data(&subject, &issuer, true));
+        assert_eq!(
+            signature_from_bytes(&bytes),
+            Some(SignatureInfo {
+                signed: true,
+                subject: Some("CN=Contoso Ltd, O=Contoso Ltd, C=US".to_string()),
+                issuer: Some("CN=Contoso Code Signing CA, O=Contoso CA, C=US".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn signature_without_embedded_certificate_reports_issuer_only() {
+        let (subject, issuer) = contoso_names();
+        let bytes = build_test_pe_with_signature(&build_signed_data(&subject, &issuer, false));
+        let info = signature_from_bytes(&bytes).expect("signature info");
+        assert!(info.signed);
+        assert_eq!(info.subject, None);
+        assert_eq!(
+            info.issuer.as_deref(),
+            Some("CN=Contoso Code Signing CA, O=Contoso CA, C=US")
+        );
+    }
+
+    #[test]
+    fn damaged_signed_data_still_reports_signature_present() {
+        let bytes = build_test_pe_with_signature(&[0x30, 0x82, 0xFF, 0xFF, 0x06]);
+        assert_eq!(
+            signature_from_bytes(&bytes),
+            Some(SignatureInfo {
+                signed: true,
+                subject: None,
+                issuer: None,
+            })
+        );
+    }
+
+    #[test]
+    fn certificate_table_past_end_of_file_is_unsigned() {
+        let mut bytes = build_test_pe(&[]).bytes;
+        let len = bytes.len() as u32;
+        write_u32(&mut bytes, DATA_DIR_START + 32, len);
+        write_u32(&mut bytes, DATA_DIR_START + 36, 0x100);
+        assert_eq!(signature_from_bytes(&bytes), Some(SignatureInfo::default()));
+    }
+
+    #[test]
+    fn name_formatting_labels_unknown_attributes_by_oid() {
+        let name = der_name(&[(&[0x55, 0x04, 0x05], "12345"), (OID_CN, "Fabrikam")]);
+        let (value, _) = der_read(&name).expect("name");
+        assert_eq!(
+            format_name(value.body).as_deref(),
+            Some("CN=Fabrikam, OID.2.5.4.5=12345")
+        );
+    }
+
+    #[test]
+    fn validation_accepts_well_formed_image() {
+        let pe = build_test_pe(&["kernel32.dll"]);
+        assert_eq!(validate_image_bytes(&pe.bytes), Ok(MachineType::X64));
+        let sectioned = build_test_pe_with_section(b".extra", &[0xCC; 16]);
+        assert_eq!(validate_image_bytes(&sectioned), Ok(MachineType::X64));
+    }
+
+    #[test]
+    fn validation_distinguishes_empty_foreign_and_truncated_files() {
+        assert_eq!(validate_image_bytes(&[]), Err(ImageDefect::ZeroLength));
+        assert_eq!(
+            validate_image_bytes(b"this is not a PE image"),
+            Err(ImageDefect::NotPe)
+        );
+
+        let pe = build_test_pe(&["kernel32.dll"]).bytes;
+        assert_eq!(
+            validate_image_bytes(&pe[..0x30]),
+            Err(ImageDefect::Truncated)
+        );
+        assert_eq!(
+            validate_image_bytes(&pe[..0x100]),
+            Err(ImageDefect::Truncated)
+        );
+        // Headers intact, section data cut short: an interrupted copy.
+        assert_eq!(
+            validate_image_bytes(&pe[..pe.len() - 1]),
+            Err(ImageDefect::Truncated)
+        );
+
+        let mut no_signature = pe.clone();
+        no_signature[PE_OFFSET..PE_OFFSET + 4].copy_from_slice(b"NE\0\0");
+        assert_eq!(validate_image_bytes(&no_signature), Err(ImageDefect::NotPe));
+    }
+
+    #[test]
+    fn validation_rejects_inconsistent_header_sizes() {
+        let mut bytes = build_test_pe(&[]).bytes;
+        write_u32(&mut bytes, OPTIONAL_HEADER_OFFSET + 60, 0x100);
+        assert_eq!(
+            validate_image_bytes(&bytes),
+            Err(ImageDefect::CorruptHeaders)
+        );
+
+        let mut bytes = build_test_pe(&[]).bytes;
+        write_u32(&mut bytes, OPTIONAL_HEADER_OFFSET + 56, 0x100);
+        assert_eq!(
+            validate_image_bytes(&bytes),
+            Err(ImageDefect::CorruptHeaders)
+        );
+
+        let mut bytes = build_test_pe(&[]).bytes;
+        write_u16(&mut bytes, OPTIONAL_HEADER_OFFSET, 0x0107);
+        assert_eq!(
+            validate_image_bytes(&bytes),
+            Err(ImageDefect::CorruptHeaders)
+        );
+    }
+
+    #[test]
+    fn validation_rejects_optional_header_machine_mismatch() {
+        let mut bytes = build_test_pe(&[]).bytes;
+        write_u16(&mut bytes, PE_OFFSET + 4, 0x014C);
+        assert_eq!(
+            validate_image_bytes(&bytes),
+            Err(ImageDefect::HeaderMachineMismatch)
+        );
+    }
+
+    #[test]
+    fn validation_rejects_overlapping_or_oversized_sections() {
+        let mut overlapping = build_test_pe_with_section(b".extra", &[0xCC; 16]);
+        write_u32(
+            &mut overlapping,
+            SECTION_TABLE_OFFSET + 40 + 12,
+            SECTION_VIRTUAL_ADDRESS,
+        );
+        assert_eq!(
+            validate_image_bytes(&overlapping),
+            Err(ImageDefect::CorruptSections)
+        );
+
+        let mut oversized = build_test_pe(&[]).bytes;
+        write_u32(&mut oversized, OPTIONAL_HEADER_OFFSET + 56, 0x1000);
+        assert_eq!(
+            validate_image_bytes(&oversized),
+            Err(ImageDefect::CorruptSections)
+        );
+    }
+
     #[test]
     fn rejects_file_too_small_for_dos_header() {
         assert_eq!(
@@ -605,6 +2632,7 @@ mod tests {
     #[test]
     fn rva_to_offset_returns_none_outside_sections() {
         let sections = vec![Section {
+            name: [0; 8],
             virtual_address: 0x1000,
             virtual_size: 0x200,
             raw_data_ptr: 0x400,
@@ -616,6 +2644,7 @@ mod tests {
     #[test]
     fn rva_to_offset_uses_max_of_virtual_and_raw_size() {
         let sections = vec![Section {
+            name: [0; 8],
             virtual_address: 0x1000,
             virtual_size: 0x20,
             raw_data_ptr: 0x400,
@@ -682,6 +2711,290 @@ mod tests {
         );
     }
 
+    #[test]
+    fn delay_imports_are_parsed_from_delay_directory() {
+        let bytes = build_test_pe_with_delay_imports(&["kernel32.dll"], &["Vendor.dll", "a.dll"]);
+        assert_eq!(
+            delay_imports_from_bytes(&bytes).unwrap(),
+            vec!["a.dll".to_string(), "vendor.dll".to_string()]
+        );
+        assert_eq!(
+            direct_imports_from_bytes(&bytes).unwrap(),
+            vec!["kernel32.dll".to_string()]
+        );
+    }
+
+    #[test]
+    fn delay_imports_are_empty_without_delay_directory() {
+        let pe = build_test_pe(&["a.dll"]);
+        assert_eq!(
+            delay_imports_from_bytes(&pe.bytes).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn delay_import_descriptors_without_rva_flag_use_virtual_addresses() {
+        let mut bytes = build_test_pe_with_delay_imports(&[], &["old.dll"]);
+        let didat_raw = build_test_pe(&[]).bytes.len();
+        let image_base = 0x0040_0000u64;
+        bytes[OPTIONAL_HEADER_OFFSET + 24..OPTIONAL_HEADER_OFFSET + 32]
+            .copy_from_slice(&image_base.to_le_bytes());
+        let name_rva = read_u32(&bytes[..], didat_raw + 4).unwrap();
+        write_u32(&mut bytes, didat_raw, 0);
+        write_u32(&mut bytes, didat_raw + 4, name_rva + 0x0040_0000);
+        assert_eq!(
+            delay_imports_from_bytes(&bytes).unwrap(),
+            vec!["old.dll".to_string()]
+        );
+    }
+
+    #[test]
+    fn module_dependencies_flag_delay_only_imports() {
+        let bytes =
+            build_test_pe_with_delay_imports(&["shared.dll", "b.dll"], &["shared.dll", "opt.dll"]);
+        assert_eq!(
+            module_dependencies_from_bytes(&bytes).unwrap(),
+            vec![
+                ImportedDll {
+                    name: "b.dll".to_string(),
+                    delay: false,
+                    functions: Vec::new(),
+                },
+                ImportedDll {
+                    name: "shared.dll".to_string(),
+                    delay: false,
+                    functions: Vec::new(),
+                },
+                ImportedDll {
+                    name: "opt.dll".to_string(),
+                    delay: true,
+                    functions: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn module_dependencies_ignore_damaged_delay_table() {
+        let mut bytes = build_test_pe_with_delay_imports(&["a.dll"], &["opt.dll"]);
+        write_u32(&mut bytes, DATA_DIR_START + 104, 0x9000);
+        assert_eq!(
+            module_dependencies_from_bytes(&bytes).unwrap(),
+            vec![ImportedDll {
+                name: "a.dll".to_string(),
+                delay: false,
+                functions: Vec::new(),
+            }]
+        );
+    }
+
+    fn name(value: &str) -> ImportedFunction {
+        ImportedFunction::Name(value.to_string())
+    }
+
+    #[test]
+    fn import_functions_are_parsed_by_name_and_ordinal() {
+        let bytes = build_test_pe_with_import_functions(&[
+            ("b.dll", &[name("Zeta"), ImportedFunction::Ordinal(7)]),
+            ("A.dll", &[name("Alpha")]),
+        ]);
+        assert_eq!(
+            module_dependencies_from_bytes(&bytes).unwrap(),
+            vec![
+                ImportedDll {
+                    name: "a.dll".to_string(),
+                    delay: false,
+                    functions: vec![name("Alpha")],
+                },
+                ImportedDll {
+                    name: "b.dll".to_string(),
+                    delay: false,
+                    functions: vec![name("Zeta"), ImportedFunction::Ordinal(7)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn import_functions_merge_across_duplicate_descriptors() {
+        let bytes = build_test_pe_with_import_functions(&[
+            ("a.dll", &[name("Two"), name("One")]),
+            ("A.DLL", &[name("One"), name("Three")]),
+        ]);
+        let deps = module_dependencies_from_bytes(&bytes).unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(
+            deps[0].functions,
+            vec![name("One"), name("Three"), name("Two")]
+        );
+    }
+
+    #[test]
+    fn import_functions_fall_back_to_unbound_iat() {
+        let mut bytes = build_test_pe_with_import_functions(&[("a.dll", &[name("Alpha")])]);
+        let descriptor = SECTION_RAW_DATA_PTR as usize;
+        let int_rva = read_u32(&bytes[..], descriptor).unwrap();
+        write_u32(&mut bytes, descriptor, 0);
+        write_u32(&mut bytes, descriptor + 16, int_rva);
+        assert_eq!(
+            module_dependencies_from_bytes(&bytes).unwrap()[0].functions,
+            vec![name("Alpha")]
+        );
+    }
+
+    #[test]
+    fn import_functions_are_unknown_for_bound_iat_without_name_table() {
+        let mut bytes = build_test_pe_with_import_functions(&[("a.dll", &[name("Alpha")])]);
+        let descriptor = SECTION_RAW_DATA_PTR as usize;
+        let int_rva = read_u32(&bytes[..], descriptor).unwrap();
+        write_u32(&mut bytes, descriptor, 0);
+        write_u32(&mut bytes, descriptor + 4, 0xFFFF_FFFF);
+        write_u32(&mut bytes, descriptor + 16, int_rva);
+        assert!(module_dependencies_from_bytes(&bytes).unwrap()[0]
+            .functions
+            .is_empty());
+    }
+
+    #[test]
+    fn damaged_thunk_array_leaves_functions_unknown() {
+        let mut bytes = build_test_pe_with_import_functions(&[("a.dll", &[name("Alpha")])]);
+        write_u32(&mut bytes, SECTION_RAW_DATA_PTR as usize, 0x9000);
+        assert_eq!(
+            module_dependencies_from_bytes(&bytes).unwrap(),
+            vec![ImportedDll {
+                name: "a.dll".to_string(),
+                delay: false,
+                functions: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn exports_are_parsed_by_name_and_ordinal() {
+        let bytes = build_test_pe_with_exports(&["Alpha", "Beta"], 5);
+        let table = exports_from_bytes(&bytes).unwrap();
+        assert!(table.provides(&name("Alpha")));
+        assert!(table.provides(&name("Beta")));
+        assert!(table.provides(&ImportedFunction::Ordinal(5)));
+        assert!(table.provides(&ImportedFunction::Ordinal(6)));
+        assert!(!table.provides(&ImportedFunction::Ordinal(7)));
+        assert!(!table.provides(&name("Gamma")));
+    }
+
+    #[test]
+    fn export_name_lookup_is_case_sensitive() {
+        let bytes = build_test_pe_with_exports(&["Alpha"], 1);
+        let table = exports_from_bytes(&bytes).unwrap();
+        assert!(!table.provides(&name("alpha")));
+    }
+
+    #[test]
+    fn exports_are_empty_without_export_directory() {
+        let pe = build_test_pe(&[]);
+        assert_eq!(
+            exports_from_bytes(&pe.bytes).unwrap(),
+            ExportTable::default()
+        );
+    }
+
+    #[test]
+    fn rejects_export_table_rva_that_cannot_be_mapped() {
+        let mut bytes = build_test_pe_with_exports(&["Alpha"], 1);
+        write_u32(&mut bytes, DATA_DIR_START, 0x9000);
+        assert_eq!(
+            exports_from_bytes(&bytes).unwrap_err(),
+            "invalid export table RVA"
+        );
+    }
+
+    #[test]
+    fn rejects_export_address_table_past_end_of_file() {
+        let mut bytes = build_test_pe_with_exports(&["Alpha"], 1);
+        let edata = build_test_pe(&[]).bytes.len();
+        write_u32(&mut bytes, edata + 20, 0x0100_0000);
+        assert_eq!(
+            exports_from_bytes(&bytes).unwrap_err(),
+            "invalid export address table"
+        );
+    }
+
+    #[test]
+    fn rejects_export_counts_larger_than_the_file() {
+        let edata = build_test_pe(&[]).bytes.len();
+        let mut bytes = build_test_pe_with_exports(&["Alpha"], 1);
+        write_u32(&mut bytes, edata + 20, u32::MAX);
+        assert_eq!(
+            exports_from_bytes(&bytes).unwrap_err(),
+            "invalid export address table"
+        );
+
+        let mut bytes = build_test_pe_with_exports(&["Alpha"], 1);
+        write_u32(&mut bytes, edata + 24, u32::MAX);
+        assert_eq!(
+            exports_from_bytes(&bytes).unwrap_err(),
+            "invalid export name table"
+        );
+    }
+
+    #[test]
+    fn forwarded_exports_resolve_by_name_and_ordinal() {
+        let bytes = build_test_pe_with_forwarded_exports(
+            &[
+                ("Plain", None),
+                ("HeapAlloc", Some("NTDLL.RtlAllocateHeap")),
+                ("ByOrdinal", Some("Vendor.#12")),
+            ],
+            1,
+        );
+        let table = exports_from_bytes(&bytes).unwrap();
+        assert!(table.provides(&name("HeapAlloc")));
+        assert_eq!(table.forward_for(&name("Plain")), None);
+        assert_eq!(table.forward_for(&ImportedFunction::Ordinal(1)), None);
+
+        let expected = ExportForward {
+            raw: "NTDLL.RtlAllocateHeap".to_string(),
+            dll: "ntdll.dll".to_string(),
+            function: name("RtlAllocateHeap"),
+        };
+        assert_eq!(table.forward_for(&name("HeapAlloc")), Some(&expected));
+        assert_eq!(
+            table.forward_for(&ImportedFunction::Ordinal(2)),
+            Some(&expected)
+        );
+        assert_eq!(
+            table.forward_for(&name("ByOrdinal")).map(|f| &f.function),
+            Some(&ImportedFunction::Ordinal(12))
+        );
+    }
+
+    #[test]
+    fn api_set_forwarders_split_on_last_dot() {
+        let bytes = build_test_pe_with_forwarded_exports(
+            &[(
+                "CoCreateInstance",
+                Some("api-ms-win-core-com-l1-1-0.CoCreateInstance"),
+            )],
+            1,
+        );
+        let table = exports_from_bytes(&bytes).unwrap();
+        let forward = table.forward_for(&name("CoCreateInstance")).unwrap();
+        assert_eq!(forward.dll, "api-ms-win-core-com-l1-1-0.dll");
+        assert_eq!(forward.function, name("CoCreateInstance"));
+    }
+
+    #[test]
+    fn malformed_forwarder_strings_are_treated_as_plain_exports() {
+        let bytes = build_test_pe_with_forwarded_exports(
+            &[("NoDot", Some("nodot")), ("BadOrdinal", Some("x.#abc"))],
+            1,
+        );
+        let table = exports_from_bytes(&bytes).unwrap();
+        assert!(table.provides(&name("NoDot")));
+        assert_eq!(table.forward_for(&name("NoDot")), None);
+        assert_eq!(table.forward_for(&name("BadOrdinal")), None);
+    }
+
     #[test]
     fn returned_imports_are_lexicographically_ordered() {
         let pe = build_test_pe(&["kernel32.dll", "a.dll", "z.dll"]);
@@ -694,4 +3007,114 @@ mod tests {
             ]
         );
     }
+
+    fn unique_temp_path(name: &str) -> std::path::PathBuf {
+        static NEXT_TEST_ID: std::sync::atomic::AtomicUsize =
+            std::sync::atomic::AtomicUsize::new(0);
+        let id = NEXT_TEST_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        std::env::temp_dir().join(format!(
+            "loadwhat-pe-{name}-{}-{id}.dll",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn a_large_image_is_read_only_where_its_directories_are() {
+        let path = unique_temp_path("large");
+        // Move the only section 1 MiB into the file, past the header read,
+        // and pad as much again after it.
+        let pe = build_test_pe(&["a.dll"]).bytes;
+        let raw = SECTION_RAW_DATA_PTR as usize;
+        let gap = 1 << 20;
+        let mut bytes = pe[..raw].to_vec();
+        bytes.resize(raw + gap, 0);
+        bytes.extend_from_slice(&pe[raw..]);
+        bytes.resize(bytes.len() + gap, 0);
+        write_u32(&mut bytes, SECTION_TABLE_OFFSET + 20, (raw + gap) as u32);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let image = PeImage::open(&path).unwrap();
+        assert_eq!(image.validate(), Ok(MachineType::X64));
+        assert_eq!(image.signature(), Some(&SignatureInfo::default()));
+        let headers = image.bytes_read();
+        let span = header_span(&bytes).unwrap();
+        assert!(headers <= span.max(HEADER_READ_SIZE));
+        assert!(headers * 100 < bytes.len());
+
+        // Directory walks read only the sections they reach, never the
+        // rest of the file. Only the import directory is present here.
+        assert_eq!(image.dependencies().unwrap()[0].name, "a.dll");
+        image.exports().unwrap();
+        assert_eq!(image.embedded_manifest(), None);
+        assert_eq!(image.version_info(), None);
+        let walked = image.bytes_read();
+        assert_eq!(walked, headers + (pe.len() - raw));
+        image.dependencies().unwrap();
+        assert_eq!(image.bytes_read(), walked);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reopening_a_cached_image_reads_nothing_more() {
+        let path = unique_temp_path("reopen");
+        std::fs::write(&path, build_test_pe(&["a.dll"]).bytes).unwrap();
+
+        let _scope = ImageCacheScope::enter();
+        let first = open_image(&path).unwrap();
+        first.validate().unwrap();
+        first.dependencies().unwrap();
+        let read = first.bytes_read();
+
+        let second = open_image(&path).unwrap();
+        assert!(Rc::ptr_eq(&first, &second));
+        second.validate().unwrap();
+        second.dependencies().unwrap();
+        second.exports().unwrap();
+        assert_eq!(second.bytes_read(), read);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn cache_keys_keep_case_on_case_sensitive_hosts() {
+        let dir = unique_temp_path("case");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("A.dll"), build_test_pe(&["upper.dll"]).bytes).unwrap();
+        std::fs::write(dir.join("a.dll"), build_test_pe(&["lower.dll"]).bytes).unwrap();
+
+        let _scope = ImageCacheScope::enter();
+        let upper = open_image(&dir.join("A.dll")).unwrap();
+        let lower = open_image(&dir.join("a.dll")).unwrap();
+        assert!(!Rc::ptr_eq(&upper, &lower));
+        assert_eq!(upper.dependencies().unwrap()[0].name, "upper.dll");
+        assert_eq!(lower.dependencies().unwrap()[0].name, "lower.dll");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn open_image_shares_images_only_within_a_cache_scope() {
+        let path = unique_temp_path("cached");
+        std::fs::write(&path, build_test_pe(&["a.dll"]).bytes).unwrap();
+
+        let first = open_image(&path).unwrap();
+        assert!(!Rc::ptr_eq(&first, &open_image(&path).unwrap()));
+
+        let scope = ImageCacheScope::enter();
+        let cached = open_image(&path).unwrap();
+        let nested = ImageCacheScope::enter();
+        drop(nested);
+        assert!(Rc::ptr_eq(&cached, &open_image(&path).unwrap()));
+        #[cfg(windows)]
+        {
+            let upper = std::path::PathBuf::from(path.to_string_lossy().to_uppercase());
+            assert!(Rc::ptr_eq(&cached, &open_image(&upper).unwrap()));
+        }
+
+        // The file changing on disk does not invalidate the scope's view.
+        std::fs::write(&path, build_test_pe(&["b.dll"]).bytes).unwrap();
+        assert_eq!(module_dependencies(&path).unwrap()[0].name, "a.dll");
+        drop(scope);
+        assert_eq!(module_dependencies(&path).unwrap()[0].name, "b.dll");
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -0,0 +1,786 @@
+// Writes the collected token stream as a SARIF 2.1.0 log for `--format sarif`.
+
+use std::collections::HashMap;
+
+use crate::emit::{
+    json_string, json_value, TokenLine, TOKEN_COM_AUDIT, TOKEN_COM_LOOKUP, TOKEN_COM_SERVER,
+    TOKEN_DYNAMIC_MISSING, TOKEN_RUN_START, TOKEN_SEARCH_PATH, TOKEN_STATIC_APISET_UNRESOLVED,
+    TOKEN_STATIC_BAD_IMAGE, TOKEN_STATIC_DELAY_MISSING, TOKEN_STATIC_FORWARD_BROKEN,
+    TOKEN_STATIC_FOUND, TOKEN_STATIC_MISSING, TOKEN_STATIC_MISSING_EXPORT, TOKEN_STATIC_START,
+};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A reporting rule. Ids are part of the output contract: code-scanning
+/// tools track alerts by them, so an id is never renamed or reused.
+struct Rule {
+    id: &'static str,
+    name: &'static str,
+    level: &'static str,
+    description: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        id: "STATIC_MISSING",
+        name: "StaticImportMissing",
+        level: "error",
+        description: "A load-time import cannot be found on the DLL search path.",
+    },
+    Rule {
+        id: "STATIC_BAD_IMAGE",
+        name: "StaticImportBadImage",
+        level: "error",
+        description: "A load-time import resolves to a file the loader cannot load.",
+    },
+    Rule {
+        id: "STATIC_APISET_UNRESOLVED",
+        name: "StaticApiSetUnresolved",
+        level: "error",
+        description: "A load-time API set import has no host DLL on this system.",
+    },
+    Rule {
+        id: "STATIC_MISSING_EXPORT",
+        name: "StaticImportMissingExport",
+        level: "error",
+        description: "A resolved import DLL does not export a function the module imports.",
+    },
+    Rule {
+        id: "STATIC_FORWARD_BROKEN",
+        name: "StaticImportForwardBroken",
+        level: "error",
+        description: "An imported function is forwarded to a DLL or export that cannot be bound.",
+    },
+    Rule {
+        id: "STATIC_DELAY_MISSING",
+        name: "StaticDelayImportMissing",
+        level: "warning",
+        description: "A delay-load import resolves to no file; the first call into it fails.",
+    },
+    Rule {
+        id: "DYNAMIC_MISSING",
+        name: "DynamicLoadMissing",
+        level: "warning",
+        description: "A run-time LoadLibrary call failed to find a DLL.",
+    },
+    Rule {
+        id: "COM_NOT_REGISTERED",
+        name: "ComNotRegistered",
+        level: "error",
+        description: "The COM class or ProgID is not registered.",
+    },
+    Rule {
+        id: "COM_PROGID_BROKEN",
+        name: "ComProgIdBroken",
+        level: "error",
+        description: "The ProgID does not lead to a registered class.",
+    },
+    Rule {
+        id: "COM_TREATAS_BROKEN",
+        name: "ComTreatAsBroken",
+        level: "error",
+        description: "A TreatAs redirection does not lead to a registered class.",
+    },
+    Rule {
+        id: "COM_BROKEN_REGISTRATION",
+        name: "ComBrokenRegistration",
+        level: "error",
+        description: "The class registration names no usable server.",
+    },
+    Rule {
+        id: "COM_SERVER_MISSING",
+        name: "ComServerMissing",
+        level: "error",
+        description: "The registered COM server file does not exist.",
+    },
+    Rule {
+        id: "COM_SERVER_BAD_IMAGE",
+        name: "ComServerBadImage",
+        level: "error",
+        description: "The COM server file is not a loadable image.",
+    },
+    Rule {
+        id: "COM_SERVER_DEPS_MISSING",
+        name: "ComServerDependencyMissing",
+        level: "error",
+        description: "A dependency of the COM server cannot be loaded.",
+    },
+    Rule {
+        id: "COM_BITNESS_MISMATCH",
+        name: "ComBitnessMismatch",
+        level: "error",
+        description: "The in-process server does not match the client's architecture.",
+    },
+    Rule {
+        id: "COM_ACCESS_DENIED",
+        name: "ComAccessDenied",
+        level: "warning",
+        description: "The registration or server could not be read.",
+    },
+    Rule {
+        id: "COM_INDETERMINATE",
+        name: "ComIndeterminate",
+        level: "note",
+        description: "The COM server could not be fully validated.",
+    },
+    Rule {
+        id: "COM_UNSUPPORTED_ARCHITECTURE",
+        name: "ComUnsupportedArchitecture",
+        level: "note",
+        description: "The target's architecture is not supported by com audit.",
+    },
+];
+
+/// The SARIF log for `lines`, one result per finding token.
+pub fn render(lines: &[TokenLine]) -> String {
+    let modules = module_paths(lines);
+    let run_target = lines
+        .iter()
+        .find(|line| line.token == TOKEN_RUN_START)
+        .and_then(|line| line.value("exe"));
+    // `com server` reports its file in `COM_SERVER`; the other COM commands
+    // emit it only as supporting detail of their own result.
+    let com_server_is_detail = lines
+        .iter()
+        .any(|line| line.token == TOKEN_COM_LOOKUP || line.token == TOKEN_COM_AUDIT);
+
+    let mut results = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some((rule_index, rule)) = rule_for(line, com_server_is_detail) else {
+            continue;
+        };
+        let mut members = vec![
+            ("ruleId", string(rule.id)),
+            ("ruleIndex", Json::Raw(rule_index.to_string())),
+            ("level", string(rule.level)),
+            ("message", object(vec![("text", string(&message(line)))])),
+        ];
+        let located = match location(line, &modules, run_target.as_deref()) {
+            Location::Artifact(path) => {
+                object(vec![("physicalLocation", physical_location(&path))])
+            }
+            Location::Logical(name) => object(vec![(
+                "logicalLocations",
+                Json::Array(vec![object(vec![
+                    ("name", string(&name)),
+                    ("kind", string("resource")),
+                ])]),
+            )]),
+        };
+        members.push(("locations", Json::Array(vec![located])));
+        let related = related_locations(lines, index, line, &modules);
+        if !related.is_empty() {
+            members.push(("relatedLocations", Json::Array(related)));
+        }
+        members.push(("properties", properties(line)));
+        results.push(object(members));
+    }
+
+    let rules = RULES
+        .iter()
+        .map(|rule| {
+            object(vec![
+                ("id", string(rule.id)),
+                ("name", string(rule.name)),
+                (
+                    "shortDescription",
+                    object(vec![("text", string(rule.description))]),
+                ),
+                (
+                    "defaultConfiguration",
+                    object(vec![("level", string(rule.level))]),
+                ),
+            ])
+        })
+        .collect();
+    let driver = object(vec![
+        ("name", string("loadwhat")),
+        ("version", string(env!("CARGO_PKG_VERSION"))),
+        ("rules", Json::Array(rules)),
+    ]);
+    let run = object(vec![
+        ("tool", object(vec![("driver", driver)])),
+        ("results", Json::Array(results)),
+    ]);
+    let log = object(vec![
+        ("$schema", string(SCHEMA)),
+        ("version", string("2.1.0")),
+        ("runs", Json::Array(vec![run])),
+    ]);
+
+    let mut out = String::new();
+    log.write(&mut out);
+    out
+}
+
+/// The rule `line` reports under, with its index in `RULES`, or `None`
+/// when the token is not a finding.
+fn rule_for(line: &TokenLine, com_server_is_detail: bool) -> Option<(usize, &'static Rule)> {
+    let id = match line.token.as_str() {
+        TOKEN_STATIC_MISSING
+        | TOKEN_STATIC_BAD_IMAGE
+        | TOKEN_STATIC_APISET_UNRESOLVED
+        | TOKEN_STATIC_MISSING_EXPORT
+        | TOKEN_STATIC_FORWARD_BROKEN
+        | TOKEN_STATIC_DELAY_MISSING
+        | TOKEN_DYNAMIC_MISSING => line.token.clone(),
+        TOKEN_COM_LOOKUP => {
+            let status = line.value("status")?;
+            match line.value("server_status") {
+                _ if status != "REGISTERED" => format!("COM_{status}"),
+                Some(server) if server != "OK" => format!("COM_{server}"),
+                _ => return None,
+            }
+        }
+        TOKEN_COM_AUDIT => com_status_rule(line)?,
+        TOKEN_COM_SERVER if !com_server_is_detail => com_status_rule(line)?,
+        _ => return None,
+    };
+    RULES.iter().enumerate().find(|(_, rule)| rule.id == id)
+}
+
+fn com_status_rule(line: &TokenLine) -> Option<String> {
+    let status = line.value("status")?;
+    (status != "OK").then(|| format!("COM_{status}"))
+}
+
+fn message(line: &TokenLine) -> String {
+    let value = |key: &str| line.value(key).unwrap_or_default();
+    match line.token.as_str() {
+        TOKEN_STATIC_MISSING => format!(
+            "{} imports {}, which was not found ({}).",
+            importer(line),
+            value("dll"),
+            value("reason")
+        ),
+        TOKEN_STATIC_BAD_IMAGE => format!(
+            "{} imports {}, which cannot be loaded ({}).",
+            importer(line),
+            value("dll"),
+            value("reason")
+        ),
+        TOKEN_STATIC_APISET_UNRESOLVED => format!(
+            "{} imports API set {}, which does not resolve to a host DLL ({}).",
+            importer(line),
+            value("dll"),
+            value("reason")
+        ),
+        TOKEN_STATIC_MISSING_EXPORT => format!(
+            "{} imports {} from {}, which does not export it.",
+            importer(line),
+            imported_function(line),
+            value("dll")
+        ),
+        TOKEN_STATIC_FORWARD_BROKEN => format!(
+            "{} imports {} from {}, which forwards it to {} ({}).",
+            importer(line),
+            imported_function(line),
+            value("dll"),
+            value("forward"),
+            value("reason")
+        ),
+        TOKEN_STATIC_DELAY_MISSING => format!(
+            "{} delay-loads {}, which was not found ({}).",
+            importer(line),
+            value("dll"),
+            value("reason")
+        ),
+        TOKEN_DYNAMIC_MISSING => format!(
+            "LoadLibrary of {} failed at run time ({}).",
+            value("dll"),
+            value("reason")
+        ),
+        TOKEN_COM_LOOKUP => match line.value("server_status") {
+            Some(server) if value("status") == "REGISTERED" => {
+                format!(
+                    "{} is registered but its server is {server}.",
+                    value("query")
+                )
+            }
+            _ => format!("{} is {}.", value("query"), value("status")),
+        },
+        TOKEN_COM_AUDIT => format!(
+            "{} for {} is {}.",
+            value("query"),
+            value("target"),
+            value("status")
+        ),
+        _ => format!("{} is {}.", value("path"), value("status")),
+    }
+}
+
+/// The module whose import failed: `via` on a first-break line, where
+/// `module` is the root, and `module` otherwise.
+fn importer(line: &TokenLine) -> String {
+    line.value("via")
+        .or_else(|| line.value("module"))
+        .unwrap_or_default()
+}
+
+/// `function` by name, or `ordinal N` for an import by ordinal.
+fn imported_function(line: &TokenLine) -> String {
+    line.value("function")
+        .or_else(|| {
+            line.value("ordinal")
+                .map(|ordinal| format!("ordinal {ordinal}"))
+        })
+        .unwrap_or_default()
+}
+
+enum Location {
+    Artifact(String),
+    Logical(String),
+}
+
+fn location(
+    line: &TokenLine,
+    modules: &HashMap<String, String>,
+    run_target: Option<&str>,
+) -> Location {
+    match line.token.as_str() {
+        TOKEN_STATIC_MISSING
+        | TOKEN_STATIC_BAD_IMAGE
+        | TOKEN_STATIC_APISET_UNRESOLVED
+        | TOKEN_STATIC_MISSING_EXPORT
+        | TOKEN_STATIC_FORWARD_BROKEN
+        | TOKEN_STATIC_DELAY_MISSING => {
+            let name = importer(line);
+            let path = modules.get(&name.to_ascii_lowercase()).cloned();
+            Location::Artifact(path.unwrap_or(name))
+        }
+        TOKEN_DYNAMIC_MISSING => match run_target {
+            Some(exe) => Location::Artifact(exe.to_string()),
+            None => Location::Logical(line.value("dll").unwrap_or_default()),
+        },
+        TOKEN_COM_LOOKUP => Location::Logical(line.value("query").unwrap_or_default()),
+        TOKEN_COM_AUDIT => Location::Artifact(line.value("target").unwrap_or_default()),
+        _ => Location::Artifact(line.value("path").unwrap_or_default()),
+    }
+}
+
+/// Full paths of the modules the token stream names: the root from
+/// `STATIC_START` and every loaded import from `STATIC_FOUND`, keyed by
+/// lowercase file name.
+fn module_paths(lines: &[TokenLine]) -> HashMap<String, String> {
+    let mut modules = HashMap::new();
+    for line in lines {
+        let path = match line.token.as_str() {
+            TOKEN_STATIC_START => line.value("module"),
+            TOKEN_STATIC_FOUND => line.value("path"),
+            _ => None,
+        };
+        if let Some(path) = path {
+            let name = path.rsplit(['\\', '/']).next().unwrap_or(&path);
+            modules.entry(name.to_ascii_lowercase()).or_insert(path);
+        }
+    }
+    modules
+}
+
+/// The `SEARCH_PATH` candidates for the finding's DLL: the run of them right
+/// before the finding (`imports`) or, failing that, right after it (`run`).
+/// Export findings point at the DLL that was loaded (and a forwarder's
+/// target when it was loaded too); `com audit` at the server it checked.
+fn related_locations(
+    lines: &[TokenLine],
+    index: usize,
+    line: &TokenLine,
+    modules: &HashMap<String, String>,
+) -> Vec<Json> {
+    let loaded = |name: &str| modules.get(&name.to_ascii_lowercase()).cloned();
+    let dll = match line.token.as_str() {
+        TOKEN_STATIC_MISSING
+        | TOKEN_STATIC_BAD_IMAGE
+        | TOKEN_STATIC_APISET_UNRESOLVED
+        | TOKEN_STATIC_DELAY_MISSING
+        | TOKEN_DYNAMIC_MISSING => line.value("dll"),
+        TOKEN_STATIC_MISSING_EXPORT => {
+            return line
+                .value("dll")
+                .and_then(|dll| loaded(&dll))
+                .map(|path| vec![related(1, &path, "Loaded DLL without the export")])
+                .unwrap_or_default();
+        }
+        TOKEN_STATIC_FORWARD_BROKEN => {
+            let mut out = Vec::new();
+            if let Some(path) = line.value("dll").and_then(|dll| loaded(&dll)) {
+                out.push(related(1, &path, "Loaded DLL that forwards the export"));
+            }
+            let target = line
+                .value("forward")
+                .and_then(|forward| Some(format!("{}.dll", forward.rsplit_once('.')?.0)));
+            if let Some(path) = target.and_then(|target| loaded(&target)) {
+                out.push(related(out.len() + 1, &path, "Forward target DLL"));
+            }
+            return out;
+        }
+        TOKEN_COM_AUDIT => {
+            return line
+                .value("server_path")
+                .map(|server| vec![related(1, &server, "COM server")])
+                .unwrap_or_default();
+        }
+        _ => None,
+    };
+    let Some(dll) = dll else {
+        return Vec::new();
+    };
+    let is_candidate = |other: &&TokenLine| {
+        other.token == TOKEN_SEARCH_PATH
+            && other
+                .value("dll")
+                .is_some_and(|name| name.eq_ignore_ascii_case(&dll))
+    };
+    let mut candidates: Vec<&TokenLine> = lines[..index]
+        .iter()
+        .rev()
+        .take_while(is_candidate)
+        .collect();
+    candidates.reverse();
+    if candidates.is_empty() {
+        candidates = lines[index + 1..].iter().take_while(is_candidate).collect();
+    }
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let mut text = format!(
+                "Search candidate {}: {}",
+                candidate.value("order").unwrap_or_default(),
+                candidate.value("result").unwrap_or_default()
+            );
+            if let Some(reason) = candidate.value("reason") {
+                text.push_str(&format!(" ({reason})"));
+            }
+            related(i + 1, &candidate.value("path").unwrap_or_default(), &text)
+        })
+        .collect()
+}
+
+fn related(id: usize, path: &str, text: &str) -> Json {
+    object(vec![
+        ("id", Json::Raw(id.to_string())),
+        ("physicalLocation", physical_location(path)),
+        ("message", object(vec![("text", string(text))])),
+    ])
+}
+
+fn physical_location(path: &str) -> Json {
+    object(vec![(
+        "artifactLocation",
+        object(vec![("uri", string(&file_uri(path)))]),
+    )])
+}
+
+/// `C:\App\a b.dll` as `file:///C:/App/a%20b.dll`; a bare file name stays a
+/// relative reference.
+fn file_uri(path: &str) -> String {
+    let slashed = path.replace('\\', "/");
+    let mut out = String::new();
+    if slashed.as_bytes().get(1) == Some(&b':') {
+        out.push_str("file:///");
+    } else if let Some(unc) = slashed.strip_prefix("//") {
+        out.push_str("file://");
+        return encode_into(out, unc);
+    }
+    encode_into(out, &slashed)
+}
+
+fn encode_into(mut out: String, path: &str) -> String {
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                out.push(byte as char)
+            }
+            other => out.push_str(&format!("%{other:02X}")),
+        }
+    }
+    out
+}
+
+/// The token and its fields, typed as in `--format ndjson`.
+fn properties(line: &TokenLine) -> Json {
+    let mut members = vec![("token".to_string(), string(&line.token))];
+    for (key, value) in &line.fields {
+        members.push((key.clone(), Json::Raw(json_value(key, value))));
+    }
+    Json::Object(members)
+}
+
+enum Json {
+    /// Already-encoded JSON text.
+    Raw(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn write(&self, out: &mut String) {
+        match self {
+            Json::Raw(text) => out.push_str(text),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    item.write(out);
+                }
+                out.push(']');
+            }
+            Json::Object(members) => {
+                out.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&json_string(key));
+                    out.push(':');
+                    value.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn string(value: &str) -> Json {
+    Json::Raw(json_string(value))
+}
+
+fn object(members: Vec<(&str, Json)>) -> Json {
+    Json::Object(
+        members
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_uri, render};
+    use crate::emit::{field, quote, TokenLine};
+
+    /// A token whose values are quoted, except those written as `=value`.
+    fn line(token: &str, fields: &[(&str, &str)]) -> TokenLine {
+        TokenLine {
+            token: token.to_string(),
+            fields: fields
+                .iter()
+                .map(|(key, value)| match value.strip_prefix('=') {
+                    Some(bare) => field(*key, bare),
+                    None => field(*key, quote(value)),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn static_findings_locate_the_importer_and_list_search_candidates() {
+        let log = render(&[
+            line(
+                "STATIC_START",
+                &[("module", r"C:\App\app.exe"), ("scope", "direct")],
+            ),
+            line(
+                "STATIC_FOUND",
+                &[
+                    ("module", "app.exe"),
+                    ("dll", "core.dll"),
+                    ("path", r"C:\App\core.dll"),
+                ],
+            ),
+            line(
+                "SEARCH_PATH",
+                &[
+                    ("dll", "gone.dll"),
+                    ("order", "=1"),
+                    ("path", r"C:\App\gone.dll"),
+                    ("result", "MISS"),
+                ],
+            ),
+            line(
+                "SEARCH_PATH",
+                &[
+                    ("dll", "gone.dll"),
+                    ("order", "=2"),
+                    ("path", r"C:\My Tools\gone.dll"),
+                    ("result", "MISS"),
+                ],
+            ),
+            line(
+                "STATIC_MISSING",
+                &[
+                    ("module", "core.dll"),
+                    ("dll", "gone.dll"),
+                    ("reason", "NOT_FOUND"),
+                    ("via", "core.dll"),
+                    ("depth", "=2"),
+                ],
+            ),
+            line(
+                "SUMMARY",
+                &[("first_break", "=false"), ("static_missing", "=1")],
+            ),
+        ]);
+
+        assert!(log.starts_with(
+            r#"{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","version":"2.1.0","runs":[{"tool":{"driver":{"name":"loadwhat""#
+        ));
+        assert_eq!(log.matches(r#""ruleId""#).count(), 1);
+        assert!(log.contains(concat!(
+            r#""results":[{"ruleId":"STATIC_MISSING","ruleIndex":0,"level":"error","#,
+            r#""message":{"text":"core.dll imports gone.dll, which was not found (NOT_FOUND)."},"#,
+            r#""locations":[{"physicalLocation":{"artifactLocation":{"uri":"file:///C:/App/core.dll"}}}],"#,
+            r#""relatedLocations":[{"id":1,"physicalLocation":{"artifactLocation":{"uri":"file:///C:/App/gone.dll"}},"message":{"text":"Search candidate 1: MISS"}},"#,
+            r#"{"id":2,"physicalLocation":{"artifactLocation":{"uri":"file:///C:/My%20Tools/gone.dll"}},"message":{"text":"Search candidate 2: MISS"}}],"#,
+            r#""properties":{"token":"STATIC_MISSING","module":"core.dll","dll":"gone.dll","reason":"NOT_FOUND","via":"core.dll","depth":2}}]"#,
+        )));
+    }
+
+    #[test]
+    fn export_and_api_set_findings_are_results() {
+        let found = |dll: &str, path: &str| {
+            line(
+                "STATIC_FOUND",
+                &[("module", "app.exe"), ("dll", dll), ("path", path)],
+            )
+        };
+        let log = render(&[
+            line("STATIC_START", &[("module", r"C:\App\app.exe")]),
+            found("core.dll", r"C:\App\core.dll"),
+            line(
+                "STATIC_MISSING_EXPORT",
+                &[
+                    ("module", "app.exe"),
+                    ("dll", "core.dll"),
+                    ("function", "CoreInit"),
+                ],
+            ),
+        ]);
+        assert_eq!(log.matches(r#""ruleId""#).count(), 1);
+        assert!(log.contains(concat!(
+            r#"{"ruleId":"STATIC_MISSING_EXPORT","ruleIndex":3,"level":"error","#,
+            r#""message":{"text":"app.exe imports CoreInit from core.dll, which does not export it."},"#,
+            r#""locations":[{"physicalLocation":{"artifactLocation":{"uri":"file:///C:/App/app.exe"}}}],"#,
+            r#""relatedLocations":[{"id":1,"physicalLocation":{"artifactLocation":{"uri":"file:///C:/App/core.dll"}},"message":{"text":"Loaded DLL without the export"}}],"#,
+        )));
+
+        let log = render(&[
+            line("STATIC_START", &[("module", r"C:\App\app.exe")]),
+            found("core.dll", r"C:\App\core.dll"),
+            found("old.dll", r"C:\App\old.dll"),
+            line(
+                "STATIC_FORWARD_BROKEN",
+                &[
+                    ("module", "app.exe"),
+                    ("dll", "core.dll"),
+                    ("ordinal", "=7"),
+                    ("forward", "old.Gone"),
+                    ("reason", "MISSING_EXPORT"),
+                ],
+            ),
+            line(
+                "STATIC_APISET_UNRESOLVED",
+                &[
+                    ("module", "app.exe"),
+                    ("dll", "api-ms-win-core-x-l1-1-0.dll"),
+                    ("reason", "NO_HOST"),
+                ],
+            ),
+        ]);
+        assert_eq!(log.matches(r#""ruleId""#).count(), 2);
+        assert!(log.contains(
+            r#""text":"app.exe imports ordinal 7 from core.dll, which forwards it to old.Gone (MISSING_EXPORT).""#
+        ));
+        assert!(log.contains(
+            r#""uri":"file:///C:/App/old.dll"}},"message":{"text":"Forward target DLL"}"#
+        ));
+        assert!(log.contains(r#""ruleId":"STATIC_APISET_UNRESOLVED""#));
+    }
+
+    #[test]
+    fn delay_load_misses_are_warnings_at_the_importer() {
+        let log = render(&[
+            line("STATIC_START", &[("module", r"C:\App\app.exe")]),
+            line(
+                "SEARCH_PATH",
+                &[
+                    ("dll", "vendor.dll"),
+                    ("order", "=1"),
+                    ("path", r"C:\App\vendor.dll"),
+                    ("result", "MISS"),
+                ],
+            ),
+            line(
+                "STATIC_DELAY_MISSING",
+                &[
+                    ("module", "app.exe"),
+                    ("dll", "vendor.dll"),
+                    ("reason", "NOT_FOUND"),
+                ],
+            ),
+        ]);
+        assert!(log.contains(concat!(
+            r#"{"ruleId":"STATIC_DELAY_MISSING","ruleIndex":5,"level":"warning","#,
+            r#""message":{"text":"app.exe delay-loads vendor.dll, which was not found (NOT_FOUND)."},"#,
+            r#""locations":[{"physicalLocation":{"artifactLocation":{"uri":"file:///C:/App/app.exe"}}}],"#,
+            r#""relatedLocations":[{"id":1,"physicalLocation":{"artifactLocation":{"uri":"file:///C:/App/vendor.dll"}}"#,
+        )));
+    }
+
+    #[test]
+    fn com_statuses_map_to_rules_and_server_detail_is_not_repeated() {
+        let log = render(&[
+            line(
+                "COM_SERVER",
+                &[
+                    ("path", r"C:\App\srv.dll"),
+                    ("status", "SERVER_DEPS_MISSING"),
+                ],
+            ),
+            line(
+                "COM_LOOKUP",
+                &[
+                    ("query_kind", "progid"),
+                    ("query", "App.Thing"),
+                    ("status", "REGISTERED"),
+                    ("server_status", "SERVER_DEPS_MISSING"),
+                ],
+            ),
+        ]);
+        assert_eq!(log.matches(r#""ruleId""#).count(), 1);
+        assert!(log.contains(r#""ruleId":"COM_SERVER_DEPS_MISSING""#));
+        assert!(log.contains(r#""logicalLocations":[{"name":"App.Thing","kind":"resource"}]"#));
+
+        let server = render(&[line(
+            "COM_SERVER",
+            &[("path", r"C:\App\srv.dll"), ("status", "BITNESS_MISMATCH")],
+        )]);
+        assert!(server.contains(r#""ruleId":"COM_BITNESS_MISMATCH""#));
+
+        let clean = render(&[line(
+            "COM_LOOKUP",
+            &[
+                ("query", "App.Thing"),
+                ("status", "REGISTERED"),
+                ("server_status", "OK"),
+            ],
+        )]);
+        assert!(clean.contains(r#""results":[]"#));
+    }
+
+    #[test]
+    fn file_uris_use_forward_slashes_and_escape_reserved_bytes() {
+        assert_eq!(
+            file_uri(r"C:\Program Files\a#b.dll"),
+            "file:///C:/Program%20Files/a%23b.dll"
+        );
+        assert_eq!(
+            file_uri(r"\\server\share\x.dll"),
+            "file://server/share/x.dll"
+        );
+        assert_eq!(file_uri("app.exe"), "app.exe");
+    }
+}
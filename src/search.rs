@@ -2,9 +2,13 @@
 
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
-use crate::pe;
+use crate::pe::{self, ImageDefect, MachineType};
+use crate::sysroot::{self, Sysroot};
+#[cfg(windows)]
 use crate::win;
 
 #[derive(Clone)]
@@ -12,16 +16,189 @@ pub struct SearchContext {
     pub app_dir: PathBuf,
     pub cwd: PathBuf,
     pub path_dirs: Vec<PathBuf>,
+    /// The PATH value `path_dirs` was split from, as the process sees it.
+    pub path_env: String,
     pub safedll: bool,
     pub system_dir: PathBuf,
     pub windows_dir: PathBuf,
     pub system16_dir: Option<PathBuf>,
+    /// Architecture of the process being modeled. Candidates built for a
+    /// different machine are skipped, as the loader does.
+    pub machine: MachineType,
+    /// How the module is loaded; the default is a plain `LoadLibrary`.
+    pub load: LoadOptions,
+    /// Lowercased names from the machine's `KnownDLLs` list. A known DLL is
+    /// mapped from its `\KnownDlls` section, backed by the system directory,
+    /// before any search or redirection.
+    pub known_dlls: HashSet<String>,
+    /// Directory `.local` redirection sends every load to first, when the
+    /// loader honors the process image's `.local` (see `redirect_dotlocal`).
+    pub dotlocal_dir: Option<PathBuf>,
+}
+
+const ERROR_SHARING_VIOLATION: i32 = 32;
+
+pub const LOAD_WITH_ALTERED_SEARCH_PATH: u32 = 0x0000_0008;
+pub const LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR: u32 = 0x0000_0100;
+pub const LOAD_LIBRARY_SEARCH_APPLICATION_DIR: u32 = 0x0000_0200;
+pub const LOAD_LIBRARY_SEARCH_USER_DIRS: u32 = 0x0000_0400;
+pub const LOAD_LIBRARY_SEARCH_SYSTEM32: u32 = 0x0000_0800;
+pub const LOAD_LIBRARY_SEARCH_DEFAULT_DIRS: u32 = 0x0000_1000;
+
+const LOAD_LIBRARY_SEARCH_MASK: u32 = LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR
+    | LOAD_LIBRARY_SEARCH_APPLICATION_DIR
+    | LOAD_LIBRARY_SEARCH_USER_DIRS
+    | LOAD_LIBRARY_SEARCH_SYSTEM32
+    | LOAD_LIBRARY_SEARCH_DEFAULT_DIRS;
+
+const LOAD_FLAG_NAMES: [(&str, u32); 6] = [
+    (
+        "LOAD_WITH_ALTERED_SEARCH_PATH",
+        LOAD_WITH_ALTERED_SEARCH_PATH,
+    ),
+    (
+        "LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR",
+        LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR,
+    ),
+    (
+        "LOAD_LIBRARY_SEARCH_APPLICATION_DIR",
+        LOAD_LIBRARY_SEARCH_APPLICATION_DIR,
+    ),
+    (
+        "LOAD_LIBRARY_SEARCH_USER_DIRS",
+        LOAD_LIBRARY_SEARCH_USER_DIRS,
+    ),
+    ("LOAD_LIBRARY_SEARCH_SYSTEM32", LOAD_LIBRARY_SEARCH_SYSTEM32),
+    (
+        "LOAD_LIBRARY_SEARCH_DEFAULT_DIRS",
+        LOAD_LIBRARY_SEARCH_DEFAULT_DIRS,
+    ),
+];
+
+/// The `LoadLibraryEx` flags and process-wide DLL directory state a load
+/// runs under.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// `LoadLibraryEx` `dwFlags`.
+    pub flags: u32,
+    /// `SetDefaultDllDirectories` flags, used when `flags` has no
+    /// `LOAD_LIBRARY_SEARCH_*` bit.
+    pub default_dirs: u32,
+    /// `SetDllDirectory` directory.
+    pub dll_directory: Option<PathBuf>,
+    /// `AddDllDirectory` directories, in call order.
+    pub user_dirs: Vec<PathBuf>,
+    /// Directory of the module handed to `LoadLibraryEx`. Its dependencies
+    /// resolve on its behalf, so `LOAD_WITH_ALTERED_SEARCH_PATH` and
+    /// `LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR` search here.
+    pub load_dir: Option<PathBuf>,
+}
+
+impl LoadOptions {
+    /// Parses a `|`- or `,`-separated list of flag names (with or without
+    /// the `LOAD_LIBRARY_`/`LOAD_` prefix, any case) or hex values.
+    pub fn parse_flags(text: &str) -> Result<u32, String> {
+        let mut flags = 0;
+        for part in text.split(['|', ',']).map(str::trim) {
+            if part.is_empty() {
+                continue;
+            }
+            let upper = part.to_ascii_uppercase();
+            let named = LOAD_FLAG_NAMES.iter().find(|(name, _)| {
+                *name == upper
+                    || name.strip_prefix("LOAD_LIBRARY_") == Some(upper.as_str())
+                    || name.strip_prefix("LOAD_") == Some(upper.as_str())
+            });
+            let value = match (named, upper.strip_prefix("0X")) {
+                (Some((_, value)), _) => *value,
+                (None, Some(hex)) => u32::from_str_radix(hex, 16)
+                    .map_err(|_| format!("invalid load flag: {part}"))?,
+                (None, None) => return Err(format!("unknown load flag: {part}")),
+            };
+            flags |= value;
+        }
+        Ok(flags)
+    }
+
+    /// Rejects combinations `LoadLibraryEx` and `SetDefaultDllDirectories`
+    /// fail with `ERROR_INVALID_PARAMETER`, and bits this model ignores.
+    pub fn validate(&self) -> Result<(), String> {
+        let known = LOAD_WITH_ALTERED_SEARCH_PATH | LOAD_LIBRARY_SEARCH_MASK;
+        if self.flags & !known != 0 {
+            return Err(format!(
+                "unsupported load flags: 0x{:08X}",
+                self.flags & !known
+            ));
+        }
+        if self.flags & LOAD_WITH_ALTERED_SEARCH_PATH != 0
+            && self.flags & LOAD_LIBRARY_SEARCH_MASK != 0
+        {
+            return Err(
+                "LOAD_WITH_ALTERED_SEARCH_PATH cannot be combined with LOAD_LIBRARY_SEARCH_* flags"
+                    .to_string(),
+            );
+        }
+        if self.default_dirs & !LOAD_LIBRARY_SEARCH_MASK != 0
+            || self.default_dirs & LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR != 0
+        {
+            return Err(
+                "default DLL directories take LOAD_LIBRARY_SEARCH_APPLICATION_DIR, USER_DIRS, SYSTEM32 or DEFAULT_DIRS"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == LoadOptions::default()
+    }
+
+    /// The `LOAD_LIBRARY_SEARCH_*` bits in effect, with `DEFAULT_DIRS`
+    /// expanded; 0 for the standard search order. Explicit flags win over
+    /// `LOAD_WITH_ALTERED_SEARCH_PATH`, which wins over the process defaults.
+    fn search_flags(&self) -> u32 {
+        let flags = if self.flags & LOAD_LIBRARY_SEARCH_MASK != 0 {
+            self.flags & LOAD_LIBRARY_SEARCH_MASK
+        } else if self.flags & LOAD_WITH_ALTERED_SEARCH_PATH != 0 {
+            0
+        } else {
+            self.default_dirs & LOAD_LIBRARY_SEARCH_MASK
+        };
+        if flags & LOAD_LIBRARY_SEARCH_DEFAULT_DIRS != 0 {
+            (flags & !LOAD_LIBRARY_SEARCH_DEFAULT_DIRS)
+                | LOAD_LIBRARY_SEARCH_APPLICATION_DIR
+                | LOAD_LIBRARY_SEARCH_USER_DIRS
+                | LOAD_LIBRARY_SEARCH_SYSTEM32
+        } else {
+            flags
+        }
+    }
+
+    /// The effective search mode as `|`-joined flag names, or `STANDARD`.
+    pub fn describe(&self) -> String {
+        let flags = match self.search_flags() {
+            0 => self.flags & LOAD_WITH_ALTERED_SEARCH_PATH,
+            search => search,
+        };
+        let names: Vec<&str> = LOAD_FLAG_NAMES
+            .iter()
+            .filter(|(_, value)| flags & value != 0)
+            .map(|(name, _)| *name)
+            .collect();
+        if names.is_empty() {
+            "STANDARD".to_string()
+        } else {
+            names.join("|")
+        }
+    }
 }
 
 #[derive(Clone)]
 pub enum ResolutionKind {
     Found,
     Missing,
+    /// The search stopped at a file the loader cannot map: a bad image, a
+    /// directory, or one it may not open. `bad_image_reason` says which.
     BadImage,
 }
 
@@ -30,6 +207,8 @@ pub struct CandidateResult {
     pub order: usize,
     pub path: PathBuf,
     pub result: &'static str,
+    /// Structural defect token for a `BAD_IMAGE` candidate.
+    pub reason: Option<&'static str>,
 }
 
 #[derive(Clone)]
@@ -39,14 +218,88 @@ pub struct Resolution {
     pub candidates: Vec<CandidateResult>,
 }
 
+impl Resolution {
+    /// `reason=` for a `BadImage` resolution: the defect of the candidate
+    /// the search stopped at, or `WRONG_MACHINE` when only images for
+    /// another architecture were found.
+    pub fn bad_image_reason(&self) -> &'static str {
+        bad_image_reason(&self.candidates)
+    }
+}
+
+pub fn bad_image_reason(candidates: &[CandidateResult]) -> &'static str {
+    candidates
+        .iter()
+        .rev()
+        .find_map(|candidate| {
+            candidate.reason.or_else(|| {
+                UNOPENABLE_RESULTS
+                    .into_iter()
+                    .find(|token| *token == candidate.result)
+            })
+        })
+        .or_else(|| {
+            candidates
+                .iter()
+                .any(|candidate| candidate.result == CandidateKind::WrongMachine.as_token())
+                .then(|| ImageDefect::WrongMachine.as_token())
+        })
+        .unwrap_or(ImageDefect::NotPe.as_token())
+}
+
+/// The PATH a process on the analyzed machine starts with: the sysroot's
+/// `Path` environment value, or this process's PATH.
+pub fn machine_path_env() -> String {
+    match sysroot::current() {
+        Some(sysroot) => sysroot.env_var("Path").unwrap_or_default().to_string(),
+        None => std::env::var_os("PATH")
+            .map(|value| value.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
 impl SearchContext {
+    /// The search environment of the machine being analyzed: the active
+    /// sysroot when there is one, this Windows host otherwise.
     pub fn from_environment(
         app_dir: &Path,
         cwd: &Path,
         path_env: Option<OsString>,
+        machine: MachineType,
+    ) -> Result<Self, String> {
+        match sysroot::current() {
+            Some(sysroot) => Ok(Self::from_sysroot(
+                &sysroot, app_dir, cwd, path_env, machine,
+            )),
+            None => Self::from_host(app_dir, cwd, path_env, machine),
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn from_host(
+        _app_dir: &Path,
+        _cwd: &Path,
+        _path_env: Option<OsString>,
+        _machine: MachineType,
+    ) -> Result<Self, String> {
+        Err("no Windows search environment on this host; pass --sysroot".to_string())
+    }
+
+    #[cfg(windows)]
+    fn from_host(
+        app_dir: &Path,
+        cwd: &Path,
+        path_env: Option<OsString>,
+        machine: MachineType,
     ) -> Result<Self, String> {
         let safedll = win::safe_dll_search_mode();
-        let system_dir = win::get_system_directory()?;
+        // A 32-bit process on 64-bit Windows sees SysWOW64 as its system
+        // directory.
+        let system_dir = if machine == MachineType::X86 {
+            win::get_system_wow64_directory()?
+        } else {
+            win::get_system_directory()?
+        };
         let windows_dir = win::get_windows_directory()?;
         let system16 = windows_dir.join("System");
         let system16_dir = if system16.exists() {
@@ -55,27 +308,129 @@ impl SearchContext {
             None
         };
 
-        let path_dirs = path_env
+        let path_env = path_env
             .or_else(|| std::env::var_os("PATH"))
-            .map(parse_path_dirs)
             .unwrap_or_default();
+        let path_dirs = parse_path_dirs(path_env.clone());
 
         Ok(Self {
             app_dir: app_dir.to_path_buf(),
             cwd: cwd.to_path_buf(),
             path_dirs,
+            path_env: path_env.to_string_lossy().into_owned(),
             safedll,
             system_dir,
             windows_dir,
             system16_dir,
+            machine,
+            load: LoadOptions::default(),
+            known_dlls: win::known_dlls().into_iter().collect(),
+            dotlocal_dir: None,
         })
     }
 
+    /// Search roots of the machine mounted at `sysroot`. Its PATH is a
+    /// `;`-separated list of Windows paths whatever the host's separator,
+    /// and an x86 process falls back to System32 on a volume without
+    /// SysWOW64 (32-bit Windows).
+    fn from_sysroot(
+        sysroot: &Sysroot,
+        app_dir: &Path,
+        cwd: &Path,
+        path_env: Option<OsString>,
+        machine: MachineType,
+    ) -> Self {
+        let windows_dir = PathBuf::from(sysroot::WINDOWS_DIR);
+        let wow64_dir = windows_dir.join("SysWOW64");
+        let system_dir = if machine == MachineType::X86 && sysroot.host_path(&wow64_dir).is_dir() {
+            wow64_dir
+        } else {
+            windows_dir.join("System32")
+        };
+        let system16 = windows_dir.join("System");
+        let system16_dir = sysroot.host_path(&system16).is_dir().then_some(system16);
+
+        let path_env = path_env
+            .map(|value| value.to_string_lossy().into_owned())
+            .or_else(|| sysroot.env_var("Path").map(str::to_string))
+            .unwrap_or_default();
+        let path_dirs = path_env
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| PathBuf::from(entry.replace('\\', "/")))
+            .collect();
+
+        Self {
+            app_dir: app_dir.to_path_buf(),
+            cwd: cwd.to_path_buf(),
+            path_dirs,
+            path_env,
+            safedll: sysroot.safe_dll_search_mode(),
+            system_dir,
+            windows_dir,
+            system16_dir,
+            machine,
+            load: LoadOptions::default(),
+            known_dlls: sysroot.known_dlls().iter().cloned().collect(),
+            dotlocal_dir: None,
+        }
+    }
+
+    /// Turns on `.local` redirection for `process_image` when
+    /// `<image>.local` exists: a directory redirects loads into itself, a
+    /// file into the application directory. The loader only honors it for
+    /// images without a manifest unless `DevOverrideEnable` is set.
+    pub fn redirect_dotlocal(&mut self, process_image: &Path) {
+        let mut dotlocal = process_image.as_os_str().to_os_string();
+        dotlocal.push(".local");
+        let dotlocal = PathBuf::from(dotlocal);
+        let host = sysroot::host_path(&dotlocal);
+        let dir = if host.is_dir() {
+            dotlocal
+        } else if host.is_file() {
+            self.app_dir.clone()
+        } else {
+            return;
+        };
+        if dev_override_enabled() || !has_manifest(process_image) {
+            self.dotlocal_dir = Some(dir);
+        }
+    }
+
+    fn is_known_dll(&self, dll_name: &str) -> bool {
+        self.known_dlls.contains(&dll_name.to_ascii_lowercase())
+    }
+
     pub fn ordered_roots(&self) -> Vec<PathBuf> {
+        let search = self.load.search_flags();
+        if search != 0 {
+            return self.restricted_roots(search);
+        }
+
+        // LOAD_WITH_ALTERED_SEARCH_PATH puts the loaded module's directory in
+        // the application directory's slot.
+        let app_dir = match &self.load.load_dir {
+            Some(dir) if self.load.flags & LOAD_WITH_ALTERED_SEARCH_PATH != 0 => dir,
+            _ => &self.app_dir,
+        };
         let mut roots = Vec::new();
-        roots.push(self.app_dir.clone());
+        roots.push(app_dir.clone());
+
+        // SetDllDirectory replaces the current directory, wherever
+        // SafeDllSearchMode would have placed it.
+        if let Some(dll_directory) = &self.load.dll_directory {
+            roots.push(dll_directory.clone());
+            roots.push(self.system_dir.clone());
+            if let Some(system16) = &self.system16_dir {
+                roots.push(system16.clone());
+            }
+            roots.push(self.windows_dir.clone());
+            roots.extend(self.path_dirs.iter().cloned());
+            return dedup_case_insensitive(roots);
+        }
 
-        let cwd_differs = normalize_cmp(&self.cwd) != normalize_cmp(&self.app_dir);
+        let cwd_differs = normalize_cmp(&self.cwd) != normalize_cmp(app_dir);
         if self.safedll {
             roots.push(self.system_dir.clone());
             if let Some(system16) = &self.system16_dir {
@@ -102,81 +457,243 @@ impl SearchContext {
 
         dedup_case_insensitive(roots)
     }
+
+    /// Roots under `LOAD_LIBRARY_SEARCH_*` flags: only the selected
+    /// directories, in the loader's fixed order. The current directory,
+    /// the Windows directory and PATH are never searched.
+    fn restricted_roots(&self, search: u32) -> Vec<PathBuf> {
+        let mut roots = Vec::new();
+        if search & LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR != 0 {
+            roots.extend(self.load.load_dir.iter().cloned());
+        }
+        if search & LOAD_LIBRARY_SEARCH_APPLICATION_DIR != 0 {
+            roots.push(self.app_dir.clone());
+        }
+        if search & LOAD_LIBRARY_SEARCH_USER_DIRS != 0 {
+            roots.extend(self.load.user_dirs.iter().cloned());
+            roots.extend(self.load.dll_directory.iter().cloned());
+        }
+        if search & LOAD_LIBRARY_SEARCH_SYSTEM32 != 0 {
+            roots.push(self.system_dir.clone());
+        }
+        dedup_case_insensitive(roots)
+    }
 }
 
 pub fn resolve_dll(dll_name: &str, context: &SearchContext) -> Resolution {
     let mut candidates = Vec::new();
     let input = PathBuf::from(dll_name);
+    let full_path = is_full_path(&input);
 
-    if input.is_absolute() {
-        return resolve_absolute(&input, &mut candidates);
+    // Known DLLs come from their section objects and cannot be redirected.
+    // A name on the list without an image behind it has no section, so the
+    // normal search applies.
+    if !full_path && context.is_known_dll(dll_name) {
+        let candidate = context.system_dir.join(dll_name);
+        if let CandidateKind::Hit = classify_candidate(&candidate, context.machine) {
+            candidates.push(CandidateResult {
+                order: 1,
+                path: candidate.clone(),
+                result: CandidateKind::KnownDll.as_token(),
+                reason: None,
+            });
+            return Resolution {
+                kind: ResolutionKind::Found,
+                chosen: Some(candidate),
+                candidates,
+            };
+        }
     }
 
-    let roots = context.ordered_roots();
-    for (idx, root) in roots.iter().enumerate() {
-        let candidate = root.join(dll_name);
-        let result = classify_candidate(&candidate);
-        let token = match result {
-            ResolutionKind::Found => "HIT",
-            ResolutionKind::Missing => "MISS",
-            ResolutionKind::BadImage => "BAD_IMAGE",
+    let dotlocal = dotlocal_candidate(&input, context);
+    let mut skipped = None::<PathBuf>;
+    for (idx, candidate) in probe_paths(dll_name, context).into_iter().enumerate() {
+        let result = match classify_candidate(&candidate, context.machine) {
+            CandidateKind::Hit if idx == 0 && dotlocal.is_some() => CandidateKind::DotLocal,
+            result => result,
         };
         candidates.push(CandidateResult {
             order: idx + 1,
             path: candidate.clone(),
-            result: token,
+            result: result.as_token(),
+            reason: result.reason(),
         });
         match result {
-            ResolutionKind::Found => {
+            CandidateKind::Hit | CandidateKind::KnownDll | CandidateKind::DotLocal => {
                 return Resolution {
                     kind: ResolutionKind::Found,
                     chosen: Some(candidate),
                     candidates,
                 }
             }
-            ResolutionKind::BadImage => {
+            CandidateKind::BadImage(_) => {
+                return Resolution {
+                    kind: ResolutionKind::BadImage,
+                    chosen: Some(candidate),
+                    candidates,
+                }
+            }
+            CandidateKind::AccessDenied
+            | CandidateKind::SharingViolation
+            | CandidateKind::IsDirectory => {
                 return Resolution {
                     kind: ResolutionKind::BadImage,
                     chosen: Some(candidate),
                     candidates,
                 }
             }
-            ResolutionKind::Missing => {}
+            CandidateKind::WrongMachine => {
+                skipped.get_or_insert(candidate);
+            }
+            CandidateKind::Miss | CandidateKind::BrokenLink => {}
         }
     }
 
-    Resolution {
-        kind: ResolutionKind::Missing,
-        chosen: None,
-        candidates,
+    if full_path {
+        return resolve_absolute(&input, context.machine, &mut candidates);
+    }
+
+    // The loader keeps searching past wrong-architecture images, but when
+    // nothing loadable turns up the process fails with an invalid image
+    // format rather than a missing DLL.
+    match skipped {
+        Some(path) => Resolution {
+            kind: ResolutionKind::BadImage,
+            chosen: Some(path),
+            candidates,
+        },
+        None => Resolution {
+            kind: ResolutionKind::Missing,
+            chosen: None,
+            candidates,
+        },
     }
 }
 
-fn resolve_absolute(path: &Path, candidates: &mut Vec<CandidateResult>) -> Resolution {
-    let kind = classify_candidate(path);
-    let token = match kind {
-        ResolutionKind::Found => "HIT",
-        ResolutionKind::Missing => "MISS",
-        ResolutionKind::BadImage => "BAD_IMAGE",
-    };
+/// Every copy of `dll_name` along the search order, winner included: the
+/// candidates `resolve_dll` evaluates, without stopping at the first hit.
+/// Missing candidates are left out. A known DLL's system copy is reported
+/// as `KNOWN_DLL`, with order 0 when the search order does not reach it.
+pub fn find_copies(dll_name: &str, context: &SearchContext) -> Vec<CandidateResult> {
+    let input = Path::new(dll_name);
+    let dotlocal = dotlocal_candidate(input, context);
+    let known = (!is_full_path(input) && context.is_known_dll(dll_name))
+        .then(|| context.system_dir.join(dll_name))
+        .filter(|path| {
+            matches!(
+                classify_candidate(path, context.machine),
+                CandidateKind::Hit
+            )
+        });
+
+    let mut probes = probe_paths(dll_name, context);
+    if is_full_path(input) {
+        probes.push(input.to_path_buf());
+    }
+    let mut copies = Vec::new();
+    for (idx, candidate) in probes.into_iter().enumerate() {
+        let result = match classify_candidate(&candidate, context.machine) {
+            CandidateKind::Miss | CandidateKind::BrokenLink | CandidateKind::IsDirectory => {
+                continue
+            }
+            CandidateKind::Hit if known.as_ref() == Some(&candidate) => CandidateKind::KnownDll,
+            CandidateKind::Hit if idx == 0 && dotlocal.is_some() => CandidateKind::DotLocal,
+            result => result,
+        };
+        copies.push(CandidateResult {
+            order: idx + 1,
+            path: candidate,
+            result: result.as_token(),
+            reason: result.reason(),
+        });
+    }
+    if let Some(known) = known {
+        if !copies.iter().any(|copy| copy.path == known) {
+            copies.insert(
+                0,
+                CandidateResult {
+                    order: 0,
+                    path: known,
+                    result: CandidateKind::KnownDll.as_token(),
+                    reason: None,
+                },
+            );
+        }
+    }
+    copies
+}
+
+/// The `.local` redirection candidate for `input`, when redirection is on.
+fn dotlocal_candidate(input: &Path, context: &SearchContext) -> Option<PathBuf> {
+    context
+        .dotlocal_dir
+        .as_ref()
+        .zip(input.file_name())
+        .map(|(dir, name)| dir.join(name))
+}
+
+/// Candidate paths in evaluation order: the `.local` candidate, which is
+/// checked first even for full paths, then each search root for a name.
+fn probe_paths(dll_name: &str, context: &SearchContext) -> Vec<PathBuf> {
+    let input = Path::new(dll_name);
+    let dotlocal = dotlocal_candidate(input, context);
+    let mut probes: Vec<PathBuf> = dotlocal.iter().cloned().collect();
+    if !is_full_path(input) {
+        probes.extend(
+            context
+                .ordered_roots()
+                .iter()
+                .map(|root| root.join(dll_name))
+                .filter(|candidate| {
+                    dotlocal
+                        .as_ref()
+                        .is_none_or(|dotlocal| normalize_cmp(dotlocal) != normalize_cmp(candidate))
+                }),
+        );
+    }
+    probes
+}
+
+/// Whether `path` names a file outright rather than a name to search for:
+/// absolute on this host, or a drive path (`C:\...`, or a sysroot's `C:/...`)
+/// on any host.
+pub fn is_full_path(path: &Path) -> bool {
+    path.is_absolute()
+        || matches!(
+            path.to_string_lossy().as_bytes(),
+            [drive, b':', b'\\' | b'/', ..] if drive.is_ascii_alphabetic()
+        )
+}
+
+fn resolve_absolute(
+    path: &Path,
+    machine: MachineType,
+    candidates: &mut Vec<CandidateResult>,
+) -> Resolution {
+    let result = classify_candidate(path, machine);
     candidates.push(CandidateResult {
-        order: 1,
+        order: candidates.len() + 1,
         path: path.to_path_buf(),
-        result: token,
+        result: result.as_token(),
+        reason: result.reason(),
     });
 
-    match kind {
-        ResolutionKind::Found => Resolution {
+    match result {
+        CandidateKind::Hit | CandidateKind::KnownDll | CandidateKind::DotLocal => Resolution {
             kind: ResolutionKind::Found,
             chosen: Some(path.to_path_buf()),
             candidates: candidates.clone(),
         },
-        ResolutionKind::BadImage => Resolution {
+        CandidateKind::BadImage(_)
+        | CandidateKind::WrongMachine
+        | CandidateKind::AccessDenied
+        | CandidateKind::SharingViolation
+        | CandidateKind::IsDirectory => Resolution {
             kind: ResolutionKind::BadImage,
             chosen: Some(path.to_path_buf()),
             candidates: candidates.clone(),
         },
-        ResolutionKind::Missing => Resolution {
+        CandidateKind::Miss | CandidateKind::BrokenLink => Resolution {
             kind: ResolutionKind::Missing,
             chosen: None,
             candidates: candidates.clone(),
@@ -184,17 +701,123 @@ fn resolve_absolute(path: &Path, candidates: &mut Vec<CandidateResult>) -> Resol
     }
 }
 
-fn classify_candidate(path: &Path) -> ResolutionKind {
-    if !path.exists() {
-        return ResolutionKind::Missing;
+/// What the loader makes of one probed path. It keeps searching past
+/// `Miss`, `BrokenLink` and `WrongMachine`; anything else ends the search.
+#[derive(Clone, Copy)]
+enum CandidateKind {
+    Hit,
+    Miss,
+    BadImage(ImageDefect),
+    WrongMachine,
+    /// A hit mapped from the `KnownDLLs` list.
+    KnownDll,
+    /// A hit in the `.local` redirection directory.
+    DotLocal,
+    /// The loader's existence check counts an access-denied path as
+    /// present, so the search stops there and the open fails.
+    AccessDenied,
+    /// Present but held open without read sharing by another process.
+    SharingViolation,
+    /// A directory with the DLL's name. It passes the existence check, then
+    /// cannot be opened as a file.
+    IsDirectory,
+    /// A symbolic link or junction whose target is gone; the existence
+    /// check follows it and finds nothing.
+    BrokenLink,
+}
+
+/// Results that stop the search without a mappable image; they stand in
+/// for a defect `reason` on the bad-image resolution they cause.
+const UNOPENABLE_RESULTS: [&str; 3] = [
+    CandidateKind::AccessDenied.as_token(),
+    CandidateKind::SharingViolation.as_token(),
+    CandidateKind::IsDirectory.as_token(),
+];
+
+impl CandidateKind {
+    const fn as_token(self) -> &'static str {
+        match self {
+            CandidateKind::Hit => "HIT",
+            CandidateKind::Miss => "MISS",
+            CandidateKind::BadImage(_) => "BAD_IMAGE",
+            CandidateKind::WrongMachine => "WRONG_MACHINE",
+            CandidateKind::KnownDll => "KNOWN_DLL",
+            CandidateKind::DotLocal => "DOTLOCAL",
+            CandidateKind::AccessDenied => "ACCESS_DENIED",
+            CandidateKind::SharingViolation => "SHARING_VIOLATION",
+            CandidateKind::IsDirectory => "IS_DIRECTORY",
+            CandidateKind::BrokenLink => "BROKEN_LINK",
+        }
     }
-    if pe::is_probably_pe_file(path) {
-        ResolutionKind::Found
-    } else {
-        ResolutionKind::BadImage
+
+    fn reason(self) -> Option<&'static str> {
+        match self {
+            CandidateKind::BadImage(defect) => Some(defect.as_token()),
+            _ => None,
+        }
     }
 }
 
+fn classify_candidate(path: &Path, machine: MachineType) -> CandidateKind {
+    let host = sysroot::host_path(path);
+    let metadata = match fs::metadata(&host) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+            return CandidateKind::AccessDenied
+        }
+        Err(_) if fs::symlink_metadata(&host).is_ok() => return CandidateKind::BrokenLink,
+        Err(_) => return CandidateKind::Miss,
+    };
+    if metadata.is_dir() {
+        return CandidateKind::IsDirectory;
+    }
+    if let Err(err) = fs::File::open(&host) {
+        return if err.kind() == io::ErrorKind::PermissionDenied {
+            CandidateKind::AccessDenied
+        } else if cfg!(windows) && err.raw_os_error() == Some(ERROR_SHARING_VIOLATION) {
+            CandidateKind::SharingViolation
+        } else {
+            CandidateKind::BadImage(ImageDefect::Unreadable)
+        };
+    }
+    match pe::validate_image(path) {
+        Err(defect) => CandidateKind::BadImage(defect),
+        Ok(found) if !machine.can_load(found) => CandidateKind::WrongMachine,
+        Ok(_) => CandidateKind::Hit,
+    }
+}
+
+/// Parses a supplied `KnownDLLs` list: one DLL name per line; blank lines
+/// and `#` comments are ignored.
+pub fn parse_known_dlls(text: &str) -> HashSet<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|name| !name.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+/// Whether `DevOverrideEnable` makes the loader honor `.local` for images
+/// that carry a manifest.
+fn dev_override_enabled() -> bool {
+    match sysroot::current() {
+        Some(sysroot) => sysroot.dev_override_enabled(),
+        #[cfg(windows)]
+        None => win::dev_override_enabled(),
+        #[cfg(not(windows))]
+        None => false,
+    }
+}
+
+/// An embedded `RT_MANIFEST` resource or a `<image>.manifest` file next to it.
+fn has_manifest(image: &Path) -> bool {
+    let mut sidecar = image.as_os_str().to_os_string();
+    sidecar.push(".manifest");
+    pe::extract_embedded_manifest(image).is_some()
+        || sysroot::host_path(Path::new(&sidecar)).is_file()
+}
+
+#[cfg(windows)]
 fn parse_path_dirs(path_env: OsString) -> Vec<PathBuf> {
     std::env::split_paths(&path_env)
         .filter(|value| !value.as_os_str().is_empty())
@@ -213,15 +836,31 @@ fn dedup_case_insensitive(paths: Vec<PathBuf>) -> Vec<PathBuf> {
     out
 }
 
+/// Whether two paths name the same file as the search order compares them:
+/// ignoring case and `/` versus `\` separators.
+pub fn same_path(left: &Path, right: &Path) -> bool {
+    normalize_cmp(left) == normalize_cmp(right)
+}
+
 fn normalize_cmp(path: &Path) -> String {
-    path.as_os_str().to_string_lossy().to_ascii_lowercase()
+    path.as_os_str()
+        .to_string_lossy()
+        .replace('/', "\\")
+        .to_ascii_lowercase()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{resolve_dll, ResolutionKind, SearchContext};
+    use super::{
+        find_copies, parse_known_dlls, resolve_dll, same_path, LoadOptions, ResolutionKind,
+        SearchContext, LOAD_LIBRARY_SEARCH_APPLICATION_DIR, LOAD_LIBRARY_SEARCH_DEFAULT_DIRS,
+        LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR, LOAD_LIBRARY_SEARCH_SYSTEM32,
+        LOAD_LIBRARY_SEARCH_USER_DIRS, LOAD_WITH_ALTERED_SEARCH_PATH,
+    };
+    use crate::pe::MachineType;
+    use std::collections::HashSet;
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::sync::atomic::{AtomicU64, Ordering};
 
     static NEXT_TEST_ID: AtomicU64 = AtomicU64::new(1);
@@ -247,6 +886,8 @@ mod tests {
             OPTIONAL_HEADER_SIZE,
         );
         write_u16(&mut bytes, OPTIONAL_HEADER_OFFSET, 0x020B);
+        write_u32(&mut bytes, OPTIONAL_HEADER_OFFSET + 56, 0x2000);
+        write_u32(&mut bytes, OPTIONAL_HEADER_OFFSET + 60, 0x200);
         write_u32(&mut bytes, DATA_DIR_START + 8, 0);
         bytes[SECTION_TABLE_OFFSET..SECTION_TABLE_OFFSET + 5].copy_from_slice(b".text");
         write_u32(&mut bytes, SECTION_TABLE_OFFSET + 8, 0x40);
@@ -256,6 +897,13 @@ mod tests {
         bytes
     }
 
+    fn build_valid_x86_pe() -> Vec<u8> {
+        let mut bytes = build_valid_pe();
+        write_u16(&mut bytes, PE_OFFSET + 4, 0x014C);
+        write_u16(&mut bytes, OPTIONAL_HEADER_OFFSET, 0x010B);
+        bytes
+    }
+
     fn write_u16(bytes: &mut [u8], offset: usize, value: u16) {
         bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
     }
@@ -289,10 +937,15 @@ mod tests {
             app_dir,
             cwd,
             path_dirs,
+            path_env: String::new(),
             safedll,
             system_dir,
             windows_dir,
             system16_dir: None,
+            machine: MachineType::X64,
+            load: LoadOptions::default(),
+            known_dlls: HashSet::new(),
+            dotlocal_dir: None,
         }
     }
 
@@ -313,10 +966,15 @@ mod tests {
             app_dir: PathBuf::from(r"C:\app"),
             cwd: PathBuf::from(r"C:\cwd"),
             path_dirs: path_dirs.iter().map(PathBuf::from).collect(),
+            path_env: path_dirs.join(";"),
             safedll,
             system_dir: PathBuf::from(r"C:\Windows\System32"),
             windows_dir: PathBuf::from(r"C:\Windows"),
             system16_dir: system16_dir.map(PathBuf::from),
+            machine: MachineType::X64,
+            load: LoadOptions::default(),
+            known_dlls: HashSet::new(),
+            dotlocal_dir: None,
         }
     }
 
@@ -424,10 +1082,15 @@ mod tests {
             app_dir: PathBuf::from(r"C:\same"),
             cwd: PathBuf::from(r"c:\SAME"),
             path_dirs: vec![PathBuf::from(r"C:\path1")],
+            path_env: r"C:\path1".to_string(),
             safedll: false,
             system_dir: PathBuf::from(r"C:\Windows\System32"),
             windows_dir: PathBuf::from(r"C:\Windows"),
             system16_dir: None,
+            machine: MachineType::X64,
+            load: LoadOptions::default(),
+            known_dlls: HashSet::new(),
+            dotlocal_dir: None,
         });
         assert_eq!(
             got,
@@ -440,6 +1103,148 @@ mod tests {
         );
     }
 
+    fn plugin_context(load: LoadOptions) -> SearchContext {
+        SearchContext {
+            load: LoadOptions {
+                load_dir: Some(PathBuf::from(r"C:\plugins")),
+                user_dirs: vec![PathBuf::from(r"C:\added")],
+                ..load
+            },
+            ..sample_context(true, None, &[r"C:\path1"])
+        }
+    }
+
+    #[test]
+    fn search_flags_restrict_the_roots_to_the_selected_directories() {
+        let got = ordered_root_strings(plugin_context(LoadOptions {
+            flags: LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR | LOAD_LIBRARY_SEARCH_DEFAULT_DIRS,
+            dll_directory: Some(PathBuf::from(r"C:\dlldir")),
+            ..LoadOptions::default()
+        }));
+        assert_eq!(
+            got,
+            vec![
+                r"C:\plugins",
+                r"C:\app",
+                r"C:\added",
+                r"C:\dlldir",
+                r"C:\Windows\System32",
+            ]
+        );
+
+        let got = ordered_root_strings(plugin_context(LoadOptions {
+            flags: LOAD_LIBRARY_SEARCH_SYSTEM32,
+            ..LoadOptions::default()
+        }));
+        assert_eq!(got, vec![r"C:\Windows\System32"]);
+    }
+
+    #[test]
+    fn default_dirs_apply_only_without_explicit_search_flags() {
+        let defaults = LoadOptions {
+            default_dirs: LOAD_LIBRARY_SEARCH_SYSTEM32 | LOAD_LIBRARY_SEARCH_USER_DIRS,
+            ..LoadOptions::default()
+        };
+        assert_eq!(
+            ordered_root_strings(plugin_context(defaults.clone())),
+            vec![r"C:\added", r"C:\Windows\System32"]
+        );
+        assert_eq!(
+            ordered_root_strings(plugin_context(LoadOptions {
+                flags: LOAD_LIBRARY_SEARCH_APPLICATION_DIR,
+                ..defaults.clone()
+            })),
+            vec![r"C:\app"]
+        );
+        // LOAD_WITH_ALTERED_SEARCH_PATH falls back to the standard order.
+        assert_eq!(
+            ordered_root_strings(plugin_context(LoadOptions {
+                flags: LOAD_WITH_ALTERED_SEARCH_PATH,
+                ..defaults
+            }))[0],
+            r"C:\plugins"
+        );
+    }
+
+    #[test]
+    fn altered_search_path_replaces_the_application_directory() {
+        let got = ordered_root_strings(plugin_context(LoadOptions {
+            flags: LOAD_WITH_ALTERED_SEARCH_PATH,
+            ..LoadOptions::default()
+        }));
+        assert_eq!(
+            got,
+            vec![
+                r"C:\plugins",
+                r"C:\Windows\System32",
+                r"C:\Windows",
+                r"C:\cwd",
+                r"C:\path1",
+            ]
+        );
+    }
+
+    #[test]
+    fn dll_directory_replaces_the_current_directory() {
+        for safedll in [true, false] {
+            let got = ordered_root_strings(SearchContext {
+                load: LoadOptions {
+                    dll_directory: Some(PathBuf::from(r"C:\dlldir")),
+                    ..LoadOptions::default()
+                },
+                ..sample_context(safedll, None, &[r"C:\path1"])
+            });
+            assert_eq!(
+                got,
+                vec![
+                    r"C:\app",
+                    r"C:\dlldir",
+                    r"C:\Windows\System32",
+                    r"C:\Windows",
+                    r"C:\path1",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn load_flags_parse_names_and_hex_and_reject_invalid_combinations() {
+        assert_eq!(
+            LoadOptions::parse_flags("search_system32|LOAD_LIBRARY_SEARCH_USER_DIRS,0x200"),
+            Ok(LOAD_LIBRARY_SEARCH_SYSTEM32
+                | LOAD_LIBRARY_SEARCH_USER_DIRS
+                | LOAD_LIBRARY_SEARCH_APPLICATION_DIR)
+        );
+        assert_eq!(
+            LoadOptions::parse_flags("with_altered_search_path"),
+            Ok(LOAD_WITH_ALTERED_SEARCH_PATH)
+        );
+        assert!(LoadOptions::parse_flags("SEARCH_EVERYWHERE")
+            .unwrap_err()
+            .contains("unknown load flag"));
+
+        let altered_and_search = LoadOptions {
+            flags: LOAD_WITH_ALTERED_SEARCH_PATH | LOAD_LIBRARY_SEARCH_SYSTEM32,
+            ..LoadOptions::default()
+        };
+        assert!(altered_and_search.validate().is_err());
+        let load_dir_default = LoadOptions {
+            default_dirs: LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR,
+            ..LoadOptions::default()
+        };
+        assert!(load_dir_default.validate().is_err());
+        let defaults = LoadOptions {
+            flags: LOAD_LIBRARY_SEARCH_DEFAULT_DIRS,
+            ..LoadOptions::default()
+        };
+        assert!(defaults.validate().is_ok());
+        assert_eq!(
+            defaults.describe(),
+            "LOAD_LIBRARY_SEARCH_APPLICATION_DIR|LOAD_LIBRARY_SEARCH_USER_DIRS|LOAD_LIBRARY_SEARCH_SYSTEM32"
+        );
+        assert_eq!(LoadOptions::default().describe(), "STANDARD");
+    }
+
     #[test]
     fn earlier_bad_image_beats_later_valid_candidate() {
         let temp = unique_temp_dir("bad-image-first");
@@ -463,6 +1268,8 @@ mod tests {
         assert_eq!(resolution.chosen, Some(app_dir.join("foo.dll")));
         assert_eq!(resolution.candidates.len(), 1);
         assert_eq!(resolution.candidates[0].result, "BAD_IMAGE");
+        assert_eq!(resolution.candidates[0].reason, Some("NOT_PE"));
+        assert_eq!(resolution.bad_image_reason(), "NOT_PE");
 
         let _ = fs::remove_dir_all(temp);
     }
@@ -502,6 +1309,61 @@ mod tests {
         let _ = fs::remove_dir_all(temp);
     }
 
+    #[test]
+    fn directory_named_like_the_dll_stops_the_search() {
+        let temp = unique_temp_dir("is-directory");
+        let app_dir = temp.join("app");
+        let path_dir = temp.join("path");
+        fs::create_dir_all(app_dir.join("foo.dll")).expect("failed to create directory");
+        fs::create_dir_all(&path_dir).expect("failed to create path dir");
+        fs::write(path_dir.join("foo.dll"), build_valid_pe())
+            .expect("failed to create valid image");
+
+        let context = temp_context(app_dir.clone(), temp.join("cwd"), vec![path_dir], true);
+        let resolution = resolve_dll("foo.dll", &context);
+        let copies = find_copies("foo.dll", &context);
+        let _ = fs::remove_dir_all(temp);
+
+        assert!(matches!(resolution.kind, ResolutionKind::BadImage));
+        assert_eq!(resolution.chosen, Some(app_dir.join("foo.dll")));
+        assert_eq!(resolution.candidates.len(), 1);
+        assert_eq!(resolution.candidates[0].result, "IS_DIRECTORY");
+        assert_eq!(resolution.candidates[0].reason, None);
+        assert_eq!(resolution.bad_image_reason(), "IS_DIRECTORY");
+        // A directory is not a copy of the DLL.
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].result, "HIT");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn broken_link_is_skipped_like_a_miss() {
+        let temp = unique_temp_dir("broken-link");
+        let app_dir = temp.join("app");
+        let path_dir = temp.join("path");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&path_dir).expect("failed to create path dir");
+        std::os::unix::fs::symlink(temp.join("gone.dll"), app_dir.join("foo.dll"))
+            .expect("failed to create link");
+        fs::write(path_dir.join("foo.dll"), build_valid_pe())
+            .expect("failed to create valid image");
+
+        let context = temp_context(app_dir, temp.join("cwd"), vec![path_dir.clone()], true);
+        let resolution = resolve_dll("foo.dll", &context);
+        let _ = fs::remove_dir_all(temp);
+
+        assert!(matches!(resolution.kind, ResolutionKind::Found));
+        assert_eq!(resolution.chosen, Some(path_dir.join("foo.dll")));
+        assert_eq!(resolution.candidates[0].result, "BROKEN_LINK");
+        assert_eq!(
+            resolution
+                .candidates
+                .last()
+                .map(|candidate| candidate.result),
+            Some("HIT")
+        );
+    }
+
     #[test]
     fn absolute_path_checks_only_requested_candidate_for_bad_image() {
         let temp = unique_temp_dir("absolute-bad-image");
@@ -532,4 +1394,260 @@ mod tests {
 
         let _ = fs::remove_dir_all(temp);
     }
+
+    #[test]
+    fn known_dll_maps_from_the_system_directory_before_app_local_copies() {
+        let temp = unique_temp_dir("known-dll");
+        let app_dir = temp.join("app");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(app_dir.join("foo.dll"), build_valid_pe()).expect("failed to create app copy");
+
+        let mut context = temp_context(app_dir.clone(), temp.join("cwd"), Vec::new(), true);
+        context.known_dlls = parse_known_dlls("# machine list\nFOO.dll\n\n");
+        fs::write(context.system_dir.join("foo.dll"), build_valid_pe())
+            .expect("failed to create system copy");
+        let resolution = resolve_dll("foo.dll", &context);
+
+        assert!(matches!(resolution.kind, ResolutionKind::Found));
+        assert_eq!(resolution.candidates.len(), 1);
+        assert_eq!(resolution.candidates[0].result, "KNOWN_DLL");
+        assert_eq!(resolution.chosen, Some(context.system_dir.join("foo.dll")));
+
+        // Without an image behind the name there is no section to map, and
+        // the normal search finds the app-local copy.
+        fs::remove_file(context.system_dir.join("foo.dll")).expect("failed to remove copy");
+        let resolution = resolve_dll("foo.dll", &context);
+        assert_eq!(resolution.candidates[0].result, "HIT");
+        assert_eq!(resolution.chosen, Some(app_dir.join("foo.dll")));
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn dotlocal_directory_is_searched_first_even_for_full_paths() {
+        let temp = unique_temp_dir("dotlocal");
+        let app_dir = temp.join("app");
+        let dotlocal = app_dir.join("app.exe.local");
+        fs::create_dir_all(&dotlocal).expect("failed to create .local dir");
+        fs::write(app_dir.join("app.exe"), build_valid_pe()).expect("failed to create image");
+        fs::write(app_dir.join("foo.dll"), build_valid_pe()).expect("failed to create app copy");
+
+        let mut context = temp_context(app_dir.clone(), temp.join("cwd"), Vec::new(), true);
+        context.redirect_dotlocal(&app_dir.join("app.exe"));
+        assert_eq!(context.dotlocal_dir, Some(dotlocal.clone()));
+
+        let resolution = resolve_dll("foo.dll", &context);
+        assert_eq!(resolution.candidates[0].path, dotlocal.join("foo.dll"));
+        assert_eq!(resolution.candidates[0].result, "MISS");
+        assert_eq!(resolution.chosen, Some(app_dir.join("foo.dll")));
+
+        fs::write(dotlocal.join("foo.dll"), build_valid_pe()).expect("failed to create copy");
+        let full_path = temp.join("elsewhere").join("foo.dll");
+        let resolution = resolve_dll(&full_path.display().to_string(), &context);
+        assert!(matches!(resolution.kind, ResolutionKind::Found));
+        assert_eq!(resolution.candidates.len(), 1);
+        assert_eq!(resolution.candidates[0].result, "DOTLOCAL");
+        assert_eq!(resolution.chosen, Some(dotlocal.join("foo.dll")));
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn dotlocal_is_ignored_for_images_with_a_manifest() {
+        let temp = unique_temp_dir("dotlocal-manifest");
+        let app_dir = temp.join("app");
+        fs::create_dir_all(app_dir.join("app.exe.local")).expect("failed to create .local dir");
+        fs::write(app_dir.join("app.exe"), build_valid_pe()).expect("failed to create image");
+        fs::write(app_dir.join("app.exe.manifest"), b"<assembly/>")
+            .expect("failed to create manifest");
+
+        let mut context = temp_context(app_dir.clone(), temp.join("cwd"), Vec::new(), true);
+        context.redirect_dotlocal(&app_dir.join("app.exe"));
+        // DevOverrideEnable on this host would honor it anyway.
+        if !super::dev_override_enabled() {
+            assert_eq!(context.dotlocal_dir, None);
+        }
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn same_path_ignores_case_and_separator_style() {
+        assert!(same_path(
+            Path::new(r"C:\App\x.dll"),
+            Path::new(r"c:\app\X.DLL")
+        ));
+        assert!(same_path(
+            Path::new("C:/App/x.dll"),
+            Path::new(r"C:\App\x.dll")
+        ));
+        assert!(!same_path(
+            Path::new(r"C:\App\x.dll"),
+            Path::new(r"C:\Other\x.dll")
+        ));
+    }
+
+    #[test]
+    fn find_copies_lists_every_copy_along_the_search_order() {
+        let temp = unique_temp_dir("find-copies");
+        let app_dir = temp.join("app");
+        let path_dir = temp.join("path");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&path_dir).expect("failed to create path dir");
+        fs::write(app_dir.join("foo.dll"), build_valid_pe()).expect("failed to create app copy");
+        fs::write(path_dir.join("foo.dll"), build_valid_x86_pe())
+            .expect("failed to create path copy");
+
+        let mut context = temp_context(
+            app_dir.clone(),
+            temp.join("cwd"),
+            vec![path_dir.clone()],
+            true,
+        );
+        let copies = find_copies("foo.dll", &context);
+        assert_eq!(copies.len(), 2);
+        assert_eq!(copies[0].path, app_dir.join("foo.dll"));
+        assert_eq!(copies[0].result, "HIT");
+        assert_eq!(copies[1].path, path_dir.join("foo.dll"));
+        assert_eq!(copies[1].result, "WRONG_MACHINE");
+        assert!(copies[0].order < copies[1].order);
+
+        // A known DLL's system copy wins even though the search order
+        // reaches it after the app directory.
+        context.known_dlls = parse_known_dlls("foo.dll");
+        fs::write(context.system_dir.join("foo.dll"), build_valid_pe())
+            .expect("failed to create system copy");
+        let copies = find_copies("foo.dll", &context);
+        assert_eq!(copies.len(), 3);
+        let known = copies
+            .iter()
+            .find(|copy| copy.result == "KNOWN_DLL")
+            .expect("known copy");
+        assert_eq!(known.path, context.system_dir.join("foo.dll"));
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn wrong_machine_candidate_is_skipped_for_later_match() {
+        let temp = unique_temp_dir("wrong-machine-skip");
+        let app_dir = temp.join("app");
+        let path_dir = temp.join("path");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&path_dir).expect("failed to create path dir");
+        fs::write(app_dir.join("foo.dll"), build_valid_x86_pe())
+            .expect("failed to create x86 image");
+        fs::write(path_dir.join("foo.dll"), build_valid_pe()).expect("failed to create x64 image");
+
+        let context = temp_context(
+            app_dir.clone(),
+            temp.join("cwd"),
+            vec![path_dir.clone()],
+            true,
+        );
+        let resolution = resolve_dll("foo.dll", &context);
+
+        assert!(matches!(resolution.kind, ResolutionKind::Found));
+        assert_eq!(resolution.chosen, Some(path_dir.join("foo.dll")));
+        assert_eq!(resolution.candidates[0].result, "WRONG_MACHINE");
+        assert_eq!(
+            resolution
+                .candidates
+                .last()
+                .map(|candidate| candidate.result),
+            Some("HIT")
+        );
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn only_wrong_machine_candidates_report_first_as_bad_image() {
+        let temp = unique_temp_dir("wrong-machine-only");
+        let app_dir = temp.join("app");
+        let path_dir = temp.join("path");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::create_dir_all(&path_dir).expect("failed to create path dir");
+        fs::write(app_dir.join("foo.dll"), build_valid_x86_pe())
+            .expect("failed to create x86 image");
+        fs::write(path_dir.join("foo.dll"), build_valid_x86_pe())
+            .expect("failed to create x86 image");
+
+        let context = temp_context(
+            app_dir.clone(),
+            temp.join("cwd"),
+            vec![path_dir.clone()],
+            true,
+        );
+        let resolution = resolve_dll("foo.dll", &context);
+
+        assert!(matches!(resolution.kind, ResolutionKind::BadImage));
+        assert_eq!(resolution.chosen, Some(app_dir.join("foo.dll")));
+        assert!(resolution
+            .candidates
+            .iter()
+            .all(|candidate| candidate.result != "HIT"));
+        assert_eq!(resolution.bad_image_reason(), "WRONG_MACHINE");
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn truncated_candidate_reports_truncated_reason() {
+        let temp = unique_temp_dir("truncated-candidate");
+        let app_dir = temp.join("app");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        let mut bytes = build_valid_pe();
+        bytes.truncate(0x220);
+        fs::write(app_dir.join("foo.dll"), bytes).expect("failed to create truncated image");
+        fs::write(app_dir.join("empty.dll"), b"").expect("failed to create empty image");
+
+        let context = temp_context(app_dir, temp.join("cwd"), Vec::new(), true);
+        let resolution = resolve_dll("foo.dll", &context);
+        assert!(matches!(resolution.kind, ResolutionKind::BadImage));
+        assert_eq!(resolution.candidates[0].reason, Some("TRUNCATED"));
+
+        let resolution = resolve_dll("empty.dll", &context);
+        assert_eq!(resolution.bad_image_reason(), "ZERO_LENGTH");
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn x86_context_accepts_x86_candidate() {
+        let temp = unique_temp_dir("x86-hit");
+        let app_dir = temp.join("app");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(app_dir.join("foo.dll"), build_valid_x86_pe())
+            .expect("failed to create x86 image");
+
+        let mut context = temp_context(app_dir.clone(), temp.join("cwd"), Vec::new(), true);
+        context.machine = MachineType::X86;
+        let resolution = resolve_dll("foo.dll", &context);
+
+        assert!(matches!(resolution.kind, ResolutionKind::Found));
+        assert_eq!(resolution.chosen, Some(app_dir.join("foo.dll")));
+
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn arm64_context_skips_x64_candidate() {
+        let temp = unique_temp_dir("arm64-skip-x64");
+        let app_dir = temp.join("app");
+        fs::create_dir_all(&app_dir).expect("failed to create app dir");
+        fs::write(app_dir.join("foo.dll"), build_valid_pe()).expect("failed to create x64 image");
+
+        let mut context = temp_context(app_dir.clone(), temp.join("cwd"), Vec::new(), true);
+        context.machine = MachineType::Arm64;
+        let resolution = resolve_dll("foo.dll", &context);
+        assert!(matches!(resolution.kind, ResolutionKind::BadImage));
+        assert_eq!(resolution.candidates[0].result, "WRONG_MACHINE");
+
+        context.machine = MachineType::Arm64ec;
+        let resolution = resolve_dll("foo.dll", &context);
+        assert!(matches!(resolution.kind, ResolutionKind::Found));
+
+        let _ = fs::remove_dir_all(temp);
+    }
 }
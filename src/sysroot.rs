@@ -0,0 +1,559 @@
+// Offline analysis of a mounted Windows volume or extracted image layer:
+// maps Windows paths onto the host directory and supplies the machine
+// settings the loader would otherwise read through Win32.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::hive::{Hive, HiveKey, HiveValue};
+
+/// Drive the mounted volume stands in for.
+pub const SYSTEM_DRIVE: &str = "C:";
+/// `%SystemRoot%` of the mounted volume, in the `/`-separated form used for
+/// paths inside a sysroot (see `to_virtual`).
+pub const WINDOWS_DIR: &str = "C:/Windows";
+
+const SYSTEM_HIVE: &str = "C:/Windows/System32/config/SYSTEM";
+const SOFTWARE_HIVE: &str = "C:/Windows/System32/config/SOFTWARE";
+const SESSION_MANAGER: &str = r"Control\Session Manager";
+const IMAGE_FILE_EXECUTION_OPTIONS: &str =
+    r"Microsoft\Windows NT\CurrentVersion\Image File Execution Options";
+
+/// A Windows directory tree on the host file system.
+///
+/// Paths inside a sysroot are written as `C:/Windows/System32/x.dll`: the
+/// system drive followed by `/`-separated components, so `Path` joins and
+/// splits them the same way on every host. File access maps them onto the
+/// host directory with case-insensitive component matching; output renders
+/// them with backslashes, exactly as a native run prints them.
+pub struct Sysroot {
+    root: PathBuf,
+    safedll: bool,
+    environment: Vec<(String, String)>,
+    known_dlls: Vec<String>,
+    listings: RefCell<HashMap<PathBuf, Vec<OsString>>>,
+}
+
+impl Sysroot {
+    /// Opens `root`, which must contain a `Windows` directory. Machine
+    /// settings come from `Windows\System32\config\SYSTEM` when present;
+    /// without it the loader defaults apply (SafeDllSearchMode on, empty
+    /// machine PATH).
+    pub fn open(root: &Path) -> Result<Sysroot, String> {
+        if !root.is_dir() {
+            return Err(format!("sysroot is not a directory: {}", root.display()));
+        }
+        let root = std::path::absolute(root)
+            .map_err(|e| format!("failed to resolve sysroot {}: {e}", root.display()))?;
+        let mut sysroot = Sysroot {
+            root,
+            safedll: true,
+            environment: Vec::new(),
+            known_dlls: Vec::new(),
+            listings: RefCell::new(HashMap::new()),
+        };
+        if !sysroot.host_path(Path::new(WINDOWS_DIR)).is_dir() {
+            return Err(format!(
+                "sysroot has no Windows directory: {}",
+                sysroot.root.display()
+            ));
+        }
+
+        let system = sysroot.host_path(Path::new(SYSTEM_HIVE));
+        let system = if system.is_file() {
+            Some(Hive::open(&system)?)
+        } else {
+            None
+        };
+        sysroot.safedll = system
+            .as_ref()
+            .and_then(|hive| {
+                let key = current_control_set_key(hive, SESSION_MANAGER)?;
+                hive.value(key, "SafeDllSearchMode")
+            })
+            .and_then(|value| value.as_dword())
+            .is_none_or(|value| value != 0);
+        sysroot.environment = machine_environment(system.as_ref());
+        sysroot.known_dlls = system.as_ref().map(known_dlls).unwrap_or_default();
+        Ok(sysroot)
+    }
+
+    /// The host directory standing in for the system drive.
+    #[cfg(test)]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The SafeDllSearchMode setting of the mounted system.
+    pub fn safe_dll_search_mode(&self) -> bool {
+        self.safedll
+    }
+
+    /// Lowercased DLL names of the mounted system's `KnownDLLs` list; empty
+    /// without a SYSTEM hive.
+    pub fn known_dlls(&self) -> &[String] {
+        &self.known_dlls
+    }
+
+    /// The `DevOverrideEnable` setting of the mounted system, read from its
+    /// SOFTWARE hive on demand since only `.local` redirection needs it.
+    pub fn dev_override_enabled(&self) -> bool {
+        let path = self.software_hive();
+        if !path.is_file() {
+            return false;
+        }
+        Hive::open(&path)
+            .ok()
+            .and_then(|hive| {
+                let key = hive.open_key(IMAGE_FILE_EXECUTION_OPTIONS)?;
+                hive.value(key, "DevOverrideEnable")
+            })
+            .and_then(|value| value.as_dword())
+            .is_some_and(|value| value != 0)
+    }
+
+    /// Host path of the machine (`HKLM\SOFTWARE`) registry hive.
+    pub fn software_hive(&self) -> PathBuf {
+        self.host_path(Path::new(SOFTWARE_HIVE))
+    }
+
+    /// A machine environment variable (`SystemRoot`, `windir`, `Path`, ...)
+    /// with references to other machine variables expanded. Names match
+    /// case-insensitively, as on Windows.
+    pub fn env_var(&self, name: &str) -> Option<&str> {
+        self.environment
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Converts a command-line path to the sysroot form. Host paths under
+    /// the sysroot are rebased onto the system drive; `C:\...` paths are
+    /// taken as already naming a file on the mounted volume.
+    pub fn to_virtual(&self, path: &Path) -> Result<PathBuf, String> {
+        let text = path.to_string_lossy();
+        if !path.is_absolute() && strip_system_drive(&text).is_some() {
+            return Ok(PathBuf::from(text.replace('\\', "/")));
+        }
+        let absolute = std::path::absolute(path)
+            .map_err(|e| format!("failed to resolve {}: {e}", path.display()))?;
+        if let Ok(rest) = absolute.strip_prefix(&self.root) {
+            let mut out = String::from(SYSTEM_DRIVE);
+            for part in rest.components() {
+                out.push('/');
+                out.push_str(&part.as_os_str().to_string_lossy());
+            }
+            if out == SYSTEM_DRIVE {
+                out.push('/');
+            }
+            return Ok(PathBuf::from(out));
+        }
+        if strip_system_drive(&text).is_some() {
+            return Ok(PathBuf::from(text.replace('\\', "/")));
+        }
+        Err(format!(
+            "{} is outside the sysroot {}",
+            path.display(),
+            self.root.display()
+        ))
+    }
+
+    /// Host location of a path on the mounted volume. Each component is
+    /// matched case-insensitively against the directory listing; the first
+    /// component that does not exist and everything after it are kept as
+    /// written. Paths on other drives are returned unchanged and so never
+    /// resolve to a sysroot file.
+    pub fn host_path(&self, path: &Path) -> PathBuf {
+        let text = path.to_string_lossy();
+        let Some(rest) = strip_system_drive(&text) else {
+            return path.to_path_buf();
+        };
+        let mut parts: Vec<&str> = Vec::new();
+        for part in rest.split(['\\', '/']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+
+        let mut host = self.root.clone();
+        for (index, part) in parts.iter().enumerate() {
+            match self.find_entry(&host, part) {
+                Some(name) => host.push(name),
+                None => {
+                    host.extend(&parts[index..]);
+                    break;
+                }
+            }
+        }
+        host
+    }
+
+    fn find_entry(&self, dir: &Path, name: &str) -> Option<OsString> {
+        let mut listings = self.listings.borrow_mut();
+        let entries = listings.entry(dir.to_path_buf()).or_insert_with(|| {
+            std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(Result::ok)
+                        .map(|entry| entry.file_name())
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+        entries
+            .iter()
+            .find(|entry| entry.to_str() == Some(name))
+            .or_else(|| {
+                let lower = name.to_lowercase();
+                entries
+                    .iter()
+                    .find(|entry| entry.to_string_lossy().to_lowercase() == lower)
+            })
+            .cloned()
+    }
+}
+
+/// Returns what follows the system drive in `C:\...`, `C:/...` or `C:`.
+fn strip_system_drive(path: &str) -> Option<&str> {
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+    let drive = path.get(..SYSTEM_DRIVE.len())?;
+    let rest = &path[SYSTEM_DRIVE.len()..];
+    (drive.eq_ignore_ascii_case(SYSTEM_DRIVE) && (rest.is_empty() || rest.starts_with(['\\', '/'])))
+        .then_some(rest)
+}
+
+/// Opens `key` in the control set the system boots with (`Select\Current`),
+/// since an offline SYSTEM hive has no `CurrentControlSet` link.
+fn current_control_set_key(hive: &Hive, key: &str) -> Option<HiveKey> {
+    let current = hive
+        .open_key("Select")
+        .and_then(|select| hive.value(select, "Current"))
+        .and_then(|value| value.as_dword())
+        .unwrap_or(1);
+    hive.open_key(&format!(r"ControlSet{current:03}\{key}"))
+}
+
+/// The string values under `Session Manager\KnownDLLs`. `DllDirectory` and
+/// `DllDirectory32` name the backing directories, not DLLs.
+fn known_dlls(system: &Hive) -> Vec<String> {
+    let Some(key) = current_control_set_key(system, &format!(r"{SESSION_MANAGER}\KnownDLLs"))
+    else {
+        return Vec::new();
+    };
+    system
+        .value_names(key)
+        .into_iter()
+        .filter(|name| !name.to_ascii_lowercase().starts_with("dlldirectory"))
+        .filter_map(|name| match system.value(key, &name)? {
+            HiveValue::String(text) | HiveValue::ExpandString(text) => {
+                Some(text.trim().to_ascii_lowercase())
+            }
+            _ => None,
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Variables the system defines for every process, followed by the machine
+/// environment block from the SYSTEM hive with each value expanded against
+/// the variables before it.
+fn machine_environment(system: Option<&Hive>) -> Vec<(String, String)> {
+    let mut environment: Vec<(String, String)> = [
+        ("SystemDrive", SYSTEM_DRIVE.to_string()),
+        ("SystemRoot", WINDOWS_DIR.replace('/', "\\")),
+        ("ProgramFiles", format!(r"{SYSTEM_DRIVE}\Program Files")),
+        (
+            "ProgramFiles(x86)",
+            format!(r"{SYSTEM_DRIVE}\Program Files (x86)"),
+        ),
+        ("ProgramW6432", format!(r"{SYSTEM_DRIVE}\Program Files")),
+        (
+            "CommonProgramFiles",
+            format!(r"{SYSTEM_DRIVE}\Program Files\Common Files"),
+        ),
+        (
+            "CommonProgramFiles(x86)",
+            format!(r"{SYSTEM_DRIVE}\Program Files (x86)\Common Files"),
+        ),
+        (
+            "CommonProgramW6432",
+            format!(r"{SYSTEM_DRIVE}\Program Files\Common Files"),
+        ),
+        ("ProgramData", format!(r"{SYSTEM_DRIVE}\ProgramData")),
+        ("ALLUSERSPROFILE", format!(r"{SYSTEM_DRIVE}\ProgramData")),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+
+    let Some(hive) = system else {
+        return environment;
+    };
+    let Some(key) = current_control_set_key(hive, &format!(r"{SESSION_MANAGER}\Environment"))
+    else {
+        return environment;
+    };
+    for name in hive.value_names(key) {
+        let Some(value) = hive.value(key, &name) else {
+            continue;
+        };
+        let value = match &value {
+            HiveValue::String(text) => text.clone(),
+            HiveValue::ExpandString(text) => expand(text, &environment),
+            _ => continue,
+        };
+        environment.retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+        environment.push((name, value));
+    }
+    environment
+}
+
+/// Expands `%NAME%` references against `environment`, keeping unknown ones
+/// literally as `ExpandEnvironmentStringsW` does.
+fn expand(value: &str, environment: &[(String, String)]) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            out.push('%');
+            rest = after;
+            break;
+        };
+        let name = &after[..end];
+        match environment
+            .iter()
+            .find(|(key, _)| !name.is_empty() && key.eq_ignore_ascii_case(name))
+        {
+            Some((_, replacement)) => out.push_str(replacement),
+            None => {
+                out.push('%');
+                out.push_str(name);
+                out.push('%');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Rc<Sysroot>>> = const { RefCell::new(None) };
+}
+
+/// Makes a sysroot the machine every lookup on this thread runs against,
+/// until dropped.
+pub struct SysrootScope {
+    previous: Option<Rc<Sysroot>>,
+}
+
+impl SysrootScope {
+    pub fn enter(sysroot: Sysroot) -> SysrootScope {
+        let previous = ACTIVE.with(|active| active.replace(Some(Rc::new(sysroot))));
+        SysrootScope { previous }
+    }
+}
+
+impl Drop for SysrootScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ACTIVE.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+/// The active sysroot, if any.
+pub fn current() -> Option<Rc<Sysroot>> {
+    ACTIVE.with(|active| active.borrow().clone())
+}
+
+/// Host location of `path`: mapped into the active sysroot, or `path`
+/// itself when none is active.
+pub fn host_path(path: &Path) -> PathBuf {
+    match current() {
+        Some(sysroot) => sysroot.host_path(path),
+        None => path.to_path_buf(),
+    }
+}
+
+/// A path string from the registry or a manifest (`C:\...`) as a `Path`
+/// that splits into components on this host.
+pub fn native_path(path: &str) -> PathBuf {
+    if current().is_some() {
+        PathBuf::from(path.replace('\\', "/"))
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+/// A machine environment variable: the sysroot's when one is active, the
+/// process's otherwise.
+pub fn env_var(name: &str) -> Option<String> {
+    match current() {
+        Some(sysroot) => sysroot.env_var(name).map(str::to_string),
+        None => std::env::var(name).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hive::testhive::{build_hive, utf16z, KeySpec};
+    use crate::hive::REG_EXPAND_SZ;
+    use std::fs;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT_TEST_ID: AtomicU64 = AtomicU64::new(0);
+
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        let id = NEXT_TEST_ID.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "loadwhat-sysroot-{name}-{}-{id}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).expect("failed to create temp dir");
+        dir
+    }
+
+    fn system_hive(safedll: u32) -> Vec<u8> {
+        let root = KeySpec::new("ROOT")
+            .at("Select", |key| key.dword("Current", 1))
+            .at(r"ControlSet001\Control\Session Manager", |key| {
+                key.dword("SafeDllSearchMode", safedll)
+            })
+            .at(r"ControlSet001\Control\Session Manager\KnownDLLs", |key| {
+                key.string("kernel32", "KERNEL32.dll").value(
+                    "DllDirectory",
+                    REG_EXPAND_SZ,
+                    utf16z(r"%SystemRoot%\system32"),
+                )
+            })
+            .at(
+                r"ControlSet001\Control\Session Manager\Environment",
+                |key| {
+                    key.value(
+                        "Path",
+                        REG_EXPAND_SZ,
+                        utf16z(r"%SystemRoot%\system32;%SYSTEMROOT%;%Missing%\bin"),
+                    )
+                    .value("windir", REG_EXPAND_SZ, utf16z("%SystemRoot%"))
+                },
+            );
+        build_hive(&root)
+    }
+
+    #[test]
+    fn maps_windows_paths_case_insensitively() {
+        let root = unique_temp_dir("case");
+        fs::create_dir_all(root.join("WINDOWS").join("system32")).unwrap();
+        fs::write(
+            root.join("WINDOWS").join("system32").join("KERNEL32.DLL"),
+            b"x",
+        )
+        .unwrap();
+
+        let sysroot = Sysroot::open(&root).unwrap();
+        assert_eq!(
+            sysroot.host_path(Path::new(r"C:\Windows\System32\kernel32.dll")),
+            sysroot.root().join("WINDOWS/system32/KERNEL32.DLL")
+        );
+        assert_eq!(
+            sysroot.host_path(Path::new("c:/windows/SYSTEM32/missing/x.dll")),
+            sysroot.root().join("WINDOWS/system32/missing/x.dll")
+        );
+        assert_eq!(
+            sysroot.host_path(Path::new(r"D:\tools\x.dll")),
+            PathBuf::from(r"D:\tools\x.dll")
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn reads_machine_settings_from_the_system_hive() {
+        let root = unique_temp_dir("hive");
+        let config = root.join("Windows").join("System32").join("config");
+        fs::create_dir_all(&config).unwrap();
+        fs::write(config.join("SYSTEM"), system_hive(0)).unwrap();
+
+        let sysroot = Sysroot::open(&root).unwrap();
+        assert!(!sysroot.safe_dll_search_mode());
+        assert_eq!(
+            sysroot.env_var("PATH"),
+            Some(r"C:\Windows\system32;C:\Windows;%Missing%\bin")
+        );
+        assert_eq!(sysroot.env_var("WINDIR"), Some(r"C:\Windows"));
+        assert_eq!(sysroot.known_dlls(), ["kernel32.dll"]);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn defaults_apply_without_a_system_hive() {
+        let root = unique_temp_dir("defaults");
+        fs::create_dir_all(root.join("Windows")).unwrap();
+
+        let sysroot = Sysroot::open(&root).unwrap();
+        assert!(sysroot.safe_dll_search_mode());
+        assert_eq!(sysroot.env_var("Path"), None);
+        assert_eq!(sysroot.env_var("systemroot"), Some(r"C:\Windows"));
+        assert!(sysroot.known_dlls().is_empty());
+        assert!(!sysroot.dev_override_enabled());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_a_directory_without_windows() {
+        let root = unique_temp_dir("empty");
+        let err = Sysroot::open(&root).err().expect("open should fail");
+        assert!(err.contains("no Windows directory"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn converts_command_line_paths_to_the_system_drive() {
+        let root = unique_temp_dir("virtual");
+        fs::create_dir_all(root.join("Windows")).unwrap();
+        let sysroot = Sysroot::open(&root).unwrap();
+
+        assert_eq!(
+            sysroot
+                .to_virtual(&root.join("Program Files").join("app.exe"))
+                .unwrap(),
+            PathBuf::from("C:/Program Files/app.exe")
+        );
+        assert_eq!(
+            sysroot.to_virtual(Path::new(r"C:\App\app.exe")).unwrap(),
+            PathBuf::from("C:/App/app.exe")
+        );
+        assert!(sysroot.to_virtual(Path::new("elsewhere.exe")).is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn scope_routes_lookups_through_the_sysroot() {
+        let root = unique_temp_dir("scope");
+        fs::create_dir_all(root.join("windows")).unwrap();
+        let sysroot = Sysroot::open(&root).unwrap();
+        let expected = sysroot.root().join("windows");
+
+        assert_eq!(
+            host_path(Path::new("C:/Windows")),
+            PathBuf::from("C:/Windows")
+        );
+        {
+            let _scope = SysrootScope::enter(sysroot);
+            assert_eq!(host_path(Path::new(r"C:\Windows")), expected);
+            assert_eq!(env_var("SystemDrive").as_deref(), Some("C:"));
+            assert_eq!(native_path(r"C:\a\b.dll"), PathBuf::from("C:/a/b.dll"));
+        }
+        assert!(current().is_none());
+        let _ = fs::remove_dir_all(root);
+    }
+}
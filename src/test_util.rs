@@ -1,5 +1,10 @@
 use std::env;
-use std::ffi::{OsStr, OsString};
+#[cfg(windows)]
+use std::ffi::OsStr;
+use std::ffi::OsString;
+
+/// Serializes tests that read or modify process environment variables.
+pub(crate) static TEST_ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
 pub(crate) struct EnvVarGuard {
     name: &'static str,
@@ -13,12 +18,14 @@ impl EnvVarGuard {
         Self { name, previous }
     }
 
+    #[cfg(windows)]
     pub(crate) fn set_os(name: &'static str, value: &OsStr) -> Self {
         let previous = env::var_os(name);
         env::set_var(name, value);
         Self { name, previous }
     }
 
+    #[cfg(windows)]
     pub(crate) fn remove(name: &'static str) -> Self {
         let previous = env::var_os(name);
         env::remove_var(name);
@@ -0,0 +1,273 @@
+// Renders the static import closure as an indented tree for `imports --tree`.
+
+use std::collections::HashMap;
+
+/// How one import edge resolved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeStatus {
+    Found,
+    KnownDll,
+    /// An API set name resolved through the schema to this host DLL.
+    ApiSet(String),
+    /// Nothing to load; the reason is the `STATIC_MISSING` one.
+    Missing(&'static str),
+    /// The search stopped at something that cannot be loaded.
+    BadImage(&'static str),
+}
+
+impl TreeStatus {
+    pub fn is_problem(&self) -> bool {
+        matches!(self, TreeStatus::Missing(_) | TreeStatus::BadImage(_))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            TreeStatus::Found => "FOUND".to_string(),
+            TreeStatus::KnownDll => "KNOWN_DLL".to_string(),
+            TreeStatus::ApiSet(host) => format!("API_SET {host}"),
+            TreeStatus::Missing(reason) => format!("MISSING {reason}"),
+            TreeStatus::BadImage(reason) => format!("BAD_IMAGE {reason}"),
+        }
+    }
+}
+
+/// One import of one module, in the order the walk met it.
+#[derive(Clone, Debug)]
+pub struct TreeEdge {
+    /// Visit key of the importing module.
+    pub parent: String,
+    /// The name as imported; an API set keeps its own name.
+    pub dll: String,
+    pub delay: bool,
+    /// A forwarder hop out of `parent`'s exports rather than an import;
+    /// only the graph shows these.
+    pub forward: bool,
+    pub status: TreeStatus,
+    /// Display path of the module loaded for this import.
+    pub path: Option<String>,
+    /// Visit key of that module, whose own imports are its children.
+    pub child: Option<String>,
+    /// The module lives under the Windows directory.
+    pub system: bool,
+}
+
+/// What `imports --tree` shows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// Deepest import level expanded; the root's imports are level 1.
+    pub max_depth: Option<usize>,
+    /// Keep only branches that lead to a missing or bad image.
+    pub only_problems: bool,
+    /// Drop modules under the Windows directory unless a problem is below them.
+    pub hide_system: bool,
+}
+
+/// The tree rooted at `root_key`, one line per node, depth-first in import
+/// order. Each module's imports are expanded where it first appears; later
+/// appearances end in `(see line N)` instead. A node whose children are cut
+/// off by `max_depth` ends in `...`.
+pub fn render(
+    root_name: &str,
+    root_path: &str,
+    root_key: &str,
+    edges: &[TreeEdge],
+    options: &TreeOptions,
+) -> Vec<String> {
+    let mut children: HashMap<&str, Vec<&TreeEdge>> = HashMap::new();
+    for edge in edges.iter().filter(|edge| !edge.forward) {
+        children.entry(edge.parent.as_str()).or_default().push(edge);
+    }
+    let mut renderer = Renderer {
+        children,
+        options,
+        problems: HashMap::new(),
+        expanded: HashMap::new(),
+        lines: vec![format!("{root_name} {root_path}")],
+    };
+    renderer.expanded.insert(root_key.to_string(), 1);
+    renderer.walk(root_key, 1);
+    renderer.lines
+}
+
+struct Renderer<'a> {
+    children: HashMap<&'a str, Vec<&'a TreeEdge>>,
+    options: &'a TreeOptions,
+    /// Whether a problem lies below each module; `false` while in progress
+    /// so import cycles terminate.
+    problems: HashMap<&'a str, bool>,
+    /// Line number of each expanded module's own line.
+    expanded: HashMap<String, usize>,
+    lines: Vec<String>,
+}
+
+impl<'a> Renderer<'a> {
+    fn walk(&mut self, key: &str, depth: usize) {
+        for edge in self.visible_children(key) {
+            let mut line = format!(
+                "{}{} [{}]",
+                "  ".repeat(depth),
+                edge.dll,
+                edge.status.label()
+            );
+            if let Some(path) = &edge.path {
+                line.push(' ');
+                line.push_str(path);
+            }
+            if edge.delay {
+                line.push_str(" (delay)");
+            }
+
+            let Some(child) = edge.child.as_deref() else {
+                self.lines.push(line);
+                continue;
+            };
+            if self.visible_children(child).is_empty() {
+                self.lines.push(line);
+            } else if let Some(first) = self.expanded.get(child) {
+                line.push_str(&format!(" (see line {first})"));
+                self.lines.push(line);
+            } else if self.options.max_depth.is_some_and(|max| depth >= max) {
+                line.push_str(" ...");
+                self.lines.push(line);
+            } else {
+                self.lines.push(line);
+                self.expanded.insert(child.to_string(), self.lines.len());
+                self.walk(child, depth + 1);
+            }
+        }
+    }
+
+    fn visible_children(&mut self, key: &str) -> Vec<&'a TreeEdge> {
+        let edges = self.children.get(key).cloned().unwrap_or_default();
+        edges
+            .into_iter()
+            .filter(|edge| {
+                let problem = edge.status.is_problem()
+                    || edge.child.as_deref().is_some_and(|c| self.has_problem(c));
+                !(self.options.only_problems && !problem
+                    || self.options.hide_system && edge.system && !problem)
+            })
+            .collect()
+    }
+
+    fn has_problem(&mut self, key: &str) -> bool {
+        let Some((&key, edges)) = self.children.get_key_value(key) else {
+            return false;
+        };
+        if let Some(&known) = self.problems.get(key) {
+            return known;
+        }
+        self.problems.insert(key, false);
+        let edges = edges.clone();
+        let problem = edges.iter().any(|edge| {
+            edge.status.is_problem() || edge.child.as_deref().is_some_and(|c| self.has_problem(c))
+        });
+        self.problems.insert(key, problem);
+        problem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, TreeEdge, TreeOptions, TreeStatus};
+
+    fn edge(parent: &str, dll: &str, status: TreeStatus, system: bool) -> TreeEdge {
+        let loaded = matches!(
+            status,
+            TreeStatus::Found | TreeStatus::KnownDll | TreeStatus::ApiSet(_)
+        );
+        let path = if system {
+            format!(r"C:\Windows\System32\{dll}")
+        } else {
+            format!(r"C:\App\{dll}")
+        };
+        TreeEdge {
+            parent: parent.to_string(),
+            dll: dll.to_string(),
+            delay: false,
+            forward: false,
+            status,
+            path: loaded.then(|| path.clone()),
+            child: loaded.then_some(path.to_ascii_lowercase()),
+            system,
+        }
+    }
+
+    fn closure() -> Vec<TreeEdge> {
+        let root = r"c:\app\app.exe";
+        let core = r"c:\app\core.dll";
+        let kernel32 = r"c:\windows\system32\kernel32.dll";
+        vec![
+            edge(root, "kernel32.dll", TreeStatus::KnownDll, true),
+            edge(root, "core.dll", TreeStatus::Found, false),
+            edge(root, "plugin.dll", TreeStatus::Found, false),
+            edge(kernel32, "ntdll.dll", TreeStatus::KnownDll, true),
+            edge(core, "kernel32.dll", TreeStatus::KnownDll, true),
+            edge(core, "missing.dll", TreeStatus::Missing("NOT_FOUND"), false),
+            edge(r"c:\app\plugin.dll", "core.dll", TreeStatus::Found, false),
+            TreeEdge {
+                forward: true,
+                ..edge(kernel32, "kernelbase.dll", TreeStatus::Found, true)
+            },
+        ]
+    }
+
+    fn lines(options: &TreeOptions) -> Vec<String> {
+        render(
+            "app.exe",
+            r"C:\App\app.exe",
+            r"c:\app\app.exe",
+            &closure(),
+            options,
+        )
+    }
+
+    #[test]
+    fn repeated_modules_point_back_to_their_first_expansion() {
+        assert_eq!(
+            lines(&TreeOptions::default()),
+            [
+                r"app.exe C:\App\app.exe",
+                r"  kernel32.dll [KNOWN_DLL] C:\Windows\System32\kernel32.dll",
+                r"    ntdll.dll [KNOWN_DLL] C:\Windows\System32\ntdll.dll",
+                r"  core.dll [FOUND] C:\App\core.dll",
+                r"    kernel32.dll [KNOWN_DLL] C:\Windows\System32\kernel32.dll (see line 2)",
+                r"    missing.dll [MISSING NOT_FOUND]",
+                r"  plugin.dll [FOUND] C:\App\plugin.dll",
+                r"    core.dll [FOUND] C:\App\core.dll (see line 4)",
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_keep_only_branches_that_lead_to_problems() {
+        let only_problems = lines(&TreeOptions {
+            only_problems: true,
+            ..TreeOptions::default()
+        });
+        assert_eq!(
+            only_problems,
+            [
+                r"app.exe C:\App\app.exe",
+                r"  core.dll [FOUND] C:\App\core.dll",
+                r"    missing.dll [MISSING NOT_FOUND]",
+                r"  plugin.dll [FOUND] C:\App\plugin.dll",
+                r"    core.dll [FOUND] C:\App\core.dll (see line 2)",
+            ]
+        );
+
+        let hide_system = lines(&TreeOptions {
+            hide_system: true,
+            max_depth: Some(1),
+            ..TreeOptions::default()
+        });
+        assert_eq!(
+            hide_system,
+            [
+                r"app.exe C:\App\app.exe",
+                r"  core.dll [FOUND] C:\App\core.dll ...",
+                r"  plugin.dll [FOUND] C:\App\plugin.dll ...",
+            ]
+        );
+    }
+}
@@ -4,7 +4,7 @@ use std::ffi::c_void;
 use std::ffi::{OsStr, OsString};
 use std::mem;
 use std::os::windows::ffi::{OsStrExt, OsStringExt};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub type Bool = i32;
 pub type Byte = u8;
@@ -23,6 +23,7 @@ pub type Lpcwstr = *const u16;
 pub type Ntstatus = i32;
 
 pub const DEBUG_ONLY_THIS_PROCESS: Dword = 0x00000002;
+pub const CREATE_UNICODE_ENVIRONMENT: Dword = 0x00000400;
 pub const WAIT_TIMEOUT: Dword = 258;
 pub const ERROR_SEM_TIMEOUT: Dword = 121;
 pub const DBG_CONTINUE: Dword = 0x00010002;
@@ -44,7 +45,6 @@ pub const KEY_SET_VALUE: Regsam = 0x00000002;
 pub const KEY_WOW64_64KEY: Regsam = 0x00000100;
 pub const KEY_WOW64_32KEY: Regsam = 0x00000200;
 pub const REG_SZ: Dword = 1;
-pub const REG_EXPAND_SZ: Dword = 2;
 pub const REG_DWORD: Dword = 4;
 pub const REG_OPTION_NON_VOLATILE: Dword = 0;
 pub const ERROR_FILE_NOT_FOUND: Dword = 2;
@@ -52,9 +52,13 @@ pub const ERROR_ACCESS_DENIED: Dword = 5;
 pub const ERROR_INVALID_PARAMETER: Dword = 87;
 pub const ERROR_MORE_DATA: Dword = 234;
 pub const ERROR_NO_MORE_ITEMS: Dword = 259;
+pub const SE_FILE_OBJECT: u32 = 1;
+pub const DACL_SECURITY_INFORMATION: Dword = 0x00000004;
+pub const TOKEN_QUERY: Dword = 0x0008;
+pub const TOKEN_USER_CLASS: u32 = 1;
 
 #[cfg(test)]
-pub(crate) static TEST_ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+pub(crate) use crate::test_util::TEST_ENV_LOCK;
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -241,10 +245,13 @@ extern "system" {
     ) -> Dword;
 
     pub fn GetSystemDirectoryW(lp_buffer: Lpwstr, u_size: Uint) -> Uint;
+    pub fn GetSystemWow64DirectoryW(lp_buffer: Lpwstr, u_size: Uint) -> Uint;
     pub fn GetWindowsDirectoryW(lp_buffer: Lpwstr, u_size: Uint) -> Uint;
     pub fn IsWow64Process(h_process: Handle, wow64_process: *mut Bool) -> Bool;
     pub fn GetModuleHandleW(lp_module_name: Lpcwstr) -> Handle;
     pub fn GetProcAddress(h_module: Handle, lp_proc_name: *const u8) -> *const c_void;
+    pub fn GetCurrentProcess() -> Handle;
+    pub fn LocalFree(h_mem: Lpvoid) -> Lpvoid;
 }
 
 #[link(name = "advapi32")]
@@ -300,7 +307,43 @@ extern "system" {
         lpft_last_write_time: Lpvoid,
     ) -> i32;
 
+    pub fn RegEnumValueW(
+        h_key: Hkey,
+        dw_index: Dword,
+        lp_value_name: Lpwstr,
+        lpcch_value_name: *mut Dword,
+        lp_reserved: *mut Dword,
+        lp_type: *mut Dword,
+        lp_data: *mut Byte,
+        lpcb_data: *mut Dword,
+    ) -> i32;
+
     pub fn RegCloseKey(h_key: Hkey) -> i32;
+
+    pub fn GetNamedSecurityInfoW(
+        p_object_name: Lpcwstr,
+        object_type: u32,
+        security_info: Dword,
+        ppsid_owner: *mut Lpvoid,
+        ppsid_group: *mut Lpvoid,
+        pp_dacl: *mut Lpvoid,
+        pp_sacl: *mut Lpvoid,
+        pp_security_descriptor: *mut Lpvoid,
+    ) -> Dword;
+
+    pub fn OpenProcessToken(
+        process_handle: Handle,
+        desired_access: Dword,
+        token_handle: *mut Handle,
+    ) -> Bool;
+
+    pub fn GetTokenInformation(
+        token_handle: Handle,
+        token_information_class: u32,
+        token_information: Lpvoid,
+        token_information_length: Dword,
+        return_length: *mut Dword,
+    ) -> Bool;
 }
 
 #[link(name = "ntdll")]
@@ -319,6 +362,25 @@ pub fn to_wide(value: &OsStr) -> Vec<u16> {
     value.encode_wide().chain(std::iter::once(0)).collect()
 }
 
+/// A `CreateProcessW` environment block: `NAME=VALUE` strings sorted by
+/// name, case-insensitively, each NUL-terminated, with a final NUL.
+pub fn environment_block(vars: &[(OsString, OsString)]) -> Vec<u16> {
+    let mut sorted: Vec<&(OsString, OsString)> = vars.iter().collect();
+    sorted.sort_by_key(|(name, _)| name.to_string_lossy().to_ascii_uppercase());
+    let mut block = Vec::new();
+    for (name, value) in sorted {
+        block.extend(name.encode_wide());
+        block.push(u16::from(b'='));
+        block.extend(value.encode_wide());
+        block.push(0);
+    }
+    if block.is_empty() {
+        block.push(0);
+    }
+    block.push(0);
+    block
+}
+
 pub fn utf16_slice_to_string(value: &[u16]) -> String {
     OsString::from_wide(value).to_string_lossy().to_string()
 }
@@ -355,6 +417,16 @@ pub fn get_system_directory() -> Result<PathBuf, String> {
     Ok(PathBuf::from(utf16_slice_to_string(&buf[..size as usize])))
 }
 
+pub fn get_system_wow64_directory() -> Result<PathBuf, String> {
+    let mut buf = vec![0u16; 32768];
+    let size = unsafe { GetSystemWow64DirectoryW(buf.as_mut_ptr(), buf.len() as Uint) };
+    if size == 0 || size as usize >= buf.len() {
+        let code = unsafe { GetLastError() };
+        return Err(format!("GetSystemWow64DirectoryW failed: 0x{code:08X}"));
+    }
+    Ok(PathBuf::from(utf16_slice_to_string(&buf[..size as usize])))
+}
+
 pub fn get_windows_directory() -> Result<PathBuf, String> {
     let mut buf = vec![0u16; 32768];
     let size = unsafe { GetWindowsDirectoryW(buf.as_mut_ptr(), buf.len() as Uint) };
@@ -365,17 +437,144 @@ pub fn get_windows_directory() -> Result<PathBuf, String> {
     Ok(PathBuf::from(utf16_slice_to_string(&buf[..size as usize])))
 }
 
+const SESSION_MANAGER: &str = r"SYSTEM\CurrentControlSet\Control\Session Manager";
+
 pub fn safe_dll_search_mode() -> bool {
     if let Some(value) = test_safe_dll_search_mode_override() {
         return value;
     }
 
-    let mut key: Hkey = 0;
-    let path = to_wide(OsStr::new(
-        r"SYSTEM\CurrentControlSet\Control\Session Manager",
-    ));
+    query_machine_dword(SESSION_MANAGER, "SafeDllSearchMode").is_none_or(|value| value != 0)
+}
+
+/// Whether `DevOverrideEnable` under Image File Execution Options is set,
+/// which makes the loader honor `.local` redirection for every image.
+pub fn dev_override_enabled() -> bool {
+    query_machine_dword(
+        r"SOFTWARE\Microsoft\Windows NT\CurrentVersion\Image File Execution Options",
+        "DevOverrideEnable",
+    )
+    .is_some_and(|value| value != 0)
+}
+
+/// Lowercased DLL names of the `KnownDLLs` list, skipping the
+/// `DllDirectory` values that name its backing directories.
+pub fn known_dlls() -> Vec<String> {
+    let Some(key) = open_machine_key(&format!(r"{SESSION_MANAGER}\KnownDLLs")) else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    let mut index: Dword = 0;
+    loop {
+        let mut name = [0u16; 256];
+        let mut name_len = name.len() as Dword;
+        let mut data = [0u16; 260];
+        let mut data_size = std::mem::size_of_val(&data) as Dword;
+        let mut data_type: Dword = 0;
+        let status = unsafe {
+            RegEnumValueW(
+                key,
+                index,
+                name.as_mut_ptr(),
+                &mut name_len as *mut Dword,
+                std::ptr::null_mut(),
+                &mut data_type as *mut Dword,
+                data.as_mut_ptr().cast::<Byte>(),
+                &mut data_size as *mut Dword,
+            )
+        };
+        match status as u32 {
+            0 => {
+                let name = utf16_slice_to_string(&name[..name_len as usize]);
+                let len = data_size as usize / 2;
+                let value = utf16_slice_to_string(&data[..len]);
+                let value = value.trim_end_matches('\0').trim();
+                if data_type == REG_SZ
+                    && !name.to_ascii_lowercase().starts_with("dlldirectory")
+                    && !value.is_empty()
+                {
+                    names.push(value.to_ascii_lowercase());
+                }
+            }
+            // Longer values are not DLL names; skip them.
+            ERROR_MORE_DATA => {}
+            _ => break,
+        }
+        index += 1;
+    }
+    unsafe {
+        RegCloseKey(key);
+    }
+    names
+}
+
+/// The DACL of a file or directory as raw ACL bytes, `Ok(None)` for a NULL
+/// DACL, or the `GetNamedSecurityInfoW` error code.
+pub fn file_dacl(path: &Path) -> Result<Option<Vec<u8>>, Dword> {
+    let wide = to_wide(path.as_os_str());
+    let mut dacl: Lpvoid = std::ptr::null_mut();
+    let mut descriptor: Lpvoid = std::ptr::null_mut();
+    let status = unsafe {
+        GetNamedSecurityInfoW(
+            wide.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut dacl as *mut Lpvoid,
+            std::ptr::null_mut(),
+            &mut descriptor as *mut Lpvoid,
+        )
+    };
+    if status != 0 {
+        return Err(status);
+    }
+    let bytes = (!dacl.is_null()).then(|| unsafe {
+        // ACL header: revision, padding, then the total AclSize.
+        let size = dacl.cast::<u8>().add(2).cast::<u16>().read_unaligned() as usize;
+        std::slice::from_raw_parts(dacl.cast::<u8>(), size).to_vec()
+    });
+    unsafe {
+        LocalFree(descriptor);
+    }
+    Ok(bytes)
+}
+
+/// The binary SID of the user this process runs as.
+pub fn current_user_sid() -> Option<Vec<u8>> {
+    let mut token: Handle = std::ptr::null_mut();
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token as *mut Handle) } == 0
+    {
+        return None;
+    }
+    // TOKEN_USER starts with a pointer to the SID stored after it; the
+    // buffer is pointer-aligned for that read.
+    let mut buf = [0usize; 64];
+    let mut len: Dword = 0;
+    let ok = unsafe {
+        GetTokenInformation(
+            token,
+            TOKEN_USER_CLASS,
+            buf.as_mut_ptr().cast(),
+            mem::size_of_val(&buf) as Dword,
+            &mut len as *mut Dword,
+        )
+    };
+    unsafe {
+        CloseHandle(token);
+    }
+    if ok == 0 || buf[0] == 0 {
+        return None;
+    }
+    let sid = buf[0] as *const u8;
+    let count = unsafe { *sid.add(1) } as usize;
+    Some(unsafe { std::slice::from_raw_parts(sid, 8 + 4 * count) }.to_vec())
+}
 
-    let open_status = unsafe {
+fn open_machine_key(subkey: &str) -> Option<Hkey> {
+    let mut key: Hkey = 0;
+    let path = to_wide(OsStr::new(subkey));
+    let status = unsafe {
         RegOpenKeyExW(
             HKEY_LOCAL_MACHINE,
             path.as_ptr(),
@@ -384,12 +583,13 @@ pub fn safe_dll_search_mode() -> bool {
             &mut key as *mut Hkey,
         )
     };
-    if open_status != 0 {
-        return true;
-    }
+    (status == 0).then_some(key)
+}
 
-    let name = to_wide(OsStr::new("SafeDllSearchMode"));
-    let mut data: Dword = 1;
+fn query_machine_dword(subkey: &str, name: &str) -> Option<Dword> {
+    let key = open_machine_key(subkey)?;
+    let name = to_wide(OsStr::new(name));
+    let mut data: Dword = 0;
     let mut data_type: Dword = 0;
     let mut data_size = std::mem::size_of::<Dword>() as Dword;
     let query_status = unsafe {
@@ -406,11 +606,7 @@ pub fn safe_dll_search_mode() -> bool {
         RegCloseKey(key);
     }
 
-    if query_status == 0 && data_type == REG_DWORD {
-        data != 0
-    } else {
-        true
-    }
+    (query_status == 0 && data_type == REG_DWORD).then_some(data)
 }
 
 #[cfg(debug_assertions)]
@@ -495,12 +691,12 @@ fn try_is_wow64_process2(process: Handle) -> Result<Option<bool>, u32> {
 #[cfg(test)]
 mod tests {
     use super::{
-        final_path_from_handle, is_wow64_process_best_effort, rtl_get_version,
+        environment_block, final_path_from_handle, is_wow64_process_best_effort, rtl_get_version,
         safe_dll_search_mode, to_wide, utf16_slice_to_string, ERROR_INVALID_PARAMETER,
         INVALID_HANDLE_VALUE, TEST_ENV_LOCK,
     };
     use crate::test_util::EnvVarGuard;
-    use std::ffi::OsStr;
+    use std::ffi::{OsStr, OsString};
 
     #[test]
     fn to_wide_encodes_ascii_and_appends_nul() {
@@ -520,6 +716,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn environment_block_sorts_names_and_double_terminates() {
+        let vars = [
+            (OsString::from("b"), OsString::from("2")),
+            (OsString::from("A"), OsString::from("1")),
+        ];
+        assert_eq!(
+            environment_block(&vars),
+            to_wide(OsStr::new("A=1"))
+                .into_iter()
+                .chain(to_wide(OsStr::new("b=2")))
+                .chain([0])
+                .collect::<Vec<u16>>()
+        );
+        assert_eq!(environment_block(&[]), vec![0, 0]);
+    }
+
     #[test]
     fn utf16_slice_to_string_decodes_basic_text() {
         assert_eq!(utf16_slice_to_string(&[0x0041, 0x0042, 0x0043]), "ABC");
@@ -11,6 +11,12 @@ const NUMBER_OF_SECTIONS_OFFSET: usize = PE_OFFSET + 6;
 const SIZE_OF_OPTIONAL_HEADER_OFFSET: usize = PE_OFFSET + 20;
 const SECTION_VIRTUAL_ADDRESS: u32 = 0x1000;
 const SECTION_RAW_DATA_PTR: u32 = 0x200;
+const DELAY_IMPORT_DIRECTORY_OFFSET: usize = DATA_DIR_START + 104;
+const DELAY_SECTION_VIRTUAL_ADDRESS: u32 = 0x3000;
+const THUNK_SECTION_VIRTUAL_ADDRESS: u32 = 0x4000;
+const EXPORT_SECTION_VIRTUAL_ADDRESS: u32 = 0x5000;
+/// Covers every section VA the builders below use.
+const SIZE_OF_IMAGE: u32 = 0x10000;
 
 pub fn write_import_test_pe(path: &Path, imports: &[&str]) -> Result<(), String> {
     if let Some(parent) = path.parent() {
@@ -21,6 +27,62 @@ pub fn write_import_test_pe(path: &Path, imports: &[&str]) -> Result<(), String>
         .map_err(|e| format!("failed to write {}: {e}", path.display()))
 }
 
+pub fn write_delay_import_test_pe(
+    path: &Path,
+    imports: &[&str],
+    delay_imports: &[&str],
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, build_delay_import_test_pe(imports, delay_imports))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// Writes a 32-bit (x86, PE32) image with the given import table.
+pub fn write_x86_import_test_pe(path: &Path, imports: &[&str]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, build_x86_import_test_pe(imports))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// Writes a PE importing each named function from its DLL through an
+/// import name table.
+pub fn write_function_import_test_pe(
+    path: &Path,
+    imports: &[(&str, &[&str])],
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, build_function_import_test_pe(imports))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+pub fn write_export_test_pe(path: &Path, exports: &[&str]) -> Result<(), String> {
+    let exports: Vec<(&str, Option<&str>)> = exports.iter().map(|name| (*name, None)).collect();
+    write_forwarding_export_test_pe(path, &exports)
+}
+
+/// Writes an export-only PE; entries with a forwarder string such as
+/// `"OTHER.Function"` are exported as forwarders.
+pub fn write_forwarding_export_test_pe(
+    path: &Path,
+    exports: &[(&str, Option<&str>)],
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, build_export_test_pe(exports))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
 pub fn build_import_test_pe(imports: &[&str]) -> Vec<u8> {
     let descriptor_bytes = (imports.len() + 1) * 20;
     let strings_bytes: usize = imports.iter().map(|name| name.len() + 1).sum();
@@ -41,6 +103,12 @@ pub fn build_import_test_pe(imports: &[&str]) -> Vec<u8> {
     );
 
     write_u16(&mut bytes, OPTIONAL_HEADER_OFFSET, 0x020B);
+    write_u32(&mut bytes, OPTIONAL_HEADER_OFFSET + 56, SIZE_OF_IMAGE);
+    write_u32(
+        &mut bytes,
+        OPTIONAL_HEADER_OFFSET + 60,
+        SECTION_RAW_DATA_PTR,
+    );
     write_u32(
         &mut bytes,
         IMPORT_DIRECTORY_RVA_OFFSET,
@@ -77,6 +145,164 @@ pub fn build_import_test_pe(imports: &[&str]) -> Vec<u8> {
     bytes
 }
 
+/// Same image as `build_import_test_pe`, rewritten as PE32 for x86. The
+/// data directories move up 16 bytes; the section table stays put because
+/// the optional header size is unchanged.
+pub fn build_x86_import_test_pe(imports: &[&str]) -> Vec<u8> {
+    let mut bytes = build_import_test_pe(imports);
+    write_u16(&mut bytes, PE_OFFSET + 4, 0x014C);
+    write_u16(&mut bytes, OPTIONAL_HEADER_OFFSET, 0x010B);
+    let pe32_data_dir_start = OPTIONAL_HEADER_OFFSET + 96;
+    bytes.copy_within(DATA_DIR_START..DATA_DIR_START + 16 * 8, pe32_data_dir_start);
+    bytes
+}
+
+/// Same image as `build_import_test_pe`, plus a second `.didat` section
+/// holding RVA-based delay-load descriptors for `delay_imports`.
+pub fn build_delay_import_test_pe(imports: &[&str], delay_imports: &[&str]) -> Vec<u8> {
+    let mut bytes = build_import_test_pe(imports);
+
+    let descriptor_bytes = (delay_imports.len() + 1) * 32;
+    let mut section = vec![0u8; descriptor_bytes];
+    for (idx, name) in delay_imports.iter().enumerate() {
+        let name_rva = DELAY_SECTION_VIRTUAL_ADDRESS + section.len() as u32;
+        write_u32(&mut section, idx * 32, 1);
+        write_u32(&mut section, idx * 32 + 4, name_rva);
+        section.extend_from_slice(name.as_bytes());
+        section.push(0);
+    }
+    append_section(
+        &mut bytes,
+        b".didat",
+        DELAY_SECTION_VIRTUAL_ADDRESS,
+        &section,
+    );
+
+    write_u32(
+        &mut bytes,
+        DELAY_IMPORT_DIRECTORY_OFFSET,
+        DELAY_SECTION_VIRTUAL_ADDRESS,
+    );
+    write_u32(
+        &mut bytes,
+        DELAY_IMPORT_DIRECTORY_OFFSET + 4,
+        descriptor_bytes as u32,
+    );
+    bytes
+}
+
+/// Same image as `build_import_test_pe`, with each descriptor's
+/// OriginalFirstThunk pointing at a PE32+ name table in a `.idata` section.
+pub fn build_function_import_test_pe(imports: &[(&str, &[&str])]) -> Vec<u8> {
+    let names: Vec<&str> = imports.iter().map(|(name, _)| *name).collect();
+    let mut bytes = build_import_test_pe(&names);
+
+    let thunk_bytes: usize = imports
+        .iter()
+        .map(|(_, functions)| (functions.len() + 1) * 8)
+        .sum();
+    let mut section = vec![0u8; thunk_bytes];
+    let mut thunk_offset = 0usize;
+    for (idx, (_, functions)) in imports.iter().enumerate() {
+        let descriptor_offset = SECTION_RAW_DATA_PTR as usize + idx * 20;
+        write_u32(
+            &mut bytes,
+            descriptor_offset,
+            THUNK_SECTION_VIRTUAL_ADDRESS + thunk_offset as u32,
+        );
+        for function in functions.iter() {
+            let by_name_rva = THUNK_SECTION_VIRTUAL_ADDRESS + section.len() as u32;
+            section.extend_from_slice(&[0, 0]);
+            section.extend_from_slice(function.as_bytes());
+            section.push(0);
+            write_u32(&mut section, thunk_offset, by_name_rva);
+            thunk_offset += 8;
+        }
+        thunk_offset += 8;
+    }
+
+    append_section(
+        &mut bytes,
+        b".idata",
+        THUNK_SECTION_VIRTUAL_ADDRESS,
+        &section,
+    );
+    bytes
+}
+
+/// Import-free image exporting `exports` by name from an `.edata` section.
+/// Forwarded entries point their address slot at the forwarder string,
+/// which lies inside the export directory.
+pub fn build_export_test_pe(exports: &[(&str, Option<&str>)]) -> Vec<u8> {
+    let mut bytes = build_import_test_pe(&[]);
+
+    let functions_offset = 40usize;
+    let names_offset = functions_offset + exports.len() * 4;
+    let ordinals_offset = names_offset + exports.len() * 4;
+    let mut section = vec![0u8; ordinals_offset + exports.len() * 2];
+    write_u32(&mut section, 16, 1);
+    write_u32(&mut section, 20, exports.len() as u32);
+    write_u32(&mut section, 24, exports.len() as u32);
+    write_u32(
+        &mut section,
+        28,
+        EXPORT_SECTION_VIRTUAL_ADDRESS + functions_offset as u32,
+    );
+    write_u32(
+        &mut section,
+        32,
+        EXPORT_SECTION_VIRTUAL_ADDRESS + names_offset as u32,
+    );
+    write_u32(
+        &mut section,
+        36,
+        EXPORT_SECTION_VIRTUAL_ADDRESS + ordinals_offset as u32,
+    );
+    for (idx, (name, forward)) in exports.iter().enumerate() {
+        let function_rva = match forward {
+            Some(forward) => {
+                let forward_rva = EXPORT_SECTION_VIRTUAL_ADDRESS + section.len() as u32;
+                section.extend_from_slice(forward.as_bytes());
+                section.push(0);
+                forward_rva
+            }
+            None => SECTION_VIRTUAL_ADDRESS,
+        };
+        write_u32(&mut section, functions_offset + idx * 4, function_rva);
+        let name_rva = EXPORT_SECTION_VIRTUAL_ADDRESS + section.len() as u32;
+        write_u32(&mut section, names_offset + idx * 4, name_rva);
+        write_u16(&mut section, ordinals_offset + idx * 2, idx as u16);
+        section.extend_from_slice(name.as_bytes());
+        section.push(0);
+    }
+    let section_size = section.len() as u32;
+
+    append_section(
+        &mut bytes,
+        b".edata",
+        EXPORT_SECTION_VIRTUAL_ADDRESS,
+        &section,
+    );
+    write_u32(&mut bytes, DATA_DIR_START, EXPORT_SECTION_VIRTUAL_ADDRESS);
+    write_u32(&mut bytes, DATA_DIR_START + 4, section_size);
+    bytes
+}
+
+/// Appends `contents` as a second section; the header gap before
+/// `SECTION_RAW_DATA_PTR` has room for exactly one more section header.
+fn append_section(bytes: &mut Vec<u8>, name: &[u8], virtual_address: u32, contents: &[u8]) {
+    let raw_offset = bytes.len();
+    bytes.extend_from_slice(contents);
+
+    write_u16(bytes, NUMBER_OF_SECTIONS_OFFSET, 2);
+    let header = SECTION_TABLE_OFFSET + 40;
+    bytes[header..header + name.len()].copy_from_slice(name);
+    write_u32(bytes, header + 8, contents.len() as u32);
+    write_u32(bytes, header + 12, virtual_address);
+    write_u32(bytes, header + 16, contents.len() as u32);
+    write_u32(bytes, header + 20, raw_offset as u32);
+}
+
 fn write_u16(bytes: &mut [u8], offset: usize, value: u16) {
     bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
 }
@@ -30,9 +30,15 @@ mod dynamic_multiple_candidates;
 #[path = "integration/dynamic_nested_loadlibrary.rs"]
 mod dynamic_nested_loadlibrary;
 #[cfg(all(windows, feature = "harness-tests"))]
+#[path = "integration/imports_api_sets.rs"]
+mod imports_api_sets;
+#[cfg(all(windows, feature = "harness-tests"))]
 #[path = "integration/imports_bad_image.rs"]
 mod imports_bad_image;
 #[cfg(all(windows, feature = "harness-tests"))]
+#[path = "integration/imports_delay_load.rs"]
+mod imports_delay_load;
+#[cfg(all(windows, feature = "harness-tests"))]
 #[path = "integration/imports_edge_cases.rs"]
 mod imports_edge_cases;
 #[cfg(all(windows, feature = "harness-tests"))]
@@ -45,6 +51,12 @@ mod imports_stability;
 #[path = "integration/imports_transitive_missing.rs"]
 mod imports_transitive_missing;
 #[cfg(all(windows, feature = "harness-tests"))]
+#[path = "integration/imports_version_info.rs"]
+mod imports_version_info;
+#[cfg(all(windows, feature = "harness-tests"))]
+#[path = "integration/imports_x86_walk.rs"]
+mod imports_x86_walk;
+#[cfg(all(windows, feature = "harness-tests"))]
 #[path = "integration/loader_snaps_note_contract.rs"]
 mod loader_snaps_note_contract;
 #[cfg(all(windows, feature = "harness-tests"))]
@@ -96,6 +108,9 @@ mod static_bad_image_direct;
 #[path = "integration/static_bad_image_transitive.rs"]
 mod static_bad_image_transitive;
 #[cfg(all(windows, feature = "harness-tests"))]
+#[path = "integration/static_broken_forward.rs"]
+mod static_broken_forward;
+#[cfg(all(windows, feature = "harness-tests"))]
 #[path = "integration/static_circular_dependency.rs"]
 mod static_circular_dependency;
 #[cfg(all(windows, feature = "harness-tests"))]
@@ -105,6 +120,9 @@ mod static_deep_chain;
 #[path = "integration/static_missing_direct.rs"]
 mod static_missing_direct;
 #[cfg(all(windows, feature = "harness-tests"))]
+#[path = "integration/static_missing_export.rs"]
+mod static_missing_export;
+#[cfg(all(windows, feature = "harness-tests"))]
 #[path = "integration/static_missing_transitive.rs"]
 mod static_missing_transitive;
 #[cfg(all(windows, feature = "harness-tests"))]
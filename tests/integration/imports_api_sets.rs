@@ -0,0 +1,86 @@
+use crate::harness;
+use std::ffi::OsString;
+use std::time::Duration;
+
+fn run_imports(
+    paths: &harness::paths::HarnessPaths,
+    case: &harness::case::TestCase,
+    root: &std::path::Path,
+    cwd: &std::path::Path,
+) -> harness::run_loadwhat::RunResult {
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(root),
+        OsString::from("--cwd"),
+        harness::case::os(cwd),
+    ];
+    harness::run_loadwhat::run_public(paths, case.root(), &args, Duration::from_secs(20))
+        .expect("failed to run loadwhat")
+}
+
+#[test]
+fn imports_maps_api_set_to_host_and_walks_it() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_api_set_host")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_import_test_pe(&root, &["api-ms-win-core-synch-l1-2-0.dll"])
+        .expect("failed to write root.exe");
+
+    let result = run_imports(&paths, &case, &root, &dir);
+
+    harness::assert::assert_not_timed_out(&result);
+
+    let lines: Vec<&str> = result.stdout.lines().map(|line| line.trim()).collect();
+    let mapping = lines.iter().find(|line| {
+        line.starts_with("STATIC_APISET ")
+            && line.contains(r#"apiset="api-ms-win-core-synch-l1-2-0.dll""#)
+    });
+    let Some(mapping) = mapping else {
+        panic!(
+            "expected STATIC_APISET for the synch API set.\nstdout:\n{}",
+            result.stdout
+        );
+    };
+    let host = mapping
+        .split(r#"host=""#)
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("STATIC_APISET must carry host");
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.starts_with("STATIC_FOUND ")
+                && line.contains(&format!(r#"dll="{host}""#))),
+        "expected the API set host {host} to be resolved.\nstdout:\n{}",
+        result.stdout
+    );
+}
+
+#[test]
+fn imports_reports_unknown_api_set_as_unresolved() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_api_set_unknown")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_import_test_pe(&root, &["api-ms-win-lwtest-absent-l1-1-0.dll"])
+        .expect("failed to write root.exe");
+
+    let result = run_imports(&paths, &case, &root, &dir);
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+    assert!(
+        result.stdout.lines().map(|line| line.trim()).any(|line| {
+            line.starts_with("STATIC_APISET_UNRESOLVED ")
+                && line.contains(r#"dll="api-ms-win-lwtest-absent-l1-1-0.dll""#)
+                && line.contains(r#"reason="UNKNOWN_API_SET""#)
+        }),
+        "expected STATIC_APISET_UNRESOLVED for the unknown API set.\nstdout:\n{}",
+        result.stdout
+    );
+}
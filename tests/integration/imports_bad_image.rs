@@ -59,7 +59,7 @@ fn imports_reports_direct_bad_image_without_runtime_tokens() {
             && lines.iter().any(|line| {
                 line.starts_with("STATIC_BAD_IMAGE ")
                     && line.contains(r#"dll="lwtest_a.dll""#)
-                    && line.contains(r#"reason="BAD_IMAGE""#)
+                    && line.contains(r#"reason="NOT_PE""#)
             }),
         "expected direct bad-image imports output.\n{}",
         result.stdout
@@ -114,9 +114,63 @@ fn imports_reports_transitive_bad_image_deterministically() {
     harness::assert::assert_exit_code(&result, 10);
     assert!(
         result.stdout.contains(
-            r#"STATIC_BAD_IMAGE module="lwtest_a.dll" dll="lwtest_b.dll" reason="BAD_IMAGE""#
+            r#"STATIC_BAD_IMAGE module="lwtest_a.dll" dll="lwtest_b.dll" reason="NOT_PE""#
         ),
         "expected transitive bad-image diagnosis.\n{}",
         result.stdout
     );
 }
+
+#[test]
+fn imports_classifies_truncated_and_empty_dlls() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_bad_image_reasons")
+        .expect("failed to initialize test case");
+    let app_dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = app_dir.join("root.exe");
+    harness::pe_builder::write_import_test_pe(&root, &["lwtest_cut.dll", "lwtest_empty.dll"])
+        .expect("failed to write root.exe");
+    let mut cut = harness::pe_builder::build_import_test_pe(&[]);
+    cut.truncate(cut.len() - 1);
+    fs::write(app_dir.join("lwtest_cut.dll"), cut).expect("failed to write truncated dll");
+    fs::write(app_dir.join("lwtest_empty.dll"), b"").expect("failed to write empty dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&app_dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+    let lines = token_lines(&result.stdout);
+    for (dll, reason) in [
+        ("lwtest_cut.dll", "TRUNCATED"),
+        ("lwtest_empty.dll", "ZERO_LENGTH"),
+    ] {
+        let dll_field = format!(r#"dll="{dll}""#);
+        let reason_field = format!(r#"reason="{reason}""#);
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("STATIC_BAD_IMAGE ")
+                    && line.contains(&dll_field)
+                    && line.contains(&reason_field)),
+            "expected STATIC_BAD_IMAGE {reason} for {dll}.\n{}",
+            result.stdout
+        );
+        assert!(
+            lines.iter().any(|line| line.starts_with("SEARCH_PATH ")
+                && line.contains(&dll_field)
+                && line.contains(r#"result="BAD_IMAGE""#)
+                && line.contains(&reason_field)),
+            "expected SEARCH_PATH reason {reason} for {dll}.\n{}",
+            result.stdout
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::harness;
+use std::ffi::OsString;
+use std::time::Duration;
+
+#[test]
+fn imports_reports_missing_delay_load_dependency_apart_from_static_misses() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_delay_load_missing")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_delay_import_test_pe(&root, &["a.dll"], &["lwtest_delay.dll"])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_import_test_pe(&dir.join("a.dll"), &[])
+        .expect("failed to write a.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+
+    let lines: Vec<&str> = result.stdout.lines().map(|line| line.trim()).collect();
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.starts_with("STATIC_DELAY_IMPORT ")
+                && line.contains(r#"needs="lwtest_delay.dll""#)),
+        "expected STATIC_DELAY_IMPORT for lwtest_delay.dll.\nstdout:\n{}",
+        result.stdout
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.starts_with("STATIC_DELAY_MISSING ")
+                && line.contains(r#"dll="lwtest_delay.dll""#)
+                && line.contains(r#"reason="NOT_FOUND""#)),
+        "expected STATIC_DELAY_MISSING for lwtest_delay.dll.\nstdout:\n{}",
+        result.stdout
+    );
+    assert!(
+        !lines.iter().any(|line| line.starts_with("STATIC_MISSING ")),
+        "delay-load misses must not be reported as STATIC_MISSING.\nstdout:\n{}",
+        result.stdout
+    );
+    assert!(
+        lines.iter().any(|line| line.starts_with("SUMMARY ")
+            && line.contains("static_missing=0")
+            && line.contains("static_delay_missing=1")),
+        "expected the delay-load miss counted apart in SUMMARY.\nstdout:\n{}",
+        result.stdout
+    );
+}
+
+#[test]
+fn imports_walks_present_delay_load_dependency_transitively() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_delay_load_transitive")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_delay_import_test_pe(&root, &[], &["lwtest_delay.dll"])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_import_test_pe(&dir.join("lwtest_delay.dll"), &["lwtest_gone.dll"])
+        .expect("failed to write lwtest_delay.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+
+    let found = result.stdout.lines().map(|line| line.trim()).any(|line| {
+        line.starts_with("STATIC_DELAY_MISSING ")
+            && line.contains(r#"dll="lwtest_gone.dll""#)
+            && line.contains(r#"via="lwtest_delay.dll""#)
+            && line.contains("depth=2")
+    });
+    assert!(
+        found,
+        "expected transitive STATIC_DELAY_MISSING for lwtest_gone.dll.\nstdout:\n{}",
+        result.stdout
+    );
+}
@@ -0,0 +1,47 @@
+use crate::harness;
+use std::ffi::OsString;
+use std::time::Duration;
+
+#[test]
+fn imports_annotates_found_system_dll_with_version_metadata() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_version_info")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_import_test_pe(&root, &["kernel32.dll", "lwtest_plain.dll"])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_import_test_pe(&dir.join("lwtest_plain.dll"), &[])
+        .expect("failed to write lwtest_plain.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+
+    let lines: Vec<&str> = result.stdout.lines().map(|line| line.trim()).collect();
+    assert!(
+        lines.iter().any(|line| line.starts_with("STATIC_FOUND ")
+            && line.contains(r#"dll="kernel32.dll""#)
+            && line.contains("file_version=\"")
+            && line.contains(r#"company="Microsoft Corporation""#)),
+        "expected version metadata on STATIC_FOUND for kernel32.dll.\nstdout:\n{}",
+        result.stdout
+    );
+    assert!(
+        lines.iter().any(|line| line.starts_with("STATIC_FOUND ")
+            && line.contains(r#"dll="lwtest_plain.dll""#)
+            && !line.contains("file_version=")
+            && line.contains("signed=false")),
+        "expected an unsigned image without version fields.\nstdout:\n{}",
+        result.stdout
+    );
+}
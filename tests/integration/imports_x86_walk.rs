@@ -0,0 +1,110 @@
+use crate::harness;
+use std::ffi::OsString;
+use std::time::Duration;
+
+fn run_imports(
+    paths: &harness::paths::HarnessPaths,
+    case: &harness::case::TestCase,
+    root: &std::path::Path,
+    cwd: &std::path::Path,
+) -> harness::run_loadwhat::RunResult {
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(root),
+        OsString::from("--cwd"),
+        harness::case::os(cwd),
+    ];
+    harness::run_loadwhat::run_public(paths, case.root(), &args, Duration::from_secs(20))
+        .expect("failed to run loadwhat")
+}
+
+#[test]
+fn imports_x86_root_skips_x64_candidate_and_keeps_searching() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_x86_skip_wrong_machine")
+        .expect("failed to initialize test case");
+    let app = case.mkdir("app").expect("failed to create app directory");
+    let cwd = case.mkdir("cwd").expect("failed to create cwd directory");
+
+    let root = app.join("root.exe");
+    harness::pe_builder::write_x86_import_test_pe(&root, &["lwtest_bits.dll"])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_import_test_pe(&app.join("lwtest_bits.dll"), &[])
+        .expect("failed to write x64 lwtest_bits.dll");
+    let x86_copy = cwd.join("lwtest_bits.dll");
+    harness::pe_builder::write_x86_import_test_pe(&x86_copy, &[])
+        .expect("failed to write x86 lwtest_bits.dll");
+
+    let result = run_imports(&paths, &case, &root, &cwd);
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 0);
+
+    let lines: Vec<&str> = result.stdout.lines().map(|line| line.trim()).collect();
+    assert!(
+        lines.iter().any(|line| line.starts_with("SEARCH_PATH ")
+            && line.contains(r#"dll="lwtest_bits.dll""#)
+            && line.contains(r#"result="WRONG_MACHINE""#)),
+        "expected WRONG_MACHINE candidate for the x64 copy.\nstdout:\n{}",
+        result.stdout
+    );
+    let expected_path = format!(r#"path="{}""#, x86_copy.display());
+    assert!(
+        lines.iter().any(|line| line.starts_with("STATIC_FOUND ")
+            && line.contains(r#"dll="lwtest_bits.dll""#)
+            && line.contains(&expected_path)),
+        "expected STATIC_FOUND for the x86 copy.\nstdout:\n{}",
+        result.stdout
+    );
+}
+
+#[test]
+fn imports_x86_root_with_only_x64_candidate_reports_bad_image() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_x86_only_wrong_machine")
+        .expect("failed to initialize test case");
+    let app = case.mkdir("app").expect("failed to create app directory");
+
+    let root = app.join("root.exe");
+    harness::pe_builder::write_x86_import_test_pe(&root, &["lwtest_bits.dll"])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_import_test_pe(&app.join("lwtest_bits.dll"), &[])
+        .expect("failed to write x64 lwtest_bits.dll");
+
+    let result = run_imports(&paths, &case, &root, &app);
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+    assert!(
+        result.stdout.lines().map(|line| line.trim()).any(|line| {
+            line.starts_with("STATIC_BAD_IMAGE ") && line.contains(r#"dll="lwtest_bits.dll""#)
+        }),
+        "expected STATIC_BAD_IMAGE for lwtest_bits.dll.\nstdout:\n{}",
+        result.stdout
+    );
+}
+
+#[test]
+fn imports_x86_root_resolves_system_dlls_from_syswow64() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "imports_x86_syswow64")
+        .expect("failed to initialize test case");
+    let app = case.mkdir("app").expect("failed to create app directory");
+
+    let root = app.join("root.exe");
+    harness::pe_builder::write_x86_import_test_pe(&root, &["version.dll"])
+        .expect("failed to write root.exe");
+
+    let result = run_imports(&paths, &case, &root, &app);
+
+    harness::assert::assert_not_timed_out(&result);
+    let found =
+        result.stdout.lines().map(|line| line.trim()).find(|line| {
+            line.starts_with("STATIC_FOUND ") && line.contains(r#"dll="version.dll""#)
+        });
+    assert!(
+        found.is_some_and(|line| line.to_ascii_lowercase().contains(r"\syswow64\version.dll")),
+        "expected version.dll to resolve from SysWOW64.\nstdout:\n{}",
+        result.stdout
+    );
+}
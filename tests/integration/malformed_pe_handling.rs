@@ -192,7 +192,7 @@ fn imports_junk_bytes_dll_in_chain_reports_bad_image() {
             .iter()
             .any(|line| line.starts_with("STATIC_BAD_IMAGE ")
                 && line.contains(r#"dll="lwtest_a.dll""#)
-                && line.contains(r#"reason="BAD_IMAGE""#)),
+                && line.contains(r#"reason="NOT_PE""#)),
         "junk bytes DLL should be reported as bad image.\n{}",
         result.stdout
     );
@@ -71,7 +71,7 @@ fn run_summary_mode_reports_direct_static_bad_image() {
     assert!(
         lines[0].starts_with("STATIC_BAD_IMAGE ")
             && lines[0].contains(r#"dll="lwtest_a.dll""#)
-            && lines[0].contains(r#"reason="BAD_IMAGE""#),
+            && lines[0].contains(r#"reason="NOT_PE""#),
         "unexpected summary output.\n{}",
         result.stdout
     );
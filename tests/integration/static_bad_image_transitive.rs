@@ -63,7 +63,7 @@ fn run_reports_transitive_static_bad_image_deterministically() {
         lines[0].starts_with("STATIC_BAD_IMAGE ")
             && lines[0].contains(r#"module="host_static_a_depends_on_b.exe""#)
             && lines[0].contains(r#"dll="lwtest_b.dll""#)
-            && lines[0].contains(r#"reason="BAD_IMAGE""#),
+            && lines[0].contains(r#"reason="NOT_PE""#),
         "unexpected transitive bad-image summary.\n{}",
         result.stdout
     );
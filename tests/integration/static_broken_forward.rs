@@ -0,0 +1,93 @@
+use crate::harness;
+use std::ffi::OsString;
+use std::time::Duration;
+
+#[test]
+fn imports_reports_forwarded_export_whose_target_dll_is_missing() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "static_broken_forward")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_function_import_test_pe(&root, &[("lwtest_fwd.dll", &["LwFoo"])])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_forwarding_export_test_pe(
+        &dir.join("lwtest_fwd.dll"),
+        &[("LwFoo", Some("lwtest_gone.LwBar"))],
+    )
+    .expect("failed to write lwtest_fwd.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+
+    let lines: Vec<&str> = result.stdout.lines().map(|line| line.trim()).collect();
+    assert!(
+        lines.contains(&r#"STATIC_FORWARD module="lwtest_fwd.dll" needs="lwtest_gone.dll""#),
+        "expected the hidden forwarder edge to be reported.\nstdout:\n{}",
+        result.stdout
+    );
+    assert!(
+        lines.contains(
+            &r#"STATIC_FORWARD_BROKEN module="root.exe" dll="lwtest_fwd.dll" function="LwFoo" forward="lwtest_gone.LwBar" reason="NOT_FOUND""#
+        ),
+        "expected STATIC_FORWARD_BROKEN for LwFoo.\nstdout:\n{}",
+        result.stdout
+    );
+}
+
+#[test]
+fn imports_walks_into_forward_target_dll() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "static_forward_target_walked")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_function_import_test_pe(&root, &[("lwtest_fwd.dll", &["LwFoo"])])
+        .expect("failed to write root.exe");
+    harness::pe_builder::write_forwarding_export_test_pe(
+        &dir.join("lwtest_fwd.dll"),
+        &[("LwFoo", Some("lwtest_target.LwBar"))],
+    )
+    .expect("failed to write lwtest_fwd.dll");
+    harness::pe_builder::write_export_test_pe(&dir.join("lwtest_target.dll"), &["LwBar"])
+        .expect("failed to write lwtest_target.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 0);
+    assert!(
+        result.stdout.lines().map(|line| line.trim()).any(|line| {
+            line.starts_with("STATIC_FOUND ")
+                && line.contains(r#"module="lwtest_fwd.dll""#)
+                && line.contains(r#"dll="lwtest_target.dll""#)
+        }),
+        "expected the forward target to resolve.\nstdout:\n{}",
+        result.stdout
+    );
+    assert!(
+        !result.stdout.contains("STATIC_FORWARD_BROKEN"),
+        "no forward should be reported broken.\nstdout:\n{}",
+        result.stdout
+    );
+}
@@ -0,0 +1,98 @@
+use crate::harness;
+use std::ffi::OsString;
+use std::time::Duration;
+
+#[test]
+fn imports_reports_function_missing_from_resolved_dll() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "static_missing_export")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_function_import_test_pe(
+        &root,
+        &[("lwtest_exports.dll", &["LwPresent", "LwMissing"])],
+    )
+    .expect("failed to write root.exe");
+    harness::pe_builder::write_export_test_pe(&dir.join("lwtest_exports.dll"), &["LwPresent"])
+        .expect("failed to write lwtest_exports.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 10);
+
+    let missing: Vec<&str> = result
+        .stdout
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| line.starts_with("STATIC_MISSING_EXPORT "))
+        .collect();
+    assert_eq!(
+        missing,
+        vec![
+            r#"STATIC_MISSING_EXPORT module="root.exe" dll="lwtest_exports.dll" function="LwMissing""#
+        ],
+        "expected exactly one STATIC_MISSING_EXPORT for LwMissing.\nstdout:\n{}",
+        result.stdout
+    );
+
+    let summary = result
+        .stdout
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with("SUMMARY "))
+        .expect("expected SUMMARY line");
+    assert!(
+        summary.contains("static_missing=1"),
+        "a missing export should count as a static miss.\n{}",
+        summary
+    );
+}
+
+#[test]
+fn imports_accepts_dll_that_exports_every_imported_function() {
+    let paths = harness::paths::require_from_env();
+    let case = harness::case::TestCase::new(&paths, "static_missing_export_none")
+        .expect("failed to initialize test case");
+    let dir = case.mkdir("app").expect("failed to create app directory");
+
+    let root = dir.join("root.exe");
+    harness::pe_builder::write_function_import_test_pe(
+        &root,
+        &[("lwtest_exports.dll", &["LwAlpha", "LwBeta"])],
+    )
+    .expect("failed to write root.exe");
+    harness::pe_builder::write_export_test_pe(
+        &dir.join("lwtest_exports.dll"),
+        &["LwAlpha", "LwBeta"],
+    )
+    .expect("failed to write lwtest_exports.dll");
+
+    let args = vec![
+        OsString::from("imports"),
+        harness::case::os(&root),
+        OsString::from("--cwd"),
+        harness::case::os(&dir),
+    ];
+    let result =
+        harness::run_loadwhat::run_public(&paths, case.root(), &args, Duration::from_secs(20))
+            .expect("failed to run loadwhat");
+
+    harness::assert::assert_not_timed_out(&result);
+    harness::assert::assert_exit_code(&result, 0);
+    assert!(
+        !result.stdout.contains("STATIC_MISSING_EXPORT"),
+        "no export should be reported missing.\nstdout:\n{}",
+        result.stdout
+    );
+}
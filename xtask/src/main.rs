@@ -5,6 +5,7 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 const CONTAINER_IMAGE: &str = "loadwhat-com-tests:local";
+#[cfg(windows)]
 const CONTAINER_CLSIDS: &[&str] = &[
     "{7F4D0001-4C57-4A54-9000-000000000001}",
     "{7F4D0002-4C57-4A54-9000-000000000002}",